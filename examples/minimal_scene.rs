@@ -0,0 +1,47 @@
+//! Smallest useful program on the library: a patch of grass with a cherry tree and a lantern,
+//! built in code and rendered once to `renders/minimal_scene.png`.
+//!
+//! cargo run --release --example minimal_scene
+
+use std::path::Path;
+
+use minecraft_raytracer::camera::Camera;
+use minecraft_raytracer::color::Color;
+use minecraft_raytracer::light::{LANTERN_LUMENS, lumens};
+use minecraft_raytracer::point_light::PointLight;
+use minecraft_raytracer::renderer::{self, RenderSettings};
+use minecraft_raytracer::scene_builder::{Prefab, SceneBuilder, Terrain};
+use minecraft_raytracer::screenshot;
+use minecraft_raytracer::utils::{Degrees, Vec3};
+
+const WIDTH: i32 = 640;
+const HEIGHT: i32 = 480;
+
+fn main() -> Result<(), String> {
+    let scene = SceneBuilder::new()
+        .with_terrain(Terrain::grass(-8, -8, 8, 8))
+        .with_prefab(Prefab::CherryTree(Vec3::zero()))
+        .with_light(PointLight::new(Vec3::new(3.0, 2.0, 3.0), Color::new(1.0, 0.8, 0.5), lumens(LANTERN_LUMENS), 12.0))
+        .build();
+
+    let camera = Camera::new(
+        Vec3::new(10.0, 7.0, 10.0),
+        Vec3::new(0.0, 2.0, 0.0),
+        Degrees(60.0),
+        WIDTH as f32 / HEIGHT as f32,
+    );
+    let settings = RenderSettings {
+        render_scale: 1,
+        frame_budget: 0.0, // Trace every tile properly, there's no frame rate to keep
+        ray_budget: 0,
+        ..RenderSettings::default()
+    };
+
+    let mut buffer = vec![raylib::prelude::Color::BLACK; (WIDTH * HEIGHT) as usize];
+    renderer::render_scene(&scene, &camera, &mut buffer, WIDTH, HEIGHT, &settings);
+
+    let path = Path::new("renders/minimal_scene.png");
+    screenshot::save_png(&buffer, WIDTH, HEIGHT, path)?;
+    println!("Saved {}", path.display());
+    Ok(())
+}
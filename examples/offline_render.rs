@@ -0,0 +1,63 @@
+//! Offline render of a scene file at export quality, like `--export-frames` does for one frame:
+//! soft shadows, ambient occlusion, a progress bar and Ctrl+C to stop early.
+//!
+//! cargo run --release --example offline_render -- [SCENE] [DAY_FRACTION]
+//! (defaults: assets/scenes/diorama.toml at 0.3, mid-afternoon)
+
+use std::env;
+use std::path::Path;
+
+use minecraft_raytracer::camera::Camera;
+use minecraft_raytracer::progress;
+use minecraft_raytracer::renderer::{self, OFFLINE_LENS_SAMPLES, RenderSettings};
+use minecraft_raytracer::scene_file::SceneFile;
+use minecraft_raytracer::screenshot;
+use minecraft_raytracer::utils::{Degrees, Vec3};
+
+const WIDTH: i32 = 1280;
+const HEIGHT: i32 = 720;
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = env::args().collect();
+    let scene_path = args.get(1).map(String::as_str).unwrap_or("assets/scenes/diorama.toml");
+    let day: f32 = match args.get(2) {
+        Some(day) => day.parse().map_err(|_| format!("Invalid day fraction '{}'", day))?,
+        None => 0.3,
+    };
+
+    let mut scene = SceneFile::load(Path::new(scene_path))?.build()?;
+    scene.time.day_fraction = day.rem_euclid(1.0);
+    scene.update_sun_position();
+
+    // The scene's first camera if it has one, otherwise the app's starting view
+    let aspect = WIDTH as f32 / HEIGHT as f32;
+    let mut camera = Camera::new(
+        scene.to_render(Vec3::new(12.0, 9.0, 12.0)),
+        scene.to_render(Vec3::new(0.0, 1.0, 0.0)),
+        Degrees(60.0),
+        aspect,
+    );
+    if let Some(view) = scene.viewpoints.first() {
+        view.apply(&mut camera);
+    }
+
+    let settings = RenderSettings {
+        render_scale: 1,
+        frame_budget: 0.0,
+        ray_budget: 0,
+        shadow_samples: 16,
+        ao_samples: 16,
+        lens_samples: OFFLINE_LENS_SAMPLES,
+        ..RenderSettings::default()
+    };
+
+    progress::install_interrupt_handler();
+    let mut buffer = vec![raylib::prelude::Color::BLACK; (WIDTH * HEIGHT) as usize];
+    let finished = renderer::render_scene_with_progress(&scene, &camera, &mut buffer, WIDTH, HEIGHT, &settings, "offline_render", None);
+
+    let path = Path::new("renders/offline_render.png");
+    let path = if finished { path.to_path_buf() } else { progress::partial_path(path) };
+    screenshot::save_png(&buffer, WIDTH, HEIGHT, &path)?;
+    println!("Saved {}", path.display());
+    Ok(())
+}
@@ -0,0 +1,93 @@
+//! Rolling hills from a noise heightmap, with a lake filling the low ground, built block by block
+//! with `SceneBuilder::with_cube` and rendered to `renders/procedural_terrain.png`.
+//!
+//! cargo run --release --example procedural_terrain -- [SEED]
+
+use std::env;
+use std::path::Path;
+
+use minecraft_raytracer::camera::Camera;
+use minecraft_raytracer::cube::Cube;
+use minecraft_raytracer::material::Material;
+use minecraft_raytracer::noise::fbm;
+use minecraft_raytracer::palette;
+use minecraft_raytracer::renderer::{self, RenderSettings};
+use minecraft_raytracer::scene_builder::SceneBuilder;
+use minecraft_raytracer::screenshot;
+use minecraft_raytracer::texture_cache;
+use minecraft_raytracer::utils::{Degrees, Real, Vec3};
+
+const WIDTH: i32 = 800;
+const HEIGHT: i32 = 600;
+const RADIUS: i32 = 16;       // The terrain covers -RADIUS..RADIUS on x and z
+const NOISE_SCALE: f32 = 0.08; // Noise cells per block: hills about a dozen blocks across
+const MAX_HEIGHT: f32 = 7.0;  // Blocks above the lowest ground
+const SEA_LEVEL: i32 = 2;     // Columns below this are under water
+const TREE_LINE: i32 = 5;     // Columns this high get stone instead of grass
+
+fn main() -> Result<(), String> {
+    let seed: f32 = match env::args().nth(1) {
+        Some(seed) => seed.parse().map_err(|_| format!("Invalid seed '{}'", seed))?,
+        None => 0.0,
+    };
+
+    // Each material goes into the scene's palette once; the cubes refer to it by id
+    let mut builder = SceneBuilder::new();
+    let grass = builder.material(Material::new(palette::GRASS_GREEN).with_texture(texture_cache::load("assets/textures/grass.jpg")));
+    let grass_side = builder.material(Material::new(palette::GRASS_SIDE_GREEN).with_texture(texture_cache::load("assets/textures/grass_side.jpg")));
+    let dirt = builder.material(Material::new(palette::DIRT_BROWN).with_texture(texture_cache::load("assets/textures/dirt.jpg")));
+    let stone = builder.material(Material::new(palette::STONE_GRAY).with_texture(texture_cache::load("assets/textures/stone.jpg")));
+    let water = builder.material(
+        Material::new(palette::WATER_BLUE)
+            .with_transparency(0.85, 1.33)
+            .with_absorption(palette::WATER_DEPTH_TINT, 0.8)
+            .with_reflectivity(0.3)
+            .with_specular(0.8, 64.0)
+            .with_waves(1.0),
+    );
+
+    for x in -RADIUS..RADIUS {
+        for z in -RADIUS..RADIUS {
+            // Offsetting the noise by the seed gives a different landscape for each one
+            let noise = fbm(x as f32 * NOISE_SCALE + seed * 17.0, z as f32 * NOISE_SCALE - seed * 31.0, 4);
+            let height = (noise * MAX_HEIGHT).round() as i32;
+            let at = |y: i32| Vec3::new(x as Real, y as Real + 0.5, z as Real);
+
+            for y in 0..height {
+                builder = builder.with_cube(Cube::new(at(y), 1.0, if y + 3 < height { stone } else { dirt }));
+            }
+            let top = if height >= TREE_LINE {
+                Cube::new(at(height), 1.0, stone)
+            } else {
+                Cube::new_multi_texture(at(height), 1.0, grass, grass_side, dirt)
+            };
+            builder = builder.with_cube(top);
+            for y in height + 1..SEA_LEVEL {
+                builder = builder.with_cube(Cube::new(at(y), 1.0, water));
+            }
+        }
+    }
+    let scene = builder.build();
+
+    let camera = Camera::new(
+        Vec3::new(24.0, 18.0, 24.0),
+        Vec3::new(0.0, 2.0, 0.0),
+        Degrees(55.0),
+        WIDTH as f32 / HEIGHT as f32,
+    );
+    let settings = RenderSettings {
+        render_scale: 1,
+        frame_budget: 0.0,
+        ray_budget: 0,
+        shadow_samples: 4,
+        ..RenderSettings::default()
+    };
+
+    let mut buffer = vec![raylib::prelude::Color::BLACK; (WIDTH * HEIGHT) as usize];
+    renderer::render_scene(&scene, &camera, &mut buffer, WIDTH, HEIGHT, &settings);
+
+    let path = Path::new("renders/procedural_terrain.png");
+    screenshot::save_png(&buffer, WIDTH, HEIGHT, path)?;
+    println!("Saved {} ({} blocks)", path.display(), scene.grid.cube_count());
+    Ok(())
+}
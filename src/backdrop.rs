@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::ray::Ray;
+use crate::texture::Texture;
+use crate::utils::{Vec3, narrow};
+
+/// How the backdrop image is laid out behind the scene
+#[derive(Clone, Copy)]
+pub enum BackdropMapping {
+    /// Pinned to the screen like a photo behind the render; moves with the camera
+    Screen,
+    /// Hung in the world on an endless plane (the image's edge pixels stretch out past its size)
+    Plane { center: Vec3, normal: Vec3, width: f32, height: f32 },
+}
+
+/// Fixed image shown where rays miss the scene, in place of the skybox
+/// (for compositing renders over photos or concept art)
+#[derive(Clone)]
+pub struct Backdrop {
+    pub texture: Arc<Texture>,
+    pub mapping: BackdropMapping,
+}
+
+impl Backdrop {
+    pub fn new(texture: impl Into<Arc<Texture>>, mapping: BackdropMapping) -> Self {
+        Self {
+            texture: texture.into(),
+            mapping,
+        }
+    }
+
+    /// Backdrop color behind a ray, or None when it misses the plate (plane behind the ray)
+    pub fn sample(&self, ray: &Ray, camera: &Camera) -> Option<Color> {
+        let (u, v) = match self.mapping {
+            BackdropMapping::Screen => {
+                // Where the ray's direction lands on the screen; reflections and refractions
+                // pick up the part of the photo they bend toward
+                let mut view = camera.world_to_view(camera.position + ray.direction);
+                view.z = view.z.max(0.001); // Pointing backward: clamp to the image edges
+                camera.view_to_screen(view)
+            }
+            BackdropMapping::Plane { center, normal, width, height } => {
+                let denom = ray.direction.dot(&normal);
+                if denom.abs() < 1e-6 {
+                    return None;
+                }
+                let t = (center - ray.origin).dot(&normal) / denom;
+                if t <= 0.0 {
+                    return None;
+                }
+                // Image "up" follows world up; a floor/ceiling plate uses -z instead
+                let reference = if normal.y.abs() > 0.99 { Vec3::new(0.0, 0.0, -1.0) } else { Vec3::new(0.0, 1.0, 0.0) };
+                let right = reference.cross(&normal).normalize();
+                let up = normal.cross(&right);
+                let local = ray.at(t) - center;
+                (narrow(local.dot(&right)) / width + 0.5, 0.5 - narrow(local.dot(&up)) / height)
+            }
+        };
+        Some(self.texture.sample(u, v))
+    }
+}
@@ -0,0 +1,148 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::camera::Camera;
+use crate::checkpoint::CheckpointOptions;
+use crate::denoise;
+use crate::post;
+use crate::renderer::{self, Band, RenderSettings};
+use crate::scene::Scene;
+
+const BAND_PIXELS: i32 = 4_000_000; // Pixels traced per band, about 64 MB of float and screen colors
+pub const LARGE_IMAGE: i64 = 16_000_000; // Exports above this many pixels go band by band (4K is 8.3M, 8K 33M)
+
+/// Downscaled copy of a banded render, small enough to show in the window
+pub struct Preview {
+    pub pixels: Vec<raylib::prelude::Color>,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// Trace a `width` x `height` image in horizontal bands and stream each one into a PNG as it
+/// finishes, so an 8K still needs the memory of a few hundred rows instead of the whole frame.
+/// The post passes (denoiser, bloom) need the pixels around each row: bands are traced with that
+/// many extra rows above and below, which are dropped again after the passes. Returns the image
+/// boxed down to `preview_size` for the window; Ctrl+C stops it and removes the unfinished file.
+/// With `checkpoint` each band keeps a checkpoint of its own until the whole file is written, so
+/// a resumed render only traces the bands (and tiles) it hadn't finished.
+#[allow(clippy::too_many_arguments)]
+pub fn render_to_png(
+    scene: &Scene,
+    camera: &Camera,
+    settings: &RenderSettings,
+    width: i32,
+    height: i32,
+    label: &str,
+    path: &Path,
+    preview_size: (i32, i32),
+    checkpoint: Option<&CheckpointOptions>,
+) -> Result<Preview, String> {
+    let settings = RenderSettings { render_scale: 1, ..*settings }; // Bands are in output pixels
+    let margin = margin(&settings, height);
+    // Even rows keep the bloom's 2x2 blocks lined up from one band to the next, and bands stay
+    // a few margins tall so the rows traced twice don't add more than half the work
+    let rows = ((BAND_PIXELS / width.max(1)).max(margin * 4).min(height).max(2) + 1) & !1;
+    let bands = (height + rows - 1) / rows;
+    let checkpoint = checkpoint.filter(|options| options.enabled()).map(|options| CheckpointOptions { keep_finished: true, ..*options });
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory '{}': {}", parent.display(), e))?;
+    }
+    let error = |e: png::EncodingError| format!("Failed to write '{}': {}", path.display(), e);
+    let file = File::create(path).map_err(|e| format!("Failed to create '{}': {}", path.display(), e))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut stream = encoder.write_header().and_then(|writer| writer.into_stream_writer()).map_err(error)?;
+
+    let mut preview = Downscale::new(width, height, preview_size);
+    let mut buffer = vec![];
+    let mut bytes = Vec::with_capacity((width * rows * 4) as usize);
+    for (index, top) in (0..height).step_by(rows as usize).enumerate() {
+        let bottom = (top + rows).min(height);
+        let start = (top - margin).max(0) & !1;
+        let end = (bottom + margin).min(height);
+        let band_settings = RenderSettings { band: Some(Band { top: start, image_height: height }), ..settings };
+
+        buffer.clear();
+        buffer.resize((width * (end - start)) as usize, raylib::prelude::Color::BLACK);
+        let band_label = format!("{} band {}/{}", label, index + 1, bands);
+        let band_checkpoint = CheckpointOptions::path_for_band(path, index);
+        let band_checkpoint = checkpoint.as_ref().map(|options| (band_checkpoint.as_path(), options));
+        if !renderer::render_scene_with_progress(scene, camera, &mut buffer, width, end - start, &band_settings, &band_label, band_checkpoint) {
+            drop(stream);
+            let _ = fs::remove_file(path);
+            return Err(format!("Interrupted, {} was not saved", path.display()));
+        }
+
+        // Only the band's own rows go out, the margins were just context for the post passes
+        let own = &buffer[((top - start) * width) as usize..((bottom - start) * width) as usize];
+        bytes.clear();
+        for (i, color) in own.iter().enumerate() {
+            bytes.extend_from_slice(&[color.r, color.g, color.b, color.a]);
+            preview.add(i as i32 % width, top + i as i32 / width, *color);
+        }
+        stream.write_all(&bytes).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+    }
+    stream.finish().map_err(error)?;
+    if checkpoint.is_some() {
+        for index in 0..bands as usize {
+            let _ = fs::remove_file(CheckpointOptions::path_for_band(path, index));
+        }
+    }
+    Ok(preview.finish())
+}
+
+/// Whether an export frame is big enough to be traced in bands
+pub fn needed(width: i32, height: i32) -> bool {
+    width as i64 * height as i64 > LARGE_IMAGE
+}
+
+// Extra rows each band needs above and below so the post passes see the same neighbours as
+// they would over the whole image
+fn margin(settings: &RenderSettings, height: i32) -> i32 {
+    let denoise = if settings.denoise { denoise::REACH } else { 0 };
+    let bloom = if settings.bloom > 0.0 { post::reach(height) } else { 0 };
+    denoise + bloom
+}
+
+// Box filter from the full image down to the preview, fed one pixel at a time
+struct Downscale {
+    sums: Vec<[u32; 5]>, // r, g, b, a and pixel count per preview pixel
+    scale: (i32, i32, i32, i32), // Full width and height, preview width and height
+}
+
+impl Downscale {
+    fn new(width: i32, height: i32, (preview_width, preview_height): (i32, i32)) -> Self {
+        let (preview_width, preview_height) = (preview_width.clamp(1, width.max(1)), preview_height.clamp(1, height.max(1)));
+        Self {
+            sums: vec![[0; 5]; (preview_width * preview_height) as usize],
+            scale: (width, height, preview_width, preview_height),
+        }
+    }
+
+    fn add(&mut self, x: i32, y: i32, color: raylib::prelude::Color) {
+        let (width, height, preview_width, preview_height) = self.scale;
+        let index = (y * preview_height / height * preview_width + x * preview_width / width) as usize;
+        let sum = &mut self.sums[index];
+        for (channel, value) in [color.r, color.g, color.b, color.a].into_iter().enumerate() {
+            sum[channel] += value as u32;
+        }
+        sum[4] += 1;
+    }
+
+    fn finish(self) -> Preview {
+        let (_, _, width, height) = self.scale;
+        let pixels = self
+            .sums
+            .iter()
+            .map(|sum| {
+                let count = sum[4].max(1);
+                let channel = |i: usize| (sum[i] / count) as u8;
+                raylib::prelude::Color::new(channel(0), channel(1), channel(2), channel(3))
+            })
+            .collect();
+        Preview { pixels, width, height }
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use serde::Deserialize;
+
+use crate::camera::Camera;
+use crate::checkpoint::CheckpointOptions;
+use crate::lighting::LightingPreset;
+use crate::progress;
+use crate::renderer::{self, OFFLINE_LENS_SAMPLES, RenderSettings};
+use crate::scene::Scene;
+use crate::scene_file::SceneFile;
+use crate::screenshot;
+use crate::utils::{Degrees, Real, Vec3};
+use crate::viewpoint::Viewpoint;
+
+/// Job list for `--batch jobs.toml` (see assets/portfolio_jobs.toml)
+#[derive(Deserialize)]
+pub struct BatchFile {
+    #[serde(default)]
+    pub jobs: Vec<JobDef>,
+}
+
+/// One still: which scene, from where, at what quality, into which file
+#[derive(Deserialize)]
+pub struct JobDef {
+    pub output: PathBuf,
+    pub scene: Option<PathBuf>,   // Defaults to the --scene / bundled diorama file
+    pub camera: Option<String>,   // Named [[cameras]] entry of the scene
+    pub position: Option<[f32; 3]>, // Explicit pose, overrides the named camera
+    pub target: Option<[f32; 3]>,
+    pub fov: Option<f32>,
+    #[serde(default = "default_size")]
+    pub size: [i32; 2],
+    #[serde(default)]
+    pub day: f32, // Day fraction, as in --day-range (0 = day, 0.5 = night)
+    pub lighting: Option<String>, // Lighting preset ("studio", "overcast", ...), overrides day and the scene's preset
+    #[serde(default = "default_quality")]
+    pub quality: String, // "low", "medium", "high" or "ultra" (same as keys 1/2/3/4)
+    #[serde(default)]
+    pub motion_blur_samples: u32,
+    #[serde(default)]
+    pub transparent: bool, // Sky pixels get alpha 0 for compositing
+    #[serde(default)]
+    pub path_samples: u32, // Paths per pixel for path-traced global illumination (0 = off)
+    #[serde(default)]
+    pub denoise: bool, // Edge-preserving filter over the finished image
+    #[serde(default)]
+    pub adaptive_threshold: f32, // Stop sampling pixels this certain of their brightness (0 = every ray)
+}
+
+fn default_size() -> [i32; 2] { [800, 600] }
+fn default_quality() -> String { "high".to_string() }
+
+impl BatchFile {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let source = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read job file '{}': {}", path.display(), e))?;
+        toml::from_str(&source).map_err(|e| format!("Failed to parse job file '{}': {}", path.display(), e))
+    }
+}
+
+pub fn render_scale(quality: &str) -> Result<i32, String> {
+    match quality {
+        "low" => Ok(4),
+        "medium" => Ok(2),
+        "high" | "ultra" => Ok(1),
+        other => Err(format!("Unknown quality '{}', expected low, medium, high or ultra", other)),
+    }
+}
+
+// Soft shadow rays per hit for a quality name (already validated by render_scale)
+pub fn shadow_samples(quality: &str) -> u32 {
+    match quality {
+        "low" => 1,
+        "medium" => 4,
+        _ => 16,
+    }
+}
+
+// Supersampling rays per pixel for a quality name (only ultra goes past one)
+pub fn aa_samples(quality: &str) -> u32 {
+    match quality {
+        "ultra" => 4,
+        _ => 0,
+    }
+}
+
+// Ambient occlusion rays per hit for a quality name
+pub fn ao_samples(quality: &str) -> u32 {
+    match quality {
+        "low" => 2,
+        "medium" => 8,
+        _ => 16,
+    }
+}
+
+fn vec3(v: [f32; 3]) -> Vec3 {
+    Vec3::new(v[0] as Real, v[1] as Real, v[2] as Real)
+}
+
+impl JobDef {
+    // Start from the default camera, then the named viewpoint, then any explicit pose fields.
+    // The default camera comes in world coordinates; poses in the job file are too.
+    fn camera(&self, scene: &Scene, default_camera: &Camera) -> Result<Camera, String> {
+        let mut camera = *default_camera;
+        camera.translate(-scene.origin);
+        camera.aspect = self.size[0] as f32 / self.size[1] as f32;
+        if let Some(ref name) = self.camera {
+            Viewpoint::find(&scene.viewpoints, name)?.apply(&mut camera);
+        }
+        if self.position.is_some() || self.target.is_some() || self.fov.is_some() {
+            camera.set_pose(
+                self.position.map_or(camera.position, |p| scene.to_render(vec3(p))),
+                self.target.map_or(camera.target, |t| scene.to_render(vec3(t))),
+                self.fov.map(Degrees).unwrap_or(camera.fov),
+            );
+        }
+        Ok(camera)
+    }
+
+    fn render(&self, scene: &mut Scene, default_camera: &Camera, checkpoint: &CheckpointOptions) -> Result<(), String> {
+        let [width, height] = self.size;
+        if width <= 0 || height <= 0 {
+            return Err(format!("Invalid size {}x{}", width, height));
+        }
+        let settings = RenderSettings {
+            render_scale: render_scale(&self.quality)?,
+            use_threading: true,
+            motion_blur_samples: self.motion_blur_samples,
+            frame_budget: 0.0,
+            ray_budget: 0,
+            transparent_background: self.transparent,
+            shadow_samples: shadow_samples(&self.quality),
+            ao_samples: ao_samples(&self.quality),
+            aa_samples: aa_samples(&self.quality),
+            path_tracing: self.path_samples > 0,
+            path_samples: self.path_samples,
+            denoise: self.denoise,
+            adaptive_threshold: self.adaptive_threshold,
+            lens_samples: OFFLINE_LENS_SAMPLES, // Only used by cameras with an aperture
+            ..RenderSettings::default()
+        };
+        let camera = self.camera(scene, default_camera)?;
+        let lighting = match self.lighting {
+            Some(ref name) => Some(LightingPreset::parse(name)?),
+            None => None,
+        };
+
+        // The scene is shared with later jobs, so its own preset is put back after rendering
+        let scene_lighting = scene.lighting.clone();
+        if lighting.is_some() {
+            scene.set_lighting(lighting);
+        }
+        scene.time.day_fraction = self.day.rem_euclid(1.0);
+        scene.time.days = self.day.div_euclid(1.0).max(0.0) as u32; // day = 3.9: the fourth night's moon
+        scene.update_sun_position();
+        scene.update_particles();
+
+        let mut buffer = vec![raylib::prelude::Color::BLACK; (width * height) as usize];
+        let checkpoint_path = CheckpointOptions::path_for(&self.output);
+        let finished = renderer::render_scene_with_progress(
+            scene,
+            &camera,
+            &mut buffer,
+            width,
+            height,
+            &settings,
+            "     ",
+            Some((&checkpoint_path, checkpoint)),
+        );
+        scene.lighting = scene_lighting;
+
+        if finished {
+            let _ = std::fs::remove_file(progress::partial_path(&self.output)); // Left over from an interrupted run
+            return screenshot::save_png(&buffer, width, height, &self.output);
+        }
+        let partial = progress::partial_path(&self.output);
+        screenshot::save_png(&buffer, width, height, &partial)?;
+        Err(format!("interrupted, partial image saved to {}", partial.display()))
+    }
+}
+
+/// Render every job in order. Failed jobs are reported and skipped so the rest still run;
+/// returns an error listing them at the end. `loaded` is the already built default scene.
+/// With --resume, jobs whose output already exists are skipped.
+pub fn run_batch(
+    path: &Path,
+    default_scene: &Path,
+    loaded: Scene,
+    default_camera: &Camera,
+    checkpoint: &CheckpointOptions,
+) -> Result<(), String> {
+    let batch = BatchFile::load(path)?;
+    progress::install_interrupt_handler();
+    if batch.jobs.is_empty() {
+        return Err(format!("No [[jobs]] in '{}'", path.display()));
+    }
+
+    // Scenes are built once and reused by every job that names them. Each may have its own
+    // render origin, so the default camera goes back to world coordinates first.
+    let mut default_camera = *default_camera;
+    default_camera.translate(loaded.origin);
+    let default_camera = &default_camera;
+    let mut scenes: HashMap<PathBuf, Scene> = HashMap::from([(default_scene.to_path_buf(), loaded)]);
+    let mut failed = Vec::new();
+    let mut rendered = 0;
+    let start = Instant::now();
+    let total = batch.jobs.len();
+
+    println!("Batch: {} jobs from {}", total, path.display());
+
+    for (index, job) in batch.jobs.iter().enumerate() {
+        let job_start = Instant::now();
+        println!(
+            "[{}/{}] {} ({}x{}, {})",
+            index + 1,
+            total,
+            job.output.display(),
+            job.size[0],
+            job.size[1],
+            job.quality
+        );
+
+        if checkpoint.resume && job.output.exists() {
+            rendered += 1;
+            println!("      already rendered, skipped");
+            continue;
+        }
+
+        let scene_path = job.scene.clone().unwrap_or_else(|| default_scene.to_path_buf());
+        let result = match scenes.get_mut(&scene_path) {
+            Some(scene) => job.render(scene, default_camera, checkpoint),
+            None => SceneFile::load(&scene_path)
+                .and_then(|file| file.build())
+                .and_then(|scene| job.render(scenes.entry(scene_path).or_insert(scene), default_camera, checkpoint)),
+        };
+
+        match result {
+            Ok(()) => {
+                rendered += 1;
+                println!("      done in {:.1}s", job_start.elapsed().as_secs_f32());
+            }
+            Err(e) => {
+                eprintln!("      failed: {}", e);
+                failed.push(job.output.display().to_string());
+            }
+        }
+
+        // Ctrl+C skips the remaining jobs too
+        if progress::interrupted() {
+            println!("Batch interrupted, {} jobs not started", total - index - 1);
+            break;
+        }
+    }
+
+    println!(
+        "Batch finished in {:.1}s: {} rendered, {} failed",
+        start.elapsed().as_secs_f32(),
+        rendered,
+        failed.len()
+    );
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Failed jobs: {}", failed.join(", ")))
+    }
+}
@@ -0,0 +1,230 @@
+use crate::camera::Camera;
+use crate::cube::Cube;
+use crate::intersection::Intersection;
+use crate::scene::Scene;
+use crate::scene_file::builtin_material_id;
+use crate::utils::{Real, Vec3};
+use crate::voxel_grid::{Cell, VoxelGrid};
+use crate::world_file::{BlockSource, WorldEdits};
+
+const GRAVITY: Real = 20.0;        // Blocks per second squared, about Minecraft's falling sand
+const MAX_FALL_SPEED: Real = 40.0; // Blocks per second
+const FALL_BELOW_WORLD: Real = 8.0; // A block with nothing under it is dropped this far below the lowest block
+const INSIDE: Real = 0.01;          // Depth under a hit face that is surely in the block hit
+
+// A block on its way down: not part of the scene's grid until it lands
+struct FallingBlock {
+    cube: Cube,
+    speed: Real,
+    source: BlockSource,
+}
+
+/// Edit mode: take out the block under the crosshair or put a sand block against the face under
+/// it. Blocks of a material with gravity (sand, gravel) that lose the block under them fall,
+/// drawn as entities while they drop, and go back into the scene's grid where they land, on the
+/// first block below or on the falling blocks that landed before them. Every edit is also kept as
+/// WorldEdits, which survive reloading the scene file and can be saved to a world file
+/// (world_file.rs).
+pub struct BlockEditor {
+    falling: Vec<FallingBlock>,
+    edits: WorldEdits,
+}
+
+impl BlockEditor {
+    pub fn new() -> Self {
+        Self { falling: Vec::new(), edits: WorldEdits::default() }
+    }
+
+    /// Start from edits loaded from a world file (already applied to the scene)
+    pub fn with_edits(mut self, edits: WorldEdits) -> Self {
+        self.edits = edits;
+        self
+    }
+
+    /// Everything edited so far, for saving
+    pub fn edits(&self) -> &WorldEdits {
+        &self.edits
+    }
+
+    /// Remove the unit block under the crosshair, returning its cell (None when nothing is
+    /// targeted). Gravity blocks resting on it start to fall.
+    pub fn break_block(&mut self, scene: &mut Scene, camera: &Camera) -> Option<Cell> {
+        let (cell, _) = Self::target(scene, camera)?;
+        self.edits.take(world_cell(scene, cell));
+        scene.grid.remove(cell);
+        scene.update_grid();
+        self.loosen(scene, (cell.0, cell.1 + 1, cell.2));
+        Some(cell)
+    }
+
+    /// Put a block of the built-in `material` ("sand", "gravel", ..., see
+    /// scene_file::builtin_material) against the face under the crosshair, returning its cell. A
+    /// gravity block placed over empty space falls right away.
+    pub fn place_block(&mut self, scene: &mut Scene, camera: &Camera, material: &str) -> Option<Cell> {
+        let (target, hit) = Self::target(scene, camera)?;
+        let position = scene.grid.get(target)?.position + hit.normal;
+        let cell = VoxelGrid::cell_of(position);
+        if scene.grid.get(cell).is_some() {
+            return None;
+        }
+        let block = builtin_material_id(&mut scene.materials, material)?;
+        self.edits.put(world_cell(scene, cell), BlockSource::Placed(material.to_string()));
+        scene.grid.add(Cube::new(position, 1.0, block));
+        scene.update_grid();
+        self.loosen(scene, cell);
+        Some(cell)
+    }
+
+    /// Advance the falling blocks by `delta_time` seconds. Returns the cells of the ones that
+    /// landed (and were put back into the scene).
+    pub fn update(&mut self, scene: &mut Scene, delta_time: f32) -> Vec<Cell> {
+        let mut landed = Vec::new();
+        // Lowest first, so a stack lands bottom up and each block comes to rest on the one below
+        self.falling.sort_by(|a, b| a.cube.position.y.total_cmp(&b.cube.position.y));
+        let mut still_falling = Vec::new();
+        for mut block in self.falling.drain(..) {
+            block.speed = (block.speed + GRAVITY * delta_time as Real).min(MAX_FALL_SPEED);
+            let y = block.cube.position.y - block.speed * delta_time as Real;
+            match Self::floor(scene, block.cube.position) {
+                Some(floor) if y <= floor => {
+                    block.cube.position.y = floor;
+                    let cell = VoxelGrid::cell_of(block.cube.position);
+                    self.edits.put(world_cell(scene, cell), block.source);
+                    landed.push(cell);
+                    scene.grid.add(block.cube);
+                }
+                None if y < scene.grid.height_range().0 as Real - FALL_BELOW_WORLD => {} // Fell out of the world
+                _ => {
+                    block.cube.position.y = y;
+                    still_falling.push(block);
+                }
+            }
+        }
+        self.falling = still_falling;
+        if !landed.is_empty() {
+            scene.update_grid(); // Once for the whole stack (floor reads the cells, not the traced lists)
+        }
+        landed
+    }
+
+    /// The blocks in the air, for Scene::entities
+    pub fn falling_cubes(&self) -> impl Iterator<Item = Cube> + '_ {
+        self.falling.iter().map(|block| block.cube)
+    }
+
+    /// Move the falling blocks with the world (floating origin)
+    pub fn translate(&mut self, shift: Vec3) {
+        for block in &mut self.falling {
+            block.cube.position = block.cube.position + shift;
+        }
+    }
+
+    /// Carry the edits over to a scene reloaded from the same file. The blocks still in the air
+    /// are dropped. Returns how many edits no longer fit the changed file (see WorldEdits::apply).
+    pub fn reapply(&mut self, scene: &mut Scene) -> Result<usize, String> {
+        self.falling.clear();
+        self.edits.apply(scene)
+    }
+
+    // Start the gravity block at `cell` falling if nothing holds it up, along with the gravity
+    // blocks stacked on it
+    fn loosen(&mut self, scene: &mut Scene, cell: Cell) {
+        let loose = |scene: &Scene, cell: Cell| scene.grid.get(cell).is_some_and(|cube| scene.materials[cube.material].gravity);
+        if !loose(scene, cell) || scene.grid.get((cell.0, cell.1 - 1, cell.2)).is_some() {
+            return;
+        }
+        let mut above = cell;
+        while loose(scene, above) {
+            let source = self.edits.take(world_cell(scene, above));
+            let cube = scene.grid.remove(above).unwrap();
+            self.falling.push(FallingBlock { cube, speed: 0.0, source });
+            above.1 += 1;
+        }
+        scene.update_grid();
+    }
+
+    // Height a block falling from `position` comes to rest at: one block above the highest block
+    // under it in its column, None when the column is empty all the way down
+    fn floor(scene: &Scene, position: Vec3) -> Option<Real> {
+        let (x, y, z) = VoxelGrid::cell_of(position);
+        let (min_y, _) = scene.grid.height_range();
+        (min_y..y)
+            .rev()
+            .filter_map(|below| scene.grid.get((x, below, z)))
+            .map(|cube| cube.position.y + 1.0)
+            .find(|&floor| floor <= position.y + 0.5)
+    }
+
+    // Nearest block along the center ray and where the ray hits it, if it's a unit block
+    fn target(scene: &Scene, camera: &Camera) -> Option<(Cell, Intersection)> {
+        let ray = camera.get_pinhole_ray(0.5, 0.5);
+        let hit = scene.intersect_blocks(&ray)?;
+        // A step back through the face that was hit lands inside the block
+        let cell = VoxelGrid::cell_of(hit.position - hit.normal * INSIDE);
+        let block = scene.grid.get(cell)?;
+        // Not a detail cube sharing (or poking into) the cell
+        block.intersect(&ray, &scene.materials).is_some_and(|own| own.t == hit.t).then_some((cell, hit))
+    }
+}
+
+// World cell of a render cell: edits are kept in world cells, which the floating origin doesn't move
+fn world_cell(scene: &Scene, cell: Cell) -> Cell {
+    let origin = VoxelGrid::cell_of(scene.origin);
+    (cell.0 + origin.0, cell.1 + origin.1, cell.2 + origin.2)
+}
+
+impl Default for BlockEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streaming::ChunkStreamer;
+    use crate::utils::Degrees;
+
+    // Scene of streamed hills around (0, 0, 0) and a camera looking down at the (3, 3) column
+    fn hills() -> (Scene, Camera, Cell) {
+        let mut scene = Scene::new();
+        scene.streaming = Some(ChunkStreamer::new(3, 2));
+        scene.stream_around(Vec3::zero());
+        let (top, _) = scene.grid.top_block(3, 3).unwrap();
+        let ground = Vec3::new(3.0, top as Real, 3.0);
+        let camera = Camera::new(ground + Vec3::new(0.0, 6.0, 0.5), ground, Degrees(60.0), 1.0);
+        (scene, camera, (3, top, 3))
+    }
+
+    #[test]
+    fn a_broken_streamed_block_stays_broken() {
+        let (mut scene, camera, top) = hills();
+        let mut editor = BlockEditor::new();
+        assert_eq!(editor.break_block(&mut scene, &camera), Some(top));
+
+        // The column stays loaded, so streaming leaves the hole alone
+        scene.stream_around(Vec3::new(16.0, 0.0, 0.0));
+        assert!(scene.grid.get(top).is_none());
+        // Dropped and loaded again the hills come back whole, and the edits put the hole back in
+        scene.stream_around(Vec3::new(160.0, 0.0, 0.0));
+        scene.stream_around(Vec3::zero());
+        assert!(scene.grid.get(top).is_some());
+        assert_eq!(editor.reapply(&mut scene), Ok(0));
+        assert!(scene.grid.get(top).is_none());
+    }
+
+    #[test]
+    fn a_placed_block_outlasts_its_column() {
+        let (mut scene, camera, top) = hills();
+        let mut editor = BlockEditor::new();
+        let placed = editor.place_block(&mut scene, &camera, "snow").unwrap();
+        assert_eq!(placed, (top.0, top.1 + 1, top.2));
+
+        scene.stream_around(Vec3::new(160.0, 0.0, 0.0));
+        assert!(scene.grid.get(top).is_none());
+        assert!(scene.grid.get(placed).is_some());
+        scene.stream_around(Vec3::zero());
+        assert!(scene.grid.get(top).is_some());
+        assert_eq!(BlockEditor::target(&scene, &camera).map(|(cell, _)| cell), Some(placed));
+    }
+}
@@ -0,0 +1,229 @@
+use wide::{CmpGe, CmpGt, CmpLe};
+
+use crate::ray::{Ray, T_MIN};
+use crate::utils::{Real, Vec3};
+
+// One SIMD register per coordinate: 8 boxes in the default f32 build, 4 with the `f64` feature
+#[cfg(not(feature = "f64"))]
+type Lanes = wide::f32x8;
+#[cfg(feature = "f64")]
+type Lanes = wide::f64x4;
+
+/// Boxes tested together by one slab test
+pub const LANES: usize = size_of::<Lanes>() / size_of::<Real>();
+
+/// Up to LANES axis-aligned boxes stored coordinate by coordinate (every min x, then every min y,
+/// ...), so the slab test runs on all of them at once instead of box by box
+#[derive(Clone)]
+pub struct BoxBatch {
+    min: [[Real; LANES]; 3],
+    max: [[Real; LANES]; 3],
+    len: usize,
+}
+
+/// A ray's origin and inverse direction repeated across the lanes, set up once per ray
+pub struct RayLanes {
+    origin: [Lanes; 3],
+    inv_direction: [Lanes; 3],
+}
+
+impl RayLanes {
+    pub fn new(ray: &Ray) -> Self {
+        let (o, d) = (ray.origin, ray.direction);
+        Self {
+            origin: [Lanes::splat(o.x), Lanes::splat(o.y), Lanes::splat(o.z)],
+            inv_direction: [Lanes::splat(1.0 / d.x), Lanes::splat(1.0 / d.y), Lanes::splat(1.0 / d.z)],
+        }
+    }
+}
+
+/// A packet of rays from one origin (neighbouring camera rays) taken as a whole: per axis, the
+/// range of the rays' inverse directions, all of one sign. Each ray's slab distances to a box lie
+/// between the ones worked out from the ends of those ranges, so boxes that every ray misses are
+/// found with one test instead of one per ray.
+pub struct RayPacket {
+    origin: [Real; 3],
+    inv_min: [Real; 3],
+    inv_max: [Real; 3],
+}
+
+impl RayPacket {
+    /// None for divergent rays: different origins (lens samples), or rays going both ways along
+    /// an axis, whose range of inverse directions would run through infinity
+    pub fn new(rays: &[Ray]) -> Option<Self> {
+        let origin = rays.first()?.origin;
+        let (mut inv_min, mut inv_max) = ([Real::INFINITY; 3], [Real::NEG_INFINITY; 3]);
+        for ray in rays {
+            if ray.origin != origin {
+                return None;
+            }
+            for (axis, direction) in [ray.direction.x, ray.direction.y, ray.direction.z].into_iter().enumerate() {
+                inv_min[axis] = inv_min[axis].min(1.0 / direction);
+                inv_max[axis] = inv_max[axis].max(1.0 / direction);
+            }
+        }
+        let one_sign = (0..3).all(|axis| inv_min[axis].is_finite() && inv_max[axis].is_finite() && (inv_min[axis] > 0.0) == (inv_max[axis] > 0.0));
+        one_sign.then_some(Self { origin: [origin.x, origin.y, origin.z], inv_min, inv_max })
+    }
+
+    /// Lower bound on where the rays enter the box and upper bound on where they leave it; the
+    /// box is missed by all of them when the first is past the second
+    pub fn slab_bounds(&self, min: Vec3, max: Vec3) -> (Real, Real) {
+        let (mut near, mut far) = (Real::NEG_INFINITY, Real::INFINITY);
+        for (axis, (low, high)) in [(min.x, max.x), (min.y, max.y), (min.z, max.z)].into_iter().enumerate() {
+            let (inv_min, inv_max) = (self.inv_min[axis], self.inv_max[axis]);
+            // Rays going the positive way enter through the low plane, the others through the high one
+            let (entry, exit) = if inv_min > 0.0 { (low, high) } else { (high, low) };
+            let (entry, exit) = (entry - self.origin[axis], exit - self.origin[axis]);
+            near = near.max((entry * inv_min).min(entry * inv_max));
+            far = far.min((exit * inv_min).max(exit * inv_max));
+        }
+        (near, far)
+    }
+}
+
+impl BoxBatch {
+    pub fn new() -> Self {
+        Self { min: [[0.0; LANES]; 3], max: [[0.0; LANES]; 3], len: 0 }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == LANES
+    }
+
+    /// Put a box in the next free lane (the batch must not be full)
+    pub fn push(&mut self, min: Vec3, max: Vec3) {
+        for (axis, (low, high)) in [(min.x, max.x), (min.y, max.y), (min.z, max.z)].into_iter().enumerate() {
+            self.min[axis][self.len] = low;
+            self.max[axis][self.len] = high;
+        }
+        self.len += 1;
+    }
+
+    /// Lanes of the boxes the ray may hit nearer than `max_t`. Same arithmetic as Cube::intersect,
+    /// and a box is only left out when that slab test would miss it too, so lanes it can't decide
+    /// (NaN from a ray running exactly along a face) are kept for the full test.
+    pub fn hits(&self, ray: &RayLanes, max_t: Real) -> impl Iterator<Item = usize> {
+        let (mut near, mut far) = (Lanes::splat(Real::NEG_INFINITY), Lanes::splat(Real::INFINITY));
+        for axis in 0..3 {
+            let t0 = (Lanes::from(self.min[axis]) - ray.origin[axis]) * ray.inv_direction[axis];
+            let t1 = (Lanes::from(self.max[axis]) - ray.origin[axis]) * ray.inv_direction[axis];
+            near = near.max(t0.min(t1));
+            far = far.min(t0.max(t1));
+        }
+        let missed = missed(near, far, max_t);
+        (0..self.len).filter(move |&lane| missed & (1 << lane) == 0)
+    }
+
+    /// Whether any ray of the packet may hit one of the boxes nearer than `max_t`: the slab test
+    /// of hits run on RayPacket's bounds, so a batch it rules out is missed by every ray
+    pub fn packet_hits(&self, packet: &RayPacket, max_t: Real) -> bool {
+        let (mut near, mut far) = (Lanes::splat(Real::NEG_INFINITY), Lanes::splat(Real::INFINITY));
+        for axis in 0..3 {
+            let (inv_min, inv_max) = (packet.inv_min[axis], packet.inv_max[axis]);
+            let (entry, exit) = if inv_min > 0.0 { (self.min[axis], self.max[axis]) } else { (self.max[axis], self.min[axis]) };
+            let origin = Lanes::splat(packet.origin[axis]);
+            let (entry, exit) = (Lanes::from(entry) - origin, Lanes::from(exit) - origin);
+            let (inv_min, inv_max) = (Lanes::splat(inv_min), Lanes::splat(inv_max));
+            near = near.max((entry * inv_min).min(entry * inv_max));
+            far = far.min((exit * inv_min).max(exit * inv_max));
+        }
+        !missed(near, far, max_t) & ((1 << self.len) - 1) != 0
+    }
+}
+
+// Bit mask of the lanes whose box is behind the origin, entered after leaving, or entered past max_t
+fn missed(near: Lanes, far: Lanes, max_t: Real) -> u32 {
+    let missed = far.cmp_le(Lanes::splat(T_MIN)) | near.cmp_gt(far) | near.cmp_ge(Lanes::splat(max_t));
+    missed.move_mask() as u32
+}
+
+impl Default for BoxBatch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::cube::Cube;
+    use crate::material::Material;
+    use crate::material_palette::MaterialPalette;
+    use crate::utils::numbers;
+
+    // A full batch of cubes (some thin, some big) in a 10 block wide box around the origin
+    fn cubes(next: &mut impl FnMut() -> Real, materials: &mut MaterialPalette) -> (BoxBatch, Vec<Cube>) {
+        let id = materials.add(Material::new(Color::new(0.5, 0.5, 0.5)));
+        let mut batch = BoxBatch::new();
+        let mut cubes = Vec::new();
+        while !batch.is_full() {
+            let position = Vec3::new((next() * 10.0 - 5.0).round(), (next() * 10.0 - 5.0).round(), (next() * 10.0 - 5.0).round());
+            let cube = Cube::new(position, [1.0, 0.2, 3.0][cubes.len() % 3], id);
+            let half = Vec3::new(cube.size, cube.size, cube.size) * 0.5;
+            batch.push(cube.position - half, cube.position + half);
+            cubes.push(cube);
+        }
+        (batch, cubes)
+    }
+
+    // Rays from around the boxes, half of them running along an axis or level
+    fn ray(next: &mut impl FnMut() -> Real, i: usize) -> Ray {
+        let origin = Vec3::new(next() * 16.0 - 8.0, next() * 16.0 - 8.0, next() * 16.0 - 8.0);
+        let direction = match i % 6 {
+            0 => Vec3::new(1.0, 0.0, 0.0),
+            1 => Vec3::new(0.0, -1.0, 0.0),
+            2 => Vec3::new(next() - 0.5, 0.0, next() - 0.5),
+            _ => Vec3::new(next() - 0.5, next() - 0.5, next() - 0.5),
+        };
+        // Some on the face planes of the unit cubes, where the slab test divides zero by zero
+        let origin = if i % 12 < 2 { Vec3::new(origin.x.round() + 0.5, origin.y.round() + 0.5, origin.z.round() + 0.5) } else { origin };
+        Ray::new(origin, direction.normalize())
+    }
+
+    #[test]
+    fn batched_slab_tests_agree_with_cubes() {
+        let mut next = numbers(5);
+        let mut materials = MaterialPalette::new();
+        let mut hit_count = 0;
+        for round in 0..200 {
+            let (batch, cubes) = cubes(&mut next, &mut materials);
+            for i in 0..50 {
+                let ray = ray(&mut next, i);
+                let max_t = if round % 2 == 0 { Real::INFINITY } else { 6.0 };
+                let lanes: Vec<usize> = batch.hits(&RayLanes::new(&ray), max_t).collect();
+                for (lane, cube) in cubes.iter().enumerate() {
+                    let hit = cube.intersect(&ray, &materials).is_some_and(|hit| hit.t < max_t);
+                    // Never a hit left out. A lane is only kept without one for a ray along an axis
+                    // (undecided), or from inside a cube it leaves past max_t.
+                    assert!(!hit || lanes.contains(&lane), "ray {:?} misses cube {:?}", ray, cube.position);
+                    let along_axis = [ray.direction.x, ray.direction.y, ray.direction.z].contains(&0.0);
+                    let offset = ray.origin - cube.position;
+                    let inside = offset.x.abs().max(offset.y.abs()).max(offset.z.abs()) < cube.size * 0.5;
+                    assert!(hit || !lanes.contains(&lane) || along_axis || inside, "ray {:?} keeps cube {:?}", ray, cube.position);
+                    hit_count += hit as usize;
+                }
+            }
+        }
+        assert!(hit_count > 50 * LANES, "only {} hits", hit_count); // Enough to have tested something
+    }
+
+    #[test]
+    fn packets_keep_every_batch_a_ray_hits() {
+        let mut next = numbers(9);
+        let mut materials = MaterialPalette::new();
+        for _ in 0..300 {
+            let (batch, cubes) = cubes(&mut next, &mut materials);
+            // A 4x4 fan of rays from one point, as a camera packet
+            let origin = Vec3::new(next() * 16.0 - 8.0, 9.0, next() * 16.0 - 8.0);
+            let center = Vec3::new(-origin.x, -9.0, -origin.z).normalize();
+            let rays: Vec<Ray> = (0..16)
+                .map(|i| Ray::new(origin, (center + Vec3::new((i % 4) as Real * 0.03, 0.0, (i / 4) as Real * 0.03)).normalize()))
+                .collect();
+            let Some(packet) = RayPacket::new(&rays) else { continue };
+            let any_hit = rays.iter().any(|ray| cubes.iter().any(|cube| cube.intersect(ray, &materials).is_some()));
+            assert!(!any_hit || batch.packet_hits(&packet, Real::INFINITY));
+        }
+    }
+}
@@ -1,35 +1,105 @@
-use crate::utils::Vec3;
+use crate::utils::{Degrees, Radians, Real, TAU, Vec3, narrow, random_f32};
 use crate::ray::Ray;
 
+// The orbit stops this far above or below the target. The basis below stays well defined all the
+// way to 90 degrees, but past it the orbit would flip over the top and come down behind.
+const MAX_PITCH: Degrees = Degrees(89.5);
+
 pub struct Camera {
     pub position: Vec3,
     pub target: Vec3,
-    pub fov: f32,
+    pub fov: Degrees, // Vertical field of view
     pub aspect: f32,
+    pub roll: Degrees,     // Tilt around the view direction, positive = clockwise (dutch angles)
+    pub horizon_lock: bool, // Orbiting levels the roll back to zero
+
+    // Depth of field: rays start anywhere on a lens this wide and meet again at focus_distance
+    pub aperture: f32,        // Lens radius in blocks (0 = pinhole, everything sharp)
+    pub focus_distance: Real, // Distance in front of the camera that stays sharp
 
     // Orbital camera parameters
-    distance: f32,
-    horizontal_angle: f32,
-    vertical_angle: f32,
+    distance: Real,
+    horizontal_angle: Radians,
+    vertical_angle: Radians,
+
+    // Pose at the moment the shutter opened (start of the frame), for motion blur
+    shutter_position: Vec3,
+    shutter_target: Vec3,
+    shutter_roll: Degrees,
 }
 
 impl Camera {
-    pub fn new(position: Vec3, target: Vec3, fov: f32, aspect: f32) -> Self {
-        let distance = (position - target).length();
-        let direction = (position - target).normalize();
-
-        let horizontal_angle = direction.z.atan2(direction.x);
-        let vertical_angle = direction.y.asin();
-
-        Self {
+    pub fn new(position: Vec3, target: Vec3, fov: Degrees, aspect: f32) -> Self {
+        let mut camera = Self {
             position,
             target,
             fov,
             aspect,
-            distance,
-            horizontal_angle,
-            vertical_angle,
+            roll: Degrees(0.0),
+            horizon_lock: true,
+            aperture: 0.0,
+            focus_distance: 1.0,
+            distance: 1.0,
+            horizontal_angle: Radians(0.0),
+            vertical_angle: Radians(0.0),
+            shutter_position: position,
+            shutter_target: target,
+            shutter_roll: Degrees(0.0),
+        };
+        camera.set_pose(position, target, fov);
+        camera.focus_distance = camera.distance; // The target is in focus
+        camera.begin_shutter();
+        camera
+    }
+
+    /// Record the current pose as the shutter-open pose.
+    /// Call once per frame before applying input so get_ray_at_time can blend across the motion.
+    pub fn begin_shutter(&mut self) {
+        self.shutter_position = self.position;
+        self.shutter_target = self.target;
+        self.shutter_roll = self.roll;
+    }
+
+    /// Move the whole pose, shutter start included (render space shifted under the camera)
+    pub fn translate(&mut self, shift: Vec3) {
+        self.position = self.position + shift;
+        self.target = self.target + shift;
+        self.shutter_position = self.shutter_position + shift;
+        self.shutter_target = self.shutter_target + shift;
+    }
+
+    /// How fast the camera moved since begin_shutter (largest of position/target speed, units per second)
+    pub fn shutter_speed(&self, delta_time: f32) -> f32 {
+        let moved = (self.position - self.shutter_position)
+            .length()
+            .max((self.target - self.shutter_target).length());
+        narrow(moved) / delta_time.max(1e-4)
+    }
+
+    /// Jump to an explicit pose (camera paths). The orbit parameters follow so manual control continues from here.
+    /// A camera sitting on its target has no view direction, so it's backed off one block along
+    /// its last orbit direction instead.
+    pub fn set_pose(&mut self, position: Vec3, target: Vec3, fov: Degrees) {
+        self.target = target;
+        self.fov = fov;
+
+        let offset = position - target;
+        let distance = offset.length();
+        if distance < 1e-6 {
+            self.distance = 1.0;
+            self.update_position_and_target();
+            return;
+        }
+        self.position = position;
+        self.distance = distance;
+
+        // Straight above or below the target the heading is undefined: keep the last one
+        let direction = offset / distance;
+        if direction.x.abs() > 1e-6 || direction.z.abs() > 1e-6 {
+            self.horizontal_angle = Radians(direction.z.atan2(direction.x));
         }
+        let max_pitch = MAX_PITCH.radians();
+        self.vertical_angle = Radians(direction.y.clamp(-1.0, 1.0).asin()).clamp(-max_pitch, max_pitch);
     }
 
     // Get the forward direction vector (where camera is looking)
@@ -37,34 +107,53 @@ impl Camera {
         (self.target - self.position).normalize()
     }
 
-    // Get the right direction vector (perpendicular to forward and up)
+    // Level right direction, for walking sideways (roll doesn't tilt the movement)
     fn get_right(&self) -> Vec3 {
-        let forward = self.get_forward();
-        forward.cross(&Vec3::new(0.0, 1.0, 0.0)).normalize()
+        self.axes(self.get_forward(), Degrees(0.0)).0
     }
 
-    // Get the up direction vector
-    fn get_up(&self) -> Vec3 {
-        let forward = self.get_forward();
-        let right = self.get_right();
-        right.cross(&forward).normalize()
+    /// Screen-up direction in the world, roll included (the raster preview's camera up vector)
+    pub fn up(&self) -> Vec3 {
+        self.axes(self.get_forward(), self.roll).1
+    }
+
+    // Camera right and up for a view direction: level with the horizon, then turned around
+    // `forward` by `roll`. Looking (nearly) straight up or down the cross product with world up
+    // shrinks to nothing and its direction is noise, so the orbit heading supplies right
+    // instead. Both agree for orbit poses, so nothing jumps at the switch.
+    fn axes(&self, forward: Vec3, roll: Degrees) -> (Vec3, Vec3) {
+        let level = forward.cross(&Vec3::new(0.0, 1.0, 0.0));
+        let right = if level.length() > 1e-3 {
+            level.normalize()
+        } else {
+            Vec3::new(self.horizontal_angle.sin(), 0.0, -self.horizontal_angle.cos())
+        };
+        let up = right.cross(&forward).normalize();
+
+        let roll = roll.radians();
+        (right * roll.cos() - up * roll.sin(), up * roll.cos() + right * roll.sin())
     }
 
     // === Rotation methods (arrow keys) ===
-    pub fn rotate_around_target(&mut self, angle_delta: f32) {
-        self.horizontal_angle += angle_delta.to_radians();
+    pub fn rotate_around_target(&mut self, angle_delta: Degrees) {
+        self.horizontal_angle = self.horizontal_angle + angle_delta.radians();
         self.update_position_and_target();
     }
 
-    pub fn rotate_vertical(&mut self, angle_delta: f32) {
-        self.vertical_angle += angle_delta.to_radians();
-        self.vertical_angle = self.vertical_angle.clamp(-1.5, 1.5);
+    pub fn rotate_vertical(&mut self, angle_delta: Degrees) {
+        let max_pitch = MAX_PITCH.radians();
+        self.vertical_angle = (self.vertical_angle + angle_delta.radians()).clamp(-max_pitch, max_pitch);
         self.update_position_and_target();
     }
 
+    /// Tilt the view around its direction (dutch angle), kept within half a turn either way
+    pub fn roll_by(&mut self, angle_delta: Degrees) {
+        self.roll = (self.roll + angle_delta).clamp(Degrees(-180.0), Degrees(180.0));
+    }
+
     // === Zoom (UP/DOWN arrow or mouse wheel) ===
     pub fn zoom(&mut self, delta: f32) {
-        self.distance -= delta;
+        self.distance -= delta as Real;
         self.distance = self.distance.max(1.0).min(50.0);
         self.update_position_and_target();
     }
@@ -72,37 +161,37 @@ impl Camera {
     // === WASD movement ===
     pub fn move_forward(&mut self, amount: f32) {
         let forward = self.get_forward();
-        self.position = self.position + forward * amount;
-        self.target = self.target + forward * amount;
+        self.position = self.position + forward * amount as Real;
+        self.target = self.target + forward * amount as Real;
     }
 
     pub fn move_backward(&mut self, amount: f32) {
         let forward = self.get_forward();
-        self.position = self.position - forward * amount;
-        self.target = self.target - forward * amount;
+        self.position = self.position - forward * amount as Real;
+        self.target = self.target - forward * amount as Real;
     }
 
     pub fn strafe_left(&mut self, amount: f32) {
         let right = self.get_right();
-        self.position = self.position - right * amount;
-        self.target = self.target - right * amount;
+        self.position = self.position - right * amount as Real;
+        self.target = self.target - right * amount as Real;
     }
 
     pub fn strafe_right(&mut self, amount: f32) {
         let right = self.get_right();
-        self.position = self.position + right * amount;
-        self.target = self.target + right * amount;
+        self.position = self.position + right * amount as Real;
+        self.target = self.target + right * amount as Real;
     }
 
     // === Vertical movement (Q/E keys) ===
     pub fn move_up(&mut self, amount: f32) {
-        let up = Vec3::new(0.0, amount, 0.0);
+        let up = Vec3::new(0.0, amount as Real, 0.0);
         self.position = self.position + up;
         self.target = self.target + up;
     }
 
     pub fn move_down(&mut self, amount: f32) {
-        let down = Vec3::new(0.0, -amount, 0.0);
+        let down = Vec3::new(0.0, -amount as Real, 0.0);
         self.position = self.position + down;
         self.target = self.target + down;
     }
@@ -113,22 +202,153 @@ impl Camera {
         let z = self.distance * self.vertical_angle.cos() * self.horizontal_angle.sin();
 
         self.position = self.target + Vec3::new(x, y, z);
+        if self.horizon_lock {
+            self.roll = Degrees(0.0);
+        }
     }
 
-    // Generate a ray for pixel coordinates (u, v) in [0, 1]
+    // Generate a ray for pixel coordinates (u, v) in [0, 1], from a random point on the lens
     pub fn get_ray(&self, u: f32, v: f32) -> Ray {
-        let forward = (self.target - self.position).normalize();
-        let right = forward.cross(&Vec3::new(0.0, 1.0, 0.0)).normalize();
-        let up = right.cross(&forward).normalize();
+        self.through_lens(self.get_pinhole_ray(u, v), self.position, self.target)
+    }
+
+    /// Ray through the center of the lens, ignoring depth of field (picking, G-buffers, depth passes)
+    pub fn get_pinhole_ray(&self, u: f32, v: f32) -> Ray {
+        self.ray_from_pose(self.position, self.target, self.roll, u, v)
+    }
+
+    // Generate a ray at a point in the shutter interval (0.0 = shutter open, 1.0 = current pose)
+    pub fn get_ray_at_time(&self, u: f32, v: f32, time: f32) -> Ray {
+        let position = self.shutter_position + (self.position - self.shutter_position) * time as Real;
+        let target = self.shutter_target + (self.target - self.shutter_target) * time as Real;
+        let roll = self.shutter_roll + (self.roll - self.shutter_roll) * time;
+
+        let ray = self.ray_from_pose(position, target, roll, u, v).with_time(time);
+        self.through_lens(ray, position, target)
+    }
 
-        let fov_rad = self.fov.to_radians();
-        let half_height = (fov_rad / 2.0).tan();
+    /// Move the focus plane by a fraction of its distance (negative = nearer)
+    pub fn adjust_focus(&mut self, fraction: f32) {
+        self.focus_distance = (self.focus_distance * (1.0 + fraction as Real)).clamp(0.5, 200.0);
+    }
+
+    // Start the pinhole ray at a random point on the lens instead, aimed at where it crosses the
+    // focus plane: things at focus_distance stay sharp, nearer and farther ones blur
+    fn through_lens(&self, ray: Ray, position: Vec3, target: Vec3) -> Ray {
+        if self.aperture <= 0.0 {
+            return ray;
+        }
+        let forward = (target - position).normalize();
+        let (right, up) = self.axes(forward, Degrees(0.0)); // The lens is round, its roll doesn't matter
+        let focus = ray.at(self.focus_distance / ray.direction.dot(&forward));
+
+        // Uniform point on the lens disk
+        let radius = self.aperture as Real * (random_f32() as Real).sqrt();
+        let angle = TAU * random_f32() as Real;
+        let origin = position + right * (radius * angle.cos()) + up * (radius * angle.sin());
+        Ray { origin, direction: (focus - origin).normalize(), ..ray }
+    }
+
+    /// Angle (radians, small-angle) covered by one pixel row when rendering `rows` rows
+    pub fn pixel_spread(&self, rows: i32) -> Real {
+        2.0 * (self.fov * 0.5).radians().tan() / rows.max(1) as Real
+    }
+
+    /// World point in camera space: x right, y up, z forward (distance in front of the camera)
+    pub fn world_to_view(&self, point: Vec3) -> Vec3 {
+        let offset = point - self.position;
+        let forward = self.get_forward();
+        let (right, up) = self.axes(forward, self.roll);
+        Vec3::new(offset.dot(&right), offset.dot(&up), offset.dot(&forward))
+    }
+
+    /// Camera-space point (z > 0) to screen coordinates (u, v) in [0, 1], the inverse of get_ray
+    pub fn view_to_screen(&self, view: Vec3) -> (f32, f32) {
+        let half_height = narrow((self.fov * 0.5).radians().tan());
+        let half_width = self.aspect * half_height;
+
+        let u = (narrow(view.x / view.z) / half_width + 1.0) / 2.0;
+        let v = (1.0 - narrow(view.y / view.z) / half_height) / 2.0;
+        (u, v)
+    }
+
+    fn ray_from_pose(&self, position: Vec3, target: Vec3, roll: Degrees, u: f32, v: f32) -> Ray {
+        let forward = (target - position).normalize();
+        let (right, up) = self.axes(forward, roll);
+
+        let half_height = narrow((self.fov * 0.5).radians().tan());
         let half_width = self.aspect * half_height;
 
         let direction = forward
-            + right * (2.0 * u - 1.0) * half_width
-            + up * (1.0 - 2.0 * v) * half_height;
+            + right * ((2.0 * u - 1.0) * half_width) as Real
+            + up * ((1.0 - 2.0 * v) * half_height) as Real;
 
-        Ray::new(self.position, direction.normalize())
+        Ray::new(position, direction.normalize())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Vec3, b: Vec3) -> bool {
+        (a - b).length() < 1e-3
+    }
+
+    // The center ray runs at the target and the screen axes are a unit frame around it
+    fn assert_sound(camera: &Camera) {
+        let forward = (camera.target - camera.position).normalize();
+        let ray = camera.get_pinhole_ray(0.5, 0.5);
+        assert!(close(ray.direction, forward), "center ray {:?} vs {:?}", ray.direction, forward);
+        let (right, up) = camera.axes(forward, camera.roll);
+        for axis in [right, up] {
+            assert!((axis.length() - 1.0).abs() < 1e-3, "axis {:?}", axis);
+            assert!(axis.dot(&forward).abs() < 1e-3);
+        }
+        assert!(right.dot(&up).abs() < 1e-3);
+    }
+
+    #[test]
+    fn orbiting_over_the_top_stops_at_the_pole() {
+        let mut camera = Camera::new(Vec3::new(0.0, 0.0, 10.0), Vec3::zero(), Degrees(60.0), 1.5);
+        for _ in 0..40 {
+            camera.rotate_vertical(Degrees(5.0));
+            assert_sound(&camera);
+        }
+        // Clamped short of the top, still on the side it came up from
+        assert!(camera.position.y > 9.9 && camera.position.z > 0.0);
+        for _ in 0..80 {
+            camera.rotate_vertical(Degrees(-5.0));
+            assert_sound(&camera);
+        }
+        assert!(camera.position.y < -9.9 && camera.position.z > 0.0);
+    }
+
+    #[test]
+    fn turning_at_the_pole_turns_the_view() {
+        let mut camera = Camera::new(Vec3::new(0.0, 0.0, 10.0), Vec3::zero(), Degrees(60.0), 1.5);
+        camera.rotate_vertical(Degrees(90.0));
+        let mut right = camera.get_right();
+        for _ in 0..36 {
+            camera.rotate_around_target(Degrees(10.0));
+            assert_sound(&camera);
+            // Right follows the heading a step at a time, it never jumps
+            let turned = camera.get_right();
+            assert!((turned.dot(&right) - Degrees(10.0).radians().cos()).abs() < 1e-2);
+            right = turned;
+        }
+    }
+
+    #[test]
+    fn a_pose_straight_above_keeps_the_heading() {
+        let mut camera = Camera::new(Vec3::new(10.0, 0.0, 0.0), Vec3::zero(), Degrees(60.0), 1.0);
+        camera.rotate_around_target(Degrees(30.0));
+        let right = camera.get_right();
+        for position in [Vec3::new(0.0, 10.0, 0.0), Vec3::new(0.0, -10.0, 0.0)] {
+            camera.set_pose(position, Vec3::zero(), Degrees(60.0));
+            assert_sound(&camera);
+            assert!(close(camera.get_right(), right));
+            assert!(close(camera.position, position)); // Kept as given, not pulled off the pole
+        }
     }
 }
@@ -0,0 +1,206 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::camera::Camera;
+use crate::utils::{Degrees, Real, Vec3, narrow};
+
+/// Camera pose at a point in time along a path
+#[derive(Clone, Copy)]
+pub struct Keyframe {
+    pub time: f32, // Seconds from the start of the path
+    pub position: Vec3,
+    pub target: Vec3,
+    pub fov: Degrees,
+    pub roll: Degrees,
+}
+
+/// On-disk form of a keyframe (see assets/camera_path.toml)
+#[derive(Serialize, Deserialize)]
+struct KeyframeDef {
+    time: f32,
+    position: [Real; 3],
+    target: [Real; 3],
+    #[serde(default = "default_fov")]
+    fov: Degrees,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    roll: Degrees, // Dutch angle, positive = clockwise
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct CameraPathFile {
+    #[serde(default)]
+    keyframes: Vec<KeyframeDef>,
+}
+
+fn default_fov() -> Degrees { Degrees(70.0) }
+fn is_zero(value: &Degrees) -> bool { *value == Degrees(0.0) }
+
+/// Keyframed camera flight, smoothly interpolated with Catmull-Rom splines
+#[derive(Clone, Default)]
+pub struct CameraPath {
+    keyframes: Vec<Keyframe>, // Sorted by time
+}
+
+impl CameraPath {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let source = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read camera path '{}': {}", path.display(), e))?;
+        let file: CameraPathFile = toml::from_str(&source)
+            .map_err(|e| format!("Failed to parse camera path '{}': {}", path.display(), e))?;
+
+        let mut camera_path = Self::new();
+        for def in file.keyframes {
+            camera_path.add(Keyframe {
+                time: def.time,
+                position: Vec3::new(def.position[0], def.position[1], def.position[2]),
+                target: Vec3::new(def.target[0], def.target[1], def.target[2]),
+                fov: def.fov,
+                roll: def.roll,
+            });
+        }
+        Ok(camera_path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let file = CameraPathFile {
+            keyframes: self
+                .keyframes
+                .iter()
+                .map(|k| KeyframeDef {
+                    time: k.time,
+                    position: [k.position.x, k.position.y, k.position.z],
+                    target: [k.target.x, k.target.y, k.target.z],
+                    fov: k.fov,
+                    roll: k.roll,
+                })
+                .collect(),
+        };
+        let source = toml::to_string(&file).map_err(|e| e.to_string())?;
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create '{}': {}", parent.display(), e))?;
+        }
+        fs::write(path, source).map_err(|e| format!("Failed to write camera path '{}': {}", path.display(), e))
+    }
+
+    /// Insert a keyframe, keeping the list sorted by time
+    pub fn add(&mut self, keyframe: Keyframe) {
+        let index = self.keyframes.partition_point(|k| k.time <= keyframe.time);
+        self.keyframes.insert(index, keyframe);
+    }
+
+    /// Append the camera's current pose `spacing` seconds after the last keyframe
+    pub fn record(&mut self, camera: &Camera, spacing: f32) {
+        let time = self.keyframes.last().map_or(0.0, |k| k.time + spacing);
+        self.add(Keyframe {
+            time,
+            position: camera.position,
+            target: camera.target,
+            fov: camera.fov,
+            roll: camera.roll,
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.keyframes.clear();
+    }
+
+    /// Move every keyframe (files are in world coordinates, playback in the scene's render space)
+    pub fn translate(&mut self, shift: Vec3) {
+        for keyframe in &mut self.keyframes {
+            keyframe.position = keyframe.position + shift;
+            keyframe.target = keyframe.target + shift;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.keyframes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    pub fn start_time(&self) -> f32 {
+        self.keyframes.first().map_or(0.0, |k| k.time)
+    }
+
+    pub fn duration(&self) -> f32 {
+        match (self.keyframes.first(), self.keyframes.last()) {
+            (Some(first), Some(last)) => last.time - first.time,
+            _ => 0.0,
+        }
+    }
+
+    /// Interpolated pose at `time` (clamped to the path), or None without keyframes
+    pub fn sample(&self, time: f32) -> Option<Keyframe> {
+        let first = self.keyframes.first()?;
+        let last = self.keyframes.last()?;
+        if self.keyframes.len() == 1 || time <= first.time {
+            return Some(Keyframe { time, ..*first });
+        }
+        if time >= last.time {
+            return Some(Keyframe { time, ..*last });
+        }
+
+        // Segment k1 -> k2 containing time; the end keyframes are repeated as outer control points
+        let i = self.keyframes.partition_point(|k| k.time <= time) - 1;
+        let k0 = &self.keyframes[i.saturating_sub(1)];
+        let k1 = &self.keyframes[i];
+        let k2 = &self.keyframes[i + 1];
+        let k3 = &self.keyframes[(i + 2).min(self.keyframes.len() - 1)];
+
+        let span = (k2.time - k1.time).max(1e-6);
+        let t = ((time - k1.time) / span) as Real;
+
+        Some(Keyframe {
+            time,
+            position: catmull_rom_vec3(k0.position, k1.position, k2.position, k3.position, t),
+            target: catmull_rom_vec3(k0.target, k1.target, k2.target, k3.target, t),
+            fov: catmull_rom_degrees(k0.fov, k1.fov, k2.fov, k3.fov, t),
+            roll: catmull_rom_degrees(k0.roll, k1.roll, k2.roll, k3.roll, t),
+        })
+    }
+
+    /// Move the camera to the pose at `time`
+    pub fn apply(&self, camera: &mut Camera, time: f32) {
+        if let Some(pose) = self.sample(time) {
+            camera.set_pose(pose.position, pose.target, pose.fov);
+            camera.roll = pose.roll;
+        }
+    }
+}
+
+// Uniform Catmull-Rom: passes through p1 at t = 0 and p2 at t = 1
+fn catmull_rom(p0: Real, p1: Real, p2: Real, p3: Real, t: Real) -> Real {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * ((2.0 * p1)
+        + (-p0 + p2) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (-p0 + 3.0 * p1 - 3.0 * p2 + p3) * t3)
+}
+
+fn catmull_rom_degrees(p0: Degrees, p1: Degrees, p2: Degrees, p3: Degrees, t: Real) -> Degrees {
+    let t = narrow(t);
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (p1 * 2.0
+        + (p2 - p0) * t
+        + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+        + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+        * 0.5
+}
+
+fn catmull_rom_vec3(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, t: Real) -> Vec3 {
+    Vec3::new(
+        catmull_rom(p0.x, p1.x, p2.x, p3.x, t),
+        catmull_rom(p0.y, p1.y, p2.y, p3.y, t),
+        catmull_rom(p0.z, p1.z, p2.z, p3.z, t),
+    )
+}
@@ -0,0 +1,276 @@
+use std::fs;
+use std::hash::Hasher;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::color::Color;
+use crate::export::arg_value;
+
+const MAGIC: &[u8; 8] = b"MCRTCKPT";
+const FORMAT_VERSION: u32 = 1;
+const DEFAULT_INTERVAL: f32 = 60.0;
+
+/// `--checkpoint-every SECS` (0 = off) and `--resume`, shared by exports and batch jobs
+#[derive(Clone, Copy)]
+pub struct CheckpointOptions {
+    pub interval: f32,
+    pub resume: bool,
+    pub keep_finished: bool, // Save a finished render's checkpoint instead of dropping it (the bands of a large export)
+}
+
+impl CheckpointOptions {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let interval = match arg_value(args, "--checkpoint-every") {
+            Some(secs) => secs
+                .parse::<f32>()
+                .ok()
+                .filter(|secs| *secs >= 0.0)
+                .ok_or_else(|| format!("Invalid checkpoint interval '{}'", secs))?,
+            None => DEFAULT_INTERVAL,
+        };
+        Ok(Self {
+            interval,
+            resume: args.iter().any(|a| a == "--resume"),
+            keep_finished: false,
+        })
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.interval > 0.0
+    }
+
+    /// Checkpoint file kept next to an output image (frame_0003.png -> frame_0003.ckpt)
+    pub fn path_for(output: &Path) -> PathBuf {
+        output.with_extension("ckpt")
+    }
+
+    /// Checkpoint file of one band of a banded output image (frame_0003.png -> frame_0003.band2.ckpt)
+    pub fn path_for_band(output: &Path, band: usize) -> PathBuf {
+        output.with_extension(format!("band{}.ckpt", band))
+    }
+}
+
+/// FNV-1a, for the keys saved in checkpoint files: std's DefaultHasher may hash differently in
+/// another Rust release, which would make every saved checkpoint look like a different render
+pub struct KeyHasher(u64);
+
+impl KeyHasher {
+    pub fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Default for KeyHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for KeyHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Accumulated color and sample counts of a render in progress, saved to disk every
+/// `interval` seconds so an interrupted render can pick up where it stopped.
+/// Colors are stored per (scaled) pixel as sums, sample counts per tile.
+pub struct Checkpoint {
+    path: PathBuf,
+    key: u64, // Fingerprint of camera, time of day and settings; a mismatch means a different render
+    width: usize,
+    height: usize,
+    tile_size: usize,
+    samples: Vec<u32>,
+    accum: Vec<[f32; 3]>,
+    interval: f32,
+    last_save: Instant,
+    dirty: bool,
+}
+
+impl Checkpoint {
+    /// Start a fresh checkpoint, or continue the one on disk when resuming and it matches
+    pub fn open(path: PathBuf, key: u64, width: usize, height: usize, tile_size: usize, options: &CheckpointOptions) -> Self {
+        let tiles = width.div_ceil(tile_size) * height.div_ceil(tile_size);
+        let mut checkpoint = Self {
+            path,
+            key,
+            width,
+            height,
+            tile_size,
+            samples: vec![0; tiles],
+            accum: vec![[0.0; 3]; width * height],
+            interval: options.interval,
+            last_save: Instant::now(),
+            dirty: false,
+        };
+
+        if options.resume && checkpoint.path.exists() {
+            match checkpoint.load() {
+                Ok(()) => println!(
+                    "  Resuming from {} ({}/{} tiles done)",
+                    checkpoint.path.display(),
+                    checkpoint.tiles_done(),
+                    tiles
+                ),
+                Err(e) => {
+                    eprintln!("Warning: {}, starting over", e);
+                    checkpoint.samples.fill(0);
+                    checkpoint.accum.fill([0.0; 3]);
+                }
+            }
+        }
+        checkpoint
+    }
+
+    pub fn tile_done(&self, tile: usize) -> bool {
+        self.samples[tile] > 0
+    }
+
+    pub fn tiles_done(&self) -> usize {
+        self.samples.iter().filter(|&&samples| samples > 0).count()
+    }
+
+    /// Add a finished tile: (x, y, color) for each of its pixels, each averaged over `samples`
+    pub fn add_tile(&mut self, tile: usize, pixels: &[(i32, i32, Color)], samples: u32) {
+        for &(x, y, color) in pixels {
+            let sum = &mut self.accum[y as usize * self.width + x as usize];
+            sum[0] += color.r * samples as f32;
+            sum[1] += color.g * samples as f32;
+            sum[2] += color.b * samples as f32;
+        }
+        self.samples[tile] += samples;
+        self.dirty = true;
+    }
+
+    /// Resolved color of a pixel in a finished tile
+    pub fn pixel(&self, x: i32, y: i32, tile: usize) -> Color {
+        let sum = self.accum[y as usize * self.width + x as usize];
+        let samples = self.samples[tile].max(1) as f32;
+        Color::new(sum[0] / samples, sum[1] / samples, sum[2] / samples)
+    }
+
+    /// Save when the interval has passed since the last save and something changed
+    pub fn maybe_save(&mut self) {
+        if self.dirty
+            && self.last_save.elapsed().as_secs_f32() >= self.interval
+            && let Err(e) = self.save()
+        {
+            eprintln!("Warning: {}", e);
+        }
+    }
+
+    pub fn save(&mut self) -> Result<(), String> {
+        let mut bytes = Vec::with_capacity(32 + self.samples.len() * 4 + self.accum.len() * 12);
+        bytes.extend_from_slice(MAGIC);
+        for value in [FORMAT_VERSION, self.width as u32, self.height as u32, self.tile_size as u32] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(&self.key.to_le_bytes());
+        for samples in &self.samples {
+            bytes.extend_from_slice(&samples.to_le_bytes());
+        }
+        for sum in &self.accum {
+            for channel in sum {
+                bytes.extend_from_slice(&channel.to_le_bytes());
+            }
+        }
+
+        // Write next to it and rename, so a crash mid-save never leaves a torn checkpoint
+        let temp = self.path.with_extension("ckpt.tmp");
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+        }
+        fs::write(&temp, &bytes).map_err(|e| format!("Failed to write checkpoint '{}': {}", temp.display(), e))?;
+        fs::rename(&temp, &self.path).map_err(|e| format!("Failed to write checkpoint '{}': {}", self.path.display(), e))?;
+
+        self.last_save = Instant::now();
+        self.dirty = false;
+        Ok(())
+    }
+
+    fn load(&mut self) -> Result<(), String> {
+        let bytes = fs::read(&self.path).map_err(|e| format!("Failed to read checkpoint '{}': {}", self.path.display(), e))?;
+        let mut reader = Reader { bytes: &bytes, offset: 0 };
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(format!("'{}' is not a checkpoint file", self.path.display()));
+        }
+        let version = reader.u32()?;
+        if version != FORMAT_VERSION {
+            return Err(format!("Checkpoint '{}' has unsupported format {}", self.path.display(), version));
+        }
+        let (width, height, tile_size) = (reader.u32()? as usize, reader.u32()? as usize, reader.u32()? as usize);
+        let key = reader.u64()?;
+        if (width, height, tile_size, key) != (self.width, self.height, self.tile_size, self.key) {
+            return Err(format!(
+                "Checkpoint '{}' belongs to a different render (size, camera, time or settings changed)",
+                self.path.display()
+            ));
+        }
+
+        for samples in self.samples.iter_mut() {
+            *samples = reader.u32()?;
+        }
+        for sum in self.accum.iter_mut() {
+            *sum = [reader.f32()?, reader.f32()?, reader.f32()?];
+        }
+        Ok(())
+    }
+
+    /// Drop the file once the render has finished
+    pub fn remove(&self) {
+        if self.path.exists() {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+// Little-endian cursor over the checkpoint bytes
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], String> {
+        let end = self.offset + count;
+        let slice = self.bytes.get(self.offset..end).ok_or("Checkpoint file is truncated")?;
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_hash_the_same_in_every_build() {
+        let mut hasher = KeyHasher::new();
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63_dc4c_8601_ec8c); // The published FNV-1a test vector
+    }
+}
@@ -0,0 +1,239 @@
+use crate::box_batch::{BoxBatch, LANES, RayPacket};
+use crate::cube::Cube;
+use crate::ray::Ray;
+use crate::utils::{Real, Vec3};
+use crate::voxel_grid::{Cell, VoxelGrid};
+
+pub const CHUNK_SIZE: i32 = 16; // Blocks per chunk side
+const CHUNK_VOLUME: usize = (CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE) as usize;
+
+/// Chunk coordinate: the cell coordinate divided by CHUNK_SIZE, rounded down
+pub type ChunkCoord = (i32, i32, i32);
+
+/// Chunk holding a cell
+pub fn chunk_of(cell: Cell) -> ChunkCoord {
+    (cell.0.div_euclid(CHUNK_SIZE), cell.1.div_euclid(CHUNK_SIZE), cell.2.div_euclid(CHUNK_SIZE))
+}
+
+/// A 16x16x16 section of the world, owning every cube centered in it. Unit blocks also have a
+/// slot in a dense array (one per cell, x fastest, then z, then y). The cubes a ray can reach,
+/// detail cubes included, are copied into a traced list with the box around all of them, so a ray
+/// that misses the box skips the whole chunk; their own boxes are kept in BoxBatches, LANES to a
+/// batch, for slab testing them several at a time. The traced list is only redone by `trace`, after
+/// the grid has gathered a round of edits.
+#[derive(Clone)]
+pub struct Chunk {
+    coord: ChunkCoord,
+    cubes: Vec<Cube>,
+    streamed: Vec<bool>, // Per cube: loaded by ChunkStreamer rather than built with the scene
+    blocks: Box<[Option<usize>]>, // Cell -> index into `cubes`
+    traced: Vec<Cube>, // The cubes rays can reach
+    boxes: Vec<BoxBatch>, // Boxes of `traced`, in the same order
+    heights: Option<(i32, i32)>, // Lowest and highest occupied cell, as of the last trace
+    min: Vec3,
+    max: Vec3,
+}
+
+impl Chunk {
+    pub fn new(coord: ChunkCoord) -> Self {
+        Self {
+            coord,
+            cubes: Vec::new(),
+            streamed: Vec::new(),
+            blocks: vec![None; CHUNK_VOLUME].into_boxed_slice(),
+            traced: Vec::new(),
+            boxes: Vec::new(),
+            heights: None,
+            min: Vec3::new(Real::INFINITY, Real::INFINITY, Real::INFINITY),
+            max: Vec3::new(Real::NEG_INFINITY, Real::NEG_INFINITY, Real::NEG_INFINITY),
+        }
+    }
+
+    pub fn coord(&self) -> ChunkCoord {
+        self.coord
+    }
+
+    // Slot of a cell in the dense array (the cell must be inside this chunk)
+    fn slot(cell: Cell) -> usize {
+        let x = cell.0.rem_euclid(CHUNK_SIZE);
+        let y = cell.1.rem_euclid(CHUNK_SIZE);
+        let z = cell.2.rem_euclid(CHUNK_SIZE);
+        ((y * CHUNK_SIZE + z) * CHUNK_SIZE + x) as usize
+    }
+
+    /// Unit block in a cell
+    pub fn get(&self, cell: Cell) -> Option<&Cube> {
+        self.blocks[Self::slot(cell)].map(|index| &self.cubes[index])
+    }
+
+    pub fn get_mut(&mut self, cell: Cell) -> Option<&mut Cube> {
+        self.blocks[Self::slot(cell)].map(|index| &mut self.cubes[index])
+    }
+
+    /// Add a cube centered in this chunk; a unit block goes in `cell`, unless that is taken.
+    /// Returns whether it went in.
+    pub fn add(&mut self, cube: Cube, cell: Option<Cell>, streamed: bool) -> bool {
+        if let Some(cell) = cell {
+            let slot = &mut self.blocks[Self::slot(cell)];
+            if slot.is_some() {
+                return false;
+            }
+            *slot = Some(self.cubes.len());
+        }
+        self.cubes.push(cube);
+        self.streamed.push(streamed);
+        true
+    }
+
+    /// Take the unit block out of a cell
+    pub fn remove(&mut self, cell: Cell) -> Option<Cube> {
+        let index = self.blocks[Self::slot(cell)].take()?;
+        Some(self.remove_at(index))
+    }
+
+    /// Take out every cube loaded by ChunkStreamer, returning how many there were
+    pub fn remove_streamed(&mut self) -> usize {
+        let mut removed = 0;
+        let mut index = 0;
+        while index < self.cubes.len() {
+            if self.streamed[index] {
+                if let Some(cell) = self.cube_cell(index) {
+                    self.blocks[Self::slot(cell)] = None;
+                }
+                self.remove_at(index);
+                removed += 1;
+            } else {
+                index += 1;
+            }
+        }
+        removed
+    }
+
+    // Cell whose slot points at cubes[index], if any
+    fn cube_cell(&self, index: usize) -> Option<Cell> {
+        let cell = VoxelGrid::cell_of(self.cubes[index].position);
+        (self.blocks[Self::slot(cell)] == Some(index)).then_some(cell)
+    }
+
+    // Swap-remove cubes[index] (already out of its slot), pointing the slot of the cube moved into
+    // its place at the new index
+    fn remove_at(&mut self, index: usize) -> Cube {
+        let last = self.cubes.len() - 1;
+        if index != last
+            && let Some(cell) = self.cube_cell(last)
+        {
+            self.blocks[Self::slot(cell)] = Some(index);
+        }
+        self.streamed.swap_remove(index);
+        self.cubes.swap_remove(index)
+    }
+
+    /// The cube centered exactly at `position`, unit block or not
+    pub fn find(&self, position: Vec3) -> Option<&Cube> {
+        self.cubes.iter().find(|cube| cube.position == position)
+    }
+
+    pub fn find_mut(&mut self, position: Vec3) -> Option<&mut Cube> {
+        self.cubes.iter_mut().find(|cube| cube.position == position)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cubes.is_empty()
+    }
+
+    /// Every cube centered in the chunk
+    pub fn cubes(&self) -> &[Cube] {
+        &self.cubes
+    }
+
+    /// Occupied cells and their blocks
+    pub fn blocks(&self) -> impl Iterator<Item = (Cell, &Cube)> + '_ {
+        let origin = (self.coord.0 * CHUNK_SIZE, self.coord.1 * CHUNK_SIZE, self.coord.2 * CHUNK_SIZE);
+        self.blocks.iter().enumerate().filter_map(move |(slot, index)| {
+            let slot = slot as i32;
+            let (x, z, y) = (slot % CHUNK_SIZE, slot / CHUNK_SIZE % CHUNK_SIZE, slot / (CHUNK_SIZE * CHUNK_SIZE));
+            index.map(|index| ((origin.0 + x, origin.1 + y, origin.2 + z), &self.cubes[index]))
+        })
+    }
+
+    /// Move the chunk and its cubes by whole chunks (`chunks` of them along each axis)
+    pub fn translate(&mut self, chunks: ChunkCoord) {
+        self.coord = (self.coord.0 + chunks.0, self.coord.1 + chunks.1, self.coord.2 + chunks.2);
+        let shift = Vec3::new(chunks.0 as Real, chunks.1 as Real, chunks.2 as Real) * CHUNK_SIZE as Real;
+        for cube in &mut self.cubes {
+            cube.position = cube.position + shift;
+        }
+    }
+
+    /// Redo the traced list: every cube but the ones marked in `buried` (one flag per cube, in
+    /// the order of `cubes`), and the box around them
+    pub fn trace(&mut self, buried: &[bool]) {
+        self.traced.clear();
+        self.boxes.clear();
+        self.heights = None;
+        self.min = Vec3::new(Real::INFINITY, Real::INFINITY, Real::INFINITY);
+        self.max = Vec3::new(Real::NEG_INFINITY, Real::NEG_INFINITY, Real::NEG_INFINITY);
+        for (index, &buried) in buried.iter().enumerate() {
+            if let Some((_, y, _)) = self.cube_cell(index) {
+                self.heights = Some(self.heights.map_or((y, y), |(low, high)| (low.min(y), high.max(y))));
+            }
+            if buried {
+                continue;
+            }
+            let cube = self.cubes[index];
+            let half = Vec3::new(cube.size, cube.size, cube.size) * 0.5;
+            let (min, max) = (cube.position - half, cube.position + half);
+            self.min = Vec3::new(self.min.x.min(min.x), self.min.y.min(min.y), self.min.z.min(min.z));
+            self.max = Vec3::new(self.max.x.max(max.x), self.max.y.max(max.y), self.max.z.max(max.z));
+            self.traced.push(cube);
+            if self.boxes.last().is_none_or(BoxBatch::is_full) {
+                self.boxes.push(BoxBatch::new());
+            }
+            self.boxes.last_mut().unwrap().push(min, max);
+        }
+    }
+
+    /// Lowest and highest occupied cell, None without unit blocks
+    pub fn height_range(&self) -> Option<(i32, i32)> {
+        self.heights
+    }
+
+    /// Number of cubes rays can reach
+    pub fn traced_count(&self) -> usize {
+        self.traced.len()
+    }
+
+    /// Box around the traced cubes (empty, min above max, when there are none)
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        (self.min, self.max)
+    }
+
+    /// The traced cubes in groups of up to LANES, each with the batch holding their boxes
+    pub fn batches(&self) -> impl Iterator<Item = (&BoxBatch, &[Cube])> {
+        self.boxes.iter().zip(self.traced.chunks(LANES))
+    }
+}
+
+/// Distance at which the ray enters the box, if it does before `max_t` (0 from inside)
+pub fn slab_entry(ray: &Ray, min: Vec3, max: Vec3, max_t: Real) -> Option<Real> {
+    let axes = [
+        (ray.origin.x, ray.direction.x, min.x, max.x),
+        (ray.origin.y, ray.direction.y, min.y, max.y),
+        (ray.origin.z, ray.direction.z, min.z, max.z),
+    ];
+    let (mut near, mut far) = (0.0 as Real, max_t);
+    for (origin, direction, min, max) in axes {
+        let inv = 1.0 / direction;
+        let (t0, t1) = ((min - origin) * inv, (max - origin) * inv);
+        near = near.max(t0.min(t1));
+        far = far.min(t0.max(t1));
+    }
+    (near <= far).then_some(near)
+}
+
+/// slab_entry for a whole packet: a lower bound on where its rays enter the box
+pub fn packet_slab_entry(packet: &RayPacket, min: Vec3, max: Vec3, max_t: Real) -> Option<Real> {
+    let (near, far) = packet.slab_bounds(min, max);
+    let (near, far) = (near.max(0.0), far.min(max_t));
+    (near <= far).then_some(near)
+}
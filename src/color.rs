@@ -1,5 +1,5 @@
 use std::ops::{Add, Mul};
-use crate::utils::{Vec3, clamp};
+use crate::utils::{Real, Vec3, clamp, narrow};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Color {
@@ -9,7 +9,7 @@ pub struct Color {
 }
 
 impl Color {
-    pub fn new(r: f32, g: f32, b: f32) -> Self {
+    pub const fn new(r: f32, g: f32, b: f32) -> Self {
         Self { r, g, b }
     }
 
@@ -20,11 +20,11 @@ impl Color {
     pub fn blue() -> Self { Self::new(0.0, 0.0, 1.0) }
 
     pub fn from_vec3(v: Vec3) -> Self {
-        Self::new(v.x, v.y, v.z)
+        Self::new(narrow(v.x), narrow(v.y), narrow(v.z))
     }
 
     pub fn to_vec3(&self) -> Vec3 {
-        Vec3::new(self.r, self.g, self.b)
+        Vec3::new(self.r as Real, self.g as Real, self.b as Real)
     }
 
     pub fn to_raylib(&self) -> raylib::prelude::Color {
@@ -44,6 +44,21 @@ impl Color {
         )
     }
 
+    /// Perceived brightness (Rec. 601 weights)
+    pub fn luminance(&self) -> f32 {
+        self.r * 0.299 + self.g * 0.587 + self.b * 0.114
+    }
+
+    /// sRGB-encoded (as stored in image files and shown on screen) to linear light
+    pub fn to_linear(&self) -> Self {
+        Self::new(srgb_to_linear(self.r), srgb_to_linear(self.g), srgb_to_linear(self.b))
+    }
+
+    /// Linear light to sRGB-encoded, for the screen and PNGs
+    pub fn to_srgb(&self) -> Self {
+        Self::new(linear_to_srgb(self.r), linear_to_srgb(self.g), linear_to_srgb(self.b))
+    }
+
     pub fn clamp(&self) -> Self {
         Self::new(
             clamp(self.r, 0.0, 1.0),
@@ -53,6 +68,15 @@ impl Color {
     }
 }
 
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+    let c = c.max(0.0);
+    if c <= 0.0031308 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 }
+}
+
 impl Add for Color {
     type Output = Color;
     fn add(self, other: Color) -> Color {
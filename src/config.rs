@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::hud::HudConfig;
+use crate::keybindings::{KeyBindings, KeyList};
+use crate::tonemap::ToneMap;
+
+/// User settings loaded from config.toml at startup
+#[derive(Default)]
+pub struct Config {
+    pub keybindings: KeyBindings,
+    pub watchdog: WatchdogConfig,
+    pub sampling: SamplingConfig,
+    pub display: DisplayConfig,
+    pub hud: HudConfig,
+}
+
+/// [watchdog] frame budgets: past them the rest of the frame is traced roughly (0 = no limit)
+#[derive(Deserialize, Clone, Copy)]
+pub struct WatchdogConfig {
+    #[serde(default = "default_frame_budget")]
+    pub frame_budget: f32, // Seconds
+    #[serde(default = "default_ray_budget")]
+    pub ray_budget: u64,
+}
+
+fn default_frame_budget() -> f32 { 2.0 }
+fn default_ray_budget() -> u64 { 100_000_000 }
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            frame_budget: default_frame_budget(),
+            ray_budget: default_ray_budget(),
+        }
+    }
+}
+
+/// [sampling] adaptive sampling: a pixel stops taking extra rays (Ultra supersampling, motion blur)
+/// once the standard error of its brightness drops below `adaptive_threshold` (0 = every ray, always)
+#[derive(Deserialize, Clone, Copy)]
+pub struct SamplingConfig {
+    #[serde(default = "default_adaptive_threshold")]
+    pub adaptive_threshold: f32,
+}
+
+fn default_adaptive_threshold() -> f32 { 0.02 }
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        Self {
+            adaptive_threshold: default_adaptive_threshold(),
+        }
+    }
+}
+
+/// [display] how the traced (linear, unbounded) colors are brought into the screen's 0-1 range
+#[derive(Deserialize, Clone, Copy)]
+pub struct DisplayConfig {
+    #[serde(default)]
+    pub tone_map: ToneMap, // "aces" (default), "reinhard" or "clamp" (hard cut at white)
+    #[serde(default = "default_exposure")]
+    pub exposure: f32, // Brightness multiplier before the tone map
+    #[serde(default = "default_bloom")]
+    pub bloom: f32, // Glow around the sun, moon and lamps (0 = off)
+    #[serde(default = "default_bloom_threshold")]
+    pub bloom_threshold: f32, // Brightness where the glow starts (white = 1.0)
+}
+
+fn default_exposure() -> f32 { 1.0 }
+fn default_bloom() -> f32 { 0.3 }
+fn default_bloom_threshold() -> f32 { 1.0 }
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            tone_map: ToneMap::default(),
+            exposure: default_exposure(),
+            bloom: default_bloom(),
+            bloom_threshold: default_bloom_threshold(),
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct ConfigFile {
+    #[serde(default)]
+    keybindings: HashMap<String, KeyList>,
+    #[serde(default)]
+    watchdog: WatchdogConfig,
+    #[serde(default)]
+    sampling: SamplingConfig,
+    #[serde(default)]
+    display: DisplayConfig,
+    #[serde(default)]
+    hud: HudConfig,
+}
+
+impl Config {
+    /// Load the config file. A missing file just means defaults.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let source = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config '{}': {}", path.display(), e))?;
+        let file: ConfigFile = toml::from_str(&source)
+            .map_err(|e| format!("Failed to parse config '{}': {}", path.display(), e))?;
+
+        Ok(Self {
+            keybindings: KeyBindings::with_overrides(&file.keybindings)?,
+            watchdog: file.watchdog,
+            sampling: file.sampling,
+            display: file.display,
+            hud: file.hud,
+        })
+    }
+}
@@ -0,0 +1,141 @@
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::cube::Cube;
+use crate::lighting::LightingPreset;
+use crate::material::Material;
+use crate::obj_loader::{Mesh, Triangle};
+use crate::palette;
+use crate::ray::Ray;
+use crate::renderer::{self, RenderSettings};
+use crate::scene::Scene;
+use crate::scene_builder::SceneBuilder;
+use crate::texture_cache;
+use crate::utils::{Degrees, Real, Vec3};
+
+const TOLERANCE: f32 = 0.01; // Largest difference in any channel before the shading counts as different
+const OFFSETS: [Real; 5] = [-0.3, -0.15, 0.0, 0.15, 0.3]; // Sample grid across each face, in blocks
+const DIAGONAL_GAP: Real = 0.05; // Second offset's shift from the first
+const RAY_SPREAD: Real = 0.001; // Cone growth of the sample rays, so both pick the same texture mip
+
+// One material of each kind the renderer handles
+fn materials() -> Vec<(&'static str, Material)> {
+    vec![
+        ("plain", Material::new(palette::STONE_GRAY)),
+        ("textured", Material::new(palette::NEUTRAL_GRAY).with_texture(texture_cache::load("assets/textures/stone.jpg"))),
+        (
+            "normal mapped",
+            Material::new(palette::NEUTRAL_GRAY)
+                .with_texture(texture_cache::load("assets/textures/stone.jpg"))
+                .with_normal_map(texture_cache::load_normal_map("assets/textures/stone_normal.png"), 1.0),
+        ),
+        ("cutout", Material::new(palette::NEUTRAL_GRAY).with_texture(texture_cache::load("assets/textures/glass.png")).with_alpha_cutoff(0.5)),
+        ("emissive", Material::new(palette::GLOWSTONE_YELLOW).with_emissive(palette::GLOWSTONE_YELLOW)),
+        ("specular", Material::new(palette::PLANKS_BROWN).with_specular(0.8, 64.0)),
+        ("reflective", Material::new(palette::STONE_GRAY).with_reflectivity(0.6)),
+        ("transparent", Material::new(palette::GLASS_WHITE).with_transparency(0.8, 1.5).with_absorption(palette::GLASS_GREEN_TINT, 0.5)),
+    ]
+}
+
+/// Unit box centered on `position` made of twelve triangles, the mesh twin of a unit cube
+pub fn box_mesh(position: Vec3, material: Material) -> Mesh {
+    let (x, y, z) = (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+    // Outward normal and two edge directions whose cross product is that normal (counter-clockwise from outside)
+    let faces = [(x, y, z), (-x, z, y), (y, z, x), (-y, x, z), (z, x, y), (-z, y, x)];
+    let mut mesh = Mesh::new(position, material);
+    for (normal, a, b) in faces {
+        let corner = |i: Real, j: Real| normal * 0.5 + a * (i - 0.5) + b * (j - 0.5);
+        mesh.triangles.push(Triangle::new(corner(0.0, 0.0), corner(1.0, 0.0), corner(1.0, 1.0)));
+        mesh.triangles.push(Triangle::new(corner(0.0, 0.0), corner(1.0, 1.0), corner(0.0, 1.0)));
+    }
+    mesh
+}
+
+// The material on a unit cube and on its mesh twin, each alone at the origin under studio
+// lighting (sun and lamps, for specular highlights) with the critters kept away
+fn scenes(material: Material) -> Result<(Scene, Scene), String> {
+    let lighting = LightingPreset::parse("studio")?;
+    let scene = |builder: SceneBuilder| builder.with_lighting(lighting.clone()).with_critters(false).build();
+    let mut cube_scene = SceneBuilder::new();
+    let id = cube_scene.material(material.clone());
+    Ok((
+        scene(cube_scene.with_cube(Cube::new(Vec3::zero(), 1.0, id))),
+        scene(SceneBuilder::new().with_mesh(box_mesh(Vec3::zero(), material))),
+    ))
+}
+
+// Largest channel difference between the two scenes over a grid of parallel rays aimed at the
+// front and the top face of the primitive
+fn max_difference(a: &Scene, b: &Scene, camera: &Camera, settings: &RenderSettings) -> f32 {
+    let views = [
+        (Vec3::new(0.0, 0.0, 10.0), Vec3::new(0.1, -0.2, -1.0)),  // Front face, from a little above and to the right
+        (Vec3::new(0.0, 10.0, 0.0), Vec3::new(-0.19, -1.0, 0.15)), // Top face (through its holes, clear of the bottom edges)
+    ];
+    let mut worst: f32 = 0.0;
+    for (back, direction) in views {
+        let direction = direction.normalize();
+        for s in OFFSETS {
+            // Shifted off s, so no sample lands on the diagonal where box_mesh splits a face
+            for t in OFFSETS.map(|t| t + DIAGONAL_GAP) {
+                // Offset across the face, then backed off along the ray so it arrives at that point
+                let across = if back.y > 0.0 { Vec3::new(s, 0.0, t) } else { Vec3::new(s, t, 0.0) };
+                let target = across + back.normalize() * 0.5;
+                let ray = Ray::new(target - direction * 10.0, direction).with_cone(0.0, RAY_SPREAD);
+                let (first, second) = (renderer::trace(&ray, a, camera, settings), renderer::trace(&ray, b, camera, settings));
+                let difference = |p: Color, q: Color| (p.r - q.r).abs().max((p.g - q.g).abs()).max((p.b - q.b).abs());
+                worst = worst.max(difference(first, second));
+            }
+        }
+    }
+    worst
+}
+
+/// `conformance`: shade the same material on a cube and on a triangle mesh of the same box, for
+/// each kind of material (texture, normal map, cutout, emissive, specular, mirror, glass), and
+/// compare the traced colors ray for ray. Exit status 0 when every pair matches within TOLERANCE.
+pub fn run(args: &[String]) -> i32 {
+    if !args.is_empty() {
+        eprintln!("Usage: conformance");
+        return 2;
+    }
+    let camera = Camera::new(Vec3::new(0.0, 3.0, 10.0), Vec3::zero(), Degrees(70.0), 1.0);
+    let settings = RenderSettings::default();
+    let mut failed = 0;
+    for (name, material) in materials() {
+        let (cube, mesh) = match scenes(material) {
+            Ok(scenes) => scenes,
+            Err(e) => {
+                eprintln!("{}", e);
+                return 2;
+            }
+        };
+        let difference = max_difference(&cube, &mesh, &camera, &settings);
+        let verdict = if difference <= TOLERANCE { "ok" } else { "DIFFERENT" };
+        println!("{:<14} cube vs mesh: max difference {:.4} {}", name, difference, verdict);
+        if difference > TOLERANCE {
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        println!("{} material(s) shade differently on cubes and meshes", failed);
+        1
+    } else {
+        println!("Cubes and meshes shade every material the same");
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cubes_and_meshes_shade_the_same() {
+        let camera = Camera::new(Vec3::new(0.0, 3.0, 10.0), Vec3::zero(), Degrees(70.0), 1.0);
+        let settings = RenderSettings::default();
+        for (name, material) in materials() {
+            let (cube, mesh) = scenes(material).unwrap();
+            let difference = max_difference(&cube, &mesh, &camera, &settings);
+            assert!(difference <= TOLERANCE, "{}: max difference {}", name, difference);
+        }
+    }
+}
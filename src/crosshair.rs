@@ -0,0 +1,45 @@
+use raylib::prelude::*;
+
+use crate::camera::Camera;
+use crate::debug_overlay::View;
+use crate::presentation::Viewport;
+use crate::scene::Scene;
+use crate::utils::{Real, Vec3};
+use crate::voxel_grid::VoxelGrid;
+
+const ARM: i32 = 8; // Pixels from the center to the end of each crosshair arm
+const OUTLINE_GROW: Real = 0.005; // The outline sits just outside the block so its faces don't hide it
+const INSIDE: Real = 0.001; // Step back from the hit point into the block that was hit
+
+/// Crosshair at the center of the frame and, around the block it points at, a wireframe of the
+/// block's box: the same block the block editor (5/6), UV grid (8) and light editor (H) act on.
+pub fn draw(d: &mut RaylibDrawHandle, scene: &Scene, camera: &Camera, viewport: Viewport) {
+    let (cx, cy) = (viewport.x + viewport.width / 2, viewport.y + viewport.height / 2);
+    d.draw_line(cx - ARM, cy, cx + ARM, cy, Color::WHITE);
+    d.draw_line(cx, cy - ARM, cx, cy + ARM, Color::WHITE);
+
+    if let Some((center, size)) = target_box(scene, camera) {
+        let view = View { camera, viewport };
+        view.draw_box(d, center, size / 2.0 + OUTLINE_GROW, Color::new(20, 20, 20, 220));
+    }
+}
+
+/// Center and size of the cube under the crosshair, None when the center ray hits nothing or a
+/// mesh. Unit blocks are found through the grid; detail cubes by checking which one holds the hit.
+pub fn target_box(scene: &Scene, camera: &Camera) -> Option<(Vec3, Real)> {
+    let hit = scene.intersect(&camera.get_pinhole_ray(0.5, 0.5))?;
+    let inside = hit.position - hit.normal * INSIDE;
+    if let Some(cube) = scene.grid.get(VoxelGrid::cell_of(inside)) {
+        return Some((cube.position, cube.size));
+    }
+    scene
+        .grid
+        .cubes()
+        .chain(&scene.entities)
+        .find(|cube| {
+            let half = cube.size / 2.0 + INSIDE;
+            let offset = inside - cube.position;
+            offset.x.abs() <= half && offset.y.abs() <= half && offset.z.abs() <= half
+        })
+        .map(|cube| (cube.position, cube.size))
+}
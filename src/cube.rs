@@ -1,19 +1,23 @@
-use crate::utils::Vec3;
-use crate::ray::Ray;
+use crate::utils::{Real, Vec3, narrow};
+use crate::ray::{Ray, T_MIN};
 use crate::material::Material;
+use crate::material_palette::{MaterialId, MaterialPalette};
 use crate::intersection::Intersection;
 
+/// An axis-aligned block. Its materials are ids into the scene's MaterialPalette, so a cube is a
+/// few plain numbers and copies for free.
 pub struct Cube {
     pub position: Vec3,
-    pub size: f32,
-    pub material: Material,
-    pub top_material: Option<Material>,
-    pub side_material: Option<Material>,
-    pub bottom_material: Option<Material>,
+    pub size: Real,
+    pub material: MaterialId,
+    pub top_material: Option<MaterialId>,
+    pub side_material: Option<MaterialId>,
+    pub bottom_material: Option<MaterialId>,
+    pub face_material: Option<(Vec3, MaterialId)>, // One side face, by outward normal, with its own material (a sign's text)
 }
 
 impl Cube {
-    pub fn new(position: Vec3, size: f32, material: Material) -> Self {
+    pub fn new(position: Vec3, size: Real, material: MaterialId) -> Self {
         Self {
             position,
             size,
@@ -21,29 +25,37 @@ impl Cube {
             top_material: None,
             side_material: None,
             bottom_material: None,
+            face_material: None,
         }
     }
 
     // Create a cube with different materials for top, sides, and bottom
     pub fn new_multi_texture(
         position: Vec3,
-        size: f32,
-        top: Material,
-        sides: Material,
-        bottom: Material,
+        size: Real,
+        top: MaterialId,
+        sides: MaterialId,
+        bottom: MaterialId,
     ) -> Self {
         Self {
             position,
             size,
-            material: sides.clone(),
+            material: sides,
             top_material: Some(top),
             side_material: Some(sides),
             bottom_material: Some(bottom),
+            face_material: None,
         }
     }
 
-    // Ray-cube intersection using slab method
-    pub fn intersect(&self, ray: &Ray) -> Option<Intersection> {
+    /// Give the side face whose outward normal is `normal` (a unit axis) its own material
+    pub fn with_face(mut self, normal: Vec3, material: MaterialId) -> Self {
+        self.face_material = Some((normal, material));
+        self
+    }
+
+    // Ray-cube intersection using slab method, with the materials looked up in the scene's palette
+    pub fn intersect(&self, ray: &Ray, materials: &MaterialPalette) -> Option<Intersection> {
         let half_size = self.size / 2.0;
         let min = self.position - Vec3::new(half_size, half_size, half_size);
         let max = self.position + Vec3::new(half_size, half_size, half_size);
@@ -61,92 +73,202 @@ impl Cube {
         let t5 = (min.z - ray.origin.z) * inv_dir.z;
         let t6 = (max.z - ray.origin.z) * inv_dir.z;
 
-        let tmin = t1.min(t2).max(t3.min(t4)).max(t5.min(t6));
-        let tmax = t1.max(t2).min(t3.max(t4)).min(t5.max(t6));
+        let (near, far) = ([t1.min(t2), t3.min(t4), t5.min(t6)], [t1.max(t2), t3.max(t4), t5.max(t6)]);
+        let tmin = near[0].max(near[1]).max(near[2]);
+        let tmax = far[0].min(far[1]).min(far[2]);
 
         if tmax < 0.0 || tmin > tmax {
             return None;
         }
 
-        let t = if tmin > 0.001 { tmin } else { tmax };
-        if t < 0.001 {
-            return None;
+        // Front face first; a cut-out hole there lets the ray through to the inside of the back face.
+        // Rays starting inside (tmin behind the origin) get the exit face, seen from within.
+        if tmin > T_MIN
+            && let Some(hit) = self.face_hit(ray, materials, tmin, Self::slab_normal(&near, tmin, ray, -1.0))
+        {
+            return Some(hit);
         }
+        if tmax > T_MIN {
+            return self.face_hit(ray, materials, tmax, Self::slab_normal(&far, tmax, ray, 1.0));
+        }
+        None
+    }
 
+    // Outward normal of the face where the ray crosses at t: the slab whose entry (sign -1) or
+    // exit (sign +1) distance is t. Taken from the slab test itself rather than comparing the hit
+    // point to the bounds, which picks the wrong face on thin cubes and far from the origin.
+    fn slab_normal(distances: &[Real; 3], t: Real, ray: &Ray, sign: Real) -> Vec3 {
+        let axis = if distances[0] == t { 0 } else if distances[1] == t { 1 } else { 2 };
+        let direction = [ray.direction.x, ray.direction.y, ray.direction.z][axis];
+        let outward = if direction >= 0.0 { sign } else { -sign };
+        match axis {
+            0 => Vec3::new(outward, 0.0, 0.0),
+            1 => Vec3::new(0.0, outward, 0.0),
+            _ => Vec3::new(0.0, 0.0, outward),
+        }
+    }
+
+    // Hit on the face at distance t, or None when that texel is cut out.
+    // Back faces (seen through a hole or from inside) end up with front_face = false.
+    fn face_hit(&self, ray: &Ray, materials: &MaterialPalette, t: Real, normal: Vec3) -> Option<Intersection> {
         let hit_point = ray.at(t);
-        let normal = self.get_normal(hit_point, &min, &max);
         let (u, v) = self.get_uv(hit_point, &normal);
 
         // Select the appropriate material based on which face was hit
-        let material = self.get_face_material(&normal);
+        let material = &materials[self.get_face_material(&normal)];
+        if material.is_hole(u, v) {
+            return None;
+        }
 
+        let (tangent, bitangent) = face_tangents(&normal);
         Some(Intersection::new(
             t,
             hit_point,
             normal,
-            material,
+            material.clone(),
             u,
             v,
-        ))
+        ).with_uv_size(self.size).with_tangents(tangent, bitangent).oriented(ray))
+    }
+
+    /// Nothing shows through any face: no transparency, cut-out texels or shadow catching
+    pub fn is_opaque(&self, materials: &MaterialPalette) -> bool {
+        let (top, sides, bottom) = self.face_materials(materials);
+        let face = self.face_material.map(|(_, id)| &materials[id]);
+        [Some(top), Some(sides), Some(bottom), face]
+            .into_iter()
+            .flatten()
+            .all(|material| material.transparency == 0.0 && material.alpha_cutoff == 0.0 && material.shadow_catcher == 0.0)
+    }
+
+    /// Material shown on the top face
+    pub fn top_face_material<'a>(&self, materials: &'a MaterialPalette) -> &'a Material {
+        &materials[self.top_material.unwrap_or(self.material)]
+    }
+
+    /// Materials of the (top, sides, bottom) faces
+    pub fn face_materials<'a>(&self, materials: &'a MaterialPalette) -> (&'a Material, &'a Material, &'a Material) {
+        (
+            self.top_face_material(materials),
+            &materials[self.side_material.unwrap_or(self.material)],
+            &materials[self.bottom_material.unwrap_or(self.material)],
+        )
     }
 
     // Get the material for a specific face based on the normal
-    fn get_face_material(&self, normal: &Vec3) -> Material {
+    fn get_face_material(&self, normal: &Vec3) -> MaterialId {
         // Top face (normal pointing up)
         if normal.y > 0.5 {
-            if let Some(ref mat) = self.top_material {
-                return mat.clone();
+            if let Some(id) = self.top_material {
+                return id;
             }
         }
         // Bottom face (normal pointing down)
         else if normal.y < -0.5 {
-            if let Some(ref mat) = self.bottom_material {
-                return mat.clone();
+            if let Some(id) = self.bottom_material {
+                return id;
             }
         }
         // Side faces
         else {
-            if let Some(ref mat) = self.side_material {
-                return mat.clone();
+            if let Some((face, id)) = self.face_material
+                && face.dot(normal) > 0.5
+            {
+                return id;
+            }
+            if let Some(id) = self.side_material {
+                return id;
             }
         }
 
         // Fallback to default material
-        self.material.clone()
+        self.material
     }
 
-    fn get_normal(&self, point: Vec3, min: &Vec3, max: &Vec3) -> Vec3 {
-        let epsilon = 0.001;
+    fn get_uv(&self, point: Vec3, normal: &Vec3) -> (f32, f32) {
+        let half = Vec3::new(0.5, 0.5, 0.5);
+        let (u, v) = face_uv((point - self.position) * (1.0 / self.size) + half, normal);
+        (narrow(u), narrow(v))
+    }
+}
 
-        if (point.x - min.x).abs() < epsilon { Vec3::new(-1.0, 0.0, 0.0) }
-        else if (point.x - max.x).abs() < epsilon { Vec3::new(1.0, 0.0, 0.0) }
-        else if (point.y - min.y).abs() < epsilon { Vec3::new(0.0, -1.0, 0.0) }
-        else if (point.y - max.y).abs() < epsilon { Vec3::new(0.0, 1.0, 0.0) }
-        else if (point.z - min.z).abs() < epsilon { Vec3::new(0.0, 0.0, -1.0) }
-        else { Vec3::new(0.0, 0.0, 1.0) }
+/// Texture coordinates on a face by the axis its normal is closest to, from the point's offset to
+/// the face's low corner in texture repeats (0-1 across a cube face). Cube faces and mesh
+/// triangles both go through this, so a material looks the same on either.
+pub fn face_uv(local: Vec3, normal: &Vec3) -> (Real, Real) {
+    if normal.x.abs() > 0.5 {
+        // Side faces (X-facing) - flip V coordinate to fix upside-down texture
+        (local.z, 1.0 - local.y)
+    } else if normal.y.abs() > 0.5 {
+        // Top/bottom faces (Y-facing) - normal UV mapping
+        (local.x, local.z)
+    } else {
+        // Side faces (Z-facing) - flip V coordinate to fix upside-down texture
+        (local.x, 1.0 - local.y)
     }
+}
 
-    fn get_uv(&self, point: Vec3, normal: &Vec3) -> (f32, f32) {
-        let local = point - self.position;
-        let half_size = self.size / 2.0;
+/// Directions in which u grows and the texture's "up" points on each face (matches face_uv)
+pub fn face_tangents(normal: &Vec3) -> (Vec3, Vec3) {
+    if normal.x.abs() > 0.5 {
+        (Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 1.0, 0.0))
+    } else if normal.y.abs() > 0.5 {
+        (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0))
+    } else {
+        (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0))
+    }
+}
 
-        let u: f32;
-        let v: f32;
-
-        if normal.x.abs() > 0.5 {
-            // Side faces (X-facing) - flip V coordinate to fix upside-down texture
-            u = (local.z + half_size) / self.size;
-            v = 1.0 - (local.y + half_size) / self.size;
-        } else if normal.y.abs() > 0.5 {
-            // Top/bottom faces (Y-facing) - normal UV mapping
-            u = (local.x + half_size) / self.size;
-            v = (local.z + half_size) / self.size;
-        } else {
-            // Side faces (Z-facing) - flip V coordinate to fix upside-down texture
-            u = (local.x + half_size) / self.size;
-            v = 1.0 - (local.y + half_size) / self.size;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    fn stone() -> (MaterialPalette, MaterialId) {
+        let mut materials = MaterialPalette::new();
+        let id = materials.add(Material::new(Color::new(0.5, 0.5, 0.5)));
+        (materials, id)
+    }
+
+    #[test]
+    fn a_ray_leaving_a_face_misses_it() {
+        let (materials, id) = stone();
+        let cube = Cube::new(Vec3::new(0.3, -2.0, 7.0), 1.0, id);
+        let ray = Ray::new(Vec3::new(-3.0, -1.2, 6.6), Vec3::new(1.0, -0.3, 0.2).normalize());
+        let hit = cube.intersect(&ray, &materials).unwrap();
+        assert!(hit.front_face);
 
-        (u, v)
+        // Reflected off the face and started right on it, as the shading rays are
+        let reflected = ray.direction - hit.normal * (2.0 * ray.direction.dot(&hit.normal));
+        assert!(cube.intersect(&Ray::new(hit.position, reflected), &materials).is_none());
+        // Refracted into it, the ray goes on to the far side instead of the face it started on
+        let inside = cube.intersect(&Ray::new(hit.position, ray.direction), &materials).unwrap();
+        assert!(!inside.front_face && inside.t > 0.5);
+        assert!(inside.normal.dot(&ray.direction) < 0.0, "exit normal faces back toward the ray");
+    }
+
+    #[test]
+    fn flat_ground_does_not_shadow_itself() {
+        let (materials, id) = stone();
+        let ground: Vec<Cube> = (-2..=2)
+            .flat_map(|x| (-2..=2).map(move |z| Cube::new(Vec3::new(x as Real, 0.0, z as Real), 1.0, id)))
+            .collect();
+        let nearest = |ray: &Ray| ground.iter().filter_map(|cube| cube.intersect(ray, &materials)).min_by(|a, b| a.t.total_cmp(&b.t));
+        // Points all over the top faces, edges and corners between blocks included, where slanted
+        // camera rays hit them (so off the surface by the usual rounding)
+        for i in 0..=40 {
+            for j in 0..=40 {
+                let aim = Vec3::new(-1.5 + i as Real * 0.075, 0.5, -1.5 + j as Real * 0.075);
+                let direction = Vec3::new(0.31, -1.0, 0.17).normalize();
+                let Some(hit) = nearest(&Ray::new(aim - direction * 9.7, direction)) else { continue };
+                let point = hit.position;
+                // Grazing sunlight from every side
+                for (x, z) in [(1.0, 0.0), (-1.0, 0.0), (0.0, 1.0), (0.0, -1.0), (0.7, 0.7)] {
+                    let toward_sun = Vec3::new(x, 0.02, z).normalize();
+                    let shadow = Ray::new(point, toward_sun);
+                    assert!(nearest(&shadow).is_none(), "{:?} shadowed toward {:?}", point, toward_sun);
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+
+use raylib::prelude::*;
+
+use crate::camera::Camera;
+use crate::chunk::CHUNK_SIZE;
+use crate::presentation::Viewport;
+use crate::ray::Ray;
+use crate::scene::Scene;
+use crate::utils::{Real, Vec3, narrow};
+use crate::voxel_grid::Cell;
+
+const LIGHT_RADIUS: i32 = 8;    // Columns around the orbit target that show light levels
+const DIRTY_SECONDS: f32 = 3.0; // How long changed blocks stay highlighted
+const NEAR_PLANE: Real = 0.05;
+
+/// World-space debug overlays rasterized with raylib on top of the traced frame:
+/// chunk borders, per-block light levels and recently changed ("dirty") blocks
+pub struct DebugOverlay {
+    pub show_chunks: bool,
+    pub show_light: bool,
+    pub show_dirty: bool,
+    light_cache: HashMap<(i32, i32), Option<(i32, u8)>>, // Column -> (top y, light level 0-15)
+    light_day: f32, // Day fraction the light cache was computed for
+    dirty: Vec<(Cell, f32)>, // Changed cells and seconds left
+}
+
+impl DebugOverlay {
+    pub fn new() -> Self {
+        Self {
+            show_chunks: false,
+            show_light: false,
+            show_dirty: true,
+            light_cache: HashMap::new(),
+            light_day: 0.0,
+            dirty: Vec::new(),
+        }
+    }
+
+    /// Highlight cells whose blocks were added or removed
+    pub fn mark_dirty(&mut self, cells: Vec<Cell>) {
+        self.dirty.extend(cells.into_iter().map(|cell| (cell, DIRTY_SECONDS)));
+    }
+
+    /// Forget cached light levels (after the scene is rebuilt)
+    pub fn invalidate(&mut self) {
+        self.light_cache.clear();
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        for (_, time_left) in &mut self.dirty {
+            *time_left -= delta_time;
+        }
+        self.dirty.retain(|(_, time_left)| *time_left > 0.0);
+    }
+
+    pub fn draw(&mut self, d: &mut RaylibDrawHandle, scene: &Scene, camera: &Camera, viewport: Viewport) {
+        let view = View { camera, viewport };
+
+        if self.show_light {
+            self.draw_light_levels(d, scene, &view);
+        }
+        if self.show_chunks {
+            Self::draw_chunk_borders(d, scene, &view);
+        }
+        if self.show_dirty {
+            for &((x, y, z), time_left) in &self.dirty {
+                let alpha = (255.0 * (time_left / DIRTY_SECONDS).min(1.0)) as u8;
+                let center = Vec3::new(x as Real, y as Real, z as Real);
+                view.draw_box(d, center, 0.52, Color::new(255, 220, 0, alpha));
+            }
+        }
+    }
+
+    fn draw_chunk_borders(d: &mut RaylibDrawHandle, scene: &Scene, view: &View) {
+        let (min_y, max_y) = scene.grid.height_range();
+        let (bottom, top) = (min_y as Real - 0.5, max_y as Real + 1.5);
+
+        let target = view.camera.target;
+        let home_x = (target.x.round() as i32).div_euclid(CHUNK_SIZE);
+        let home_z = (target.z.round() as i32).div_euclid(CHUNK_SIZE);
+
+        for chunk_z in home_z - 1..=home_z + 1 {
+            for chunk_x in home_x - 1..=home_x + 1 {
+                // Cells sit on integer centers, so chunk edges fall on half-integers
+                let x0 = (chunk_x * CHUNK_SIZE) as Real - 0.5;
+                let z0 = (chunk_z * CHUNK_SIZE) as Real - 0.5;
+                let (x1, z1) = (x0 + CHUNK_SIZE as Real, z0 + CHUNK_SIZE as Real);
+
+                let color = if chunk_x == home_x && chunk_z == home_z { Color::YELLOW } else { Color::SKYBLUE };
+                let corners = [(x0, z0), (x1, z0), (x1, z1), (x0, z1)];
+                for i in 0..4 {
+                    let (ax, az) = corners[i];
+                    let (bx, bz) = corners[(i + 1) % 4];
+                    view.draw_line(d, Vec3::new(ax, bottom, az), Vec3::new(bx, bottom, bz), color);
+                    view.draw_line(d, Vec3::new(ax, top, az), Vec3::new(bx, top, bz), color);
+                    view.draw_line(d, Vec3::new(ax, bottom, az), Vec3::new(ax, top, az), color);
+                }
+            }
+        }
+    }
+
+    fn draw_light_levels(&mut self, d: &mut RaylibDrawHandle, scene: &Scene, view: &View) {
+        // Light changes with the time of day, so recompute once the sun has moved a bit
+        if (scene.time.day_fraction - self.light_day).abs() > 0.01 {
+            self.light_cache.clear();
+            self.light_day = scene.time.day_fraction;
+        }
+
+        let center_x = view.camera.target.x.round() as i32;
+        let center_z = view.camera.target.z.round() as i32;
+
+        for z in center_z - LIGHT_RADIUS..=center_z + LIGHT_RADIUS {
+            for x in center_x - LIGHT_RADIUS..=center_x + LIGHT_RADIUS {
+                let column = *self.light_cache.entry((x, z)).or_insert_with(|| {
+                    let (y, _) = scene.grid.top_block(x, z)?;
+                    Some((y, light_level(scene, Vec3::new(x as Real, y as Real + 0.5, z as Real))))
+                });
+
+                if let Some((y, level)) = column {
+                    // Dark = red, bright = green (0-15 like Minecraft's light levels)
+                    let t = level as f32 / 15.0;
+                    let tint = Color::new((255.0 * (1.0 - t)) as u8, (255.0 * t) as u8, 40, 90);
+                    view.draw_top_face(d, Vec3::new(x as Real, y as Real, z as Real), tint);
+                }
+            }
+        }
+    }
+}
+
+impl Default for DebugOverlay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Rough light level (0-15) of an upward-facing point: ambient, sun with shadow, and point lights
+fn light_level(scene: &Scene, point: Vec3) -> u8 {
+    let normal = Vec3::new(0.0, 1.0, 0.0);
+    let origin = point + normal * 0.001;
+    let day_time = scene.time.day_fraction;
+
+    let mut light = 0.45 * (1.0 - day_time) + 0.05 * day_time;
+
+    let (celestial, intensity) = scene.key_light();
+    let sun_dir = -celestial.direction;
+    let sun_strength = narrow(normal.dot(&sun_dir)).max(0.0);
+    if sun_strength > 0.0 && scene.intersect(&Ray::new(origin, sun_dir)).is_none() {
+        light += sun_strength * intensity;
+    }
+
+    for (position, _, direction, color) in scene.light_samples(point) {
+        let strength = narrow(normal.dot(&direction)).max(0.0);
+        if strength <= 0.0 {
+            continue;
+        }
+        let distance = (position - point).length();
+        let blocked = scene
+            .intersect(&Ray::new(origin, direction))
+            .is_some_and(|hit| hit.t < distance);
+        if !blocked {
+            light += (color.r + color.g + color.b) / 3.0 * strength;
+        }
+    }
+
+    (light.min(1.5) / 1.5 * 15.0).round() as u8
+}
+
+/// Projects world-space geometry to the screen for the overlay pass (also used by the light editor's gizmo)
+pub struct View<'a> {
+    pub camera: &'a Camera,
+    pub viewport: Viewport, // Where the traced frame sits in the window
+}
+
+impl View<'_> {
+    /// Screen position of a world point, None when it's behind the camera
+    pub fn project(&self, point: Vec3) -> Option<Vector2> {
+        let view = self.camera.world_to_view(point);
+        (view.z >= NEAR_PLANE).then(|| self.to_screen(view))
+    }
+
+    fn to_screen(&self, view: Vec3) -> Vector2 {
+        let (u, v) = self.camera.view_to_screen(view);
+        Vector2::new(
+            self.viewport.x as f32 + u * self.viewport.width as f32,
+            self.viewport.y as f32 + v * self.viewport.height as f32,
+        )
+    }
+
+    /// Line segment clipped against the near plane
+    pub fn draw_line(&self, d: &mut RaylibDrawHandle, a: Vec3, b: Vec3, color: Color) {
+        let mut a = self.camera.world_to_view(a);
+        let mut b = self.camera.world_to_view(b);
+        if a.z < NEAR_PLANE && b.z < NEAR_PLANE {
+            return;
+        }
+        if a.z < NEAR_PLANE {
+            a = a + (b - a) * ((NEAR_PLANE - a.z) / (b.z - a.z));
+        } else if b.z < NEAR_PLANE {
+            b = b + (a - b) * ((NEAR_PLANE - b.z) / (a.z - b.z));
+        }
+        d.draw_line_ex(self.to_screen(a), self.to_screen(b), 1.0, color);
+    }
+
+    /// Wireframe cube around a cell center
+    pub fn draw_box(&self, d: &mut RaylibDrawHandle, center: Vec3, half: Real, color: Color) {
+        let corner = |i: usize| {
+            Vec3::new(
+                center.x + if i & 1 == 0 { -half } else { half },
+                center.y + if i & 2 == 0 { -half } else { half },
+                center.z + if i & 4 == 0 { -half } else { half },
+            )
+        };
+        for i in 0..8 {
+            for axis in [1, 2, 4] {
+                if i & axis == 0 {
+                    self.draw_line(d, corner(i), corner(i | axis), color);
+                }
+            }
+        }
+    }
+
+    /// Filled translucent quad just above a block's top face
+    fn draw_top_face(&self, d: &mut RaylibDrawHandle, center: Vec3, color: Color) {
+        let y = center.y + 0.51;
+        let corners = [
+            Vec3::new(center.x - 0.5, y, center.z - 0.5),
+            Vec3::new(center.x + 0.5, y, center.z - 0.5),
+            Vec3::new(center.x + 0.5, y, center.z + 0.5),
+            Vec3::new(center.x - 0.5, y, center.z + 0.5),
+        ];
+        let view: Vec<Vec3> = corners.iter().map(|&c| self.camera.world_to_view(c)).collect();
+        if view.iter().any(|v| v.z < NEAR_PLANE) {
+            return; // Partly behind the camera, not worth clipping for a debug view
+        }
+        let screen: Vec<Vector2> = view.iter().map(|&v| self.to_screen(v)).collect();
+        draw_triangle_any_winding(d, screen[0], screen[1], screen[2], color);
+        draw_triangle_any_winding(d, screen[0], screen[2], screen[3], color);
+    }
+}
+
+// raylib only fills counter-clockwise triangles (as seen on screen), so flip clockwise ones
+fn draw_triangle_any_winding(d: &mut RaylibDrawHandle, a: Vector2, b: Vector2, c: Vector2, color: Color) {
+    let cross = (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x);
+    if cross < 0.0 {
+        d.draw_triangle(a, b, c, color);
+    } else {
+        d.draw_triangle(a, c, b, color);
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::cube::Cube;
+use crate::material::Material;
+use crate::material_palette::{MaterialId, MaterialPalette};
+use crate::noise::{fbm, smoothstep};
+use crate::palette;
+use crate::scene::Scene;
+use crate::texture_cache;
+use crate::utils::{Real, Vec3};
+use crate::voxel_grid::VoxelGrid;
+
+const STRUCTURE_HEIGHT: i32 = 3; // A column this much higher than a neighbour is a wall, not a hill
+const PATCH_SCALE: f32 = 0.25;   // Noise cells per block: flowers grow in patches a few blocks across
+const BUSH_SHARE: f32 = 0.35;    // Share of the spots right by a structure that get a bush instead of a flower
+const GARDEN_SIZE: (i32, i32) = (6, 5); // Fenced garden, fence included (along x, along z)
+const PIXEL: Real = 0.125;       // Flowers and crops are built from cubes this big
+const FENCE: Real = 0.25;        // Fence posts and rails
+
+// Everything the decorator puts down, added to the scene's palette once per pass
+struct Materials {
+    stem: MaterialId,
+    center: MaterialId,
+    petals: [MaterialId; 3],
+    bush: MaterialId,
+    fence: MaterialId,
+    bed: MaterialId,
+    wheat: MaterialId,
+}
+
+impl Materials {
+    fn new(scene_materials: &mut MaterialPalette) -> Self {
+        let mut add = |material: Material| scene_materials.add(material);
+        Self {
+            stem: add(Material::new(palette::LEAF_GREEN)),
+            center: add(Material::new(palette::FLOWER_YELLOW)),
+            petals: [
+                add(Material::new(palette::FLOWER_RED)),
+                add(Material::new(palette::FLOWER_YELLOW)),
+                add(Material::new(palette::FLOWER_BLUE)),
+            ],
+            bush: add(Material::new(palette::LEAF_GREEN).with_texture(texture_cache::load("assets/textures/grass.jpg"))),
+            fence: add(Material::new(palette::NEUTRAL_GRAY).with_texture(texture_cache::load("assets/textures/wood.png"))),
+            bed: add(Material::new(palette::DIRT_BROWN).with_texture(texture_cache::load("assets/textures/dirt.jpg"))),
+            wheat: add(Material::new(palette::WHEAT_YELLOW)),
+        }
+    }
+}
+
+// Unit blocks stacked at one (x, z)
+#[derive(Clone, Copy)]
+struct Column {
+    top: i32,      // Cell height of the highest block
+    bottom: i32,   // Cell height of the lowest block
+    blocks: usize, // Fewer than top - bottom + 1 means there is air under the top (a roof, a canopy)
+}
+
+// A column the decorator may plant on: grass-like top block, nothing above it
+#[derive(Clone, Copy)]
+struct Spot {
+    top: i32,        // Cell height of the top block
+    surface: Real,   // Height of its top face
+    distance: i32,   // Blocks to the nearest structure column (Chebyshev), radius + 1 when none is near
+}
+
+/// Decorator pass over a built scene: scatters flowers and bushes on the grass around its
+/// structures (walls, trees, anything that stands at least three blocks above the ground next to
+/// it or has a gap under it) and lays out a small fenced garden next to the biggest one. Flowers
+/// grow in noise patches whose fill is set by `density` (0-1), bushes hug the structures, and no
+/// two decorations are planted side by side. The same seed always decorates the same way.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Decorator {
+    pub seed: u32,
+    pub density: f32, // 0 = bare, 1 = every spot in a patch gets something
+    pub radius: i32,  // Blocks around the structures that get decorated
+    pub garden: bool,
+}
+
+impl Decorator {
+    pub fn new(seed: u32) -> Self {
+        Self { seed, density: 0.5, radius: 6, garden: true }
+    }
+
+    pub fn with_density(mut self, density: f32) -> Self {
+        self.density = density.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_radius(mut self, radius: i32) -> Self {
+        self.radius = radius.max(1);
+        self
+    }
+
+    pub fn with_garden(mut self, garden: bool) -> Self {
+        self.garden = garden;
+        self
+    }
+
+    /// Add the decorations to the scene's cubes (updating its grid first). Returns how many
+    /// flowers, bushes and gardens were placed.
+    pub fn decorate(&self, scene: &mut Scene) -> usize {
+        scene.update_grid();
+        let (columns, structures) = Self::survey(scene);
+        let mut spots = self.spots(scene, &columns, &structures);
+        let materials = Materials::new(&mut scene.materials);
+        let mut planted: HashSet<(i32, i32)> = HashSet::new();
+        let mut placed = 0;
+
+        if self.garden && let Some(site) = self.garden_site(&structures, &spots) {
+            self.build_garden(scene, &materials, &mut spots, site);
+            let (corner, _) = site;
+            for x in corner.0 - 1..=corner.0 + GARDEN_SIZE.0 {
+                for z in corner.1 - 1..=corner.1 + GARDEN_SIZE.1 {
+                    planted.insert((x, z)); // Keep the scattered flowers off the fence
+                }
+            }
+            placed += 1;
+        }
+
+        let mut order: Vec<(i32, i32)> = spots.keys().copied().collect();
+        order.sort_unstable();
+        for (x, z) in order {
+            let spot = spots[&(x, z)];
+            if spot.distance < 2 || spot.distance > self.radius {
+                continue; // Right against a wall (doors, paths) or out of reach
+            }
+            let crowded = (-1..=1).any(|dx| (-1..=1).any(|dz| planted.contains(&(x + dx, z + dz))));
+            if crowded {
+                continue;
+            }
+            let shift = (self.seed % 1024) as f32 * 7.1; // Each seed samples its own stretch of noise
+            let patch = fbm(x as f32 * PATCH_SCALE + shift, z as f32 * PATCH_SCALE - shift, 2);
+            let chance = self.density * smoothstep(0.35, 0.65, patch);
+            if hash(x, z, self.seed, 0) >= chance {
+                continue;
+            }
+            let base = Vec3::new(x as Real, spot.surface, z as Real);
+            if spot.distance == 2 && hash(x, z, self.seed, 1) < BUSH_SHARE {
+                scene.grid.extend(bush(base, &materials, |channel| hash(x, z, self.seed, channel)));
+            } else {
+                let offset = |channel| (hash(x, z, self.seed, channel) as Real - 0.5) * 0.4;
+                let base = base + Vec3::new(offset(2), 0.0, offset(3));
+                let petals = materials.petals[(hash(x, z, self.seed, 4) * 3.0) as usize % 3];
+                scene.grid.extend(flower(base, &materials, petals));
+            }
+            planted.insert((x, z));
+            placed += 1;
+        }
+
+        scene.update_grid();
+        placed
+    }
+
+    // Every column of unit blocks, and the ones that belong to structures
+    fn survey(scene: &Scene) -> (HashMap<(i32, i32), Column>, HashSet<(i32, i32)>) {
+        let mut columns: HashMap<(i32, i32), Column> = HashMap::new();
+        for chunk in scene.grid.chunks() {
+            for ((x, y, z), _) in chunk.blocks() {
+                let column = columns.entry((x, z)).or_insert(Column { top: y, bottom: y, blocks: 0 });
+                column.top = column.top.max(y);
+                column.bottom = column.bottom.min(y);
+                column.blocks += 1;
+            }
+        }
+        let structures = columns
+            .iter()
+            .filter(|&(&(x, z), column)| {
+                let gap = column.blocks < (column.top - column.bottom + 1) as usize;
+                let towers = [(1, 0), (-1, 0), (0, 1), (0, -1)]
+                    .iter()
+                    .any(|(dx, dz)| columns.get(&(x + dx, z + dz)).is_some_and(|next| column.top - next.top >= STRUCTURE_HEIGHT));
+                gap || towers
+            })
+            .map(|(&column, _)| column)
+            .collect();
+        (columns, structures)
+    }
+
+    // Columns with grass on top, away from the structures' own columns and free of detail cubes
+    fn spots(
+        &self,
+        scene: &Scene,
+        columns: &HashMap<(i32, i32), Column>,
+        structures: &HashSet<(i32, i32)>,
+    ) -> HashMap<(i32, i32), Spot> {
+        let occupied: HashSet<(i32, i32, i32)> = scene
+            .grid
+            .cubes()
+            .filter(|cube| cube.size < 0.99)
+            .map(|cube| VoxelGrid::cell_of(cube.position))
+            .collect();
+        let mut spots = HashMap::new();
+        for (&(x, z), &Column { top, .. }) in columns {
+            let Some(cube) = scene.grid.get((x, top, z)) else { continue };
+            let material = cube.top_face_material(&scene.materials);
+            let grassy = material.albedo.g > material.albedo.r && material.albedo.g > material.albedo.b;
+            if structures.contains(&(x, z)) || !grassy || material.is_emissive() || material.transparency > 0.0 || occupied.contains(&(x, top + 1, z)) {
+                continue;
+            }
+            let distance = (1..=self.radius)
+                .find(|&r| (-r..=r).any(|dx| (-r..=r).any(|dz| (dx.abs() == r || dz.abs() == r) && structures.contains(&(x + dx, z + dz)))))
+                .unwrap_or(self.radius + 1);
+            let surface = cube.position.y + cube.size / 2.0;
+            spots.insert((x, z), Spot { top, surface, distance });
+        }
+        spots
+    }
+
+    // Corner (lowest x and z) of a free, level patch of grass beside the biggest structure, and
+    // the fence cell (relative to the corner) where the gate goes, on the side facing it
+    fn garden_site(&self, structures: &HashSet<(i32, i32)>, spots: &HashMap<(i32, i32), Spot>) -> Option<((i32, i32), (i32, i32))> {
+        let biggest = clusters(structures).into_iter().max_by_key(Vec::len)?;
+        let (min_x, max_x) = (biggest.iter().map(|c| c.0).min()?, biggest.iter().map(|c| c.0).max()?);
+        let (min_z, max_z) = (biggest.iter().map(|c| c.1).min()?, biggest.iter().map(|c| c.1).max()?);
+        let (width, depth) = GARDEN_SIZE;
+        let (center_x, center_z) = ((min_x + max_x) / 2 - width / 2, (min_z + max_z) / 2 - depth / 2);
+        // In front (+z), to the sides, then behind, two blocks clear of the walls
+        let sides = [
+            ((center_x, max_z + 2), (width / 2, 0), true),
+            ((max_x + 2, center_z), (0, depth / 2), false),
+            ((min_x - width - 1, center_z), (width - 1, depth / 2), false),
+            ((center_x, min_z - depth - 1), (width / 2, depth - 1), true),
+        ];
+        sides.into_iter().find_map(|((x, z), gate, along_x)| {
+            // Nudged along the wall, nearest first, until the whole patch fits
+            (0..=6).map(|step| if step % 2 == 0 { step / 2 } else { -(step + 1) / 2 }).find_map(|shift| {
+                let corner = if along_x { (x + shift, z) } else { (x, z + shift) };
+                let level = spots.get(&corner)?.top;
+                let fits = (0..width).all(|dx| {
+                    (0..depth).all(|dz| {
+                        spots.get(&(corner.0 + dx, corner.1 + dz)).is_some_and(|spot| spot.top == level && spot.distance >= 2)
+                    })
+                });
+                fits.then_some((corner, gate))
+            })
+        })
+    }
+
+    // Fence of posts and two rails around the patch, open at the gate, and beds of tilled dirt
+    // inside with rows of wheat and flowers
+    fn build_garden(&self, scene: &mut Scene, materials: &Materials, spots: &mut HashMap<(i32, i32), Spot>, (corner, gate): ((i32, i32), (i32, i32))) {
+        let (width, depth) = GARDEN_SIZE;
+        let surface = spots[&corner].surface;
+        let fence = |x: Real, z: Real, height: Real| Cube::new(Vec3::new(x, surface + FENCE * height, z), FENCE, materials.fence);
+        let on_fence = |dx: i32, dz: i32| dx == 0 || dz == 0 || dx == width - 1 || dz == depth - 1;
+
+        for dx in 0..width {
+            for dz in 0..depth {
+                let (x, z) = (corner.0 + dx, corner.1 + dz);
+                if (dx, dz) == gate {
+                    continue;
+                }
+                if on_fence(dx, dz) {
+                    for height in [0.5, 1.5, 2.5] {
+                        scene.grid.add(fence(x as Real, z as Real, height));
+                    }
+                    // Rails toward the next post along the fence, +x on the front and back, +z on the sides
+                    let next = [(dx + 1, dz, dz == 0 || dz == depth - 1), (dx, dz + 1, dx == 0 || dx == width - 1)];
+                    for (nx, nz, along) in next {
+                        if !along || nx >= width || nz >= depth || (nx, nz) == gate {
+                            continue;
+                        }
+                        for step in 1..=3 {
+                            let t = step as Real * 0.25;
+                            let (rx, rz) = (x as Real + (nx - dx) as Real * t, z as Real + (nz - dz) as Real * t);
+                            scene.grid.add(fence(rx, rz, 1.5));
+                            scene.grid.add(fence(rx, rz, 2.5));
+                        }
+                    }
+                    continue;
+                }
+                // Beds: wheat on odd rows, flowers on even ones
+                let Some(spot) = spots.get(&(x, z)) else { continue };
+                if let Some(cube) = scene.grid.get_mut((x, spot.top, z)) {
+                    cube.top_material = Some(materials.bed);
+                }
+                let base = Vec3::new(x as Real, surface, z as Real);
+                if dz % 2 == 1 {
+                    for (ox, oz) in [(-0.25, -0.25), (0.25, -0.25), (-0.25, 0.25), (0.25, 0.25)] {
+                        let stalk = base + Vec3::new(ox, 0.0, oz);
+                        for (y, material) in [(0.5, materials.stem), (1.5, materials.stem), (2.5, materials.wheat)] {
+                            scene.grid.add(Cube::new(stalk + Vec3::new(0.0, PIXEL * y, 0.0), PIXEL, material));
+                        }
+                    }
+                } else {
+                    let petals = materials.petals[dx.rem_euclid(3) as usize];
+                    scene.grid.extend(flower(base, materials, petals));
+                }
+            }
+        }
+        for dx in 0..width {
+            for dz in 0..depth {
+                spots.remove(&(corner.0 + dx, corner.1 + dz));
+            }
+        }
+    }
+}
+
+impl Default for Decorator {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+// A flower standing on `base`: a three-cube stem and a cross of petals around a yellow center,
+// with one more petal on top
+fn flower(base: Vec3, materials: &Materials, petals: MaterialId) -> Vec<Cube> {
+    let at = |x: Real, y: Real, z: Real| base + Vec3::new(x * PIXEL, (y + 0.5) * PIXEL, z * PIXEL);
+    let mut cubes: Vec<Cube> = (0..3).map(|y| Cube::new(at(0.0, y as Real, 0.0), PIXEL, materials.stem)).collect();
+    cubes.push(Cube::new(at(0.0, 3.0, 0.0), PIXEL, materials.center));
+    for (x, y, z) in [(1.0, 3.0, 0.0), (-1.0, 3.0, 0.0), (0.0, 3.0, 1.0), (0.0, 3.0, -1.0), (0.0, 4.0, 0.0)] {
+        cubes.push(Cube::new(at(x, y, z), PIXEL, petals));
+    }
+    cubes
+}
+
+// A leaf cluster on `base`: a layer of four half-block cubes with one or two more on top
+fn bush(base: Vec3, materials: &Materials, random: impl Fn(u32) -> f32) -> Vec<Cube> {
+    let mut cubes = Vec::new();
+    for (i, (x, z)) in [(-0.25, -0.25), (0.25, -0.25), (-0.25, 0.25), (0.25, 0.25)].into_iter().enumerate() {
+        cubes.push(Cube::new(base + Vec3::new(x, 0.25, z), 0.5, materials.bush));
+        if random(5 + i as u32) < 0.4 {
+            cubes.push(Cube::new(base + Vec3::new(x, 0.75, z), 0.5, materials.bush));
+        }
+    }
+    cubes
+}
+
+// Structure columns grouped into 8-connected clusters
+fn clusters(columns: &HashSet<(i32, i32)>) -> Vec<Vec<(i32, i32)>> {
+    let mut seen = HashSet::new();
+    let mut clusters = Vec::new();
+    let mut sorted: Vec<&(i32, i32)> = columns.iter().collect();
+    sorted.sort_unstable();
+    for &start in sorted {
+        if !seen.insert(start) {
+            continue;
+        }
+        let (mut cluster, mut stack) = (Vec::new(), vec![start]);
+        while let Some((x, z)) = stack.pop() {
+            cluster.push((x, z));
+            for dx in -1..=1 {
+                for dz in -1..=1 {
+                    let next = (x + dx, z + dz);
+                    if columns.contains(&next) && seen.insert(next) {
+                        stack.push(next);
+                    }
+                }
+            }
+        }
+        clusters.push(cluster);
+    }
+    clusters
+}
+
+// Deterministic random value in [0, 1) for a column, one per channel
+fn hash(x: i32, z: i32, seed: u32, channel: u32) -> f32 {
+    let mut h = (x as u32).wrapping_mul(0x8DA6_B343) ^ (z as u32).wrapping_mul(0xD816_3841) ^ seed.wrapping_mul(0xCB1A_B31F);
+    h ^= channel.wrapping_mul(0x9E37_79B1);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x5BD1_E995);
+    h ^= h >> 15;
+    (h >> 8) as f32 / (1u32 << 24) as f32
+}
@@ -0,0 +1,149 @@
+use crate::color::Color;
+use crate::intersection::Intersection;
+use crate::ray::Ray;
+use crate::scene::Scene;
+use crate::utils::{Real, Vec3, narrow};
+
+// B3-spline weights of the 5x5 à-trous kernel (one axis)
+const KERNEL: [f32; 5] = [1.0 / 16.0, 1.0 / 4.0, 3.0 / 8.0, 1.0 / 4.0, 1.0 / 16.0];
+const PASSES: u32 = 4; // Taps 1, 2, 4 and 8 pixels apart: about 30 pixels of reach
+pub const REACH: i32 = 30; // How far (pixels) a pixel's result can pull from, 2 taps of each pass
+const NORMAL_SHARPNESS: f32 = 64.0; // Exponent on the normals' dot product, higher = stops at gentler creases
+const DEPTH_TOLERANCE: Real = 0.03; // Depth difference (as a share of the distance) that drops a neighbour to about a third
+const MIN_ALBEDO: f32 = 0.02; // Below this the surface color isn't divided out (near-black texels)
+
+/// What the camera sees first at each (scaled) pixel: the edges the denoiser must not blur across.
+/// Filled by the renderer from the frame's own camera rays as the tiles come in.
+pub struct GBuffer {
+    normal: Vec<Vec3>,
+    depth: Vec<Real>,
+    albedo: Vec<Color>,
+    filtered: Vec<bool>, // Plain opaque surface; sky, glass, water, mirrors and lamps are left alone
+}
+
+/// One pixel of a GBuffer
+#[derive(Clone, Copy)]
+pub struct Surface {
+    normal: Vec3,
+    depth: Real,
+    albedo: Color,
+    filtered: bool,
+}
+
+impl Surface {
+    /// Nothing hit: infinitely far and never filtered
+    pub const SKY: Surface = Surface { normal: Vec3 { x: 0.0, y: 0.0, z: 0.0 }, depth: Real::INFINITY, albedo: Color { r: 1.0, g: 1.0, b: 1.0 }, filtered: false };
+
+    /// Where the unjittered camera ray `ray` (end of the shutter) hit first, if anywhere
+    pub fn seen(scene: &Scene, ray: &Ray, hit: Option<&Intersection>) -> Self {
+        let Some(hit) = hit else {
+            return Self::SKY;
+        };
+        let material = &hit.material;
+        // Same mip as the renderer picks, so dividing the color out and back in cancels
+        let slant = hit.normal.dot(&ray.direction).abs().max(0.3);
+        let footprint = narrow(ray.footprint_at(hit.t) / slant / hit.uv_size);
+        Self {
+            normal: hit.normal,
+            depth: hit.t,
+            albedo: material.get_color(hit.u, hit.v, footprint, scene.time.elapsed),
+            filtered: material.transparency <= 0.0
+                && material.reflectivity <= 0.0
+                && material.shadow_catcher <= 0.0
+                && !material.is_emissive(),
+        }
+    }
+}
+
+impl GBuffer {
+    /// All sky until the pixels are set
+    pub fn new(width: i32, height: i32) -> Self {
+        let count = (width * height).max(0) as usize;
+        Self {
+            normal: vec![Surface::SKY.normal; count],
+            depth: vec![Surface::SKY.depth; count],
+            albedo: vec![Surface::SKY.albedo; count],
+            filtered: vec![Surface::SKY.filtered; count],
+        }
+    }
+
+    pub fn set(&mut self, index: usize, surface: Surface) {
+        self.normal[index] = surface.normal;
+        self.depth[index] = surface.depth;
+        self.albedo[index] = surface.albedo;
+        self.filtered[index] = surface.filtered;
+    }
+
+    /// Distance to the first hit per pixel (infinite = sky)
+    pub fn depth(&self) -> &[Real] {
+        &self.depth
+    }
+}
+
+/// Edge-avoiding à-trous wavelet filter (Dammertz et al.) over a float image the size of the
+/// G-buffer. The surface color is divided out first so only the lighting is smoothed and block
+/// textures stay crisp; neighbours only count when their normal and depth match.
+/// The weights depend on the G-buffer alone, so denoising then averaging frames is the same as
+/// averaging then denoising.
+pub fn denoise(colors: &mut [Color], gbuffer: &GBuffer, width: i32, height: i32) {
+    let mut lighting: Vec<Color> = colors
+        .iter()
+        .zip(&gbuffer.albedo)
+        .map(|(&color, &albedo)| demodulate(color, albedo))
+        .collect();
+    let mut next = lighting.clone();
+
+    for pass in 0..PASSES {
+        let step = 1 << pass;
+        for y in 0..height {
+            for x in 0..width {
+                let index = (y * width + x) as usize;
+                if !gbuffer.filtered[index] {
+                    continue;
+                }
+                let normal = gbuffer.normal[index];
+                let depth = gbuffer.depth[index];
+                let mut sum = Color::black();
+                let mut total = 0.0;
+                for (ky, wy) in KERNEL.iter().enumerate() {
+                    let ny = y + (ky as i32 - 2) * step;
+                    if ny < 0 || ny >= height {
+                        continue;
+                    }
+                    for (kx, wx) in KERNEL.iter().enumerate() {
+                        let nx = x + (kx as i32 - 2) * step;
+                        if nx < 0 || nx >= width {
+                            continue;
+                        }
+                        let neighbour = (ny * width + nx) as usize;
+                        if !gbuffer.filtered[neighbour] {
+                            continue;
+                        }
+                        let facing = narrow(normal.dot(&gbuffer.normal[neighbour])).max(0.0).powf(NORMAL_SHARPNESS);
+                        let gap = (depth - gbuffer.depth[neighbour]).abs() / (DEPTH_TOLERANCE * depth.max(1.0) * step as Real);
+                        let weight = wx * wy * facing * narrow((-gap).exp());
+                        sum = sum + lighting[neighbour] * weight;
+                        total += weight;
+                    }
+                }
+                // The center tap always counts, so total is never zero
+                next[index] = sum * (1.0 / total);
+            }
+        }
+        std::mem::swap(&mut lighting, &mut next);
+    }
+
+    for ((color, light), &albedo) in colors.iter_mut().zip(&lighting).zip(&gbuffer.albedo) {
+        *color = remodulate(*light, albedo);
+    }
+}
+
+fn demodulate(color: Color, albedo: Color) -> Color {
+    let divide = |c: f32, a: f32| if a > MIN_ALBEDO { c / a } else { c };
+    Color::new(divide(color.r, albedo.r), divide(color.g, albedo.g), divide(color.b, albedo.b))
+}
+
+fn remodulate(light: Color, albedo: Color) -> Color {
+    let multiply = |c: f32, a: f32| if a > MIN_ALBEDO { c * a } else { c };
+    Color::new(multiply(light.r, albedo.r), multiply(light.g, albedo.g), multiply(light.b, albedo.b))
+}
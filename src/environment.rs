@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use crate::color::Color;
+use crate::texture::Texture;
+use crate::utils::{Degrees, PI, Real, TAU, Vec3, narrow};
+
+// Cosine lobe convolution per spherical harmonics band (Ramamoorthi & Hanrahan), already divided
+// by pi so the result stands in for the flat ambient color
+const BAND_WEIGHTS: [f32; 3] = [1.0, 2.0 / 3.0, 1.0 / 4.0];
+
+/// Equirectangular (latitude-longitude) HDR image, usually a .hdr or .exr, shown behind the scene
+/// in place of the skybox. With `lighting` on it also replaces the flat ambient color: surfaces
+/// pick up the image's light from the directions they face, blue from above under an open sky,
+/// warm from the side where the sun sits low, and path traced bounces that escape bring back
+/// the image itself.
+#[derive(Clone)]
+pub struct Environment {
+    pub texture: Arc<Texture>,
+    pub intensity: f32,    // Multiplier on the image's values
+    pub rotation: Degrees, // Turn around the vertical axis, to line the image's sun up with the scene's
+    pub lighting: bool,    // Ambient light from the image instead of the day/night (or preset) color
+    irradiance: [Color; 9], // Image projected onto the first 9 spherical harmonics, for the ambient light
+}
+
+impl Environment {
+    pub fn load(path: &str, intensity: f32, rotation: Degrees, lighting: bool) -> Result<Self, String> {
+        Ok(Self::new(Texture::load_hdr(path)?, intensity, rotation, lighting))
+    }
+
+    pub fn new(texture: impl Into<Arc<Texture>>, intensity: f32, rotation: Degrees, lighting: bool) -> Self {
+        let texture = texture.into();
+        let irradiance = project(&texture);
+        Self { texture, intensity, rotation, lighting, irradiance }
+    }
+
+    /// Light arriving from a direction (the image behind the scene)
+    pub fn sample(&self, direction: Vec3) -> Color {
+        let (u, v) = self.uv(direction);
+        self.texture.sample(u, v) * self.intensity
+    }
+
+    /// Ambient light on a surface facing `normal`: the whole image, cosine-weighted around it
+    pub fn ambient(&self, normal: Vec3) -> Color {
+        let basis = sh_basis(self.rotate(normal));
+        let mut color = Color::black();
+        for (i, (coefficient, value)) in self.irradiance.iter().zip(basis).enumerate() {
+            let band = if i == 0 { 0 } else if i < 4 { 1 } else { 2 };
+            color = color + *coefficient * (value * BAND_WEIGHTS[band]);
+        }
+        Color::new(color.r.max(0.0), color.g.max(0.0), color.b.max(0.0)) * self.intensity
+    }
+
+    // World direction into the image's frame (turned by -rotation around the vertical)
+    fn rotate(&self, direction: Vec3) -> Vec3 {
+        let angle = self.rotation.radians();
+        let (sin, cos) = (angle.sin(), angle.cos());
+        Vec3::new(direction.x * cos + direction.z * sin, direction.y, direction.z * cos - direction.x * sin)
+    }
+
+    // Equirectangular lookup: -z in the middle of the image, up at the top row
+    fn uv(&self, direction: Vec3) -> (f32, f32) {
+        let d = self.rotate(direction.normalize());
+        let u = 0.5 + narrow(d.x.atan2(-d.z) / TAU);
+        let v = narrow(d.y.clamp(-1.0, 1.0).acos() / PI);
+        (u, v)
+    }
+}
+
+// Direction of a texel's center in the image's frame, the inverse of Environment::uv
+fn texel_direction(x: usize, y: usize, width: usize, height: usize) -> Vec3 {
+    let phi = ((x as Real + 0.5) / width as Real - 0.5) * TAU;
+    let theta = (y as Real + 0.5) / height as Real * PI;
+    Vec3::new(theta.sin() * phi.sin(), theta.cos(), -theta.sin() * phi.cos())
+}
+
+// Every texel weighted by the solid angle it covers (rows shrink toward the poles)
+fn project(texture: &Texture) -> [Color; 9] {
+    let (width, height) = (texture.width, texture.height);
+    let mut coefficients = [Color::black(); 9];
+    for y in 0..height {
+        let theta = (y as Real + 0.5) / height as Real * PI;
+        let solid_angle = narrow(TAU / width as Real * PI / height as Real * theta.sin());
+        for x in 0..width {
+            let color = texture.data[y * width + x] * solid_angle;
+            for (coefficient, value) in coefficients.iter_mut().zip(sh_basis(texel_direction(x, y, width, height))) {
+                *coefficient = *coefficient + color * value;
+            }
+        }
+    }
+    coefficients
+}
+
+// Real spherical harmonics up to band 2
+fn sh_basis(direction: Vec3) -> [f32; 9] {
+    let (x, y, z) = (narrow(direction.x), narrow(direction.y), narrow(direction.z));
+    [
+        0.282095,
+        0.488603 * y,
+        0.488603 * z,
+        0.488603 * x,
+        1.092548 * x * y,
+        1.092548 * y * z,
+        0.315392 * (3.0 * z * z - 1.0),
+        1.092548 * x * z,
+        0.546274 * (x * x - y * y),
+    ]
+}
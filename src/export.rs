@@ -0,0 +1,318 @@
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::bands;
+use crate::camera::Camera;
+use crate::camera_path::CameraPath;
+use crate::checkpoint::CheckpointOptions;
+use crate::lighting::LightingPreset;
+use crate::progress;
+use crate::renderer::{self, OFFLINE_LENS_SAMPLES, RenderSettings};
+use crate::scene::Scene;
+use crate::screenshot;
+use crate::tonemap::ToneMap;
+use crate::viewpoint::Viewpoint;
+
+const MAX_SIDE: i32 = 16_384; // Largest --size side (16K), so pixel counts and indices stay inside i32
+
+/// Offline frame-sequence export (e.g. a day/night timelapse)
+pub struct ExportOptions {
+    pub frames: u32,
+    pub output_dir: PathBuf,
+    pub width: i32,
+    pub height: i32,
+    pub day_start: f32, // Day fraction of the first frame
+    pub day_end: f32,   // Day fraction of the last frame
+    pub camera_path: Option<CameraPath>, // Flown over the whole export when given
+    pub views: Vec<String>, // Scene cameras rendered every frame, each into its own folder ("all" = every camera)
+    pub lighting: Option<LightingPreset>, // Fixed look for every frame (the day range is ignored)
+    pub settings: RenderSettings,
+    pub checkpoint: CheckpointOptions, // Periodic saves of unfinished frames, --resume continues them
+}
+
+impl ExportOptions {
+    /// Parse `--export-frames N [--out DIR] [--size WxH] [--day-range A:B] [--camera-path FILE] [--views all|A,B]
+    /// [--lighting PRESET] [--path-tracing SAMPLES] [--denoise] [--motion-blur SAMPLES] [--shutter FRACTION]
+    /// [--tone-map clamp|reinhard|aces] [--exposure X] [--bloom STRENGTH] [--checkpoint-every SECS] [--resume] [--transparent]` from the command line
+    pub fn from_args(args: &[String]) -> Option<Result<Self, String>> {
+        let frames = arg_value(args, "--export-frames")?;
+        Some(Self::parse(args, frames))
+    }
+
+    fn parse(args: &[String], frames: &str) -> Result<Self, String> {
+        let frames: u32 = frames
+            .parse()
+            .map_err(|_| format!("Invalid frame count '{}'", frames))?;
+        if frames == 0 {
+            return Err("Frame count must be at least 1".to_string());
+        }
+
+        let output_dir = PathBuf::from(arg_value(args, "--out").unwrap_or("frames"));
+
+        let (width, height) = match arg_value(args, "--size") {
+            Some(size) => parse_size(size)?,
+            None => (800, 600),
+        };
+
+        let (day_start, day_end) = match arg_value(args, "--day-range") {
+            Some(range) => parse_pair(range, ':').ok_or_else(|| format!("Invalid day range '{}', expected A:B", range))?,
+            None => (0.0, 1.0),
+        };
+
+        let camera_path = match arg_value(args, "--camera-path") {
+            Some(path) => Some(CameraPath::load(Path::new(path))?),
+            None => None,
+        };
+
+        let views: Vec<String> = arg_value(args, "--views")
+            .map(|list| list.split(',').map(|name| name.trim().to_string()).filter(|name| !name.is_empty()).collect())
+            .unwrap_or_default();
+        if !views.is_empty() && camera_path.is_some() {
+            return Err("--views and --camera-path can't be combined".to_string());
+        }
+
+        let lighting = match arg_value(args, "--lighting") {
+            Some(name) => Some(LightingPreset::parse(name)?),
+            None => None,
+        };
+        let path_samples = path_tracing_samples(args)?;
+        let motion_blur_samples = match arg_value(args, "--motion-blur") {
+            Some(samples) => samples
+                .parse()
+                .ok()
+                .filter(|samples| *samples > 1)
+                .ok_or_else(|| format!("Invalid motion blur sample count '{}', expected 2 or more", samples))?,
+            None => 0,
+        };
+        let shutter = match arg_value(args, "--shutter") {
+            Some(fraction) => fraction
+                .parse()
+                .ok()
+                .filter(|fraction| *fraction > 0.0 && *fraction <= 1.0)
+                .ok_or_else(|| format!("Invalid shutter '{}', expected a fraction of a frame (0.5 = 180 degrees)", fraction))?,
+            None => RenderSettings::default().shutter,
+        };
+        let tone_map = match arg_value(args, "--tone-map") {
+            Some(name) => ToneMap::by_name(name)?,
+            None => ToneMap::default(),
+        };
+        let exposure = match arg_value(args, "--exposure") {
+            Some(exposure) => exposure
+                .parse()
+                .ok()
+                .filter(|exposure: &f32| *exposure > 0.0)
+                .ok_or_else(|| format!("Invalid exposure '{}', expected a positive multiplier", exposure))?,
+            None => 1.0,
+        };
+        let bloom = match arg_value(args, "--bloom") {
+            Some(strength) => strength
+                .parse()
+                .ok()
+                .filter(|strength: &f32| *strength >= 0.0)
+                .ok_or_else(|| format!("Invalid bloom strength '{}', expected a number (0 = off, 0.3 = the app's default)", strength))?,
+            None => 0.0,
+        };
+
+        Ok(Self {
+            frames,
+            output_dir,
+            width,
+            height,
+            day_start,
+            day_end,
+            camera_path,
+            views,
+            lighting,
+            settings: RenderSettings {
+                render_scale: 1, // Offline renders always use full resolution
+                use_threading: true,
+                frame_budget: 0.0,
+                ray_budget: 0,
+                transparent_background: args.iter().any(|a| a == "--transparent"),
+                shadow_samples: 16, // Smooth penumbrae
+                ao_samples: 16,
+                path_tracing: path_samples > 0,
+                path_samples,
+                denoise: args.iter().any(|a| a == "--denoise"),
+                motion_blur_samples, // Camera paths only, the poses are spread over the open shutter
+                shutter,
+                lens_samples: OFFLINE_LENS_SAMPLES, // Only used by cameras with an aperture
+                tone_map,
+                exposure,
+                bloom,
+                ..RenderSettings::default()
+            },
+            checkpoint: CheckpointOptions::from_args(args)?,
+        })
+    }
+}
+
+/// Paths per pixel from `--path-tracing N` (0 = Whitted shading, the default)
+pub fn path_tracing_samples(args: &[String]) -> Result<u32, String> {
+    match arg_value(args, "--path-tracing") {
+        Some(samples) => samples
+            .parse()
+            .ok()
+            .filter(|samples| *samples > 0)
+            .ok_or_else(|| format!("Invalid path tracing sample count '{}'", samples)),
+        None => Ok(0),
+    }
+}
+
+/// Value following a command line flag (`--flag value`)
+pub fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(|s| s.as_str())
+}
+
+pub fn parse_pair<T: std::str::FromStr>(text: &str, separator: char) -> Option<(T, T)> {
+    let (a, b) = text.split_once(separator)?;
+    Some((a.trim().parse().ok()?, b.trim().parse().ok()?))
+}
+
+/// `--size WxH`, both sides from 1 to MAX_SIDE pixels
+pub fn parse_size(size: &str) -> Result<(i32, i32), String> {
+    parse_pair(size, 'x')
+        .filter(|&(width, height)| (1..=MAX_SIDE).contains(&width) && (1..=MAX_SIDE).contains(&height))
+        .ok_or_else(|| format!("Invalid size '{}', expected WxH with sides from 1 to {}", size, MAX_SIDE))
+}
+
+// Render one frame with a progress bar and save it. After Ctrl+C the finished tiles
+// go to <name>_partial.png and the export stops. With --resume, frames already on disk
+// are skipped and an unfinished frame continues from its checkpoint.
+fn render_frame(
+    scene: &Scene,
+    camera: &Camera,
+    buffer: &mut [raylib::prelude::Color],
+    options: &ExportOptions,
+    path: &Path,
+    label: &str,
+) -> Result<(), String> {
+    if options.checkpoint.resume && path.exists() {
+        return Ok(());
+    }
+    // Frames past 4K go band by band straight into the file, each band checkpointed on its own
+    if banded(options) {
+        return bands::render_to_png(scene, camera, &options.settings, options.width, options.height, label, path, (1, 1), Some(&options.checkpoint)).map(|_| ());
+    }
+    buffer.fill(raylib::prelude::Color::BLACK);
+    let checkpoint = CheckpointOptions::path_for(path);
+    let finished = renderer::render_scene_with_progress(
+        scene,
+        camera,
+        buffer,
+        options.width,
+        options.height,
+        &options.settings,
+        label,
+        Some((&checkpoint, &options.checkpoint)),
+    );
+    let partial = progress::partial_path(path);
+    if finished {
+        let _ = std::fs::remove_file(&partial); // Left over from an interrupted run
+        return screenshot::save_png(buffer, options.width, options.height, path);
+    }
+    screenshot::save_png(buffer, options.width, options.height, &partial)?;
+    Err(format!("Interrupted, partial frame saved to {}", partial.display()))
+}
+
+fn banded(options: &ExportOptions) -> bool {
+    bands::needed(options.width, options.height)
+}
+
+/// Render every frame to numbered PNGs (frame_0000.png, ...) with progress on the console
+pub fn export_frames(scene: &mut Scene, camera: &Camera, options: &ExportOptions) -> Result<(), String> {
+    progress::install_interrupt_handler();
+    let mut camera = *camera;
+    camera.aspect = options.width as f32 / options.height as f32;
+    camera.begin_shutter(); // Without a camera path nothing moves during a frame
+
+    let pixels = if banded(options) { 0 } else { (options.width * options.height) as usize };
+    let mut buffer = vec![raylib::prelude::Color::BLACK; pixels];
+    let start = Instant::now();
+
+    // Named views are resolved up front so a typo fails before any rendering
+    let views: Vec<Viewpoint> = if options.views.iter().any(|name| name == "all") {
+        scene.viewpoints.clone()
+    } else {
+        options
+            .views
+            .iter()
+            .map(|name| Viewpoint::find(&scene.viewpoints, name).cloned())
+            .collect::<Result<_, _>>()?
+    };
+    if !options.views.is_empty() && views.is_empty() {
+        return Err("The scene has no [[cameras]] to export".to_string());
+    }
+    // Path files are in world coordinates
+    let camera_path = options.camera_path.clone().map(|mut path| {
+        path.translate(-scene.origin);
+        path
+    });
+
+    println!(
+        "Exporting {} frames ({}x{}) to {}",
+        options.frames,
+        options.width,
+        options.height,
+        options.output_dir.display()
+    );
+    if let Some(ref lighting) = options.lighting {
+        println!("  lighting: {}", lighting.name);
+        scene.set_lighting(Some(lighting.clone()));
+    }
+    if !views.is_empty() {
+        let names: Vec<&str> = views.iter().map(|v| v.name.as_str()).collect();
+        println!("  views: {}", names.join(", "));
+    }
+
+    // Share of the whole export one frame covers (the last frame is at 1.0)
+    let frame_step = if options.frames > 1 { 1.0 / (options.frames - 1) as f32 } else { 0.0 };
+    for frame in 0..options.frames {
+        // Sweep the day cycle from day_start to day_end (inclusive)
+        let t = frame as f32 * frame_step;
+        let day = options.day_start + (options.day_end - options.day_start) * t;
+        scene.time.day_fraction = day.rem_euclid(1.0);
+        scene.time.days = day.div_euclid(1.0).max(0.0) as u32; // Ranges past 1.0 step the moon through its phases
+        scene.update_sun_position();
+        scene.update_particles();
+
+        if let Some(ref path) = camera_path {
+            // Motion blur: the shutter opens on the previous frame's pose and the rays are spread
+            // over the poses in between (the first frame has no earlier pose, so it's sharp)
+            path.apply(&mut camera, path.start_time() + path.duration() * (t - frame_step));
+            camera.begin_shutter();
+            path.apply(&mut camera, path.start_time() + path.duration() * t);
+        }
+
+        let file_name = format!("frame_{:04}.png", frame);
+        let label = format!("frame {}/{}", frame + 1, options.frames);
+        if views.is_empty() {
+            render_frame(scene, &camera, &mut buffer, options, &options.output_dir.join(&file_name), &label)?;
+        }
+        for view in &views {
+            view.apply(&mut camera);
+            camera.begin_shutter();
+            let path = options.output_dir.join(&view.name).join(&file_name);
+            render_frame(scene, &camera, &mut buffer, options, &path, &format!("{} {}", label, view.name))?;
+        }
+
+        let done = frame + 1;
+        let elapsed = start.elapsed().as_secs_f32();
+        let per_frame = elapsed / done as f32;
+        let eta = per_frame * (options.frames - done) as f32;
+        println!(
+            "  [{:>3}%] frame {}/{} - {:.2}s/frame, ETA {:.0}s",
+            done * 100 / options.frames,
+            done,
+            options.frames,
+            per_frame,
+            eta
+        );
+    }
+
+    println!("Export finished in {:.1}s", start.elapsed().as_secs_f32());
+    Ok(())
+}
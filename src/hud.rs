@@ -0,0 +1,174 @@
+use raylib::prelude::*;
+use serde::Deserialize;
+
+use crate::locale::Strings;
+
+/// [hud] section of config.toml
+#[derive(Deserialize, Clone)]
+pub struct HudConfig {
+    #[serde(default = "default_theme")]
+    pub theme: String, // "default", "colorblind" (red/green safe) or "tritanopia" (blue/yellow safe)
+    #[serde(default = "default_scale")]
+    pub scale: f32, // Text size multiplier, e.g. 2.0 on high-DPI screens
+    #[serde(default)]
+    pub high_contrast: bool, // Dark panels behind all text, brighter colors
+    #[serde(default = "default_language")]
+    pub language: String, // "en" or "es": HUD text, controls help and in-app console messages
+    #[serde(default = "default_true")]
+    pub crosshair: bool, // Crosshair and an outline around the block it points at
+}
+
+fn default_theme() -> String { "default".to_string() }
+fn default_scale() -> f32 { 1.0 }
+fn default_language() -> String { "en".to_string() }
+fn default_true() -> bool { true }
+
+impl Default for HudConfig {
+    fn default() -> Self {
+        Self {
+            theme: default_theme(),
+            scale: default_scale(),
+            high_contrast: false,
+            language: default_language(),
+            crosshair: true,
+        }
+    }
+}
+
+/// HUD colors by meaning instead of hue, so a palette swap keeps good/bad distinguishable
+#[derive(Clone, Copy)]
+pub struct HudTheme {
+    pub good: Color,   // High FPS, high quality, success messages
+    pub warn: Color,   // Medium FPS, low quality, soft warnings
+    pub bad: Color,    // Low FPS, errors
+    pub info: Color,   // Neutral values (medium quality, time of day)
+    pub accent: Color, // Mode tags like [AUTO PERF]
+    pub text: Color,
+    pub dim: Color,
+    pub help: Color, // Controls list at the bottom
+}
+
+impl HudTheme {
+    pub fn by_name(name: &str) -> Result<Self, String> {
+        match name {
+            "default" => Ok(Self {
+                good: Color::LIME,
+                warn: Color::ORANGE,
+                bad: Color::RED,
+                info: Color::SKYBLUE,
+                accent: Color::GOLD,
+                text: Color::WHITE,
+                dim: Color::LIGHTGRAY,
+                help: Color::BLACK,
+            }),
+            // Okabe-Ito palette: blue/orange/vermillion stay apart with deuteranopia and protanopia
+            "colorblind" => Ok(Self {
+                good: Color::new(86, 180, 233, 255),
+                warn: Color::new(230, 159, 0, 255),
+                bad: Color::new(213, 94, 0, 255),
+                info: Color::new(204, 121, 167, 255),
+                accent: Color::new(240, 228, 66, 255),
+                text: Color::WHITE,
+                dim: Color::LIGHTGRAY,
+                help: Color::BLACK,
+            }),
+            // Blue and yellow look alike with tritanopia, so lean on red/cyan and brightness
+            "tritanopia" => Ok(Self {
+                good: Color::new(0, 200, 200, 255),
+                warn: Color::new(255, 140, 180, 255),
+                bad: Color::new(220, 30, 50, 255),
+                info: Color::new(230, 230, 230, 255),
+                accent: Color::new(255, 90, 120, 255),
+                text: Color::WHITE,
+                dim: Color::LIGHTGRAY,
+                help: Color::BLACK,
+            }),
+            other => Err(format!(
+                "Unknown HUD theme '{}', expected default, colorblind or tritanopia",
+                other
+            )),
+        }
+    }
+
+    // Same hues pushed toward full brightness, white text on the dark panels
+    fn high_contrast(self) -> Self {
+        let brighten = |c: Color| {
+            let boost = 255.0 / c.r.max(c.g).max(c.b).max(1) as f32;
+            Color::new(
+                (c.r as f32 * boost) as u8,
+                (c.g as f32 * boost) as u8,
+                (c.b as f32 * boost) as u8,
+                255,
+            )
+        };
+        Self {
+            good: brighten(self.good),
+            warn: brighten(self.warn),
+            bad: brighten(self.bad),
+            info: brighten(self.info),
+            accent: brighten(self.accent),
+            text: Color::WHITE,
+            dim: Color::WHITE,
+            help: Color::WHITE,
+        }
+    }
+}
+
+/// Draws HUD text in the configured theme and size. Positions are given for scale 1 and
+/// scaled from the window edge they're anchored to.
+pub struct Hud {
+    pub theme: HudTheme,
+    pub scale: f32,
+    pub high_contrast: bool,
+    pub crosshair: bool,
+    pub strings: &'static Strings,
+}
+
+impl Hud {
+    pub fn new(config: &HudConfig) -> Result<Self, String> {
+        let theme = HudTheme::by_name(&config.theme)?;
+        let strings = Strings::by_name(&config.language)?;
+        if !(0.5..=4.0).contains(&config.scale) {
+            return Err(format!("HUD scale must be between 0.5 and 4.0, got {}", config.scale));
+        }
+        Ok(Self {
+            theme: if config.high_contrast { theme.high_contrast() } else { theme },
+            scale: config.scale,
+            high_contrast: config.high_contrast,
+            crosshair: config.crosshair,
+            strings,
+        })
+    }
+
+    /// Scale a length (position or size) given at scale 1
+    pub fn px(&self, value: i32) -> i32 {
+        (value as f32 * self.scale).round() as i32
+    }
+
+    /// Text anchored to the top-left corner
+    pub fn text(&self, d: &mut RaylibDrawHandle, text: &str, x: i32, y: i32, size: i32, color: Color) {
+        self.draw(d, text, self.px(x), self.px(y), size, color);
+    }
+
+    /// Text anchored to the bottom edge: `from_bottom` is the distance of its top from the window bottom
+    pub fn text_bottom(&self, d: &mut RaylibDrawHandle, text: &str, x: i32, from_bottom: i32, size: i32, color: Color) {
+        let y = d.get_screen_height() - self.px(from_bottom);
+        self.draw(d, text, self.px(x), y, size, color);
+    }
+
+    /// Text anchored to the bottom-right corner: `from_right` is the distance of its left edge from the right
+    pub fn text_bottom_right(&self, d: &mut RaylibDrawHandle, text: &str, from_right: i32, from_bottom: i32, size: i32, color: Color) {
+        let (x, y) = (d.get_screen_width() - self.px(from_right), d.get_screen_height() - self.px(from_bottom));
+        self.draw(d, text, x, y, size, color);
+    }
+
+    fn draw(&self, d: &mut RaylibDrawHandle, text: &str, x: i32, y: i32, size: i32, color: Color) {
+        let size = self.px(size);
+        if self.high_contrast {
+            let pad = self.px(2);
+            let width = d.measure_text(text, size);
+            d.draw_rectangle(x - pad, y - pad, width + pad * 2, size + pad * 2, Color::new(0, 0, 0, 220));
+        }
+        d.draw_text(text, x, y, size, color);
+    }
+}
@@ -1,18 +1,23 @@
-use crate::utils::Vec3;
+use crate::utils::{Real, Vec3};
 use crate::material::Material;
+use crate::ray::Ray;
 
 #[derive(Clone)]
 pub struct Intersection {
-    pub t: f32,
+    pub t: Real,
     pub position: Vec3,
     pub normal: Vec3,
     pub material: Material,
     pub u: f32,
     pub v: f32,
+    pub uv_size: Real, // World size spanned by the 0..1 uv range (face size, for mip selection)
+    pub tangent: Vec3,   // World direction of +u on the surface (zero when the surface has no uv frame)
+    pub bitangent: Vec3, // World direction of "up" in the texture (-v), for normal maps
+    pub front_face: bool, // Ray arrived from outside; false when leaving a solid or looking at a back face
 }
 
 impl Intersection {
-    pub fn new(t: f32, position: Vec3, normal: Vec3, material: Material, u: f32, v: f32) -> Self {
+    pub fn new(t: Real, position: Vec3, normal: Vec3, material: Material, u: f32, v: f32) -> Self {
         Self {
             t,
             position,
@@ -20,6 +25,33 @@ impl Intersection {
             material,
             u,
             v,
+            uv_size: 1.0,
+            tangent: Vec3::zero(),
+            bitangent: Vec3::zero(),
+            front_face: true,
         }
     }
+
+    pub fn with_uv_size(mut self, uv_size: Real) -> Self {
+        self.uv_size = uv_size;
+        self
+    }
+
+    pub fn with_tangents(mut self, tangent: Vec3, bitangent: Vec3) -> Self {
+        self.tangent = tangent;
+        self.bitangent = bitangent;
+        self
+    }
+
+    /// Convention for every primitive: `normal` comes in pointing outward and leaves facing
+    /// against the ray, with `front_face` telling which side was hit. Renderer code relies on it
+    /// (offsets go along +normal to stay outside, -normal to get through) so it never has to
+    /// look at the ray direction again.
+    pub fn oriented(mut self, ray: &Ray) -> Self {
+        self.front_face = self.normal.dot(&ray.direction) < 0.0;
+        if !self.front_face {
+            self.normal = -self.normal;
+        }
+        self
+    }
 }
@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+
+use raylib::prelude::*;
+use serde::Deserialize;
+
+/// Everything the user can trigger from the keyboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    LookUp,
+    LookDown,
+    LookLeft,
+    LookRight,
+    ZoomIn,
+    ZoomOut,
+    MoveUp,
+    MoveDown,
+    QualityLow,
+    QualityMedium,
+    QualityHigh,
+    QualityUltra,
+    AutoQuality,
+    Threading,
+    MotionBlur,
+    ThirdPerson,
+    Minimap,
+    Hybrid,
+    PathPlay,
+    PathRecord,
+    NextCamera,
+    Lighting,
+    DayCycle,
+    Rain,
+    Reload,
+    Screenshot,
+    DebugChunks,
+    DebugLight,
+    DebugDirty,
+    PresentMode,
+    PathTracing,
+    Denoise,
+    TemporalAa,
+    DepthOfField,
+    FocusNear,
+    FocusFar,
+    SunDrag,
+    ShadowPreview,
+    RollLeft,
+    RollRight,
+    HorizonLock,
+    UvGrid,
+    PhotoMode,
+    PhotoResolution,
+    LightEdit,
+    LightPlacePoint,
+    LightPlaceSpot,
+    LightSelect,
+    LightDelete,
+    LightSave,
+    LightXMinus,
+    LightXPlus,
+    LightYMinus,
+    LightYPlus,
+    LightZMinus,
+    LightZPlus,
+    LightRadiusDown,
+    LightRadiusUp,
+    BlockBreak,
+    BlockPlace,
+    WorldSave,
+}
+
+// Config name and default keys of every action
+const DEFAULTS: &[(Action, &str, &[KeyboardKey])] = &[
+    (Action::LookUp, "look_up", &[KeyboardKey::KEY_W]),
+    (Action::LookDown, "look_down", &[KeyboardKey::KEY_S]),
+    (Action::LookLeft, "look_left", &[KeyboardKey::KEY_A, KeyboardKey::KEY_LEFT]),
+    (Action::LookRight, "look_right", &[KeyboardKey::KEY_D, KeyboardKey::KEY_RIGHT]),
+    (Action::ZoomIn, "zoom_in", &[KeyboardKey::KEY_UP]),
+    (Action::ZoomOut, "zoom_out", &[KeyboardKey::KEY_DOWN]),
+    (Action::MoveUp, "move_up", &[KeyboardKey::KEY_Q]),
+    (Action::MoveDown, "move_down", &[KeyboardKey::KEY_E]),
+    (Action::QualityLow, "quality_low", &[KeyboardKey::KEY_ONE]),
+    (Action::QualityMedium, "quality_medium", &[KeyboardKey::KEY_TWO]),
+    (Action::QualityHigh, "quality_high", &[KeyboardKey::KEY_THREE]),
+    (Action::QualityUltra, "quality_ultra", &[KeyboardKey::KEY_FOUR]),
+    (Action::AutoQuality, "auto_quality", &[KeyboardKey::KEY_P]),
+    (Action::Threading, "threading", &[KeyboardKey::KEY_T]),
+    (Action::MotionBlur, "motion_blur", &[KeyboardKey::KEY_M]),
+    (Action::ThirdPerson, "third_person", &[KeyboardKey::KEY_V]),
+    (Action::Minimap, "minimap", &[KeyboardKey::KEY_TAB]),
+    (Action::Hybrid, "hybrid", &[KeyboardKey::KEY_R]),
+    (Action::PathPlay, "path_play", &[KeyboardKey::KEY_L]),
+    (Action::PathRecord, "path_record", &[KeyboardKey::KEY_K]),
+    (Action::NextCamera, "next_camera", &[KeyboardKey::KEY_C]),
+    (Action::Lighting, "lighting", &[KeyboardKey::KEY_G]),
+    (Action::DayCycle, "day_cycle", &[KeyboardKey::KEY_N]),
+    (Action::Rain, "rain", &[KeyboardKey::KEY_SEVEN]),
+    (Action::Reload, "reload", &[KeyboardKey::KEY_F5]),
+    (Action::Screenshot, "screenshot", &[KeyboardKey::KEY_F12]),
+    (Action::DebugChunks, "debug_chunks", &[KeyboardKey::KEY_F1]),
+    (Action::DebugLight, "debug_light", &[KeyboardKey::KEY_F2]),
+    (Action::DebugDirty, "debug_dirty", &[KeyboardKey::KEY_F3]),
+    (Action::PresentMode, "present_mode", &[KeyboardKey::KEY_F4]),
+    (Action::PathTracing, "path_tracing", &[KeyboardKey::KEY_I]),
+    (Action::Denoise, "denoise", &[KeyboardKey::KEY_O]),
+    (Action::TemporalAa, "temporal_aa", &[KeyboardKey::KEY_U]),
+    (Action::DepthOfField, "depth_of_field", &[KeyboardKey::KEY_F]),
+    (Action::FocusNear, "focus_near", &[KeyboardKey::KEY_Z]),
+    (Action::FocusFar, "focus_far", &[KeyboardKey::KEY_X]),
+    (Action::SunDrag, "sun_drag", &[KeyboardKey::KEY_F7]),
+    (Action::ShadowPreview, "shadow_preview", &[KeyboardKey::KEY_F8]),
+    (Action::RollLeft, "roll_left", &[KeyboardKey::KEY_NINE]),
+    (Action::RollRight, "roll_right", &[KeyboardKey::KEY_ZERO]),
+    (Action::HorizonLock, "horizon_lock", &[KeyboardKey::KEY_F9]),
+    (Action::UvGrid, "uv_grid", &[KeyboardKey::KEY_EIGHT]),
+    (Action::PhotoMode, "photo_mode", &[KeyboardKey::KEY_F10]),
+    (Action::PhotoResolution, "photo_resolution", &[KeyboardKey::KEY_F11]),
+    (Action::LightEdit, "light_edit", &[KeyboardKey::KEY_H]),
+    (Action::LightPlacePoint, "light_place_point", &[KeyboardKey::KEY_B]),
+    (Action::LightPlaceSpot, "light_place_spot", &[KeyboardKey::KEY_Y]),
+    (Action::LightSelect, "light_select", &[KeyboardKey::KEY_J]),
+    (Action::LightDelete, "light_delete", &[KeyboardKey::KEY_DELETE, KeyboardKey::KEY_BACKSPACE]),
+    (Action::LightSave, "light_save", &[KeyboardKey::KEY_F6]),
+    (Action::LightXMinus, "light_x_minus", &[KeyboardKey::KEY_COMMA, KeyboardKey::KEY_KP_4]),
+    (Action::LightXPlus, "light_x_plus", &[KeyboardKey::KEY_PERIOD, KeyboardKey::KEY_KP_6]),
+    (Action::LightYMinus, "light_y_minus", &[KeyboardKey::KEY_PAGE_DOWN, KeyboardKey::KEY_KP_3]),
+    (Action::LightYPlus, "light_y_plus", &[KeyboardKey::KEY_PAGE_UP, KeyboardKey::KEY_KP_9]),
+    (Action::LightZMinus, "light_z_minus", &[KeyboardKey::KEY_LEFT_BRACKET, KeyboardKey::KEY_KP_8]),
+    (Action::LightZPlus, "light_z_plus", &[KeyboardKey::KEY_RIGHT_BRACKET, KeyboardKey::KEY_KP_2]),
+    (Action::LightRadiusDown, "light_radius_down", &[KeyboardKey::KEY_MINUS]),
+    (Action::LightRadiusUp, "light_radius_up", &[KeyboardKey::KEY_EQUAL]),
+    (Action::BlockBreak, "block_break", &[KeyboardKey::KEY_FIVE]),
+    (Action::BlockPlace, "block_place", &[KeyboardKey::KEY_SIX]),
+    (Action::WorldSave, "world_save", &[KeyboardKey::KEY_INSERT]),
+];
+
+// Names accepted in the config file (raylib / US layout key positions)
+const KEY_NAMES: &[(&str, KeyboardKey)] = &[
+    ("A", KeyboardKey::KEY_A), ("B", KeyboardKey::KEY_B), ("C", KeyboardKey::KEY_C),
+    ("D", KeyboardKey::KEY_D), ("E", KeyboardKey::KEY_E), ("F", KeyboardKey::KEY_F),
+    ("G", KeyboardKey::KEY_G), ("H", KeyboardKey::KEY_H), ("I", KeyboardKey::KEY_I),
+    ("J", KeyboardKey::KEY_J), ("K", KeyboardKey::KEY_K), ("L", KeyboardKey::KEY_L),
+    ("M", KeyboardKey::KEY_M), ("N", KeyboardKey::KEY_N), ("O", KeyboardKey::KEY_O),
+    ("P", KeyboardKey::KEY_P), ("Q", KeyboardKey::KEY_Q), ("R", KeyboardKey::KEY_R),
+    ("S", KeyboardKey::KEY_S), ("T", KeyboardKey::KEY_T), ("U", KeyboardKey::KEY_U),
+    ("V", KeyboardKey::KEY_V), ("W", KeyboardKey::KEY_W), ("X", KeyboardKey::KEY_X),
+    ("Y", KeyboardKey::KEY_Y), ("Z", KeyboardKey::KEY_Z),
+    ("0", KeyboardKey::KEY_ZERO), ("1", KeyboardKey::KEY_ONE), ("2", KeyboardKey::KEY_TWO),
+    ("3", KeyboardKey::KEY_THREE), ("4", KeyboardKey::KEY_FOUR), ("5", KeyboardKey::KEY_FIVE),
+    ("6", KeyboardKey::KEY_SIX), ("7", KeyboardKey::KEY_SEVEN), ("8", KeyboardKey::KEY_EIGHT),
+    ("9", KeyboardKey::KEY_NINE),
+    ("F1", KeyboardKey::KEY_F1), ("F2", KeyboardKey::KEY_F2), ("F3", KeyboardKey::KEY_F3),
+    ("F4", KeyboardKey::KEY_F4), ("F5", KeyboardKey::KEY_F5), ("F6", KeyboardKey::KEY_F6),
+    ("F7", KeyboardKey::KEY_F7), ("F8", KeyboardKey::KEY_F8), ("F9", KeyboardKey::KEY_F9),
+    ("F10", KeyboardKey::KEY_F10), ("F11", KeyboardKey::KEY_F11), ("F12", KeyboardKey::KEY_F12),
+    ("UP", KeyboardKey::KEY_UP), ("DOWN", KeyboardKey::KEY_DOWN),
+    ("LEFT", KeyboardKey::KEY_LEFT), ("RIGHT", KeyboardKey::KEY_RIGHT),
+    ("SPACE", KeyboardKey::KEY_SPACE), ("ENTER", KeyboardKey::KEY_ENTER), ("TAB", KeyboardKey::KEY_TAB),
+    ("BACKSPACE", KeyboardKey::KEY_BACKSPACE), ("INSERT", KeyboardKey::KEY_INSERT),
+    ("DELETE", KeyboardKey::KEY_DELETE), ("HOME", KeyboardKey::KEY_HOME), ("END", KeyboardKey::KEY_END),
+    ("PAGE_UP", KeyboardKey::KEY_PAGE_UP), ("PAGE_DOWN", KeyboardKey::KEY_PAGE_DOWN),
+    ("MINUS", KeyboardKey::KEY_MINUS), ("EQUAL", KeyboardKey::KEY_EQUAL), ("COMMA", KeyboardKey::KEY_COMMA),
+    ("PERIOD", KeyboardKey::KEY_PERIOD), ("SLASH", KeyboardKey::KEY_SLASH),
+    ("SEMICOLON", KeyboardKey::KEY_SEMICOLON), ("APOSTROPHE", KeyboardKey::KEY_APOSTROPHE),
+    ("LEFT_BRACKET", KeyboardKey::KEY_LEFT_BRACKET), ("RIGHT_BRACKET", KeyboardKey::KEY_RIGHT_BRACKET),
+    ("BACKSLASH", KeyboardKey::KEY_BACKSLASH), ("GRAVE", KeyboardKey::KEY_GRAVE),
+    ("KP_0", KeyboardKey::KEY_KP_0), ("KP_1", KeyboardKey::KEY_KP_1), ("KP_2", KeyboardKey::KEY_KP_2),
+    ("KP_3", KeyboardKey::KEY_KP_3), ("KP_4", KeyboardKey::KEY_KP_4), ("KP_5", KeyboardKey::KEY_KP_5),
+    ("KP_6", KeyboardKey::KEY_KP_6), ("KP_7", KeyboardKey::KEY_KP_7), ("KP_8", KeyboardKey::KEY_KP_8),
+    ("KP_9", KeyboardKey::KEY_KP_9), ("KP_ADD", KeyboardKey::KEY_KP_ADD),
+    ("KP_SUBTRACT", KeyboardKey::KEY_KP_SUBTRACT),
+];
+
+/// One key name or a list of them in the config file (`zoom_in = "UP"` / `look_left = ["A", "LEFT"]`)
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Maps actions to keys: built-in defaults, overridden per action by the [keybindings] config section
+pub struct KeyBindings {
+    keys: HashMap<Action, Vec<KeyboardKey>>,
+}
+
+impl KeyBindings {
+    pub fn new() -> Self {
+        Self {
+            keys: DEFAULTS
+                .iter()
+                .map(|(action, _, keys)| (*action, keys.to_vec()))
+                .collect(),
+        }
+    }
+
+    /// Defaults with the given `action_name -> keys` overrides applied
+    pub fn with_overrides(overrides: &HashMap<String, KeyList>) -> Result<Self, String> {
+        let mut bindings = Self::new();
+        for (name, list) in overrides {
+            let action = DEFAULTS
+                .iter()
+                .find(|(_, config_name, _)| config_name == name)
+                .map(|(action, _, _)| *action)
+                .ok_or_else(|| format!("Unknown action '{}' in [keybindings]", name))?;
+
+            let names = match list {
+                KeyList::One(key) => std::slice::from_ref(key),
+                KeyList::Many(keys) => keys.as_slice(),
+            };
+            let keys = names
+                .iter()
+                .map(|key| key_from_name(key).ok_or_else(|| format!("Unknown key '{}' for action '{}'", key, name)))
+                .collect::<Result<Vec<_>, _>>()?;
+            bindings.keys.insert(action, keys);
+        }
+        Ok(bindings)
+    }
+
+    fn keys(&self, action: Action) -> &[KeyboardKey] {
+        self.keys.get(&action).map_or(&[], |keys| keys.as_slice())
+    }
+
+    /// Any key of the action held this frame
+    pub fn down(&self, rl: &RaylibHandle, action: Action) -> bool {
+        self.keys(action).iter().any(|&key| rl.is_key_down(key))
+    }
+
+    /// Any key of the action went down this frame
+    pub fn pressed(&self, rl: &RaylibHandle, action: Action) -> bool {
+        self.keys(action).iter().any(|&key| rl.is_key_pressed(key))
+    }
+
+    /// Display name of the action's first key, for the on-screen controls
+    pub fn label(&self, action: Action) -> &'static str {
+        self.keys(action)
+            .first()
+            .and_then(|&key| KEY_NAMES.iter().find(|(_, k)| *k == key))
+            .map_or("-", |(name, _)| *name)
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn key_from_name(name: &str) -> Option<KeyboardKey> {
+    let name = name.trim().to_uppercase();
+    KEY_NAMES.iter().find(|(n, _)| *n == name).map(|(_, key)| *key)
+}
@@ -0,0 +1,100 @@
+//! Minecraft-style voxel raytracer. The `minecraft-raytracer` binary (src/main.rs) is the app and
+//! the headless tools; everything it renders with lives here, so other programs can build scenes
+//! (`scene_builder::SceneBuilder`) and render them (`renderer::render_scene`) too.
+
+// The library: scenes, materials and lights, and the renderer
+pub mod camera;
+pub mod camera_path;
+pub mod ray;
+pub mod material;
+pub mod material_palette;
+pub mod texture;
+pub mod procedural_texture;
+pub mod texture_atlas;
+pub mod texture_cache;
+pub mod color;
+pub mod palette;
+pub mod scene;
+pub mod scene_builder;
+pub mod scene_file;
+pub mod scene_time;
+pub mod scene_events;
+pub mod screenshot;
+pub mod progress;
+pub mod watchdog;
+pub mod cube;
+pub mod sign;
+pub mod decorations;
+pub mod light;
+pub mod lighting;
+pub mod point_light;
+pub mod skybox;
+pub mod noise;
+pub mod weather;
+pub mod particles;
+pub mod environment;
+pub mod backdrop;
+pub mod obj_loader;
+pub mod intersection;
+pub mod renderer;
+pub mod denoise;
+pub mod tonemap;
+pub mod utils;
+pub mod voxel_grid;
+pub mod streaming;
+pub mod viewpoint;
+
+// Parts of the renderer and the scene loader behind that API
+mod scene_migration;
+mod box_batch;
+mod chunk;
+mod shoreline;
+mod waves;
+mod post;
+mod bands;
+
+// The app and the headless tools (src/main.rs), not part of the library's API
+#[doc(hidden)]
+pub mod config;
+#[doc(hidden)]
+pub mod keybindings;
+#[doc(hidden)]
+pub mod hud;
+#[doc(hidden)]
+pub mod locale;
+#[doc(hidden)]
+pub mod player;
+#[doc(hidden)]
+pub mod preview;
+#[doc(hidden)]
+pub mod presentation;
+#[doc(hidden)]
+pub mod crosshair;
+#[doc(hidden)]
+pub mod debug_overlay;
+#[doc(hidden)]
+pub mod uv_debug;
+#[doc(hidden)]
+pub mod minimap;
+#[doc(hidden)]
+pub mod photo_mode;
+#[doc(hidden)]
+pub mod light_editor;
+#[doc(hidden)]
+pub mod block_edit;
+#[doc(hidden)]
+pub mod world_file;
+#[doc(hidden)]
+pub mod temporal;
+#[doc(hidden)]
+pub mod export;
+#[doc(hidden)]
+pub mod batch;
+#[doc(hidden)]
+pub mod checkpoint;
+#[doc(hidden)]
+pub mod turntable;
+#[doc(hidden)]
+pub mod scene_diff;
+#[doc(hidden)]
+pub mod conformance;
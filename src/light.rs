@@ -1,10 +1,51 @@
-use crate::utils::Vec3;
+use crate::utils::{Degrees, Radians, Real, TAU, Vec3, narrow, random_f32};
 use crate::color::Color;
+use crate::palette;
+use crate::point_light::Falloff;
+
+// The real sun is about 0.27°; a bit larger gives visible soft edges under the trees
+const SUN_ANGULAR_RADIUS: Degrees = Degrees(1.5);
+const MOON_ANGULAR_RADIUS: Degrees = Degrees(0.75);
+const MIN_SUN_ELEVATION: Degrees = Degrees(2.0); // A dragged sun stays this far above the horizon
+
+// === Light units ===
+// Shading works in display units: a white surface facing a light of intensity 1.0 head-on comes
+// out white, and ambient colors add on top on the same scale. `lux` and `lumens` put real-world-ish
+// numbers on it, so a new light can be picked by what it is rather than tuned by eye.
+//
+// There is no exposure control, so the two scales are paired by a fixed exposure instead of by
+// physics: daylight is metered for the noon sun (like a camera at about EV 15) and lamps as if the
+// eye had adjusted to lamplight, where a 100 lm bulb lights the block next to it about as brightly
+// as the sun lights open ground. (A real bulb is some 10 000x dimmer than the sun; metered together,
+// torches would vanish by day.) Emissive blocks glow at their `emissive` color on screen and give
+// off GLOWSTONE_LUMENS per fully lit block.
+
+/// Illuminance of sunlight at intensity 1.0; sun, moon and ambient (sky) light are on this scale
+pub const FULL_SUN_LUX: f32 = 100_000.0;
+/// Luminous flux of a point or spot light at intensity 1.0
+pub const LUMENS_PER_INTENSITY: f32 = 100.0;
+
+// Reference values for new lights
+pub const NOON_SUN_LUX: f32 = 120_000.0;  // The day/night cycle's sun at its highest
+pub const MOONLIGHT_LUX: f32 = 25_000.0;  // Exposed for a night scene, so far above the real ~0.3 lx
+pub const LANTERN_LUMENS: f32 = 250.0;    // The house lanterns; a Minecraft torch is about the same
+pub const GLOWSTONE_LUMENS: f32 = 300.0;  // A full block glowing white (emissive = [1, 1, 1])
+
+/// Sun, moon or ambient intensity from an illuminance in lux
+pub const fn lux(lux: f32) -> f32 {
+    lux / FULL_SUN_LUX
+}
+
+/// Point or spot light intensity from a luminous flux in lumens
+pub const fn lumens(lumens: f32) -> f32 {
+    lumens / LUMENS_PER_INTENSITY
+}
 
 pub struct DirectionalLight {
     pub direction: Vec3,
     pub color: Color,
     pub intensity: f32,
+    pub angular_radius: Degrees, // Size of the light's disk in the sky (0 = hard shadows)
 }
 
 impl DirectionalLight {
@@ -13,11 +54,63 @@ impl DirectionalLight {
             direction: direction.normalize(),
             color,
             intensity,
+            angular_radius: Degrees(0.0),
         }
     }
 
     pub fn sun(direction: Vec3, intensity: f32) -> Self {
-        Self::new(direction, Color::new(1.0, 0.95, 0.9), intensity)
+        Self::new(direction, Color::new(1.0, 0.95, 0.9), intensity).with_angular_radius(SUN_ANGULAR_RADIUS)
+    }
+
+    /// Dim blue-tinted light for night scenes (Scene::moon)
+    pub fn moon(direction: Vec3, intensity: f32) -> Self {
+        Self::new(direction, palette::MOONLIGHT, intensity).with_angular_radius(MOON_ANGULAR_RADIUS)
+    }
+
+    pub fn with_angular_radius(mut self, radius: Degrees) -> Self {
+        self.angular_radius = radius.max(Degrees(0.0));
+        self
+    }
+
+    /// Direction toward a random point of the light's disk (toward its center when it has no size),
+    /// for jittered soft shadow rays
+    pub fn sample_toward(&self) -> Vec3 {
+        let toward = -self.direction;
+        if self.angular_radius <= Degrees(0.0) {
+            return toward;
+        }
+        // Uniform point on a disk of the cone's radius, one unit along the light direction
+        let helper = if toward.y.abs() < 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+        let tangent = toward.cross(&helper).normalize();
+        let bitangent = toward.cross(&tangent);
+        let radius = self.angular_radius.radians().tan() * random_f32().sqrt() as Real;
+        let angle = TAU * random_f32() as Real;
+        (toward + tangent * (radius * angle.cos()) + bitangent * (radius * angle.sin())).normalize()
+    }
+}
+
+/// Where the sun sits in the sky: azimuth around the vertical axis (0 = +X, 90 = +Z) and
+/// elevation above the horizon
+#[derive(Clone, Copy)]
+pub struct SunAngles {
+    pub azimuth: Degrees,
+    pub elevation: Degrees,
+}
+
+impl SunAngles {
+    /// The sun in the direction `toward` (from the scene up to the sun), kept above the horizon
+    pub fn toward(toward: Vec3) -> Self {
+        let toward = toward.normalize();
+        Self {
+            azimuth: Radians(toward.z.atan2(toward.x)).degrees().wrapped(),
+            elevation: Radians(toward.y.asin()).degrees().clamp(MIN_SUN_ELEVATION, Degrees(90.0)),
+        }
+    }
+
+    /// Direction the sunlight travels (down from the sun), as DirectionalLight::direction
+    pub fn light_direction(&self) -> Vec3 {
+        let (azimuth, elevation) = (self.azimuth.radians(), self.elevation.radians());
+        -Vec3::new(elevation.cos() * azimuth.cos(), elevation.sin(), elevation.cos() * azimuth.sin())
     }
 }
 
@@ -36,3 +129,59 @@ impl PointLight {
         }
     }
 }
+
+/// Light from a point, limited to a cone: full brightness inside `inner_angle`, fading out
+/// toward `outer_angle` (degrees from the axis). Porch lamps, stage-style diorama lighting.
+#[derive(Clone)]
+pub struct SpotLight {
+    pub position: Vec3,
+    pub direction: Vec3, // Cone axis, pointing away from the lamp
+    pub color: Color,
+    pub intensity: f32,
+    pub inner_angle: f32,
+    pub outer_angle: f32,
+    pub radius: Real, // Maximum distance the light can reach
+}
+
+impl SpotLight {
+    pub fn new(position: Vec3, direction: Vec3, color: Color, intensity: f32, inner_angle: f32, outer_angle: f32) -> Self {
+        Self {
+            position,
+            direction: direction.normalize(),
+            color,
+            intensity,
+            inner_angle: inner_angle.min(outer_angle),
+            outer_angle,
+            radius: 16.0,
+        }
+    }
+
+    pub fn with_radius(mut self, radius: Real) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Same as PointLight::illuminate: (direction to the light, attenuated color), black outside the cone
+    pub fn illuminate(&self, point: &Vec3) -> (Vec3, Color) {
+        let light_vec = self.position - *point;
+        let distance = light_vec.length();
+        if distance > self.radius {
+            return (Vec3::new(0.0, 0.0, 0.0), Color::black());
+        }
+        let light_dir = light_vec.normalize();
+
+        // Smooth edge between the two cone angles
+        let cos_angle = (-light_dir).dot(&self.direction);
+        let cos_outer = self.outer_angle.to_radians().cos() as Real;
+        let cos_inner = self.inner_angle.to_radians().cos() as Real;
+        if cos_angle <= cos_outer {
+            return (light_dir, Color::black());
+        }
+        let edge = ((cos_angle - cos_outer) / (cos_inner - cos_outer).max(1e-4)).min(1.0);
+        let cone = narrow(edge * edge * (3.0 - 2.0 * edge));
+
+        // Same falloff as point lights by default
+        let attenuation = Falloff::Smooth.attenuation(distance, self.radius);
+        (light_dir, self.color * (self.intensity * attenuation * cone))
+    }
+}
@@ -0,0 +1,249 @@
+use std::path::Path;
+
+use raylib::prelude::*;
+
+use crate::camera::Camera;
+use crate::debug_overlay::View;
+use crate::light::LANTERN_LUMENS;
+use crate::point_light::Falloff;
+use crate::presentation::Viewport;
+use crate::scene::Scene;
+use crate::scene_file::{self, PointLightDef, SceneFile, SpotLightDef};
+use crate::utils::{Real, TAU, Vec3, narrow};
+
+const NUDGE_STEP: f32 = 0.25;  // Blocks a light moves per key press (placed lights snap to this grid too)
+const RING_SEGMENTS: usize = 48; // Line segments of the radius ring
+const LAMP_COLOR: [f32; 3] = [1.0, 0.8, 0.55]; // Same warm white as the house lamps
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Selection {
+    Point(usize),
+    Spot(usize),
+}
+
+/// In-app editing of the scene file's [[point_lights]] and [[spot_lights]]: place them on the block
+/// face under the crosshair, pick one, nudge it along the axes and save back into the file.
+/// Edits show up in the scene right away; lights from prefabs (the house lamps) aren't editable.
+pub struct LightEditor {
+    pub active: bool,
+    pub selected: Option<Selection>,
+    pub unsaved: bool,
+    point_lights: Vec<PointLightDef>,
+    spot_lights: Vec<SpotLightDef>,
+    point_base: usize, // Where the file's lights start in the scene's light lists
+    spot_base: usize,
+}
+
+impl LightEditor {
+    pub fn new() -> Self {
+        Self {
+            active: false,
+            selected: None,
+            unsaved: false,
+            point_lights: Vec::new(),
+            spot_lights: Vec::new(),
+            point_base: 0,
+            spot_base: 0,
+        }
+    }
+
+    /// Read the lights from the scene file `scene` was built from (unsaved edits are dropped,
+    /// the selection stays if that light still exists)
+    pub fn load(&mut self, path: &Path, scene: &Scene) -> Result<(), String> {
+        let file = SceneFile::load(path)?;
+        self.point_base = scene.point_lights.len().saturating_sub(file.point_lights.len());
+        self.spot_base = scene.spot_lights.len().saturating_sub(file.spot_lights.len());
+        self.point_lights = file.point_lights;
+        self.spot_lights = file.spot_lights;
+        self.selected = self.selected.filter(|selection| match *selection {
+            Selection::Point(i) => i < self.point_lights.len(),
+            Selection::Spot(i) => i < self.spot_lights.len(),
+        });
+        self.unsaved = false;
+        Ok(())
+    }
+
+    pub fn light_count(&self) -> usize {
+        self.point_lights.len() + self.spot_lights.len()
+    }
+
+    /// World position just in front of the block face under the crosshair, and that face's normal
+    fn target(scene: &Scene, camera: &Camera) -> Option<([f32; 3], Vec3)> {
+        let hit = scene.intersect(&camera.get_pinhole_ray(0.5, 0.5))?;
+        let world = hit.position + hit.normal * 0.5 + scene.origin;
+        let snap = |v: Real| (narrow(v) / NUDGE_STEP).round() * NUDGE_STEP;
+        Some(([snap(world.x), snap(world.y), snap(world.z)], hit.normal))
+    }
+
+    /// A lantern-like point light in front of the targeted face. False when nothing is targeted.
+    pub fn place_point(&mut self, scene: &Scene, camera: &Camera) -> bool {
+        let Some((position, _)) = Self::target(scene, camera) else {
+            return false;
+        };
+        self.point_lights.push(PointLightDef {
+            position,
+            color: LAMP_COLOR,
+            intensity: None,
+            lumens: Some(LANTERN_LUMENS),
+            radius: 8.0,
+            source_radius: 0.2,
+            falloff: Falloff::default(),
+        });
+        self.selected = Some(Selection::Point(self.point_lights.len() - 1));
+        self.unsaved = true;
+        true
+    }
+
+    /// A spot light in front of the targeted face, shining away from it (down from a ceiling)
+    pub fn place_spot(&mut self, scene: &Scene, camera: &Camera) -> bool {
+        let Some((position, normal)) = Self::target(scene, camera) else {
+            return false;
+        };
+        self.spot_lights.push(SpotLightDef {
+            position,
+            direction: [narrow(normal.x), narrow(normal.y), narrow(normal.z)],
+            color: LAMP_COLOR,
+            intensity: None,
+            lumens: Some(600.0), // As bright as the house's porch lamp
+            inner_angle: 25.0,
+            outer_angle: 45.0,
+            radius: 12.0,
+        });
+        self.selected = Some(Selection::Spot(self.spot_lights.len() - 1));
+        self.unsaved = true;
+        true
+    }
+
+    /// Point lights first, then spot lights, then back to the first
+    pub fn select_next(&mut self) {
+        let count = self.light_count();
+        if count == 0 {
+            self.selected = None;
+            return;
+        }
+        let index = match self.selected {
+            Some(Selection::Point(i)) => i + 1,
+            Some(Selection::Spot(i)) => self.point_lights.len() + i + 1,
+            None => 0,
+        } % count;
+        self.selected = Some(if index < self.point_lights.len() {
+            Selection::Point(index)
+        } else {
+            Selection::Spot(index - self.point_lights.len())
+        });
+    }
+
+    /// Move the selected light by whole nudge steps along each axis
+    pub fn nudge(&mut self, steps: [f32; 3]) {
+        let Some(position) = self.selected_position() else {
+            return;
+        };
+        for (value, step) in position.iter_mut().zip(steps) {
+            *value += step * NUDGE_STEP;
+        }
+        self.unsaved = true;
+    }
+
+    /// Grow or shrink the selected light's reach by whole blocks (at least one)
+    pub fn resize(&mut self, blocks: f32) {
+        let radius = match self.selected {
+            Some(Selection::Point(i)) => &mut self.point_lights[i].radius,
+            Some(Selection::Spot(i)) => &mut self.spot_lights[i].radius,
+            None => return,
+        };
+        *radius = (*radius + blocks).max(1.0);
+        self.unsaved = true;
+    }
+
+    pub fn delete(&mut self) {
+        match self.selected.take() {
+            Some(Selection::Point(i)) => {
+                self.point_lights.remove(i);
+            }
+            Some(Selection::Spot(i)) => {
+                self.spot_lights.remove(i);
+            }
+            None => return,
+        }
+        self.unsaved = true;
+    }
+
+    fn selected_position(&mut self) -> Option<&mut [f32; 3]> {
+        match self.selected {
+            Some(Selection::Point(i)) => Some(&mut self.point_lights[i].position),
+            Some(Selection::Spot(i)) => Some(&mut self.spot_lights[i].position),
+            None => None,
+        }
+    }
+
+    /// Replace the file's lights in the scene with the edited ones
+    pub fn apply(&self, scene: &mut Scene) -> Result<(), String> {
+        let point_lights = self.point_lights.iter().map(|def| def.to_light()).collect::<Result<Vec<_>, String>>()?;
+        let spot_lights = self.spot_lights.iter().map(|def| def.to_light()).collect::<Result<Vec<_>, String>>()?;
+        // The file is in world coordinates, the scene may be shifted (floating origin)
+        let origin = scene.origin;
+        scene.point_lights.truncate(self.point_base);
+        scene.point_lights.extend(point_lights.into_iter().map(|mut light| {
+            light.position = light.position - origin;
+            light
+        }));
+        scene.spot_lights.truncate(self.spot_base);
+        scene.spot_lights.extend(spot_lights.into_iter().map(|mut light| {
+            light.position = light.position - origin;
+            light
+        }));
+        Ok(())
+    }
+
+    /// Write the edited lights into the scene file (the rest of the file is left alone)
+    pub fn save(&mut self, path: &Path) -> Result<(), String> {
+        scene_file::save_lights(path, &self.point_lights, &self.spot_lights)?;
+        self.unsaved = false;
+        Ok(())
+    }
+
+    /// A small box per light and, for the selected one, axes, its radius as a ring and a
+    /// label with position and radius
+    pub fn draw(&self, d: &mut RaylibDrawHandle, scene: &Scene, camera: &Camera, viewport: Viewport) {
+        let view = View { camera, viewport };
+
+        let points = self.point_lights.iter().enumerate().map(|(i, l)| (Selection::Point(i), l.position, l.radius, None));
+        let spots = self.spot_lights.iter().enumerate().map(|(i, l)| (Selection::Spot(i), l.position, l.radius, Some(l.direction)));
+        for (selection, position, radius, direction) in points.chain(spots) {
+            let center = scene.to_render(light_position(position));
+            if self.selected != Some(selection) {
+                view.draw_box(d, center, 0.15, Color::new(255, 255, 255, 140));
+                continue;
+            }
+
+            view.draw_box(d, center, 0.15, Color::YELLOW);
+            let axes = [(Vec3::new(1.0, 0.0, 0.0), Color::RED), (Vec3::new(0.0, 1.0, 0.0), Color::LIME), (Vec3::new(0.0, 0.0, 1.0), Color::SKYBLUE)];
+            for (axis, color) in axes {
+                view.draw_line(d, center, center + axis * 0.75, color);
+            }
+            if let Some(direction) = direction {
+                view.draw_line(d, center, center + light_position(direction).normalize() * 1.5, Color::ORANGE);
+            }
+            let radius = radius as Real;
+            for segment in 0..RING_SEGMENTS {
+                let angle = |s: usize| TAU * s as Real / RING_SEGMENTS as Real;
+                let at = |s: usize| center + Vec3::new(angle(s).cos() * radius, 0.0, angle(s).sin() * radius);
+                view.draw_line(d, at(segment), at(segment + 1), Color::new(255, 220, 0, 120));
+            }
+            if let Some(screen) = view.project(center) {
+                let label = format!("({}, {}, {})  r={}", position[0], position[1], position[2], radius);
+                d.draw_text(&label, screen.x as i32 + 10, screen.y as i32 - 20, 16, Color::YELLOW);
+            }
+        }
+    }
+}
+
+impl Default for LightEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn light_position(p: [f32; 3]) -> Vec3 {
+    Vec3::new(p[0] as Real, p[1] as Real, p[2] as Real)
+}
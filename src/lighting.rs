@@ -0,0 +1,82 @@
+use crate::color::Color;
+use crate::light::{DirectionalLight, MOONLIGHT_LUX, lumens, lux};
+use crate::point_light::PointLight;
+use crate::utils::{Degrees, Vec3};
+
+/// Preset names in the order G cycles through them
+pub const PRESET_NAMES: [&str; 4] = ["studio", "overcast", "golden_hour", "night"];
+
+/// Curated look that replaces the day/night cycle's lighting: sun, ambient, sky and extra lights.
+/// Picked with G in the app, `lighting = "..."` in a scene file or `--lighting` on exports.
+#[derive(Clone)]
+pub struct LightingPreset {
+    pub name: &'static str,
+    pub day_fraction: f32, // Pins the sky blend (0.0 = day, 1.0 = night)
+    pub sun: DirectionalLight, // Intensity used as is, without the day/night dimming
+    pub ambient: Color,
+    pub lights: Vec<PointLight>, // Added to the scene's own point lights
+}
+
+impl LightingPreset {
+    pub fn by_name(name: &str) -> Option<Self> {
+        let preset = match name {
+            // Key light from the front left, warm fill from the right, cool rim light from behind
+            "studio" => Self {
+                name: "studio",
+                day_fraction: 0.0,
+                sun: DirectionalLight::new(Vec3::new(-0.4, -1.0, -0.9).normalize(), Color::new(1.0, 1.0, 1.0), lux(110_000.0))
+                    .with_angular_radius(Degrees(3.0)),
+                ambient: Color::new(0.25, 0.25, 0.27),
+                lights: vec![
+                    PointLight::new(Vec3::new(16.0, 6.0, -4.0), Color::new(1.0, 0.9, 0.8), lumens(1000.0), 30.0),
+                    PointLight::new(Vec3::new(-6.0, 10.0, -22.0), Color::new(0.8, 0.9, 1.0), lumens(1500.0), 30.0),
+                ],
+            },
+            // Sun hidden behind clouds: a huge, dim light from above and a bright flat sky
+            "overcast" => Self {
+                name: "overcast",
+                day_fraction: 0.15,
+                sun: DirectionalLight::new(Vec3::new(0.1, -1.0, 0.2).normalize(), Color::new(0.85, 0.88, 0.95), lux(35_000.0))
+                    .with_angular_radius(Degrees(25.0)),
+                ambient: Color::new(0.55, 0.57, 0.62),
+                lights: Vec::new(),
+            },
+            // Low orange sun from the west with long shadows
+            "golden_hour" => Self {
+                name: "golden_hour",
+                day_fraction: 0.3,
+                sun: DirectionalLight::new(Vec3::new(0.9, -0.25, 0.3).normalize(), Color::new(1.0, 0.65, 0.35), lux(130_000.0))
+                    .with_angular_radius(Degrees(1.5)),
+                ambient: Color::new(0.35, 0.28, 0.3),
+                lights: Vec::new(),
+            },
+            // Faint blue moonlight; the lanterns carry the scene
+            "night" => Self {
+                name: "night",
+                day_fraction: 0.9,
+                sun: DirectionalLight::new(Vec3::new(-0.3, -1.0, 0.4).normalize(), Color::new(0.55, 0.6, 0.9), lux(MOONLIGHT_LUX))
+                    .with_angular_radius(Degrees(0.5)),
+                ambient: Color::new(0.04, 0.05, 0.09),
+                lights: Vec::new(),
+            },
+            _ => return None,
+        };
+        Some(preset)
+    }
+
+    /// Same as by_name, with the list of valid names in the error
+    pub fn parse(name: &str) -> Result<Self, String> {
+        Self::by_name(name).ok_or_else(|| {
+            format!("Unknown lighting preset '{}', expected one of: {}", name, PRESET_NAMES.join(", "))
+        })
+    }
+
+    /// Preset after `current` in PRESET_NAMES; after the last one back to the day/night cycle (None)
+    pub fn next(current: Option<&LightingPreset>) -> Option<Self> {
+        let index = match current {
+            Some(preset) => PRESET_NAMES.iter().position(|name| *name == preset.name)? + 1,
+            None => 0,
+        };
+        PRESET_NAMES.get(index).and_then(|name| Self::by_name(name))
+    }
+}
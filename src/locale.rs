@@ -0,0 +1,292 @@
+use std::fmt::Display;
+
+/// Language names accepted by `language = "..."` in the [hud] config section
+pub const LANGUAGE_NAMES: [&str; 2] = ["en", "es"];
+
+/// Every user-facing HUD and in-app console string, one table per language.
+/// `{}` marks where `fill` puts the values, in order. Kept to ASCII (no accents) since
+/// raylib's built-in font only has those glyphs.
+pub struct Strings {
+    // Status line
+    pub camera_path_keyframes: &'static str,
+    pub camera_path_save_failed: &'static str,
+    pub no_cameras: &'static str,
+    pub camera: &'static str,
+    pub lighting: &'static str,
+    pub day_night_cycle: &'static str,
+    pub scene_reloaded: &'static str,
+    pub scene_reload_failed: &'static str,
+    pub presentation: &'static str,
+    pub letterbox: &'static str,
+    pub match_window: &'static str,
+    pub integrator: &'static str,
+    pub whitted: &'static str,
+    pub path_traced: &'static str,
+    pub denoiser: &'static str,
+    pub temporal_aa: &'static str,
+    pub depth_of_field: &'static str,
+    pub lens: &'static str,
+    pub sun_drag: &'static str,
+    pub sun_pinned: &'static str,
+    pub shadow_preview: &'static str,
+    pub horizon_lock: &'static str,
+    pub rain: &'static str,
+    pub uv_grid: &'static str,
+    pub photo_mode: &'static str,
+    pub photo_hud: &'static str,
+    pub console_photo: &'static str,
+    pub light_editor: &'static str,
+    pub light_editor_failed: &'static str,
+    pub light_no_target: &'static str,
+    pub lights_saved: &'static str,
+    pub block_broken: &'static str,
+    pub block_placed: &'static str,
+    pub block_blocked: &'static str,
+    pub world_saved: &'static str,
+    pub world_save_failed: &'static str,
+    pub over_budget: &'static str,
+    pub saved: &'static str,
+    pub screenshot_failed: &'static str,
+
+    // Stats
+    pub fps: &'static str,
+    pub quality: &'static str,
+    pub quality_low: &'static str,
+    pub quality_medium: &'static str,
+    pub quality_high: &'static str,
+    pub quality_ultra: &'static str,
+    pub auto_perf: &'static str,
+    pub pixels: &'static str,
+    pub threading: &'static str,
+    pub on: &'static str,
+    pub off: &'static str,
+    pub day_time: &'static str,
+    pub motion_blur: &'static str,
+    pub third_person: &'static str,
+    pub raster_preview: &'static str,
+    pub path_frames: &'static str,
+    pub denoised: &'static str,
+    pub dof: &'static str,
+    pub sun_drag_mode: &'static str,
+    pub shadow_view: &'static str,
+    pub averaged_frames: &'static str,
+    pub light_edit: &'static str,
+    pub light_point: &'static str,
+    pub light_spot: &'static str,
+    pub light_none: &'static str,
+    pub unsaved: &'static str,
+    pub path_progress: &'static str,
+    pub sun_dir: &'static str,
+
+    // Controls help (key names are filled in from the bindings)
+    pub controls_title: &'static str,
+    pub controls_look: &'static str,
+    pub controls_move: &'static str,
+    pub controls_scene: &'static str,
+    pub controls_render: &'static str,
+    pub controls_tip: &'static str,
+    pub controls_light_edit: &'static str,
+
+    // Console
+    pub console_reloaded: &'static str,
+    pub console_screenshot: &'static str,
+    pub console_quality_down: &'static str,
+    pub console_quality_up: &'static str,
+}
+
+const ENGLISH: Strings = Strings {
+    camera_path_keyframes: "Camera path: {} keyframes",
+    camera_path_save_failed: "Saving camera path failed (see console)",
+    no_cameras: "No cameras in this scene",
+    camera: "Camera: {}",
+    lighting: "Lighting: {}",
+    day_night_cycle: "day/night cycle",
+    scene_reloaded: "Scene reloaded",
+    scene_reload_failed: "Scene reload failed (see console)",
+    presentation: "Presentation: {}",
+    letterbox: "letterbox",
+    match_window: "match window",
+    integrator: "Integrator: {}",
+    whitted: "Whitted (direct light + ambient)",
+    path_traced: "path tracing (global illumination)",
+    denoiser: "Denoiser: {}",
+    temporal_aa: "Temporal AA: {}",
+    depth_of_field: "Depth of field: {}",
+    lens: "aperture {}, focus at {} blocks",
+    sun_drag: "Sun drag: {}",
+    sun_pinned: "Sun: azimuth {} deg, elevation {} deg",
+    shadow_preview: "Shadow preview: {}",
+    horizon_lock: "Horizon lock: {}",
+    rain: "Rain: {}",
+    uv_grid: "UV grid: {}",
+    photo_mode: "Photo mode: {}",
+    photo_hud: "PHOTO  {}x = {}x{}  |  {}: Resolution  |  {}: Capture  |  {}: Exit",
+    console_photo: "Tracing a {}x{} photo...",
+    light_editor: "Light editor: {}",
+    light_editor_failed: "Light editor failed (see console)",
+    light_no_target: "No block under the crosshair",
+    lights_saved: "Saved {} lights to {}",
+    block_broken: "Removed block at {}",
+    block_placed: "Placed sand at {}",
+    block_blocked: "No room for a block there",
+    world_saved: "Saved {} edited blocks to {}",
+    world_save_failed: "Could not save the world (see console)",
+    over_budget: "Frame over budget, drawn at reduced quality (see console)",
+    saved: "Saved {}",
+    screenshot_failed: "Screenshot failed (see console)",
+
+    fps: "FPS: {}",
+    quality: "Quality: {}",
+    quality_low: "Low (4x)",
+    quality_medium: "Medium (2x)",
+    quality_high: "High (1x)",
+    quality_ultra: "Ultra (1x, SSAA 4x)",
+    auto_perf: "[AUTO PERF]",
+    pixels: "Pixels: {}% ({}/{})",
+    threading: "Threading: {}",
+    on: "ON",
+    off: "OFF",
+    day_time: "Day Time: {} (day {})",
+    motion_blur: "[MOTION BLUR]",
+    third_person: "[THIRD PERSON]",
+    raster_preview: "[RASTER PREVIEW]",
+    path_frames: "[PATH TRACED x{}]",
+    denoised: "[DENOISED]",
+    dof: "[DOF]",
+    sun_drag_mode: "[SUN DRAG] click or drag on the sky",
+    shadow_view: "[SHADOWS]",
+    averaged_frames: "[AVERAGED x{}]",
+    light_edit: "[LIGHT EDIT] {} lights, selected: {} {}",
+    light_point: "point #{}",
+    light_spot: "spot #{}",
+    light_none: "none",
+    unsaved: "(unsaved)",
+    path_progress: "[PATH {}/{}s]",
+    sun_dir: "Sun Dir: ({}, {}, {})",
+
+    controls_title: "=== CONTROLS ===",
+    controls_look: "{}{}{}{}: Look Around (Up, Down, Left, Right)",
+    controls_move: "{}/{}: Zoom In/Out  |  {}/{}: Move Position Up/Down",
+    controls_scene: "{}: Toggle Day/Night  |  {}: Lighting  |  {}: Rain  |  {}: Reload Scene  |  {}: Screenshot",
+    controls_render: "{}/{}/{}/{}: Quality  |  {}: Auto-Performance  |  {}: Threading  |  {}: Motion Blur  |  {}: Player  |  {}/{}: Path  |  {}: Hybrid",
+    controls_tip: "TIP: {} looks up at the sun  |  {}: Map  |  {}/{}/{}/{}: Debug  |  {}: Camera  |  {}: GI  |  {}: Denoise  |  {}: TAA  |  {}/{}/{}: Focus  |  {}: Sun  |  {}: Shadows  |  {}/{}/{}: Roll  |  {}: Photo  |  {}: Lights  |  {}/{}: Blocks",
+    controls_light_edit: "{}: Point light  |  {}: Spot light  |  {}: Select  |  {}/{} {}/{} {}/{}: Move X/Y/Z  |  {}/{}: Radius  |  {}: Delete  |  {}: Save",
+
+    console_reloaded: "Reloaded scene: {}",
+    console_screenshot: "Saved screenshot: {}",
+    console_quality_down: "Auto-scaling: Lowering quality to improve FPS (avg: {})",
+    console_quality_up: "Auto-scaling: Raising quality (avg: {})",
+};
+
+const SPANISH: Strings = Strings {
+    camera_path_keyframes: "Recorrido de camara: {} fotogramas clave",
+    camera_path_save_failed: "No se pudo guardar el recorrido (ver consola)",
+    no_cameras: "Esta escena no tiene camaras",
+    camera: "Camara: {}",
+    lighting: "Iluminacion: {}",
+    day_night_cycle: "ciclo dia/noche",
+    scene_reloaded: "Escena recargada",
+    scene_reload_failed: "No se pudo recargar la escena (ver consola)",
+    presentation: "Presentacion: {}",
+    letterbox: "con bandas",
+    match_window: "ajustada a la ventana",
+    integrator: "Integrador: {}",
+    whitted: "Whitted (luz directa + ambiente)",
+    path_traced: "path tracing (iluminacion global)",
+    denoiser: "Filtro de ruido: {}",
+    temporal_aa: "AA temporal: {}",
+    depth_of_field: "Profundidad de campo: {}",
+    lens: "apertura {}, enfoque a {} bloques",
+    sun_drag: "Arrastrar el sol: {}",
+    sun_pinned: "Sol: azimut {} grados, elevacion {} grados",
+    shadow_preview: "Vista de sombras: {}",
+    horizon_lock: "Horizonte fijo: {}",
+    rain: "Lluvia: {}",
+    uv_grid: "Rejilla UV: {}",
+    photo_mode: "Modo foto: {}",
+    photo_hud: "FOTO  {}x = {}x{}  |  {}: Resolucion  |  {}: Capturar  |  {}: Salir",
+    console_photo: "Trazando una foto de {}x{}...",
+    light_editor: "Editor de luces: {}",
+    light_editor_failed: "Error en el editor de luces (ver consola)",
+    light_no_target: "No hay ningun bloque bajo la mira",
+    lights_saved: "{} luces guardadas en {}",
+    block_broken: "Bloque quitado en {}",
+    block_placed: "Arena colocada en {}",
+    block_blocked: "No hay lugar para un bloque ahi",
+    world_saved: "{} bloques editados guardados en {}",
+    world_save_failed: "No se pudo guardar el mundo (ver consola)",
+    over_budget: "Cuadro fuera de presupuesto, dibujado con menor calidad (ver consola)",
+    saved: "Guardado {}",
+    screenshot_failed: "No se pudo guardar la captura (ver consola)",
+
+    fps: "FPS: {}",
+    quality: "Calidad: {}",
+    quality_low: "Baja (4x)",
+    quality_medium: "Media (2x)",
+    quality_high: "Alta (1x)",
+    quality_ultra: "Ultra (1x, SSAA 4x)",
+    auto_perf: "[RENDIMIENTO AUTO]",
+    pixels: "Pixeles: {}% ({}/{})",
+    threading: "Hilos: {}",
+    on: "SI",
+    off: "NO",
+    day_time: "Hora del dia: {} (dia {})",
+    motion_blur: "[DESENFOQUE]",
+    third_person: "[TERCERA PERSONA]",
+    raster_preview: "[VISTA PREVIA]",
+    path_frames: "[PATH TRACING x{}]",
+    denoised: "[SIN RUIDO]",
+    dof: "[PROF. DE CAMPO]",
+    sun_drag_mode: "[SOL] clic o arrastra sobre el cielo",
+    shadow_view: "[SOMBRAS]",
+    averaged_frames: "[PROMEDIO x{}]",
+    light_edit: "[EDITOR DE LUCES] {} luces, seleccionada: {} {}",
+    light_point: "punto #{}",
+    light_spot: "foco #{}",
+    light_none: "ninguna",
+    unsaved: "(sin guardar)",
+    path_progress: "[RECORRIDO {}/{}s]",
+    sun_dir: "Dir. del sol: ({}, {}, {})",
+
+    controls_title: "=== CONTROLES ===",
+    controls_look: "{}{}{}{}: Mirar (Arriba, Abajo, Izquierda, Derecha)",
+    controls_move: "{}/{}: Acercar/Alejar  |  {}/{}: Subir/Bajar camara",
+    controls_scene: "{}: Dia/Noche  |  {}: Iluminacion  |  {}: Lluvia  |  {}: Recargar escena  |  {}: Captura",
+    controls_render: "{}/{}/{}/{}: Calidad  |  {}: Rendimiento auto  |  {}: Hilos  |  {}: Desenfoque  |  {}: Jugador  |  {}/{}: Recorrido  |  {}: Hibrido",
+    controls_tip: "TIP: {} mira hacia el sol  |  {}: Mapa  |  {}/{}/{}/{}: Depuracion  |  {}: Camara  |  {}: GI  |  {}: Filtro  |  {}: TAA  |  {}/{}/{}: Enfoque  |  {}: Sol  |  {}: Sombras  |  {}/{}/{}: Inclinar  |  {}: Foto  |  {}: Luces  |  {}/{}: Bloques",
+    controls_light_edit: "{}: Luz puntual  |  {}: Foco  |  {}: Elegir  |  {}/{} {}/{} {}/{}: Mover X/Y/Z  |  {}/{}: Alcance  |  {}: Borrar  |  {}: Guardar",
+
+    console_reloaded: "Escena recargada: {}",
+    console_screenshot: "Captura guardada: {}",
+    console_quality_down: "Escalado auto: bajando la calidad para subir los FPS (prom.: {})",
+    console_quality_up: "Escalado auto: subiendo la calidad (prom.: {})",
+};
+
+impl Strings {
+    pub fn by_name(name: &str) -> Result<&'static Self, String> {
+        match name {
+            "en" => Ok(&ENGLISH),
+            "es" => Ok(&SPANISH),
+            other => Err(format!(
+                "Unknown language '{}', expected one of: {}",
+                other,
+                LANGUAGE_NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// Replace each `{}` in `template` with the next value (extra placeholders stay as they are)
+pub fn fill(template: &str, values: &[&dyn Display]) -> String {
+    let mut values = values.iter();
+    let mut parts = template.split("{}");
+    let mut text = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        match values.next() {
+            Some(value) => text.push_str(&value.to_string()),
+            None => text.push_str("{}"),
+        }
+        text.push_str(part);
+    }
+    text
+}
@@ -1,49 +1,231 @@
 use raylib::prelude::*;
 
-mod camera;
-mod ray;
-mod material;
-mod texture;
-mod color;
-mod scene;
-mod cube;
-mod light;
-mod point_light;
-mod skybox;
-mod obj_loader;
-mod intersection;
-mod renderer;
-mod utils;
+use minecraft_raytracer::{
+    camera, config, keybindings, camera_path, scene, scene_file, scene_diff, screenshot, export,
+    batch, checkpoint, light, lighting, renderer, temporal, turntable, utils, player, viewpoint,
+    minimap, photo_mode, uv_debug, debug_overlay, light_editor, hud, locale, preview, presentation,
+    block_edit, sign, crosshair, conformance, world_file,
+};
 
 use camera::Camera;
+use camera_path::CameraPath;
+use config::Config;
+use debug_overlay::DebugOverlay;
+use export::ExportOptions;
+use hud::{Hud, HudConfig};
+use keybindings::{Action, KeyBindings};
+use light::SunAngles;
+use block_edit::BlockEditor;
+use world_file::WorldEdits;
+use light_editor::{LightEditor, Selection};
+use lighting::LightingPreset;
+use locale::fill;
+use minimap::Minimap;
+use photo_mode::PhotoMode;
+use uv_debug::UvDebug;
+use player::Player;
+use presentation::{PresentMode, Presentation};
+use preview::{HybridSwitch, RasterPreview};
+use renderer::{Accumulator, RenderSettings};
 use scene::Scene;
+use scene_file::SceneWatcher;
+use temporal::TemporalFilter;
+use turntable::TurntableOptions;
+use viewpoint::Viewpoint;
+use utils::Degrees;
 
 const WIDTH: i32 = 800;
 const HEIGHT: i32 = 600;
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+const DEFAULT_SCENE_FILE: &str = "assets/scenes/diorama.toml";
+const DEFAULT_CAMERA_PATH: &str = "saves/camera_path.toml";
+const BUNDLED_CAMERA_PATH: &str = "assets/camera_path.toml"; // Played until a path of your own is recorded
+const DEFAULT_WORLD_FILE: &str = "saves/world.mcw";
+const KEYFRAME_SPACING: f32 = 3.0; // Seconds between keyframes recorded with K
+const DAY_CYCLE_SPEED: f32 = 0.3; // Day fraction per second while N is held
+const SSAA_SAMPLES: u32 = 4; // Jittered rays per pixel at Ultra quality
+const APERTURES: [f32; 4] = [0.0, 0.05, 0.15, 0.4]; // Lens radii F cycles through (0.4 blurs the diorama like a miniature)
+const FOCUS_SPEED: f32 = 0.8; // Share of the focus distance per second while Z/X are held
+const WATCHDOG_LOG_INTERVAL: f32 = 5.0; // Seconds between console reports of over-budget frames
 
 fn main() {
-    let (mut rl, thread) = raylib::init()
-        .size(WIDTH, HEIGHT)
-        .title("Minecraft Raytracer - Diorama")
-        .build();
+    let args: Vec<String> = std::env::args().collect();
 
-    rl.set_target_fps(60);
+    // === Scene Diff === (`diff old.toml new.toml`, headless)
+    if args.get(1).map(String::as_str) == Some("diff") {
+        std::process::exit(scene_diff::run(&args[2..]));
+    }
+
+    // === Signs === (`sign x y z facing "TEXT"`, headless: appends a [[signs]] table to the scene file)
+    if args.get(1).map(String::as_str) == Some("sign") {
+        std::process::exit(sign::run(&args[2..], DEFAULT_SCENE_FILE));
+    }
+
+    // === Shading Conformance === (`conformance`, headless: the same materials on a cube and a mesh)
+    if args.get(1).map(String::as_str) == Some("conformance") {
+        std::process::exit(conformance::run(&args[2..]));
+    }
 
-    let mut scene = Scene::new();
-    scene.build_cherry_tree_diorama();
+    // Edited world: --world <path> loads the block edits saved there (and the scene file they were
+    // made to, unless --scene picks another); Insert saves them back to it, or to saves/world.mcw
+    let world_file = std::path::PathBuf::from(export::arg_value(&args, "--world").unwrap_or(DEFAULT_WORLD_FILE));
+    let saved_world = if export::arg_value(&args, "--world").is_some() && world_file.is_file() {
+        WorldEdits::load(&world_file).map_err(|e| eprintln!("{}", e)).ok()
+    } else {
+        None
+    };
+
+    // Scene file: --scene <path>, otherwise the world's or the bundled diorama file
+    let scene_path = export::arg_value(&args, "--scene")
+        .or_else(|| saved_world.as_ref().map(|world| world.scene.as_str()))
+        .unwrap_or(DEFAULT_SCENE_FILE)
+        .to_string();
+
+    let mut scene_watcher = SceneWatcher::new(scene_path.as_str().into());
+    let mut scene = match scene_watcher.load() {
+        Ok(scene) => scene,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("Falling back to the built-in diorama");
+            let mut scene = Scene::new();
+            scene.build_cherry_tree_diorama(utils::Vec3::zero());
+            scene.update_grid();
+            scene
+        }
+    };
+
+    let mut world_edits = WorldEdits::new(&scene_path);
+    if let Some(saved) = saved_world {
+        match saved.apply(&mut scene) {
+            Ok(skipped) => {
+                println!("Loaded {} edited blocks from {}", saved.len(), world_file.display());
+                if skipped > 0 {
+                    eprintln!("{} block edits no longer fit {} and were skipped", skipped, scene_path);
+                }
+                world_edits = WorldEdits { scene: scene_path.clone(), ..saved };
+            }
+            Err(e) => eprintln!("{}", e),
+        }
+    }
 
     let mut camera = Camera::new(
         utils::Vec3::new(0.0, 5.0, 15.0),
         utils::Vec3::new(0.0, 0.0, 0.0),
-        70.0,
+        Degrees(70.0),
         WIDTH as f32 / HEIGHT as f32,
     );
+    camera.translate(-scene.origin); // The default pose is in world coordinates
+
+    // Start from a named scene camera: --view <name>
+    if let Some(name) = export::arg_value(&args, "--view") {
+        match Viewpoint::find(&scene.viewpoints, name) {
+            Ok(view) => view.apply(&mut camera),
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+
+    // === Batch Render Queue === (headless: --batch jobs.toml renders every job and exits)
+    if let Some(jobs) = export::arg_value(&args, "--batch") {
+        let result = checkpoint::CheckpointOptions::from_args(&args).and_then(|checkpoint| {
+            batch::run_batch(std::path::Path::new(jobs), std::path::Path::new(&scene_path), scene, &camera, &checkpoint)
+        });
+        if let Err(e) = result {
+            eprintln!("Batch failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // === Offline Frame Export === (headless, no window)
+    if let Some(options) = ExportOptions::from_args(&args) {
+        let result = options.and_then(|options| export::export_frames(&mut scene, &camera, &options));
+        if let Err(e) = result {
+            eprintln!("Export failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // === Turntable === (headless: --turntable N orbits the scene into a looping GIF/APNG)
+    if let Some(options) = TurntableOptions::from_args(&args) {
+        let result = options.and_then(|options| turntable::render_turntable(&mut scene, &options));
+        if let Err(e) = result {
+            eprintln!("Turntable failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // User config: --config <path>, otherwise config.toml next to the executable's working dir
+    let config_path = export::arg_value(&args, "--config").unwrap_or(DEFAULT_CONFIG_FILE);
+    let config = Config::load(std::path::Path::new(config_path)).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        eprintln!("Using default settings");
+        Config::default()
+    });
+    let keys = &config.keybindings;
+    let hud = Hud::new(&config.hud).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        eprintln!("Using the default HUD");
+        Hud::new(&HudConfig::default()).unwrap()
+    });
+    let text = hud.strings; // HUD and console strings in the configured language
+
+    // Camera path: --camera-path <path>, otherwise saves/camera_path.toml (K records into this file).
+    // Until that has been recorded the bundled flight plays, which recording never overwrites.
+    let camera_path_file = std::path::PathBuf::from(export::arg_value(&args, "--camera-path").unwrap_or(DEFAULT_CAMERA_PATH));
+    let camera_path_source = if export::arg_value(&args, "--camera-path").is_none() && !camera_path_file.is_file() {
+        std::path::PathBuf::from(BUNDLED_CAMERA_PATH)
+    } else {
+        camera_path_file.clone()
+    };
+    let mut camera_path = CameraPath::load(&camera_path_source).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        CameraPath::new()
+    });
+    camera_path.translate(-scene.origin);
+    let mut path_playing = false;
+    let mut path_time = 0.0f32;
+    let mut next_view = 0; // Scene camera C switches to next
+
+    let (mut rl, thread) = raylib::init()
+        .size(WIDTH, HEIGHT)
+        .title("Minecraft Raytracer - Diorama")
+        .resizable()
+        .build();
+
+    rl.set_target_fps(60);
+    rl.set_window_min_size(320, 240);
+
+    // Status message shown on screen after reloads (text, color, seconds left)
+    let mut status_message: Option<(String, Color, f32)> = None;
 
     let mut quality_level = 1;
     let mut manual_quality_level = 1; // User's preferred quality
     let mut use_threading = true;
-    let mut day_time = 0.0f32;
     let mut auto_quality = false; // Auto performance scaling
+    let mut motion_blur = false;
+    let mut third_person = false;
+    let mut show_minimap = true;
+    let mut minimap = Minimap::new();
+    let mut debug_overlay = DebugOverlay::new();
+    let mut light_editor = LightEditor::new();
+    let mut block_editor = BlockEditor::new().with_edits(world_edits); // 5/6: break and place blocks, sand and gravel fall
+    let mut raster_preview = true; // Rasterize while the camera moves, raytrace once it settles
+    let mut path_tracing = false; // Global illumination, noisy until a few frames are averaged
+    let mut denoise = false; // Edge-preserving filter over each traced frame
+    let mut accumulator = Accumulator::new(); // Averages frames while the view holds still
+    let mut temporal_aa = true; // Blends in the last frame, reprojected, while the camera moves
+    let mut temporal = TemporalFilter::new();
+    let mut sun_drag = false; // F7: clicking the sky places the sun there
+    let mut sun_dragging = false; // Mouse went down on the sky and hasn't been released yet
+    let mut shadow_preview = false; // F8: gray shadows only, at low resolution, for placing the sun and lights
+    let mut photo_mode = PhotoMode::new(); // F10: clock stopped, composition guides, high-res capture
+    let mut uv_debug = UvDebug::new(); // 8: UV grid on the block under the crosshair
+    let mut preview = RasterPreview::new();
+    let mut hybrid = HybridSwitch::new();
+    let mut presentation = Presentation::new(WIDTH, HEIGHT); // Letterboxed WIDTHxHEIGHT, F4 renders at the window size
+    let mut player = Player::new(camera.target);
 
     // FPS tracking for auto quality
     let mut fps_history: Vec<u32> = Vec::new();
@@ -53,40 +235,366 @@ fn main() {
     const HIGH_FPS_THRESHOLD: u32 = 45;
 
     let mut image_buffer = vec![Color::BLACK; (WIDTH * HEIGHT) as usize];
+    let mut watchdog_log_timer = 0.0f32;
 
     while !rl.window_should_close() {
         let delta_time = rl.get_frame_time();
         let current_fps = rl.get_fps();
 
-        handle_camera_input(&rl, &mut camera, delta_time);
+        // Shutter opens at the pose from the end of last frame
+        camera.begin_shutter();
+        scene.begin_shutter();
+
+        // === Camera Path === (L: play/pause, K: record keyframe, Shift+K: clear)
+        if keys.pressed(&rl, Action::PathPlay) && !camera_path.is_empty() {
+            path_playing = !path_playing;
+            if path_playing && path_time >= camera_path.duration() {
+                path_time = 0.0; // Restart a finished flight
+            }
+        }
+        if keys.pressed(&rl, Action::PathRecord) {
+            let shift = rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT) || rl.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT);
+            if shift {
+                camera_path.clear();
+                path_playing = false;
+                path_time = 0.0;
+            } else {
+                camera_path.record(&camera, KEYFRAME_SPACING);
+            }
+            let mut world_path = camera_path.clone();
+            world_path.translate(scene.origin);
+            let message = match world_path.save(&camera_path_file) {
+                Ok(()) => (fill(text.camera_path_keyframes, &[&camera_path.len()]), hud.theme.good),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    (text.camera_path_save_failed.to_string(), hud.theme.bad)
+                }
+            };
+            status_message = Some((message.0, message.1, 2.0));
+        }
+
+        // === Scene Cameras === (C: jump to the next named viewpoint)
+        if keys.pressed(&rl, Action::NextCamera) {
+            if scene.viewpoints.is_empty() {
+                status_message = Some((text.no_cameras.to_string(), hud.theme.warn, 2.0));
+            } else {
+                let view = &scene.viewpoints[next_view % scene.viewpoints.len()];
+                view.apply(&mut camera);
+                path_playing = false;
+                status_message = Some((fill(text.camera, &[&view.name]), hud.theme.good, 2.0));
+                next_view = (next_view + 1) % scene.viewpoints.len();
+            }
+        }
+
+        // === Lighting Presets === (G: studio -> overcast -> golden hour -> night -> day/night cycle)
+        if keys.pressed(&rl, Action::Lighting) {
+            scene.set_lighting(LightingPreset::next(scene.lighting.as_ref()));
+            let name = scene.lighting.as_ref().map_or(text.day_night_cycle, |lighting| lighting.name);
+            status_message = Some((fill(text.lighting, &[&name]), hud.theme.good, 2.0));
+        }
+
+        if path_playing {
+            path_time += delta_time;
+            if path_time >= camera_path.duration() {
+                path_time = camera_path.duration();
+                path_playing = false;
+            }
+            camera_path.apply(&mut camera, camera_path.start_time() + path_time);
+        } else {
+            // Photo mode nudges the camera in small steps for framing
+            let step_time = if photo_mode.active { delta_time * photo_mode::NUDGE_SPEED } else { delta_time };
+            handle_camera_input(&rl, keys, &mut camera, step_time);
+        }
+
+        // === Floating Origin === wandering far out moves the world back around the camera
+        if let Some(shift) = scene.recenter_around(camera.target) {
+            camera.translate(shift);
+            camera_path.translate(shift);
+            block_editor.translate(shift);
+            minimap.invalidate_all();
+            debug_overlay.invalidate();
+            preview.invalidate();
+        }
+
+        // === Render Distance === a streamed world loads the chunks around the camera and drops the rest
+        if scene.stream_around(camera.target) {
+            minimap.invalidate_all();
+            debug_overlay.invalidate();
+            preview.invalidate();
+        }
 
         // === Quality Control ===
-        if rl.is_key_pressed(KeyboardKey::KEY_ONE) {
+        if keys.pressed(&rl, Action::QualityLow) {
             manual_quality_level = 0;
             if !auto_quality { quality_level = 0; }
         }
-        if rl.is_key_pressed(KeyboardKey::KEY_TWO) {
+        if keys.pressed(&rl, Action::QualityMedium) {
             manual_quality_level = 1;
             if !auto_quality { quality_level = 1; }
         }
-        if rl.is_key_pressed(KeyboardKey::KEY_THREE) {
+        if keys.pressed(&rl, Action::QualityHigh) {
             manual_quality_level = 2;
             if !auto_quality { quality_level = 2; }
         }
+        if keys.pressed(&rl, Action::QualityUltra) {
+            manual_quality_level = 3;
+            if !auto_quality { quality_level = 3; }
+        }
 
         // Toggle auto performance mode
-        if rl.is_key_pressed(KeyboardKey::KEY_P) {
+        if keys.pressed(&rl, Action::AutoQuality) {
             auto_quality = !auto_quality;
             if !auto_quality {
                 quality_level = manual_quality_level; // Restore manual quality
             }
         }
 
-        if rl.is_key_pressed(KeyboardKey::KEY_T) { use_threading = !use_threading; }
+        if keys.pressed(&rl, Action::Threading) { use_threading = !use_threading; }
+        if keys.pressed(&rl, Action::MotionBlur) { motion_blur = !motion_blur; }
+        if keys.pressed(&rl, Action::ThirdPerson) { third_person = !third_person; }
+        if keys.pressed(&rl, Action::Minimap) { show_minimap = !show_minimap; }
+        if keys.pressed(&rl, Action::Hybrid) { raster_preview = !raster_preview; }
+        if keys.pressed(&rl, Action::PathTracing) {
+            path_tracing = !path_tracing;
+            let name = if path_tracing { text.path_traced } else { text.whitted };
+            status_message = Some((fill(text.integrator, &[&name]), hud.theme.good, 2.0));
+        }
+        if keys.pressed(&rl, Action::Denoise) {
+            denoise = !denoise;
+            let state = if denoise { text.on } else { text.off };
+            status_message = Some((fill(text.denoiser, &[&state]), hud.theme.good, 2.0));
+        }
+        if keys.pressed(&rl, Action::TemporalAa) {
+            temporal_aa = !temporal_aa;
+            temporal.reset();
+            let state = if temporal_aa { text.on } else { text.off };
+            status_message = Some((fill(text.temporal_aa, &[&state]), hud.theme.good, 2.0));
+        }
+        if keys.pressed(&rl, Action::ShadowPreview) {
+            shadow_preview = !shadow_preview;
+            let state = if shadow_preview { text.on } else { text.off };
+            status_message = Some((fill(text.shadow_preview, &[&state]), hud.theme.good, 2.0));
+        }
+        // === UV Grid === (8: numbered grid on the block or mesh under the crosshair, 8 again restores it)
+        if keys.pressed(&rl, Action::UvGrid) {
+            match uv_debug.toggle(&mut scene, &camera) {
+                Some(on) => {
+                    accumulator.reset(); // Same view, different surface: the averaged frames are stale
+                    temporal.reset();
+                    preview.invalidate();
+                    minimap.invalidate_all();
+                    debug_overlay.invalidate();
+                    let state = if on { text.on } else { text.off };
+                    status_message = Some((fill(text.uv_grid, &[&state]), hud.theme.good, 2.0));
+                }
+                None => status_message = Some((text.light_no_target.to_string(), hud.theme.warn, 2.0)),
+            }
+        }
+        // === Block Editing === (5: break the block under the crosshair, 6: put sand against it)
+        if keys.pressed(&rl, Action::BlockBreak) || keys.pressed(&rl, Action::BlockPlace) {
+            let breaking = keys.pressed(&rl, Action::BlockBreak);
+            let edited = if breaking {
+                block_editor.break_block(&mut scene, &camera)
+            } else {
+                block_editor.place_block(&mut scene, &camera, "sand")
+            };
+            match edited {
+                Some(cell) => {
+                    accumulator.reset();
+                    temporal.reset();
+                    preview.invalidate();
+                    minimap.invalidate_all();
+                    debug_overlay.invalidate();
+                    debug_overlay.mark_dirty(vec![cell]);
+                    let at = format!("{}, {}, {}", cell.0, cell.1, cell.2);
+                    let template = if breaking { text.block_broken } else { text.block_placed };
+                    status_message = Some((fill(template, &[&at]), hud.theme.good, 2.0));
+                }
+                None if breaking => status_message = Some((text.light_no_target.to_string(), hud.theme.warn, 2.0)),
+                None => status_message = Some((text.block_blocked.to_string(), hud.theme.warn, 2.0)),
+            }
+        }
+        // === World Save === (Insert: the block edits go to the world file, --world or saves/world.mcw)
+        if keys.pressed(&rl, Action::WorldSave) {
+            let message = match block_editor.edits().save(&world_file) {
+                Ok(()) => (fill(text.world_saved, &[&block_editor.edits().len(), &world_file.display()]), hud.theme.good),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    (text.world_save_failed.to_string(), hud.theme.bad)
+                }
+            };
+            status_message = Some((message.0, message.1, 3.0));
+        }
+        // === Photo Mode === (F10: stops the clock and the camera path, F11 picks the capture size)
+        if keys.pressed(&rl, Action::PhotoMode) {
+            photo_mode.active = !photo_mode.active;
+            path_playing = false;
+            let state = if photo_mode.active { text.on } else { text.off };
+            status_message = Some((fill(text.photo_mode, &[&state]), hud.theme.good, 2.0));
+        }
+        if photo_mode.active && keys.pressed(&rl, Action::PhotoResolution) {
+            photo_mode.next_scale();
+        }
+        if keys.pressed(&rl, Action::HorizonLock) {
+            camera.horizon_lock = !camera.horizon_lock;
+            if camera.horizon_lock {
+                camera.roll = Degrees(0.0);
+            }
+            let state = if camera.horizon_lock { text.on } else { text.off };
+            status_message = Some((fill(text.horizon_lock, &[&state]), hud.theme.good, 2.0));
+        }
+        // === Rain === (7: puddles spread on the stone paths while it rains and dry up after it stops)
+        if keys.pressed(&rl, Action::Rain) {
+            scene.weather.raining = !scene.weather.raining;
+            let state = if scene.weather.raining { text.on } else { text.off };
+            status_message = Some((fill(text.rain, &[&state]), hud.theme.good, 2.0));
+        }
+        // Depth of field: F picks the lens size, Z/X pull the focus nearer/farther
+        let focus_change = if keys.down(&rl, Action::FocusNear) {
+            -FOCUS_SPEED * delta_time
+        } else if keys.down(&rl, Action::FocusFar) {
+            FOCUS_SPEED * delta_time
+        } else {
+            0.0
+        };
+        if focus_change != 0.0 {
+            camera.adjust_focus(focus_change);
+        }
+        if keys.pressed(&rl, Action::DepthOfField) {
+            let next = APERTURES.iter().position(|&a| a > camera.aperture).unwrap_or(0);
+            camera.aperture = APERTURES[next];
+        }
+        if focus_change != 0.0 || keys.pressed(&rl, Action::DepthOfField) {
+            let state = if camera.aperture > 0.0 {
+                fill(text.lens, &[&camera.aperture, &format!("{:.1}", camera.focus_distance)])
+            } else {
+                text.off.to_string()
+            };
+            status_message = Some((fill(text.depth_of_field, &[&state]), hud.theme.good, 2.0));
+        }
+
+        // Debug overlays: F1 chunk borders, F2 light levels, F3 changed-block highlight
+        if keys.pressed(&rl, Action::DebugChunks) { debug_overlay.show_chunks = !debug_overlay.show_chunks; }
+        if keys.pressed(&rl, Action::DebugLight) { debug_overlay.show_light = !debug_overlay.show_light; }
+        if keys.pressed(&rl, Action::DebugDirty) { debug_overlay.show_dirty = !debug_overlay.show_dirty; }
+
+        // === Light Editor === (H: place, pick, nudge and save the scene file's point and spot lights)
+        if keys.pressed(&rl, Action::LightEdit) {
+            light_editor.active = !light_editor.active;
+            if light_editor.active && let Err(e) = light_editor.load(&scene_watcher.path, &scene) {
+                eprintln!("{}", e);
+                light_editor.active = false;
+            }
+            let state = if light_editor.active { text.on } else { text.off };
+            status_message = Some((fill(text.light_editor, &[&state]), hud.theme.good, 2.0));
+        }
+        if light_editor.active {
+            let mut edited = false;
+            if keys.pressed(&rl, Action::LightPlacePoint) || keys.pressed(&rl, Action::LightPlaceSpot) {
+                let placed = if keys.pressed(&rl, Action::LightPlacePoint) {
+                    light_editor.place_point(&scene, &camera)
+                } else {
+                    light_editor.place_spot(&scene, &camera)
+                };
+                if !placed {
+                    status_message = Some((text.light_no_target.to_string(), hud.theme.warn, 2.0));
+                }
+                edited |= placed;
+            }
+            if keys.pressed(&rl, Action::LightSelect) { light_editor.select_next(); }
+            let axis = |minus, plus| keys.pressed(&rl, plus) as i32 as f32 - keys.pressed(&rl, minus) as i32 as f32;
+            let steps = [
+                axis(Action::LightXMinus, Action::LightXPlus),
+                axis(Action::LightYMinus, Action::LightYPlus),
+                axis(Action::LightZMinus, Action::LightZPlus),
+            ];
+            let resize = axis(Action::LightRadiusDown, Action::LightRadiusUp);
+            if light_editor.selected.is_some() && (steps != [0.0; 3] || resize != 0.0) {
+                light_editor.nudge(steps);
+                light_editor.resize(resize);
+                edited = true;
+            }
+            if keys.pressed(&rl, Action::LightDelete) && light_editor.selected.is_some() {
+                light_editor.delete();
+                edited = true;
+            }
+            if edited {
+                match light_editor.apply(&mut scene) {
+                    Ok(()) => debug_overlay.invalidate(),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        status_message = Some((text.light_editor_failed.to_string(), hud.theme.bad, 4.0));
+                    }
+                }
+            }
+            // Saving changes the file, which the hot-reload below then picks up
+            if keys.pressed(&rl, Action::LightSave) {
+                let message = match light_editor.save(&scene_watcher.path) {
+                    Ok(()) => (fill(text.lights_saved, &[&light_editor.light_count(), &scene_watcher.path.display()]), hud.theme.good),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        (text.light_editor_failed.to_string(), hud.theme.bad)
+                    }
+                };
+                status_message = Some((message.0, message.1, 3.0));
+            }
+        }
+
+        // === Scene Hot-Reload === (file change or F5; the camera is kept)
+        let file_changed = scene_watcher.poll(delta_time);
+        if file_changed || keys.pressed(&rl, Action::Reload) {
+            match scene_watcher.load() {
+                Ok(mut new_scene) => {
+                    new_scene.time = scene.time; // Keep the clock running across reloads
+                    new_scene.weather = scene.weather; // And the rain toggled with 7
+                    new_scene.lamps_on = scene.lamps_on; // And the lamps as the last event left them
+                    new_scene.sun_pin = scene.sun_pin; // And the dragged sun
+                    if new_scene.lighting.is_none() {
+                        // And the preset picked with G (afresh, its lamps are in the old scene's render space)
+                        new_scene.set_lighting(scene.lighting.as_ref().and_then(|lighting| LightingPreset::by_name(lighting.name)));
+                    }
+                    // The block edits carry over to the reloaded file
+                    match block_editor.reapply(&mut new_scene) {
+                        Ok(0) => {}
+                        Ok(skipped) => eprintln!("{} block edits no longer fit the reloaded scene and were skipped", skipped),
+                        Err(e) => eprintln!("{}", e),
+                    }
+                    debug_overlay.mark_dirty(scene.grid.changed_cells(&new_scene.grid));
+                    // The edited scene may be centered elsewhere; keep the camera on the same world spot
+                    let shift = scene.origin - new_scene.origin;
+                    camera.translate(shift);
+                    camera_path.translate(shift);
+                    scene = new_scene;
+                    uv_debug.clear();
+                    if light_editor.active && let Err(e) = light_editor.load(&scene_watcher.path, &scene) {
+                        eprintln!("{}", e);
+                    }
+                    minimap.invalidate_all();
+                    debug_overlay.invalidate();
+                    preview.invalidate();
+                    temporal.reset();
+                    println!("{}", fill(text.console_reloaded, &[&scene_watcher.path.display()]));
+                    status_message = Some((text.scene_reloaded.to_string(), hud.theme.good, 2.0));
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    status_message = Some((text.scene_reload_failed.to_string(), hud.theme.bad, 4.0));
+                }
+            }
+        }
+        if let Some((_, _, ref mut time_left)) = status_message {
+            *time_left -= delta_time;
+        }
+        if status_message.as_ref().is_some_and(|(_, _, time_left)| *time_left <= 0.0) {
+            status_message = None;
+        }
 
-        if rl.is_key_down(KeyboardKey::KEY_N) {
-            day_time = (day_time + 0.01) % 1.0;
+        // Day/night advances through the scene clock while N is held (which drops a lighting preset)
+        if keys.down(&rl, Action::DayCycle) && scene.lighting.is_some() {
+            scene.set_lighting(None);
+            status_message = Some((fill(text.lighting, &[&text.day_night_cycle]), hud.theme.good, 2.0));
         }
+        scene.time.day_speed = if keys.down(&rl, Action::DayCycle) { DAY_CYCLE_SPEED } else { 0.0 };
 
         // === Auto Quality Adjustment ===
         if auto_quality {
@@ -107,95 +615,339 @@ fn main() {
                 // Lower quality if FPS is too low
                 if avg_fps < LOW_FPS_THRESHOLD && quality_level < 2 {
                     quality_level += 1;
-                    println!("Auto-scaling: Lowering quality to improve FPS (avg: {})", avg_fps);
+                    println!("{}", fill(text.console_quality_down, &[&avg_fps]));
                 }
                 // Raise quality if FPS is consistently high
                 else if avg_fps > HIGH_FPS_THRESHOLD && quality_level > 0 {
                     // Only increase if we can maintain good FPS
                     if quality_level > manual_quality_level {
                         quality_level -= 1;
-                        println!("Auto-scaling: Raising quality (avg: {})", avg_fps);
+                        println!("{}", fill(text.console_quality_up, &[&avg_fps]));
                     }
                 }
             }
         }
 
-        scene.update_sun_position(day_time);
+        scene.update(if photo_mode.active { 0.0 } else { delta_time });
+        photo_mode.update(delta_time);
+        debug_overlay.update(delta_time);
+        let landed = block_editor.update(&mut scene, if photo_mode.active { 0.0 } else { delta_time });
+        if !landed.is_empty() {
+            preview.invalidate();
+            minimap.invalidate_all();
+            debug_overlay.invalidate();
+            debug_overlay.mark_dirty(landed);
+        }
+
+        // === Third-Person Player === stands on the ground at the orbit target, facing away from the camera
+        scene.entities.clear();
+        if third_person {
+            let target = camera.target;
+            let ground = scene.ground_height(target.x, target.z, target.y + 1.0).unwrap_or(target.y);
+            player.feet = utils::Vec3::new(target.x, ground, target.z);
+            player.yaw = (target.z - camera.position.z).atan2(target.x - camera.position.x);
+            scene.entities.extend(player.build_cubes(&mut scene.materials));
+        }
+        scene.entities.extend(block_editor.falling_cubes());
+
+        // === Window Fit === (F4: letterboxed fixed size <-> render at the window's size and aspect)
+        if keys.pressed(&rl, Action::PresentMode) {
+            presentation.toggle();
+            let name = match presentation.mode {
+                PresentMode::Letterbox => text.letterbox,
+                PresentMode::MatchWindow => text.match_window,
+            };
+            status_message = Some((fill(text.presentation, &[&name]), hud.theme.good, 2.0));
+        }
+        let (window_width, window_height) = (rl.get_screen_width(), rl.get_screen_height());
+        let (render_width, render_height) = presentation.render_size(window_width, window_height);
+        let viewport = presentation.viewport(window_width, window_height);
+        if image_buffer.len() != (render_width * render_height) as usize {
+            image_buffer = vec![Color::BLACK; (render_width * render_height) as usize];
+        }
+        camera.aspect = render_width as f32 / render_height as f32; // Vertical FOV is kept, so only the sides change
+
+        // === Sun Drag (F7) === press on the sky and drag: the sun follows the mouse and stays
+        // there (over the day/night clock) until F7 is pressed again
+        if keys.pressed(&rl, Action::SunDrag) {
+            sun_drag = !sun_drag;
+            if !sun_drag {
+                sun_dragging = false;
+                scene.sun_pin = None;
+                scene.update_sun_position();
+            }
+            let state = if sun_drag { text.on } else { text.off };
+            status_message = Some((fill(text.sun_drag, &[&state]), hud.theme.good, 2.0));
+        }
+        if sun_drag {
+            let mouse = rl.get_mouse_position();
+            let u = (mouse.x - viewport.x as f32) / viewport.width.max(1) as f32;
+            let v = (mouse.y - viewport.y as f32) / viewport.height.max(1) as f32;
+            let ray = camera.get_pinhole_ray(u, v);
+            if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+                let on_frame = (0.0..=1.0).contains(&u) && (0.0..=1.0).contains(&v);
+                sun_dragging = on_frame && scene.intersect(&ray).is_none();
+            }
+            if !rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+                sun_dragging = false;
+            }
+            // Once grabbed the sun keeps following over the blocks too, down to the horizon
+            if sun_dragging {
+                let pin = SunAngles::toward(ray.direction);
+                scene.sun_pin = Some(pin);
+                scene.update_sun_position();
+                let angles = [pin.azimuth, pin.elevation].map(|a| format!("{:.1}", a.0));
+                status_message = Some((fill(text.sun_pinned, &[&angles[0], &angles[1]]), hud.theme.info, 2.0));
+            }
+        }
 
         let render_scale = match quality_level {
             0 => 4,  // Low: 4x downscale (1/16th pixels)
             1 => 2,  // Medium: 2x downscale (1/4th pixels)
-            _ => 1,  // High: Native resolution
+            _ => 1,  // High and Ultra: Native resolution
         };
 
-        renderer::render_scene(
-            &scene,
-            &camera,
-            &mut image_buffer,
-            WIDTH,
-            HEIGHT,
+        let settings = RenderSettings {
             render_scale,
             use_threading,
-            day_time,
-        );
+            motion_blur_samples: if motion_blur { 4 } else { 0 },
+            shadow_samples: match quality_level {
+                0 => 1, // Hard shadows
+                1 => 2,
+                _ => 4,
+            },
+            ao_samples: match quality_level {
+                0 => 2, // Noisy but cheap
+                1 => 4,
+                _ => 8,
+            },
+            aa_samples: if quality_level >= 3 { SSAA_SAMPLES } else { 0 }, // Ultra: supersampled edges
+            path_tracing,
+            path_samples: 1, // More comes from averaging frames
+            denoise,
+            frame_budget: config.watchdog.frame_budget,
+            ray_budget: config.watchdog.ray_budget,
+            adaptive_threshold: config.sampling.adaptive_threshold,
+            tone_map: config.display.tone_map,
+            exposure: config.display.exposure,
+            bloom: config.display.bloom,
+            bloom_threshold: config.display.bloom_threshold,
+            ..RenderSettings::default()
+        };
+        // Shadow preview: at most Medium resolution and none of the extra rays, so it keeps up
+        // while the sun or a light is dragged around (soft shadows still average out when still)
+        let settings = if shadow_preview {
+            RenderSettings {
+                render_scale: render_scale.max(2),
+                motion_blur_samples: 0,
+                ao_samples: 0,
+                aa_samples: 0,
+                path_tracing: false,
+                denoise: false,
+                bloom: 0.0,
+                shadow_preview: true,
+                ..settings
+            }
+        } else {
+            settings
+        };
+        // Nothing changed since the last frame: jitter the rays so the average also smooths edges,
+        // and stop tracing once the image has converged. Temporal AA jitters moving frames too.
+        let still = accumulator.is_still(&scene, &camera, &settings, render_width, render_height);
+        let settings = RenderSettings { jitter: still || temporal_aa, ..settings };
+
+        // Raster preview replaces the (slow) trace while the camera moves, then the trace fades back in.
+        // It has no shadows, so the shadow preview always traces.
+        hybrid.update(&camera, delta_time);
+        let raster = raster_preview && !shadow_preview;
+        let previewing = raster && hybrid.should_rasterize();
+        let traced_opacity = if raster { hybrid.traced_opacity() } else { 1.0 };
+        if (!raster || hybrid.should_trace()) && !(still && accumulator.converged()) {
+            let (stats, colors, gbuffer) = renderer::render_scene_with_colors(&scene, &camera, &mut image_buffer, render_width, render_height, &settings, temporal_aa);
+            accumulator.add(&mut image_buffer, &colors, &scene, &camera, &settings, render_width, render_height);
+            // Moving: blend with the last frame reprojected (still views are averaged above instead)
+            if let Some(gbuffer) = gbuffer {
+                temporal.resolve(&mut image_buffer, &scene, &camera, gbuffer.depth(), settings.render_scale, render_width, render_height, !still);
+            }
+
+            // === Watchdog === frames over budget finish roughly; say why (at most every few seconds)
+            watchdog_log_timer -= delta_time;
+            if stats.degraded() {
+                status_message = Some((text.over_budget.to_string(), hud.theme.warn, 2.0));
+                if watchdog_log_timer <= 0.0 {
+                    eprintln!("{}", stats.report(&settings));
+                    watchdog_log_timer = WATCHDOG_LOG_INTERVAL;
+                }
+            }
+        }
+
+        // === Screenshot (F12) === saves the traced frame without the HUD; in photo mode it traces
+        // a new still at the chosen multiple of the render size instead (blocking, see the console)
+        if keys.pressed(&rl, Action::Screenshot) && photo_mode.active {
+            println!("{}", fill(text.console_photo, &[&(render_width * photo_mode.scale), &(render_height * photo_mode.scale)]));
+            match photo_mode.capture(&scene, &camera, &settings, render_width, render_height) {
+                Ok(path) => {
+                    println!("{}", fill(text.console_screenshot, &[&path.display()]));
+                    status_message = Some((fill(text.saved, &[&path.display()]), hud.theme.good, 3.0));
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    status_message = Some((text.screenshot_failed.to_string(), hud.theme.bad, 4.0));
+                }
+            }
+        } else if keys.pressed(&rl, Action::Screenshot) {
+            match screenshot::save_screenshot(&image_buffer, render_width, render_height) {
+                Ok(path) => {
+                    println!("{}", fill(text.console_screenshot, &[&path.display()]));
+                    status_message = Some((fill(text.saved, &[&path.display()]), hud.theme.good, 3.0));
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    status_message = Some((text.screenshot_failed.to_string(), hud.theme.bad, 4.0));
+                }
+            }
+        }
 
         let mut d = rl.begin_drawing(&thread);
         d.clear_background(Color::BLACK);
-        draw_buffer(&mut d, &image_buffer, WIDTH, HEIGHT);
+        if previewing {
+            preview.draw(&mut d, &scene, &camera, viewport, window_height);
+        }
+        if let Some(review) = photo_mode.review() {
+            Presentation::draw_buffer(&mut d, &review.pixels, review.width, review.height, viewport, 1.0);
+        } else if traced_opacity > 0.0 {
+            Presentation::draw_buffer(&mut d, &image_buffer, render_width, render_height, viewport, traced_opacity);
+        }
+        debug_overlay.draw(&mut d, &scene, &camera, viewport);
+        if (hud.crosshair || light_editor.active) && !photo_mode.active {
+            crosshair::draw(&mut d, &scene, &camera, viewport);
+        }
+        if light_editor.active {
+            light_editor.draw(&mut d, &scene, &camera, viewport);
+        }
+
+        // Photo mode keeps the picture clear: guides and one line of help instead of the HUD
+        if photo_mode.active {
+            photo_mode.draw_guides(&mut d, viewport);
+            let k = |action| keys.label(action);
+            let size = [photo_mode.scale, render_width * photo_mode.scale, render_height * photo_mode.scale];
+            hud.text(&mut d, &fill(text.photo_hud, &[&size[0], &size[1], &size[2], &k(Action::PhotoResolution), &k(Action::Screenshot), &k(Action::PhotoMode)]), 10, 10, 16, hud.theme.accent);
+            if let Some((ref message, color, _)) = status_message {
+                hud.text(&mut d, message, 10, 35, 18, color);
+            }
+            continue;
+        }
 
         // === Performance Display ===
         let fps = d.get_fps();
         let fps_color = if fps >= 50 {
-            Color::GREEN
+            hud.theme.good
         } else if fps >= 25 {
-            Color::YELLOW
+            hud.theme.warn
         } else {
-            Color::RED
+            hud.theme.bad
         };
-        d.draw_text(&format!("FPS: {}", fps), 10, 10, 20, fps_color);
+        hud.text(&mut d, &fill(text.fps, &[&fps]), 10, 10, 20, fps_color);
 
         // Quality display with color coding
         let (quality_text, quality_color) = match quality_level {
-            0 => ("Low (4x)", Color::ORANGE),
-            1 => ("Medium (2x)", Color::SKYBLUE),
-            _ => ("High (1x)", Color::LIME),
+            0 => (text.quality_low, hud.theme.warn),
+            1 => (text.quality_medium, hud.theme.info),
+            2 => (text.quality_high, hud.theme.good),
+            _ => (text.quality_ultra, hud.theme.accent),
         };
-        d.draw_text(&format!("Quality: {}", quality_text), 10, 35, 20, quality_color);
+        hud.text(&mut d, &fill(text.quality, &[&quality_text]), 10, 35, 20, quality_color);
 
         // Show auto-quality status
         if auto_quality {
-            d.draw_text("[AUTO PERF]", 200, 35, 20, Color::GOLD);
+            hud.text(&mut d, text.auto_perf, 200, 35, 20, hud.theme.accent);
         }
 
         // Render scale info
-        let pixels_rendered = ((WIDTH * HEIGHT) / (render_scale * render_scale)) as f32;
-        let percentage = (pixels_rendered / (WIDTH * HEIGHT) as f32) * 100.0;
-        d.draw_text(
-            &format!("Pixels: {:.0}% ({}/{})", percentage, pixels_rendered as i32, WIDTH * HEIGHT),
+        let pixels_rendered = ((render_width * render_height) / (settings.render_scale * settings.render_scale)) as f32;
+        let percentage = (pixels_rendered / (render_width * render_height) as f32) * 100.0;
+        hud.text(
+            &mut d,
+            &fill(text.pixels, &[&format!("{:.0}", percentage), &(pixels_rendered as i32), &(render_width * render_height)]),
             10, 60,
             16,
-            Color::LIGHTGRAY,
+            hud.theme.dim,
         );
 
-        d.draw_text(&format!("Threading: {}", if use_threading { "ON" } else { "OFF" }), 10, 85, 16, Color::WHITE);
-        d.draw_text(&format!("Day Time: {:.2}", day_time), 10, 105, 16, Color::YELLOW);
+        hud.text(&mut d, &fill(text.threading, &[&if use_threading { text.on } else { text.off }]), 10, 85, 16, hud.theme.text);
+        hud.text(&mut d, &fill(text.day_time, &[&format!("{:.2}", scene.time.day_fraction), &(scene.time.days + 1)]), 10, 105, 16, hud.theme.info);
+        if motion_blur {
+            hud.text(&mut d, text.motion_blur, 200, 85, 16, hud.theme.accent);
+        }
+        if third_person {
+            hud.text(&mut d, text.third_person, 340, 85, 16, hud.theme.accent);
+        }
+        if shadow_preview {
+            hud.text(&mut d, text.shadow_view, 480, 105, 16, hud.theme.accent);
+        }
+        if path_tracing && !shadow_preview {
+            hud.text(&mut d, &fill(text.path_frames, &[&accumulator.frames()]), 340, 125, 16, hud.theme.accent);
+        } else if accumulator.frames() > 1 {
+            hud.text(&mut d, &fill(text.averaged_frames, &[&accumulator.frames()]), 340, 125, 16, hud.theme.accent);
+        }
+        if denoise {
+            hud.text(&mut d, text.denoised, 200, 125, 16, hud.theme.accent);
+        }
+        if camera.aperture > 0.0 {
+            hud.text(&mut d, text.dof, 480, 85, 16, hud.theme.accent);
+        }
+        if sun_drag {
+            hud.text(&mut d, text.sun_drag_mode, 10, 190, 16, hud.theme.accent);
+        }
+        if previewing {
+            hud.text(&mut d, text.raster_preview, 340, 105, 16, hud.theme.accent);
+        }
+        if path_playing {
+            hud.text(&mut d, &fill(text.path_progress, &[&format!("{:.1}", path_time), &format!("{:.1}", camera_path.duration())]), 200, 105, 16, hud.theme.accent);
+        }
         
         // Show sun direction for debugging
-        d.draw_text(&format!("Sun Dir: ({:.2}, {:.2}, {:.2})", 
-            -scene.sun.direction.x, -scene.sun.direction.y, -scene.sun.direction.z), 
-            10, 125, 14, Color::ORANGE);
+        let sun = [-scene.sun.direction.x, -scene.sun.direction.y, -scene.sun.direction.z].map(|v| format!("{:.2}", v));
+        hud.text(&mut d, &fill(text.sun_dir, &[&sun[0], &sun[1], &sun[2]]), 10, 125, 14, hud.theme.info);
+
+        if show_minimap {
+            minimap.draw(&mut d, &scene, &camera, window_width - Minimap::size() - 12, 12);
+        }
+
+        if let Some((ref message, color, _)) = status_message {
+            hud.text(&mut d, message, 10, 145, 18, color);
+        }
+        if light_editor.active {
+            let selected = match light_editor.selected {
+                Some(Selection::Point(i)) => fill(text.light_point, &[&(i + 1)]),
+                Some(Selection::Spot(i)) => fill(text.light_spot, &[&(i + 1)]),
+                None => text.light_none.to_string(),
+            };
+            let unsaved = if light_editor.unsaved { text.unsaved } else { "" };
+            hud.text(&mut d, &fill(text.light_edit, &[&light_editor.light_count(), &selected, &unsaved]), 10, 170, 16, hud.theme.accent);
+        }
 
         // Controls display with better readability
-        d.draw_text("=== CONTROLS ===", 10, HEIGHT - 110, 18, Color::BLACK);
-        d.draw_text("WASD: Look Around (W=Up, S=Down, A=Left, D=Right)", 10, HEIGHT - 85, 16, Color::BLACK);
-        d.draw_text("Arrow UP/DOWN: Zoom In/Out  |  Arrow L/R: Rotate Camera", 10, HEIGHT - 65, 16, Color::BLACK);
-        d.draw_text("Q/E: Move Position Up/Down  |  N: Toggle Day/Night", 10, HEIGHT - 45, 16, Color::BLACK);
-        d.draw_text("1/2/3: Quality  |  P: Auto-Performance  |  T: Threading", 10, HEIGHT - 25, 14, Color::BLACK);
-        d.draw_text("TIP: Press W to look up and see the sun!", WIDTH - 350, HEIGHT - 25, 14, Color::BLACK);
+        hud.text_bottom(&mut d, text.controls_title, 10, 110, 18, hud.theme.help);
+        // Key names come from the bindings so remapped controls are shown correctly
+        let k = |action| keys.label(action);
+        if light_editor.active {
+            hud.text_bottom(&mut d, &fill(text.controls_light_edit, &[&k(Action::LightPlacePoint), &k(Action::LightPlaceSpot), &k(Action::LightSelect),
+                &k(Action::LightXMinus), &k(Action::LightXPlus), &k(Action::LightYMinus), &k(Action::LightYPlus), &k(Action::LightZMinus), &k(Action::LightZPlus),
+                &k(Action::LightRadiusDown), &k(Action::LightRadiusUp), &k(Action::LightDelete), &k(Action::LightSave)]), 10, 135, 14, hud.theme.accent);
+        }
+        hud.text_bottom(&mut d, &fill(text.controls_look, &[&k(Action::LookUp), &k(Action::LookDown), &k(Action::LookLeft), &k(Action::LookRight)]), 10, 85, 16, hud.theme.help);
+        hud.text_bottom(&mut d, &fill(text.controls_move, &[&k(Action::ZoomIn), &k(Action::ZoomOut), &k(Action::MoveUp), &k(Action::MoveDown)]), 10, 65, 16, hud.theme.help);
+        hud.text_bottom(&mut d, &fill(text.controls_scene, &[&k(Action::DayCycle), &k(Action::Lighting), &k(Action::Rain), &k(Action::Reload), &k(Action::Screenshot)]), 10, 45, 16, hud.theme.help);
+        hud.text_bottom(&mut d, &fill(text.controls_render, &[&k(Action::QualityLow), &k(Action::QualityMedium), &k(Action::QualityHigh), &k(Action::QualityUltra), &k(Action::AutoQuality), &k(Action::Threading),
+            &k(Action::MotionBlur), &k(Action::ThirdPerson), &k(Action::PathPlay), &k(Action::PathRecord), &k(Action::Hybrid)]), 10, 25, 14, hud.theme.help);
+        hud.text_bottom_right(&mut d, &fill(text.controls_tip, &[&k(Action::LookUp), &k(Action::Minimap), &k(Action::DebugChunks), &k(Action::DebugLight), &k(Action::DebugDirty), &k(Action::UvGrid), &k(Action::NextCamera), &k(Action::PathTracing), &k(Action::Denoise), &k(Action::TemporalAa),
+            &k(Action::DepthOfField), &k(Action::FocusNear), &k(Action::FocusFar), &k(Action::SunDrag), &k(Action::ShadowPreview), &k(Action::RollLeft), &k(Action::RollRight), &k(Action::HorizonLock), &k(Action::PhotoMode), &k(Action::LightEdit),
+            &k(Action::BlockBreak), &k(Action::BlockPlace)]), 600, 110, 14, hud.theme.help);
     }
 }
 
-fn handle_camera_input(rl: &RaylibHandle, camera: &mut Camera, delta_time: f32) {
+fn handle_camera_input(rl: &RaylibHandle, keys: &KeyBindings, camera: &mut Camera, delta_time: f32) {
     // Camera control speeds (units/degrees per second)
     let rotation_speed = 60.0; // degrees per second
     let zoom_speed = 10.0;
@@ -206,50 +958,44 @@ fn handle_camera_input(rl: &RaylibHandle, camera: &mut Camera, delta_time: f32)
     let zoom_amount = zoom_speed * delta_time;
     let vertical_amount = vertical_speed * delta_time;
 
-    // === WASD - Look Around (Camera View Control) ===
-    if rl.is_key_down(KeyboardKey::KEY_W) {
-        camera.rotate_vertical(rotate_amount); // Look UP
+    // === Look Around (WASD / arrows by default, see config.toml) ===
+    if keys.down(rl, Action::LookUp) {
+        camera.rotate_vertical(Degrees(rotate_amount)); // Look UP
     }
-    if rl.is_key_down(KeyboardKey::KEY_S) {
-        camera.rotate_vertical(-rotate_amount); // Look DOWN
+    if keys.down(rl, Action::LookDown) {
+        camera.rotate_vertical(-Degrees(rotate_amount)); // Look DOWN
     }
-    if rl.is_key_down(KeyboardKey::KEY_A) {
-        camera.rotate_around_target(-rotate_amount); // Look LEFT
+    if keys.down(rl, Action::LookLeft) {
+        camera.rotate_around_target(-Degrees(rotate_amount)); // Look LEFT
     }
-    if rl.is_key_down(KeyboardKey::KEY_D) {
-        camera.rotate_around_target(rotate_amount); // Look RIGHT
+    if keys.down(rl, Action::LookRight) {
+        camera.rotate_around_target(Degrees(rotate_amount)); // Look RIGHT
     }
 
-    // === Arrow Keys - Rotation and Zoom ===
-    if rl.is_key_down(KeyboardKey::KEY_LEFT) {
-        camera.rotate_around_target(-rotate_amount);
+    // === Roll (dutch angle) ===
+    // Rolling releases the horizon lock, or the next orbit step would level it again
+    if keys.down(rl, Action::RollLeft) {
+        camera.horizon_lock = false;
+        camera.roll_by(-Degrees(rotate_amount));
     }
-    if rl.is_key_down(KeyboardKey::KEY_RIGHT) {
-        camera.rotate_around_target(rotate_amount);
+    if keys.down(rl, Action::RollRight) {
+        camera.horizon_lock = false;
+        camera.roll_by(Degrees(rotate_amount));
     }
 
-    // === Arrow Keys - Zoom ===
-    if rl.is_key_down(KeyboardKey::KEY_UP) {
+    // === Zoom ===
+    if keys.down(rl, Action::ZoomIn) {
         camera.zoom(-zoom_amount); // Zoom IN
     }
-    if rl.is_key_down(KeyboardKey::KEY_DOWN) {
+    if keys.down(rl, Action::ZoomOut) {
         camera.zoom(zoom_amount); // Zoom OUT
     }
 
-    // === Q/E - Move Camera Position Up/Down ===
-    if rl.is_key_down(KeyboardKey::KEY_Q) {
+    // === Move Camera Position Up/Down ===
+    if keys.down(rl, Action::MoveUp) {
         camera.move_up(vertical_amount);
     }
-    if rl.is_key_down(KeyboardKey::KEY_E) {
+    if keys.down(rl, Action::MoveDown) {
         camera.move_down(vertical_amount);
     }
 }
-
-fn draw_buffer(d: &mut RaylibDrawHandle, buffer: &[Color], width: i32, height: i32) {
-    for y in 0..height {
-        for x in 0..width {
-            let idx = (y * width + x) as usize;
-            d.draw_pixel(x, y, buffer[idx]);
-        }
-    }
-}
@@ -1,16 +1,45 @@
+use std::sync::{Arc, OnceLock};
+
 use crate::color::Color;
-use crate::texture::Texture;
+use crate::palette;
+use crate::procedural_texture;
+use crate::texture::{Texture, TextureAnimation, TextureFilter};
+use crate::texture_atlas::{TextureAtlas, UvRect};
+use crate::texture_cache;
+use crate::utils::{Real, Vec3};
+
+const SPARKLE_CELLS: Real = 24.0; // Crystal cells per block along each axis
+const SPARKLE_DENSITY: Real = 0.06; // Share of the cells holding a crystal
+const SPARKLE_TILT: Real = 0.8; // How far the crystal facets lean off the surface normal
+const GLINT_CELLS: Real = 48.0; // Metal flakes per block along each axis, finer than snow crystals
+const GLINT_DENSITY: Real = 0.1; // Share of the cells holding a flake
+const GLINT_SEED: u32 = 0x2C1B_3C6D; // Keeps the flakes apart from the crystals of the same cells
 
 #[derive(Clone)]
 pub struct Material {
     pub albedo: Color,
-    pub texture: Option<Texture>,
-    pub reflectivity: f32,
+    pub texture: Option<Arc<Texture>>, // Shared between clones (every cube holds its own Material)
+    pub uv_rect: UvRect,                // Part of the texture used (an atlas tile, or the whole image)
+    pub animation: Option<TextureAnimation>, // uv_rect holds a vertical strip of frames cycled over time
+    pub reflectivity: f32,    // Mirror share of opaque materials (transparent ones use Fresnel from the IOR)
     pub specular: f32,        // Specular intensity (0.0 = no specular, 1.0 = full specular)
     pub shininess: f32,       // Specular shininess/glossiness (higher = sharper highlights)
     pub emissive: Color,
     pub refractive_index: f32,
     pub transparency: f32,
+    pub absorption: Color, // Beer-Lambert coefficients per unit travelled inside (black = perfectly clear)
+    pub shadow_catcher: f32, // > 0: invisible except for the shadows (this dark) and reflections it receives
+    pub alpha_cutoff: f32, // Texels less opaque than this are holes (0.0 = no cutout)
+    pub normal_map: Option<Arc<Texture>>, // Tangent-space normals (OpenGL style, green = up), covers the whole face
+    pub normal_strength: f32,             // Scales the map's tilt (0 = flat, 1 = as authored)
+    pub waves: f32, // Animated waves tilting the top face's normal (0 = flat, 1 = a rippled pond), see waves.rs
+    pub foam: f32, // Shoreline foam on the top face where it meets solid blocks (0 = off, 1 = thick)
+    pub frost: f32,      // Refraction roughness: how far refracted rays scatter (0 = clear, ice ~0.15)
+    pub subsurface: f32, // Light wrapping past the terminator, for soft translucent looks (0 = plain diffuse)
+    pub sparkle: f32,    // Strength of glints from tiny crystals catching the sun (0 = none)
+    pub glint: f32,      // Strength of metal flakes glinting in the surface color, from sun and lamps (0 = none)
+    pub puddles: f32,    // How much rain pools on the top face (0 = none, 1 = stone paths), see weather.rs
+    pub gravity: bool,   // Falls when the block under it is taken away (sand, gravel), see block_edit.rs
 }
 
 impl Material {
@@ -18,17 +47,114 @@ impl Material {
         Self {
             albedo,
             texture: None,
+            uv_rect: UvRect::FULL,
+            animation: None,
             reflectivity: 0.0,
             specular: 0.0,
             shininess: 32.0,
             emissive: Color::black(),
             refractive_index: 1.0,
             transparency: 0.0,
+            absorption: Color::black(),
+            shadow_catcher: 0.0,
+            alpha_cutoff: 0.0,
+            normal_map: None,
+            normal_strength: 1.0,
+            waves: 0.0,
+            foam: 0.0,
+            frost: 0.0,
+            subsurface: 0.0,
+            sparkle: 0.0,
+            glint: 0.0,
+            puddles: 0.0,
+            gravity: false,
         }
     }
 
-    pub fn with_texture(mut self, texture: Texture) -> Self {
-        self.texture = Some(texture);
+    /// Debug material: plain white under the numbered UV grid (procedural_texture::uv_grid),
+    /// for checking which way each face's texture runs. Every copy shares one grid texture.
+    pub fn uv_grid() -> Self {
+        static GRID: OnceLock<Arc<Texture>> = OnceLock::new();
+        let grid = GRID.get_or_init(|| Arc::new(procedural_texture::uv_grid()));
+        Self::new(Color::white()).with_texture(Arc::clone(grid))
+    }
+
+    /// Ice block: clear pale blue, frosted so what's behind it blurs, with a faint cyan depth
+    pub fn ice() -> Self {
+        Self::new(palette::ICE_BLUE)
+            .with_texture(texture_cache::load("assets/textures/ice.png"))
+            .with_transparency(0.85, 1.31)
+            .with_absorption(palette::ICE_DEPTH_TINT, 0.4)
+            .with_frost(0.15)
+            .with_reflectivity(0.05)
+            .with_specular(0.7, 96.0)
+    }
+
+    /// Snow block: bright, soft-edged diffuse light and sparkling crystals in the sun
+    pub fn snow() -> Self {
+        Self::new(palette::SNOW_WHITE)
+            .with_texture(texture_cache::load("assets/textures/snow.png"))
+            .with_subsurface(0.4)
+            .with_sparkle(1.0)
+    }
+
+    /// Sand block: pale and grainy, falls when nothing holds it up
+    pub fn sand() -> Self {
+        Self::new(palette::SAND_YELLOW)
+            .with_texture(texture_cache::load("assets/textures/sand.png"))
+            .with_gravity()
+    }
+
+    /// Gravel block: gray pebbles, falls when nothing holds it up
+    pub fn gravel() -> Self {
+        Self::new(palette::NEUTRAL_GRAY)
+            .with_texture(texture_cache::load("assets/textures/gravel.png"))
+            .with_gravity()
+    }
+
+    /// Accepts an owned Texture or a shared one from texture_cache::load
+    pub fn with_texture(mut self, texture: impl Into<Arc<Texture>>) -> Self {
+        self.texture = Some(texture.into());
+        self.uv_rect = UvRect::FULL;
+        self
+    }
+
+    /// Use one tile of a shared atlas sheet as this material's texture
+    pub fn with_atlas_tile(mut self, atlas: &TextureAtlas, column: usize, row: usize) -> Result<Self, String> {
+        self.uv_rect = atlas.tile(column, row)?;
+        self.texture = Some(Arc::clone(&atlas.texture));
+        Ok(self)
+    }
+
+    /// Play the texture (or atlas tile) as a vertical strip of frames (call after with_texture)
+    pub fn with_animation(mut self, animation: TextureAnimation) -> Self {
+        self.animation = Some(animation);
+        self
+    }
+
+    /// Texture area shown at `time`: the current animation frame, or the whole uv_rect
+    fn frame_rect(&self, time: f32) -> UvRect {
+        match self.animation {
+            Some(animation) if animation.frames > 1 => {
+                let height = self.uv_rect.height / animation.frames as f32;
+                UvRect {
+                    v: self.uv_rect.v + height * animation.frame_at(time) as f32,
+                    height,
+                    ..self.uv_rect
+                }
+            }
+            _ => self.uv_rect,
+        }
+    }
+
+    /// Texture filtering for this material (call after with_texture).
+    /// A texture shared with other materials is copied first if the filter differs.
+    pub fn with_filter(mut self, filter: TextureFilter) -> Self {
+        if let Some(ref mut texture) = self.texture
+            && texture.filter != filter
+        {
+            Arc::make_mut(texture).filter = filter;
+        }
         self
     }
 
@@ -54,9 +180,198 @@ impl Material {
         self
     }
 
-    pub fn get_color(&self, u: f32, v: f32) -> Color {
+    /// Tint light picks up travelling through the material: channels missing from `color` are
+    /// absorbed at `density` per block, so thin panes stay almost clear and deep water turns blue-green.
+    pub fn with_absorption(mut self, color: Color, density: f32) -> Self {
+        self.absorption = Color::new(
+            (1.0 - color.r).max(0.0) * density,
+            (1.0 - color.g).max(0.0) * density,
+            (1.0 - color.b).max(0.0) * density,
+        );
+        self
+    }
+
+    /// Foam band along the edges of the top face where solid blocks border it (shoreline.rs), for water
+    pub fn with_foam(mut self, strength: f32) -> Self {
+        self.foam = strength.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Animated waves on the surface (water): reflections and refraction ripple instead of being
+    /// mirror flat. 1.0 is a gently rippled pond; larger is choppier
+    pub fn with_waves(mut self, strength: f32) -> Self {
+        self.waves = strength.max(0.0);
+        self
+    }
+
+    /// Frosted refraction: rays through the surface scatter up to `roughness` (about radians) off
+    /// the sharp direction, so the view through it blurs. Noisy per frame, smooth once averaged.
+    pub fn with_frost(mut self, roughness: f32) -> Self {
+        self.frost = roughness.max(0.0);
+        self
+    }
+
+    /// Wrap diffuse light past the terminator, like light scattering under the surface (snow, wax)
+    pub fn with_subsurface(mut self, wrap: f32) -> Self {
+        self.subsurface = wrap.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sun glints from tiny crystals scattered over the surface (snow)
+    pub fn with_sparkle(mut self, strength: f32) -> Self {
+        self.sparkle = strength.max(0.0);
+        self
+    }
+
+    /// Metallic glints: tiny flakes in the surface's own color that twinkle as the camera moves,
+    /// lit by the sun and every lamp (gold, ores)
+    pub fn with_glint(mut self, strength: f32) -> Self {
+        self.glint = strength.max(0.0);
+        self
+    }
+
+    /// Rain puddles on the top face: while the scene's weather is wet, water pools in patches that
+    /// darken the surface and mirror the sky, growing as it rains and drying up after
+    pub fn with_puddles(mut self, strength: f32) -> Self {
+        self.puddles = strength.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Make blocks of this material fall when the block under them is removed (block_edit.rs)
+    pub fn with_gravity(mut self) -> Self {
+        self.gravity = true;
+        self
+    }
+
+    /// Turn the surface into a shadow catcher (a ground plane for compositing): it shows whatever is
+    /// behind it, darkened by `opacity` where it's in shadow, plus `reflectivity` of the blocks it mirrors
+    pub fn with_shadow_catcher(mut self, opacity: f32) -> Self {
+        self.shadow_catcher = opacity.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Treat texels with alpha below the threshold as holes (leaves, glass panes); 0.5 suits most textures
+    pub fn with_alpha_cutoff(mut self, threshold: f32) -> Self {
+        self.alpha_cutoff = threshold;
+        self
+    }
+
+    /// Bumpy surface relief from a normal map texture (e.g. texture_cache::load_normal_map("assets/textures/stone_normal.png"))
+    pub fn with_normal_map(mut self, normal_map: impl Into<Arc<Texture>>, strength: f32) -> Self {
+        self.normal_map = Some(normal_map.into());
+        self.normal_strength = strength;
+        self
+    }
+
+    /// Normal used for lighting at (u, v): the surface normal tilted by the normal map.
+    /// Without a map, or on surfaces without a uv frame (meshes), the normal is returned as is.
+    pub fn shading_normal(&self, normal: Vec3, tangent: Vec3, bitangent: Vec3, u: f32, v: f32) -> Vec3 {
+        let Some(ref normal_map) = self.normal_map else {
+            return normal;
+        };
+        if tangent.length() == 0.0 {
+            return normal;
+        }
+        let texel = normal_map.sample(u, v);
+        let x = (texel.r * 2.0 - 1.0) * self.normal_strength;
+        let y = (texel.g * 2.0 - 1.0) * self.normal_strength;
+        let z = (texel.b * 2.0 - 1.0).max(0.05);
+        (tangent * x as Real + bitangent * y as Real + normal * z as Real).normalize()
+    }
+
+    /// Share of light reflected at a view angle (cos_theta = view·normal). Transparent materials
+    /// follow Schlick's approximation from their IOR: about 2% head-on for water and 4% for glass,
+    /// rising to a mirror at grazing angles. Opaque materials reflect their fixed `reflectivity`.
+    /// From inside (`entering` false) Schlick needs the angle on the air side, and past the
+    /// critical angle everything is reflected (total internal reflection).
+    pub fn fresnel(&self, cos_theta: f32, entering: bool) -> f32 {
+        if self.transparency <= 0.0 {
+            return self.reflectivity;
+        }
+        let cos_theta = if entering {
+            cos_theta
+        } else {
+            let sin_t2 = self.refractive_index * self.refractive_index * (1.0 - cos_theta * cos_theta);
+            if sin_t2 >= 1.0 {
+                return 1.0;
+            }
+            (1.0 - sin_t2).sqrt()
+        };
+        let r0 = ((1.0 - self.refractive_index) / (1.0 + self.refractive_index)).powi(2);
+        r0 + (1.0 - r0) * (1.0 - cos_theta).powi(5)
+    }
+
+    /// Diffuse factor for light arriving at `cos` to the normal, wrapped by `subsurface`
+    pub fn diffuse(&self, cos: f32) -> f32 {
+        ((cos + self.subsurface) / (1.0 + self.subsurface)).max(0.0)
+    }
+
+    /// Facet normal of the crystal at this point, if one sits there. The surface is split into
+    /// SPARKLE_CELLS cells per block, and a hash of the cell decides whether it holds a crystal and
+    /// which way it's tilted, so the glints stay put on the block as the camera moves.
+    pub fn sparkle_normal(&self, position: Vec3, normal: Vec3) -> Option<Vec3> {
+        if self.sparkle <= 0.0 {
+            return None;
+        }
+        facet_normal(position, normal, SPARKLE_CELLS, SPARKLE_DENSITY, 0)
+    }
+
+    /// Facet normal of the metal flake at this point, if one sits there: like sparkle_normal with
+    /// finer, denser cells. Each flake flashes only while it mirrors a light toward the camera.
+    pub fn glint_normal(&self, position: Vec3, normal: Vec3) -> Option<Vec3> {
+        if self.glint <= 0.0 {
+            return None;
+        }
+        facet_normal(position, normal, GLINT_CELLS, GLINT_DENSITY, GLINT_SEED)
+    }
+
+    /// Glowing surfaces are drawn at their emissive color instead of being lit
+    pub fn is_emissive(&self) -> bool {
+        self.emissive.r > 0.0 || self.emissive.g > 0.0 || self.emissive.b > 0.0
+    }
+
+    /// True when (u, v) falls on a cut-out texel that rays should pass through
+    /// (animated textures use the first frame's alpha)
+    pub fn is_hole(&self, u: f32, v: f32) -> bool {
+        if self.alpha_cutoff <= 0.0 {
+            return false;
+        }
+        let Some(ref texture) = self.texture else {
+            return false;
+        };
+        let rect = self.frame_rect(0.0);
+        let (u, v) = if rect.is_full() { (u, v) } else { rect.map(u, v, texture) };
+        texture.alpha_at(u, v) < self.alpha_cutoff
+    }
+
+    /// Representative flat color of the material (texture average when textured)
+    pub fn average_color(&self) -> Color {
         if let Some(ref texture) = self.texture {
-            texture.sample(u, v)
+            let rect = self.frame_rect(0.0);
+            if rect.is_full() {
+                texture.average_color()
+            } else {
+                texture.average_color_in(&rect)
+            }
+        } else {
+            self.albedo
+        }
+    }
+
+    /// Surface color at (u, v) at `time` seconds on the scene clock (picks the animation frame);
+    /// footprint is the uv area one pixel covers (0 = full resolution)
+    pub fn get_color(&self, u: f32, v: f32, footprint: f32, time: f32) -> Color {
+        if let Some(ref texture) = self.texture {
+            let rect = self.frame_rect(time);
+            if rect.is_full() {
+                return texture.sample_lod(u, v, footprint);
+            }
+            // Atlas tile or animation frame: the footprint is measured in the rect's texels, and mips stop at one texel per rect
+            let (u, v) = rect.map(u, v, texture);
+            let rect_width = rect.width * texture.width as f32;
+            let rect_height = rect.height * texture.height as f32;
+            let texels = footprint * rect_width.max(rect_height);
+            texture.sample_texels(u, v, texels, rect_width.min(rect_height).log2().floor())
         } else {
             self.albedo
         }
@@ -68,3 +383,24 @@ impl Default for Material {
         Self::new(Color::white())
     }
 }
+
+// Tilted facet of the crystal or flake in this point's cell (cells per block along each axis), if
+// the cell's hash puts one there (a `density` share of the cells)
+fn facet_normal(position: Vec3, normal: Vec3, cells: Real, density: Real, seed: u32) -> Option<Vec3> {
+    let cell = |x: Real| (x * cells).floor() as i32 as u32;
+    let mut h = cell(position.x).wrapping_mul(0x8DA6_B343)
+        ^ cell(position.y).wrapping_mul(0xD816_3841)
+        ^ cell(position.z).wrapping_mul(0xCB1A_B31F)
+        ^ seed;
+    let mut next = || {
+        h ^= h >> 13;
+        h = h.wrapping_mul(0x5BD1_E995);
+        h ^= h >> 15;
+        (h >> 8) as Real / (1u32 << 24) as Real
+    };
+    if next() > density {
+        return None;
+    }
+    let tilt = Vec3::new(next() - 0.5, next() - 0.5, next() - 0.5) * SPARKLE_TILT;
+    Some((normal + tilt).normalize())
+}
@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+use std::ops::Index;
+use std::sync::Arc;
+
+use crate::material::Material;
+
+/// A material's place in its scene's MaterialPalette, what cubes store instead of the material
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MaterialId(u32);
+
+/// The materials a scene's cubes are made of, each stored once. Cubes hold MaterialIds, so a
+/// block is a handful of numbers however much its material carries, and copying the cube list
+/// (scene clones for the render threads, block edits) copies no materials at all.
+/// Materials are only ever added, so an id stays valid for as long as the scene lives. Cubes
+/// built over and over (the player model, critters, smoke) ask for their materials by name and
+/// get the same id every time, instead of adding a copy per frame.
+#[derive(Clone, Default)]
+pub struct MaterialPalette {
+    materials: Vec<Arc<Material>>,
+    names: HashMap<String, MaterialId>,
+}
+
+impl MaterialPalette {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a material, returning its id
+    pub fn add(&mut self, material: Material) -> MaterialId {
+        let id = MaterialId(self.materials.len() as u32);
+        self.materials.push(Arc::new(material));
+        id
+    }
+
+    /// Id of the material added under `name`, adding the one `make` builds the first time
+    pub fn intern(&mut self, name: &str, make: impl FnOnce() -> Material) -> MaterialId {
+        if let Some(&id) = self.names.get(name) {
+            return id;
+        }
+        let id = self.add(make());
+        self.names.insert(name.to_string(), id);
+        id
+    }
+
+    /// Same as intern for materials that can fail to build (unknown names in a scene file)
+    pub fn try_intern<E>(&mut self, name: &str, make: impl FnOnce() -> Result<Material, E>) -> Result<MaterialId, E> {
+        if let Some(&id) = self.names.get(name) {
+            return Ok(id);
+        }
+        let material = make()?;
+        Ok(self.intern(name, || material))
+    }
+
+    /// Number of distinct materials
+    pub fn len(&self) -> usize {
+        self.materials.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.materials.is_empty()
+    }
+}
+
+impl Index<MaterialId> for MaterialPalette {
+    type Output = Material;
+
+    fn index(&self, id: MaterialId) -> &Material {
+        &self.materials[id.0 as usize]
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use raylib::prelude::*;
+
+use crate::camera::Camera;
+use crate::scene::Scene;
+
+const RADIUS: i32 = 16;  // Blocks shown around the camera in each direction
+const CELL_PIXELS: i32 = 4;
+const MAX_CACHED: usize = 4 * ((RADIUS * 2 + 1) * (RADIUS * 2 + 1)) as usize; // Columns kept before the far ones are dropped
+
+/// Top-down map of the top-most block colors around the camera.
+/// Column colors are computed lazily from the voxel grid and cached, so only
+/// columns that scroll into view (or were invalidated) cost anything. Once the cache
+/// holds MAX_CACHED columns, the ones out of view are dropped.
+pub struct Minimap {
+    columns: HashMap<(i32, i32), Option<raylib::prelude::Color>>, // Cached column colors (None = empty)
+}
+
+impl Minimap {
+    pub fn new() -> Self {
+        Self {
+            columns: HashMap::new(),
+        }
+    }
+
+    /// Forget all cached columns (after the scene is rebuilt)
+    pub fn invalidate_all(&mut self) {
+        self.columns.clear();
+    }
+
+    fn column_color(scene: &Scene, x: i32, z: i32) -> Option<raylib::prelude::Color> {
+        let (y, cube) = scene.grid.top_block(x, z)?;
+        let base = cube.top_face_material(&scene.materials).average_color();
+
+        // Higher blocks are drawn brighter so terrain height reads on the map
+        let (min_y, max_y) = scene.grid.height_range();
+        let height = if max_y > min_y { (y - min_y) as f32 / (max_y - min_y) as f32 } else { 1.0 };
+        Some((base * (0.6 + 0.4 * height)).to_srgb().to_raylib()) // Texture averages are linear
+    }
+
+    pub fn draw(&mut self, d: &mut RaylibDrawHandle, scene: &Scene, camera: &Camera, screen_x: i32, screen_y: i32) {
+        let size = Self::size();
+        let center_x = camera.position.x.round() as i32;
+        let center_z = camera.position.z.round() as i32;
+
+        d.draw_rectangle(screen_x - 2, screen_y - 2, size + 4, size + 4, Color::BLACK);
+
+        if self.columns.len() > MAX_CACHED {
+            self.columns.retain(|&(x, z), _| (x - center_x).abs() <= RADIUS && (z - center_z).abs() <= RADIUS);
+        }
+
+        for dz in -RADIUS..=RADIUS {
+            for dx in -RADIUS..=RADIUS {
+                let (x, z) = (center_x + dx, center_z + dz);
+                let cached = *self
+                    .columns
+                    .entry((x, z))
+                    .or_insert_with(|| Self::column_color(scene, x, z));
+
+                if let Some(color) = cached {
+                    d.draw_rectangle(
+                        screen_x + (dx + RADIUS) * CELL_PIXELS,
+                        screen_y + (dz + RADIUS) * CELL_PIXELS,
+                        CELL_PIXELS,
+                        CELL_PIXELS,
+                        color,
+                    );
+                }
+            }
+        }
+
+        // Camera marker with a line showing where it faces (map: +X right, +Z down)
+        let marker_x = screen_x + RADIUS * CELL_PIXELS + CELL_PIXELS / 2;
+        let marker_y = screen_y + RADIUS * CELL_PIXELS + CELL_PIXELS / 2;
+        let forward = camera.target - camera.position;
+        let horizontal = (forward.x * forward.x + forward.z * forward.z).sqrt().max(1e-4);
+        let line_length = 12.0;
+        d.draw_line(
+            marker_x,
+            marker_y,
+            marker_x + (forward.x / horizontal * line_length) as i32,
+            marker_y + (forward.z / horizontal * line_length) as i32,
+            Color::RED,
+        );
+        d.draw_circle(marker_x, marker_y, 3.0, Color::RED);
+        d.draw_rectangle_lines(screen_x - 2, screen_y - 2, size + 4, size + 4, Color::WHITE);
+    }
+
+    /// Width/height of the widget in pixels
+    pub fn size() -> i32 {
+        (RADIUS * 2 + 1) * CELL_PIXELS
+    }
+}
+
+impl Default for Minimap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,37 @@
+/// Fractal Brownian motion: octaves of value noise, each twice as fine and half as strong (0-1).
+/// Clouds, hills, waves, rain puddles and the shoreline foam all come from it.
+pub fn fbm(x: f32, z: f32, octaves: u32) -> f32 {
+    let (mut sum, mut amplitude, mut frequency, mut total) = (0.0, 0.5, 1.0, 0.0);
+    for octave in 0..octaves {
+        sum += value_noise(x * frequency, z * frequency, octave) * amplitude;
+        total += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    sum / total
+}
+
+// Smoothly interpolated random values on the integer lattice (0-1)
+fn value_noise(x: f32, z: f32, seed: u32) -> f32 {
+    let (cell_x, cell_z) = (x.floor(), z.floor());
+    let (fx, fz) = (smoothstep(0.0, 1.0, x - cell_x), smoothstep(0.0, 1.0, z - cell_z));
+    let at = |dx: i32, dz: i32| lattice(cell_x as i32 + dx, cell_z as i32 + dz, seed);
+    let near = at(0, 0) + (at(1, 0) - at(0, 0)) * fx;
+    let far = at(0, 1) + (at(1, 1) - at(0, 1)) * fx;
+    near + (far - near) * fz
+}
+
+// Deterministic random value in [0, 1) for a lattice point
+fn lattice(x: i32, z: i32, seed: u32) -> f32 {
+    let mut h = (x as u32).wrapping_mul(0x8DA6_B343) ^ (z as u32).wrapping_mul(0xD816_3841) ^ seed.wrapping_mul(0xCB1A_B31F);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x5BD1_E995);
+    h ^= h >> 15;
+    (h >> 8) as f32 / (1u32 << 24) as f32
+}
+
+/// 0 below edge0, 1 above edge1 and an S-curve between
+pub fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
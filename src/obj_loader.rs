@@ -1,7 +1,8 @@
-use crate::utils::Vec3;
-use crate::ray::Ray;
+use crate::utils::{Real, Vec3, narrow};
+use crate::ray::{Ray, T_MIN};
 use crate::material::Material;
 use crate::intersection::Intersection;
+use crate::cube::{face_tangents, face_uv};
 
 pub struct Triangle {
     pub v0: Vec3,
@@ -20,7 +21,7 @@ impl Triangle {
     }
 
     // Möller-Trumbore intersection algorithm
-    pub fn intersect(&self, ray: &Ray) -> Option<f32> {
+    pub fn intersect(&self, ray: &Ray) -> Option<Real> {
         let edge1 = self.v1 - self.v0;
         let edge2 = self.v2 - self.v0;
         let h = ray.direction.cross(&edge2);
@@ -47,7 +48,7 @@ impl Triangle {
 
         let t = f * edge2.dot(&q);
 
-        if t > 0.001 {
+        if t > T_MIN {
             Some(t)
         } else {
             None
@@ -58,7 +59,8 @@ impl Triangle {
 pub struct Mesh {
     pub triangles: Vec<Triangle>,
     pub position: Vec3,
-    pub scale: f32,
+    pub previous_position: Vec3, // Position when the shutter opened (motion blur)
+    pub scale: Real,
     pub material: Material,
 }
 
@@ -67,13 +69,14 @@ impl Mesh {
         Self {
             triangles: Vec::new(),
             position,
+            previous_position: position,
             scale: 1.0,
             material,
         }
     }
 
     /// Load an OBJ file and create a mesh with scale
-    pub fn load_obj(path: &str, position: Vec3, scale: f32, material: Material) -> Self {
+    pub fn load_obj(path: &str, position: Vec3, scale: Real, material: Material) -> Self {
         println!("Loading OBJ model: {} (scale: {})", path, scale);
 
         // Try to load the OBJ file using tobj
@@ -106,21 +109,21 @@ impl Mesh {
                         let idx2 = indices[i + 2] as usize;
 
                         let v0 = Vec3::new(
-                            positions[idx0 * 3] * scale,
-                            positions[idx0 * 3 + 1] * scale,
-                            positions[idx0 * 3 + 2] * scale,
+                            positions[idx0 * 3] as Real * scale,
+                            positions[idx0 * 3 + 1] as Real * scale,
+                            positions[idx0 * 3 + 2] as Real * scale,
                         );
 
                         let v1 = Vec3::new(
-                            positions[idx1 * 3] * scale,
-                            positions[idx1 * 3 + 1] * scale,
-                            positions[idx1 * 3 + 2] * scale,
+                            positions[idx1 * 3] as Real * scale,
+                            positions[idx1 * 3 + 1] as Real * scale,
+                            positions[idx1 * 3 + 2] as Real * scale,
                         );
 
                         let v2 = Vec3::new(
-                            positions[idx2 * 3] * scale,
-                            positions[idx2 * 3 + 1] * scale,
-                            positions[idx2 * 3 + 2] * scale,
+                            positions[idx2 * 3] as Real * scale,
+                            positions[idx2 * 3 + 1] as Real * scale,
+                            positions[idx2 * 3 + 2] as Real * scale,
                         );
 
                         triangles.push(Triangle::new(v0, v1, v2));
@@ -132,6 +135,7 @@ impl Mesh {
                 Self {
                     triangles,
                     position,
+                    previous_position: position,
                     scale,
                     material,
                 }
@@ -167,6 +171,7 @@ impl Mesh {
                 Self {
                     triangles,
                     position,
+                    previous_position: position,
                     scale,
                     material,
                 }
@@ -175,7 +180,7 @@ impl Mesh {
     }
 
     /// Rotate all triangles around the Y axis by the given angle (in radians)
-    pub fn rotate_y(&mut self, angle: f32) {
+    pub fn rotate_y(&mut self, angle: Real) {
         let cos_angle = angle.cos();
         let sin_angle = angle.sin();
 
@@ -205,32 +210,51 @@ impl Mesh {
         }
     }
 
+    /// Position interpolated to a time within the shutter interval
+    pub fn position_at(&self, time: f32) -> Vec3 {
+        self.previous_position + (self.position - self.previous_position) * time as Real
+    }
+
+    /// Nearest hit, shaded like a cube face: the texture repeats once per block along the axis
+    /// plane the triangle faces most (lined up with the block grid around the mesh's position),
+    /// with tangents for normal maps and cut-out texels let through.
     pub fn intersect(&self, ray: &Ray) -> Option<Intersection> {
-        let mut closest_t = f32::INFINITY;
-        let mut closest_triangle: Option<&Triangle> = None;
+        let mut closest: Option<(Real, &Triangle, (f32, f32))> = None;
 
         // Transform ray to local space
-        let local_ray = Ray::new(ray.origin - self.position, ray.direction);
+        let local_ray = Ray::new(ray.origin - self.position_at(ray.time), ray.direction);
 
         for triangle in &self.triangles {
-            if let Some(t) = triangle.intersect(&local_ray) {
-                if t < closest_t {
-                    closest_t = t;
-                    closest_triangle = Some(triangle);
-                }
+            let Some(t) = triangle.intersect(&local_ray) else { continue };
+            if closest.is_some_and(|(closest_t, _, _)| t >= closest_t) {
+                continue;
+            }
+            let uv = Self::uv(local_ray.at(t), &triangle.normal);
+            if !self.material.is_hole(uv.0, uv.1) {
+                closest = Some((t, triangle, uv));
             }
         }
 
-        closest_triangle.map(|tri| {
-            let hit_point = ray.at(closest_t);
+        closest.map(|(t, tri, (u, v))| {
+            let hit_point = ray.at(t);
+            let (tangent, bitangent) = face_tangents(&tri.normal);
+            // Flattened onto the triangle's plane, which a slanted triangle doesn't share with the axis face
+            let flatten = |direction: Vec3| (direction - tri.normal * direction.dot(&tri.normal)).normalize();
+            // Triangles are two-sided: from behind (e.g. a ray inside the mesh) it's a back face
             Intersection::new(
-                closest_t,
+                t,
                 hit_point,
                 tri.normal,
                 self.material.clone(),
-                0.0,
-                0.0,
-            )
+                u,
+                v,
+            ).with_tangents(flatten(tangent), flatten(bitangent)).oriented(ray)
         })
     }
+
+    // Texture coordinates of a point in mesh space (one repeat per block, like cube faces)
+    fn uv(local: Vec3, normal: &Vec3) -> (f32, f32) {
+        let (u, v) = face_uv(local + Vec3::new(0.5, 0.5, 0.5), normal);
+        (narrow(u.rem_euclid(1.0)), narrow(v.rem_euclid(1.0)))
+    }
 }
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::color::Color;
+
+// Block colors (albedo under the textures, or the whole look for untextured blocks)
+pub const GRASS_GREEN: Color = Color::new(0.3, 0.7, 0.3);
+pub const GRASS_SIDE_GREEN: Color = Color::new(0.5, 0.6, 0.4);
+pub const DIRT_BROWN: Color = Color::new(0.4, 0.3, 0.2);
+pub const STONE_GRAY: Color = Color::new(0.6, 0.6, 0.6);
+pub const NEUTRAL_GRAY: Color = Color::new(0.5, 0.5, 0.5); // Lets a texture show its own colors
+pub const CHERRY_LOG_BROWN: Color = Color::new(0.5, 0.3, 0.2);
+pub const CHERRY_PINK: Color = Color::new(1.0, 0.7, 0.8);
+pub const PLANKS_BROWN: Color = Color::new(0.6, 0.4, 0.3);
+pub const GLASS_WHITE: Color = Color::new(0.9, 0.9, 1.0);
+pub const WINDOW_BLUE: Color = Color::new(0.8, 0.9, 1.0);
+pub const WATER_BLUE: Color = Color::new(0.2, 0.5, 0.9);
+pub const GOLD: Color = Color::new(1.0, 0.84, 0.0);
+pub const ICE_BLUE: Color = Color::new(0.85, 0.93, 1.0);
+pub const SNOW_WHITE: Color = Color::new(0.95, 0.97, 1.0);
+pub const SAND_YELLOW: Color = Color::new(0.86, 0.8, 0.6);
+pub const LANTERN_AMBER: Color = Color::new(1.0, 0.8, 0.5);
+pub const GLOWSTONE_YELLOW: Color = Color::new(1.0, 0.85, 0.55);
+pub const LAVA_ORANGE: Color = Color::new(1.0, 0.5, 0.1);
+
+// Axolotl
+pub const AXOLOTL_PINK: Color = Color::new(1.0, 0.7, 0.8);
+pub const AXOLOTL_MOUTH: Color = Color::new(0.7, 0.3, 0.4);
+pub const AXOLOTL_GILLS: Color = Color::new(1.0, 0.4, 0.6);
+pub const EYE_BLACK: Color = Color::new(0.05, 0.05, 0.05);
+
+// Glow (Material::with_emissive); above 1 is brighter than white and blooms
+pub const EYE_GLOW: Color = Color::new(0.1, 0.1, 0.1);
+pub const AXOLOTL_MOUTH_GLOW: Color = Color::new(0.1, 0.05, 0.05);
+pub const AXOLOTL_GILLS_GLOW: Color = Color::new(1.2, 0.4, 0.6);
+
+// Tints picked up inside transparent blocks (Material::with_absorption)
+pub const GLASS_GREEN_TINT: Color = Color::new(0.85, 0.95, 0.9);
+pub const WATER_DEPTH_TINT: Color = Color::new(0.3, 0.75, 0.7);
+pub const ICE_DEPTH_TINT: Color = Color::new(0.75, 0.92, 0.98);
+pub const FOAM_WHITE: Color = Color::new(0.85, 0.92, 0.92); // Shoreline foam on water
+pub const SMOKE_GRAY: Color = Color::new(0.55, 0.55, 0.58); // Chimney smoke puffs
+pub const BUTTERFLY_ORANGE: Color = Color::new(1.0, 0.55, 0.1);
+pub const BUTTERFLY_WHITE: Color = Color::new(0.95, 0.95, 0.85);
+pub const FIREFLY_GLOW: Color = Color::new(0.8, 1.0, 0.3);
+pub const SIGN_INK: Color = Color::new(0.12, 0.1, 0.08); // Letters on signs and banners
+pub const BANNER_WHITE: Color = Color::new(0.92, 0.89, 0.8);
+pub const LEAF_GREEN: Color = Color::new(0.25, 0.55, 0.2); // Bushes and flower stems (decorations.rs)
+pub const FLOWER_RED: Color = Color::new(0.85, 0.12, 0.1);
+pub const FLOWER_YELLOW: Color = Color::new(1.0, 0.85, 0.2);
+pub const FLOWER_BLUE: Color = Color::new(0.35, 0.5, 0.95);
+pub const WHEAT_YELLOW: Color = Color::new(0.85, 0.72, 0.35);
+
+// Light colors
+pub const LANTERN_LIGHT: Color = Color::new(1.0, 0.75, 0.45);
+pub const PORCH_LIGHT: Color = Color::new(1.0, 0.8, 0.55);
+pub const MOONLIGHT: Color = Color::new(0.6, 0.7, 1.0);
+pub const DAY_AMBIENT: Color = Color::new(0.45, 0.45, 0.52);
+pub const NIGHT_AMBIENT: Color = Color::new(0.05, 0.05, 0.08);
+
+// Every constant by its name in palette and scene files
+const NAMED: [(&str, Color); 46] = [
+    ("grass_green", GRASS_GREEN),
+    ("grass_side_green", GRASS_SIDE_GREEN),
+    ("dirt_brown", DIRT_BROWN),
+    ("stone_gray", STONE_GRAY),
+    ("neutral_gray", NEUTRAL_GRAY),
+    ("cherry_log_brown", CHERRY_LOG_BROWN),
+    ("cherry_pink", CHERRY_PINK),
+    ("planks_brown", PLANKS_BROWN),
+    ("glass_white", GLASS_WHITE),
+    ("window_blue", WINDOW_BLUE),
+    ("water_blue", WATER_BLUE),
+    ("gold", GOLD),
+    ("ice_blue", ICE_BLUE),
+    ("snow_white", SNOW_WHITE),
+    ("sand_yellow", SAND_YELLOW),
+    ("lantern_amber", LANTERN_AMBER),
+    ("glowstone_yellow", GLOWSTONE_YELLOW),
+    ("lava_orange", LAVA_ORANGE),
+    ("axolotl_pink", AXOLOTL_PINK),
+    ("axolotl_mouth", AXOLOTL_MOUTH),
+    ("axolotl_gills", AXOLOTL_GILLS),
+    ("eye_black", EYE_BLACK),
+    ("eye_glow", EYE_GLOW),
+    ("axolotl_mouth_glow", AXOLOTL_MOUTH_GLOW),
+    ("axolotl_gills_glow", AXOLOTL_GILLS_GLOW),
+    ("glass_green_tint", GLASS_GREEN_TINT),
+    ("water_depth_tint", WATER_DEPTH_TINT),
+    ("ice_depth_tint", ICE_DEPTH_TINT),
+    ("foam_white", FOAM_WHITE),
+    ("smoke_gray", SMOKE_GRAY),
+    ("butterfly_orange", BUTTERFLY_ORANGE),
+    ("butterfly_white", BUTTERFLY_WHITE),
+    ("firefly_glow", FIREFLY_GLOW),
+    ("sign_ink", SIGN_INK),
+    ("banner_white", BANNER_WHITE),
+    ("leaf_green", LEAF_GREEN),
+    ("flower_red", FLOWER_RED),
+    ("flower_yellow", FLOWER_YELLOW),
+    ("flower_blue", FLOWER_BLUE),
+    ("wheat_yellow", WHEAT_YELLOW),
+    ("lantern_light", LANTERN_LIGHT),
+    ("porch_light", PORCH_LIGHT),
+    ("moonlight", MOONLIGHT),
+    ("day_ambient", DAY_AMBIENT),
+    ("night_ambient", NIGHT_AMBIENT),
+    ("white", Color::new(1.0, 1.0, 1.0)),
+];
+
+/// Color written in a palette file: linear [r, g, b] (0-1, above 1 for glow) or sRGB "#rrggbb"
+/// as color pickers give it
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Entry {
+    Rgb([f32; 3]),
+    Hex(String),
+}
+
+/// Named colors for scene files: the built-in block colors above, plus any a palette file adds
+/// or restyles. A palette file is a flat TOML table, one `name = [r, g, b]` or `name = "#rrggbb"`
+/// per line, so `grass_green = "#5f9f3f"` repaints every material that names grass_green.
+#[derive(Clone)]
+pub struct Palette {
+    colors: HashMap<String, Color>,
+}
+
+impl Palette {
+    pub fn new() -> Self {
+        Self { colors: NAMED.iter().map(|(name, color)| (name.to_string(), *color)).collect() }
+    }
+
+    /// Built-in colors with the file's entries on top
+    pub fn load(path: &str) -> Result<Self, String> {
+        let source = fs::read_to_string(Path::new(path)).map_err(|e| format!("Failed to read palette '{}': {}", path, e))?;
+        let entries: HashMap<String, Entry> = toml::from_str(&source).map_err(|e| format!("Palette '{}': {}", path, e))?;
+        let mut palette = Self::new();
+        for (name, entry) in entries {
+            let color = match entry {
+                Entry::Rgb([r, g, b]) => Color::new(r, g, b),
+                Entry::Hex(hex) => parse_hex(&hex).ok_or_else(|| format!("Palette '{}': '{}' is not a #rrggbb color", path, hex))?,
+            };
+            palette.colors.insert(name, color);
+        }
+        Ok(palette)
+    }
+
+    pub fn get(&self, name: &str) -> Option<Color> {
+        self.colors.get(name).copied()
+    }
+
+    /// Like get, with the error a scene file reports for a missing name
+    pub fn color(&self, name: &str) -> Result<Color, String> {
+        self.get(name).ok_or_else(|| format!("Unknown palette color '{}'", name))
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// "#rrggbb" is sRGB-encoded like image files, decoded to the linear values materials hold
+fn parse_hex(hex: &str) -> Option<Color> {
+    let digits = hex.strip_prefix('#')?;
+    if digits.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(digits.get(i..i + 2)?, 16).ok();
+    Some(Color::from_u8(channel(0)?, channel(2)?, channel(4)?).to_linear())
+}
@@ -0,0 +1,244 @@
+use crate::cube::Cube;
+use crate::light::lumens;
+use crate::material::Material;
+use crate::material_palette::MaterialPalette;
+use crate::palette;
+use crate::point_light::PointLight;
+use crate::utils::{Real, TAU, Vec3, narrow};
+
+const PUFF_START_SIZE: Real = 0.2; // Block widths of a puff as it leaves the chimney...
+const PUFF_END_SIZE: Real = 0.7;   // ...and as it fades out
+const PUFF_START_CLEAR: f32 = 0.45; // Transparency of a fresh puff (0 = solid gray)
+const WIND: [Real; 2] = [0.25, 0.1]; // Blocks per second the smoke drifts along (x, z) as it rises
+const FADE_STEPS: f32 = 32.0; // Smoke thinning and firefly glow go in this many steps, one palette material each
+
+const WANDER_SPEED: Real = 0.15; // Turns per second of a critter's loop around its swarm's center
+const BUTTERFLY_FLAPS: Real = 6.0; // Wing beats per second
+const BUTTERFLY_BODY: Real = 0.05; // Block widths of a butterfly's body...
+const BUTTERFLY_WING: Real = 0.1;  // ...and of each wing
+const FIREFLY_SIZE: Real = 0.05;
+const FIREFLY_BLINK: Real = 0.4;   // Blinks per second
+const FIREFLY_LUMENS: f32 = 6.0;   // At the peak of a blink
+const FIREFLY_REACH: Real = 2.5;   // Blocks a firefly's light reaches
+
+const PETAL_COUNT: u32 = 24;
+const PETAL_SIZE: Real = 0.06;
+const PETAL_LIFETIME: f32 = 5.0; // Seconds from the burst until the last petal is gone
+const PETAL_SCATTER: Real = 1.2; // Blocks per second petals fly out at, before the air slows them
+const PETAL_DRAG: Real = 0.6;    // Seconds for the outward speed to drop to about a third
+const PETAL_FALL: Real = 0.45;   // Blocks per second they sink
+const PETAL_FLUTTER: Real = 0.8; // Side-to-side sways per second on the way down
+
+/// Chimney smoke: gray puffs leave `position` at a steady rate, rise while drifting downwind and
+/// spreading out, then grow, thin and fade over their lifetime. Each puff's path depends only on
+/// when it left and the scene time, so any moment can be rendered without simulating up to it
+/// (exports and batch jobs get the same smoke as the app).
+#[derive(Debug, Clone, Copy)]
+pub struct SmokeEmitter {
+    pub position: Vec3,
+    pub rate: f32,     // Puffs per second
+    pub lifetime: f32, // Seconds a puff lasts
+    pub rise: Real,    // Blocks per second puffs go up
+    pub spread: Real,  // Blocks a puff wanders sideways over its life
+}
+
+impl SmokeEmitter {
+    /// Wisp of smoke from a house chimney whose top is at `position`
+    pub fn chimney(position: Vec3) -> Self {
+        Self {
+            position,
+            rate: 2.5,
+            lifetime: 4.0,
+            rise: 0.8,
+            spread: 0.6,
+        }
+    }
+
+    /// The puffs alive at scene time `time` (seconds), as small see-through gray cubes. They dim
+    /// light passing through them like any transparent block, so the smoke casts a faint shadow.
+    pub fn puffs(&self, time: f32, materials: &mut MaterialPalette) -> Vec<Cube> {
+        let newest = (time * self.rate).floor() as i64;
+        let oldest = ((time - self.lifetime) * self.rate).floor() as i64 + 1;
+        (oldest..=newest)
+            .filter_map(|puff| {
+                let age = time - puff as f32 / self.rate;
+                let life = age / self.lifetime;
+                if !(0.0..1.0).contains(&life) {
+                    return None;
+                }
+                // Each puff wanders off in its own direction, further as it ages
+                let (wander_x, wander_z) = (jitter(puff, 0) * 2.0 - 1.0, jitter(puff, 1) * 2.0 - 1.0);
+                let (age, life) = (age as Real, life as Real);
+                let offset = Vec3::new(
+                    WIND[0] * age + wander_x * self.spread * life,
+                    self.rise * age,
+                    WIND[1] * age + wander_z * self.spread * life,
+                );
+                let size = PUFF_START_SIZE + (PUFF_END_SIZE - PUFF_START_SIZE) * life;
+                let clear = fade_step(PUFF_START_CLEAR + (1.0 - PUFF_START_CLEAR) * life as f32);
+                let material = materials.intern(&format!("smoke {}", clear), || Material::new(palette::SMOKE_GRAY).with_transparency(clear, 1.0));
+                Some(Cube::new(self.position + offset, size, material))
+            })
+            .collect()
+    }
+}
+
+// Fraction rounded to one of the FADE_STEPS levels, so fading things reuse a few materials
+fn fade_step(fraction: f32) -> f32 {
+    (fraction * FADE_STEPS).round() / FADE_STEPS
+}
+
+// Deterministic random value in [0, 1) for a puff (one per channel)
+fn jitter(puff: i64, channel: u32) -> Real {
+    let mut h = (puff as u32).wrapping_mul(0x9E37_79B1) ^ channel.wrapping_mul(0x85EB_CA77);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2C1B_3C6D);
+    h ^= h >> 12;
+    (h >> 8) as Real / (1u32 << 24) as Real
+}
+
+/// Cherry petals shaken loose all at once (the "petals" event action): small pink flakes thrown
+/// out from `position` that flutter down and are gone after PETAL_LIFETIME seconds. Where each
+/// petal is depends only on the time since `start`, like the smoke.
+#[derive(Debug, Clone, Copy)]
+pub struct PetalBurst {
+    pub position: Vec3,
+    pub start: f32, // Scene time of the burst
+}
+
+impl PetalBurst {
+    pub fn new(position: Vec3, start: f32) -> Self {
+        Self { position, start }
+    }
+
+    /// The last petal has fallen by `time`
+    pub fn is_over(&self, time: f32) -> bool {
+        time - self.start >= PETAL_LIFETIME
+    }
+
+    /// The petals in the air at scene time `time`
+    pub fn petals(&self, time: f32, materials: &mut MaterialPalette) -> Vec<Cube> {
+        let age = time - self.start;
+        if !(0.0..PETAL_LIFETIME).contains(&age) {
+            return Vec::new();
+        }
+        let material = materials.intern("petal", || Material::new(palette::CHERRY_PINK));
+        let age = age as Real;
+        (0..PETAL_COUNT)
+            .map(|i| {
+                let seed = i as i64 + 3000;
+                let (heading, speed) = (jitter(seed, 0) * TAU, PETAL_SCATTER * (0.5 + 0.5 * jitter(seed, 1)));
+                // Thrown out fast, then carried only as far as the drag lets them
+                let out = speed * PETAL_DRAG * (1.0 - (-age / PETAL_DRAG).exp());
+                let sway = (age * PETAL_FLUTTER * TAU + jitter(seed, 2) * TAU).sin() * 0.2;
+                let fall = PETAL_FALL * (0.7 + 0.6 * jitter(seed, 3)) * age;
+                let offset = Vec3::new(heading.cos() * out + sway, -fall, heading.sin() * out);
+                Cube::new(self.position + offset, PETAL_SIZE, material)
+            })
+            .collect()
+    }
+}
+
+/// What a swarm is made of
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CritterKind {
+    Butterfly, // Out while the sun is up, flapping around flowers and trees
+    Firefly,   // Out at night, glowing and lighting what's near them
+}
+
+/// Ambient critters wandering around a spot, each on its own lazy loop within `radius` blocks of
+/// `center`, bobbing up and down. Like the smoke they're a function of the scene time.
+#[derive(Debug, Clone, Copy)]
+pub struct Swarm {
+    pub kind: CritterKind,
+    pub center: Vec3,
+    pub radius: Real,
+    pub count: u32,
+}
+
+impl Swarm {
+    pub fn butterflies(center: Vec3, radius: Real) -> Self {
+        Self { kind: CritterKind::Butterfly, center, radius, count: 3 }
+    }
+
+    pub fn fireflies(center: Vec3, radius: Real) -> Self {
+        Self { kind: CritterKind::Firefly, center, radius, count: 8 }
+    }
+
+    /// Butterflies come out by day, fireflies at night
+    pub fn is_out(&self, sun_is_up: bool) -> bool {
+        match self.kind {
+            CritterKind::Butterfly => sun_is_up,
+            CritterKind::Firefly => !sun_is_up,
+        }
+    }
+
+    // Where critter i is at `time`: a loop of its own size, speed and height around the center
+    fn position(&self, i: u32, time: f32) -> Vec3 {
+        let seed = i as i64 + ((self.kind == CritterKind::Firefly) as i64) * 1000;
+        let (reach, speed, phase) = (0.4 + 0.6 * jitter(seed, 2), 0.6 + 0.8 * jitter(seed, 3), jitter(seed, 4) * TAU);
+        let angle = time as Real * WANDER_SPEED * TAU * speed + phase;
+        // Different x and z rates make each loop a wobbly figure instead of a circle
+        let (x, z) = ((angle * 1.3).sin(), (angle * 0.7).cos());
+        let bob = (angle * 3.0).sin() * 0.3;
+        self.center + Vec3::new(x * reach * self.radius, bob, z * reach * self.radius)
+    }
+
+    // Brightness of firefly i at `time`: mostly dark, with a slow glow up and back down
+    fn blink(&self, i: u32, time: f32) -> f32 {
+        let phase = jitter(i as i64 + 2000, 0);
+        let wave = narrow(((time as Real * FIREFLY_BLINK + phase) * TAU).sin());
+        wave.max(0.0).powi(2)
+    }
+
+    /// The critters at `time`: a butterfly is a body between two wings that beat up and down,
+    /// a firefly a tiny glowing block
+    pub fn critters(&self, time: f32, materials: &mut MaterialPalette) -> Vec<Cube> {
+        let mut cubes = Vec::new();
+        for i in 0..self.count {
+            let position = self.position(i, time);
+            match self.kind {
+                CritterKind::Butterfly => {
+                    let body = materials.intern("butterfly body", || Material::new(palette::EYE_BLACK));
+                    let wings = if i % 2 == 0 {
+                        materials.intern("butterfly orange", || Material::new(palette::BUTTERFLY_ORANGE))
+                    } else {
+                        materials.intern("butterfly white", || Material::new(palette::BUTTERFLY_WHITE))
+                    };
+                    let flap = (time as Real * BUTTERFLY_FLAPS * TAU + i as Real).sin();
+                    // Wings up close together, wings down spread out to the sides
+                    let spread = BUTTERFLY_WING * (0.6 + 0.4 * (1.0 - flap.abs()));
+                    let lift = Vec3::new(0.0, flap * BUTTERFLY_WING * 0.5, 0.0);
+                    cubes.push(Cube::new(position, BUTTERFLY_BODY, body));
+                    cubes.push(Cube::new(position + Vec3::new(spread, 0.0, 0.0) + lift, BUTTERFLY_WING, wings));
+                    cubes.push(Cube::new(position - Vec3::new(spread, 0.0, 0.0) + lift, BUTTERFLY_WING, wings));
+                }
+                CritterKind::Firefly => {
+                    let level = fade_step(0.2 + 0.8 * self.blink(i, time));
+                    let glow = materials.intern(&format!("firefly {}", level), || {
+                        Material::new(palette::FIREFLY_GLOW).with_emissive(palette::FIREFLY_GLOW * level)
+                    });
+                    cubes.push(Cube::new(position, FIREFLY_SIZE, glow));
+                }
+            }
+        }
+        cubes
+    }
+
+    /// Point lights of the glowing critters at `time` (none for butterflies); a firefly between
+    /// blinks gives off nothing
+    pub fn lights(&self, time: f32) -> Vec<PointLight> {
+        if self.kind != CritterKind::Firefly {
+            return Vec::new();
+        }
+        (0..self.count)
+            .filter_map(|i| {
+                let blink = self.blink(i, time);
+                (blink > 0.05).then(|| {
+                    PointLight::new(self.position(i, time), palette::FIREFLY_GLOW, lumens(FIREFLY_LUMENS) * blink, FIREFLY_REACH)
+                        .with_source_radius(FIREFLY_SIZE / 2.0)
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,95 @@
+use std::path::{Path, PathBuf};
+
+use raylib::prelude::*;
+
+use crate::bands::{self, Preview};
+use crate::camera::Camera;
+use crate::presentation::Viewport;
+use crate::renderer::{OFFLINE_LENS_SAMPLES, RenderSettings};
+use crate::scene::Scene;
+use crate::screenshot;
+
+pub const SCALES: [i32; 5] = [1, 2, 3, 4, 6]; // Capture size multipliers the resolution key cycles through (6x a 720p render is 8K)
+pub const NUDGE_SPEED: f32 = 0.2; // Camera keys move at this share of their usual speed
+const PATH_SAMPLES: u32 = 64; // Paths per pixel for a path traced still
+const REVIEW_SECONDS: f32 = 3.0; // The finished still stays on screen this long, like a camera's review
+const GUIDE_COLOR: Color = Color::new(255, 255, 255, 110);
+
+/// Photo mode: the scene clock stops, composition guides go over the frame, the camera keys
+/// move in small steps, and the screenshot key traces a still at a multiple of the render size
+pub struct PhotoMode {
+    pub active: bool,
+    pub scale: i32, // Capture size as a multiple of the render size
+    review: Option<(Preview, f32)>, // Last capture scaled down to the window, and seconds left to show it
+}
+
+impl PhotoMode {
+    pub fn new() -> Self {
+        Self { active: false, scale: 2, review: None }
+    }
+
+    pub fn next_scale(&mut self) {
+        let next = SCALES.iter().position(|&s| s > self.scale).unwrap_or(0);
+        self.scale = SCALES[next];
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        if let Some((_, ref mut left)) = self.review {
+            *left -= delta_time;
+            if *left <= 0.0 {
+                self.review = None;
+            }
+        }
+    }
+
+    /// The last capture, downscaled to the render size, while it's still up for review
+    pub fn review(&self) -> Option<&Preview> {
+        self.review.as_ref().map(|(preview, _)| preview)
+    }
+
+    /// Rule-of-thirds grid and a center cross over the frame
+    pub fn draw_guides(&self, d: &mut RaylibDrawHandle, viewport: Viewport) {
+        let (right, bottom) = (viewport.x + viewport.width, viewport.y + viewport.height);
+        for third in 1..3 {
+            let x = viewport.x + viewport.width * third / 3;
+            let y = viewport.y + viewport.height * third / 3;
+            d.draw_line(x, viewport.y, x, bottom, GUIDE_COLOR);
+            d.draw_line(viewport.x, y, right, y, GUIDE_COLOR);
+        }
+        let (cx, cy) = (viewport.x + viewport.width / 2, viewport.y + viewport.height / 2);
+        d.draw_line(cx - 12, cy, cx + 12, cy, GUIDE_COLOR);
+        d.draw_line(cx, cy - 12, cx, cy + 12, GUIDE_COLOR);
+    }
+
+    /// Trace the still at `scale` times the render size (a progress bar runs in the console)
+    /// and save it into screenshots/. The interactive settings keep the look (tone map, bloom,
+    /// path tracing, denoiser); resolution and sample counts go up to export quality. The still is
+    /// traced in bands (bands.rs) so even 8K fits in memory, and comes back downscaled for review.
+    pub fn capture(&mut self, scene: &Scene, camera: &Camera, settings: &RenderSettings, width: i32, height: i32) -> Result<PathBuf, String> {
+        let (width, height) = (width * self.scale, height * self.scale);
+        let settings = RenderSettings {
+            render_scale: 1,
+            use_threading: true,
+            frame_budget: 0.0, // Take as long as it needs
+            ray_budget: 0,
+            motion_blur_samples: 0, // The clock is stopped, nothing moves
+            shadow_samples: settings.shadow_samples.max(16),
+            ao_samples: settings.ao_samples.max(16),
+            path_samples: if settings.path_tracing { PATH_SAMPLES } else { 1 },
+            lens_samples: OFFLINE_LENS_SAMPLES,
+            jitter: false,
+            ..*settings
+        };
+
+        let path = Path::new(screenshot::SCREENSHOT_DIR).join(format!("photo_{}.png", screenshot::timestamp()));
+        let preview = bands::render_to_png(scene, camera, &settings, width, height, "Photo", &path, (width / self.scale, height / self.scale), None)?;
+        self.review = Some((preview, REVIEW_SECONDS));
+        Ok(path)
+    }
+}
+
+impl Default for PhotoMode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
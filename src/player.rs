@@ -0,0 +1,91 @@
+use crate::color::Color;
+use crate::cube::Cube;
+use crate::material::Material;
+use crate::material_palette::{MaterialId, MaterialPalette};
+use crate::utils::{Real, Vec3};
+
+// The model is built from small cubes (cubes can't be rotated or stretched),
+// so every body part is a stack of voxels of this size
+const VOXEL: Real = 0.225;
+
+/// Blocky Minecraft-style character shown in third-person mode
+pub struct Player {
+    pub feet: Vec3, // Bottom-center of the character, standing on the ground
+    pub yaw: Real,  // Facing direction around the Y axis (radians, 0 = +X)
+    skin: Material,
+    shirt: Material,
+    pants: Material,
+    eyes: Material,
+}
+
+impl Player {
+    pub fn new(feet: Vec3) -> Self {
+        Self {
+            feet,
+            yaw: 0.0,
+            skin: Material::new(Color::new(0.85, 0.65, 0.5)).with_specular(0.1, 16.0),
+            shirt: Material::new(Color::new(0.0, 0.6, 0.65)),
+            pants: Material::new(Color::new(0.25, 0.25, 0.6)),
+            eyes: Material::new(Color::new(0.2, 0.15, 0.5)),
+        }
+    }
+
+    /// Build the cubes of the model for the current position and facing, its materials taken
+    /// from (and added once to) the scene's palette.
+    /// Facing is snapped to the nearest axis because cubes are axis-aligned.
+    pub fn build_cubes(&self, materials: &mut MaterialPalette) -> Vec<Cube> {
+        let skin = materials.intern("player skin", || self.skin.clone());
+        let shirt = materials.intern("player shirt", || self.shirt.clone());
+        let pants = materials.intern("player pants", || self.pants.clone());
+        let eyes = materials.intern("player eyes", || self.eyes.clone());
+
+        let (forward, right) = if self.yaw.cos().abs() >= self.yaw.sin().abs() {
+            let sign = self.yaw.cos().signum();
+            (Vec3::new(sign, 0.0, 0.0), Vec3::new(0.0, 0.0, sign))
+        } else {
+            let sign = self.yaw.sin().signum();
+            (Vec3::new(0.0, 0.0, sign), Vec3::new(-sign, 0.0, 0.0))
+        };
+
+        let mut cubes = Vec::new();
+        // Place a voxel by (right, up, forward) grid offsets from the feet
+        let mut place = |side: Real, up: Real, front: Real, size: Real, material: MaterialId| {
+            let position = self.feet
+                + right * (side * VOXEL)
+                + Vec3::new(0.0, (up + 0.5) * VOXEL, 0.0)
+                + forward * (front * VOXEL);
+            cubes.push(Cube::new(position, size, material));
+        };
+        let mut voxel = |side: Real, up: Real, front: Real, material: MaterialId| place(side, up, front, VOXEL, material);
+
+        // Legs (3 voxels tall, one column per leg)
+        for up in 0..3 {
+            voxel(-0.5, up as Real, 0.0, pants);
+            voxel(0.5, up as Real, 0.0, pants);
+        }
+
+        // Body (2 wide, 3 tall) and arms on either side
+        for up in 3..6 {
+            voxel(-0.5, up as Real, 0.0, shirt);
+            voxel(0.5, up as Real, 0.0, shirt);
+            let arm = if up == 3 { skin } else { shirt };
+            voxel(-1.5, up as Real, 0.0, arm);
+            voxel(1.5, up as Real, 0.0, arm);
+        }
+
+        // Head (2x2x2)
+        for up in 6..8 {
+            for side in [-0.5, 0.5] {
+                for front in [-0.5, 0.5] {
+                    voxel(side, up as Real, front, skin);
+                }
+            }
+        }
+
+        // Eyes: small cubes poking out of the face
+        place(-0.5, 6.6, 1.0, VOXEL * 0.4, eyes);
+        place(0.5, 6.6, 1.0, VOXEL * 0.4, eyes);
+
+        cubes
+    }
+}
@@ -1,24 +1,79 @@
 use crate::color::Color;
-use crate::utils::Vec3;
+use crate::utils::{Real, TAU, Vec3, narrow, random_f32};
+use serde::{Deserialize, Serialize};
+
+// Inverse-square is clamped this close to the light so a hit right at the bulb doesn't blow up
+const MIN_FALLOFF_DISTANCE: Real = 0.5;
+
+/// How a light dims with distance, up to its `radius` where it's cut off
+#[derive(Clone, Debug, PartialEq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Falloff {
+    #[default]
+    Smooth,        // 1 / (1 + d^2 / 2): soft quadratic, bright enough to light a room without blowing out
+    InverseSquare, // 1 / d^2: physically correct, needs much higher intensities
+    Linear,        // Minecraft-style: full at the light, the same step less per block, zero at `radius`
+    Curve(Vec<f32>), // Brightness at evenly spaced distances from the light (first) to `radius` (last)
+}
+
+impl Falloff {
+    /// Share of the light's intensity left `distance` away (0 beyond `radius`)
+    pub fn attenuation(&self, distance: Real, radius: Real) -> f32 {
+        if distance > radius {
+            return 0.0;
+        }
+        match self {
+            Falloff::Smooth => 1.0 / (1.0 + narrow(distance * distance) * 0.5),
+            Falloff::InverseSquare => 1.0 / narrow(distance.max(MIN_FALLOFF_DISTANCE).powi(2)),
+            Falloff::Linear => narrow(1.0 - distance / radius.max(1e-4)),
+            Falloff::Curve(points) => {
+                let Some(&last) = points.last() else {
+                    return 0.0;
+                };
+                let position = narrow(distance / radius.max(1e-4)) * (points.len() - 1) as f32;
+                let index = position as usize;
+                if index + 1 >= points.len() {
+                    return last.max(0.0);
+                }
+                let t = position - index as f32;
+                (points[index] * (1.0 - t) + points[index + 1] * t).max(0.0)
+            }
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct PointLight {
     pub position: Vec3,
     pub color: Color,
     pub intensity: f32,
-    pub radius: f32, // Maximum distance the light can reach
+    pub radius: Real, // Maximum distance the light can reach
+    pub source_radius: Real, // Size of the glowing sphere the light comes from (0 = a point, hard shadows)
+    pub falloff: Falloff,
 }
 
 impl PointLight {
-    pub fn new(position: Vec3, color: Color, intensity: f32, radius: f32) -> Self {
+    pub fn new(position: Vec3, color: Color, intensity: f32, radius: Real) -> Self {
         Self {
             position,
             color,
             intensity,
             radius,
+            source_radius: 0.0,
+            falloff: Falloff::Smooth,
         }
     }
 
+    pub fn with_falloff(mut self, falloff: Falloff) -> Self {
+        self.falloff = falloff;
+        self
+    }
+
+    pub fn with_source_radius(mut self, source_radius: Real) -> Self {
+        self.source_radius = source_radius.max(0.0);
+        self
+    }
+
     /// Calculate the light contribution at a given point
     /// Returns (light_direction, light_color_with_attenuation)
     pub fn illuminate(&self, point: &Vec3) -> (Vec3, Color) {
@@ -31,13 +86,28 @@ impl PointLight {
         }
 
         let light_dir = light_vec.normalize();
-
-        // Quadratic attenuation: 1 / (1 + d^2)
-        // This makes light fall off realistically with distance
-        let attenuation = 1.0 / (1.0 + distance * distance * 0.5);
+        let attenuation = self.falloff.attenuation(distance, self.radius);
 
         let attenuated_color = self.color * (self.intensity * attenuation);
 
         (light_dir, attenuated_color)
     }
 }
+
+/// Random point on the half of a light's sphere that faces `point`, for jittered soft shadow rays
+/// (the center itself when the light has no size)
+pub fn sample_sphere(center: Vec3, source_radius: Real, point: Vec3) -> Vec3 {
+    if source_radius <= 0.0 {
+        return center;
+    }
+    // Uniform direction on the sphere, flipped onto the near side
+    let height = 1.0 - 2.0 * random_f32() as Real;
+    let ring = (1.0 - height * height).max(0.0).sqrt();
+    let angle = TAU * random_f32() as Real;
+    let mut offset = Vec3::new(ring * angle.cos(), height, ring * angle.sin());
+    let toward_point = point - center;
+    if offset.dot(&toward_point) < 0.0 {
+        offset = -offset;
+    }
+    center + offset * source_radius
+}
@@ -0,0 +1,104 @@
+use crate::color::Color;
+
+const KNEE: f32 = 0.5; // Pixels start glowing this far below the threshold, fading in instead of switching on
+const RADIUS: f32 = 0.025; // Blur spread (Gaussian sigma) as a share of the image height
+
+/// Bloom over a float image (linear, unclamped, before the tone map): whatever is brighter than
+/// `threshold` (white = 1.0) is blurred and added back on top at `strength`, so the sun, the moon,
+/// lamps and glowing blocks bleed a soft halo into their surroundings. The blur runs at half
+/// resolution, a wide glow doesn't need more and it's 8x cheaper. `image_height` sets the glow's
+/// size, it's `height` unless the colors are one band of a taller image.
+pub fn bloom(colors: &mut [Color], width: i32, height: i32, image_height: i32, threshold: f32, strength: f32) {
+    if strength <= 0.0 || width < 2 || height < 2 {
+        return;
+    }
+
+    // Threshold extract, averaged over 2x2 blocks
+    let half_width = width / 2;
+    let half_height = height / 2;
+    let mut bright = Vec::with_capacity((half_width * half_height) as usize);
+    for y in 0..half_height {
+        for x in 0..half_width {
+            let mut sum = Color::black();
+            for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                sum = sum + extract(colors[((2 * y + dy) * width + 2 * x + dx) as usize], threshold);
+            }
+            bright.push(sum * 0.25);
+        }
+    }
+
+    let kernel = gaussian(RADIUS * (image_height / 2) as f32);
+    let bright = blur(&bright, half_width, half_height, &kernel, true);
+    let glow = blur(&bright, half_width, half_height, &kernel, false);
+
+    // Additive composite, the half resolution glow scaled back up bilinearly
+    for y in 0..height {
+        for x in 0..width {
+            let index = (y * width + x) as usize;
+            let halo = sample(&glow, half_width, half_height, (x as f32 + 0.5) * 0.5 - 0.5, (y as f32 + 0.5) * 0.5 - 0.5);
+            colors[index] = colors[index] + halo * strength;
+        }
+    }
+}
+
+/// Rows a bright pixel's glow reaches in an image `image_height` rows tall (3 sigma, at full resolution)
+pub fn reach(image_height: i32) -> i32 {
+    (RADIUS * image_height as f32 * 3.0).ceil() as i32 + 2
+}
+
+// The part of a pixel over the threshold, with a quadratic knee so the edge of the glow is smooth
+fn extract(color: Color, threshold: f32) -> Color {
+    let brightness = color.r.max(color.g).max(color.b);
+    if brightness <= 0.0 {
+        return Color::black();
+    }
+    let soft = (brightness - threshold + KNEE).clamp(0.0, 2.0 * KNEE);
+    let soft = soft * soft / (4.0 * KNEE);
+    let over = (brightness - threshold).max(soft);
+    color * (over / brightness)
+}
+
+// Normalized weights from the center tap outwards, 3 sigma wide
+fn gaussian(sigma: f32) -> Vec<f32> {
+    let sigma = sigma.max(0.5);
+    let weights: Vec<f32> = (0..=(sigma * 3.0).ceil() as i32)
+        .map(|i| (-(i * i) as f32 / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let total = weights[0] + 2.0 * weights[1..].iter().sum::<f32>();
+    weights.iter().map(|w| w / total).collect()
+}
+
+// One axis of the separable blur, edge pixels repeated past the border
+fn blur(image: &[Color], width: i32, height: i32, kernel: &[f32], horizontal: bool) -> Vec<Color> {
+    let mut out = Vec::with_capacity(image.len());
+    for y in 0..height {
+        for x in 0..width {
+            let at = |offset: i32| {
+                let (nx, ny) = if horizontal {
+                    ((x + offset).clamp(0, width - 1), y)
+                } else {
+                    (x, (y + offset).clamp(0, height - 1))
+                };
+                image[(ny * width + nx) as usize]
+            };
+            let mut sum = at(0) * kernel[0];
+            for (i, &weight) in kernel.iter().enumerate().skip(1) {
+                sum = sum + (at(i as i32) + at(-(i as i32))) * weight;
+            }
+            out.push(sum);
+        }
+    }
+    out
+}
+
+fn sample(image: &[Color], width: i32, height: i32, x: f32, y: f32) -> Color {
+    let x = x.clamp(0.0, (width - 1) as f32);
+    let y = y.clamp(0.0, (height - 1) as f32);
+    let (x0, y0) = (x as i32, y as i32);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+    let texel = |tx: i32, ty: i32| image[(ty * width + tx) as usize];
+    let top = texel(x0, y0) * (1.0 - fx) + texel(x1, y0) * fx;
+    let bottom = texel(x0, y1) * (1.0 - fx) + texel(x1, y1) * fx;
+    top * (1.0 - fy) + bottom * fy
+}
@@ -0,0 +1,96 @@
+use raylib::prelude::*;
+
+use crate::utils::{Degrees, Radians, Real, narrow};
+
+/// How the traced frame is fitted into a resizable window
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PresentMode {
+    /// Keep the fixed render size and aspect, scaled to fit with black bars
+    /// (letterbox above/below, pillarbox left/right) so nothing stretches
+    Letterbox,
+    /// Render at the window's own size; the camera aspect follows the window and the
+    /// vertical field of view stays the same, so a wider window shows more to the sides
+    MatchWindow,
+}
+
+/// Screen rectangle the frame is drawn into
+#[derive(Clone, Copy)]
+pub struct Viewport {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// Maps the traced frame onto the window (F4 switches modes)
+pub struct Presentation {
+    pub mode: PresentMode,
+    width: i32, // Render size in letterbox mode
+    height: i32,
+}
+
+impl Presentation {
+    pub fn new(width: i32, height: i32) -> Self {
+        Self {
+            mode: PresentMode::Letterbox,
+            width,
+            height,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.mode = match self.mode {
+            PresentMode::Letterbox => PresentMode::MatchWindow,
+            PresentMode::MatchWindow => PresentMode::Letterbox,
+        };
+    }
+
+    /// Size of the traced frame for a window of this size
+    pub fn render_size(&self, window_width: i32, window_height: i32) -> (i32, i32) {
+        match self.mode {
+            PresentMode::Letterbox => (self.width, self.height),
+            PresentMode::MatchWindow => (window_width.max(1), window_height.max(1)),
+        }
+    }
+
+    /// Largest rectangle with the render aspect that fits the window, centered
+    pub fn viewport(&self, window_width: i32, window_height: i32) -> Viewport {
+        let (width, height) = self.render_size(window_width, window_height);
+        let scale = (window_width as f32 / width as f32).min(window_height as f32 / height as f32);
+        let (fit_width, fit_height) = ((width as f32 * scale).round() as i32, (height as f32 * scale).round() as i32);
+        Viewport {
+            x: (window_width - fit_width) / 2,
+            y: (window_height - fit_height) / 2,
+            width: fit_width,
+            height: fit_height,
+        }
+    }
+
+    /// Draw an RGBA frame buffer (width x height) scaled into the viewport
+    pub fn draw_buffer(d: &mut RaylibDrawHandle, buffer: &[Color], width: i32, height: i32, viewport: Viewport, opacity: f32) {
+        let alpha = (opacity.clamp(0.0, 1.0) * 255.0) as u8;
+        let same_size = viewport.width == width && viewport.height == height;
+        for y in 0..height {
+            // Pixel edges rounded to the screen so neighbours tile without gaps
+            let top = viewport.y + y * viewport.height / height;
+            let bottom = viewport.y + (y + 1) * viewport.height / height;
+            for x in 0..width {
+                let color = Color { a: alpha, ..buffer[(y * width + x) as usize] };
+                if same_size {
+                    d.draw_pixel(viewport.x + x, top, color);
+                    continue;
+                }
+                let left = viewport.x + x * viewport.width / width;
+                let right = viewport.x + (x + 1) * viewport.width / width;
+                d.draw_rectangle(left, top, right - left, bottom - top, color);
+            }
+        }
+    }
+
+    /// Vertical FOV for the raster preview, which projects over the whole window height:
+    /// widened so the part inside the viewport matches the traced frame
+    pub fn preview_fov(fov: Degrees, viewport: Viewport, window_height: i32) -> Degrees {
+        let half = narrow((fov * 0.5).radians().tan()) * window_height as f32 / viewport.height.max(1) as f32;
+        Radians(half.atan() as Real * 2.0).degrees()
+    }
+}
@@ -0,0 +1,233 @@
+use raylib::prelude::*;
+
+use crate::camera::Camera;
+use crate::color;
+use crate::cube::Cube;
+use crate::material::Material;
+use crate::material_palette::MaterialPalette;
+use crate::presentation::{Presentation, Viewport};
+use crate::scene::Scene;
+use crate::utils::{Real, Vec3, narrow};
+
+// Cube faces as (normal, u axis, v axis) with u x v = normal, so corners go counter-clockwise seen from outside
+const FACES: [(Vec3, Vec3, Vec3); 6] = [
+    (Vec3 { x: 1.0, y: 0.0, z: 0.0 }, Vec3 { x: 0.0, y: 1.0, z: 0.0 }, Vec3 { x: 0.0, y: 0.0, z: 1.0 }),
+    (Vec3 { x: -1.0, y: 0.0, z: 0.0 }, Vec3 { x: 0.0, y: 0.0, z: 1.0 }, Vec3 { x: 0.0, y: 1.0, z: 0.0 }),
+    (Vec3 { x: 0.0, y: 1.0, z: 0.0 }, Vec3 { x: 0.0, y: 0.0, z: 1.0 }, Vec3 { x: 1.0, y: 0.0, z: 0.0 }),
+    (Vec3 { x: 0.0, y: -1.0, z: 0.0 }, Vec3 { x: 1.0, y: 0.0, z: 0.0 }, Vec3 { x: 0.0, y: 0.0, z: 1.0 }),
+    (Vec3 { x: 0.0, y: 0.0, z: 1.0 }, Vec3 { x: 1.0, y: 0.0, z: 0.0 }, Vec3 { x: 0.0, y: 1.0, z: 0.0 }),
+    (Vec3 { x: 0.0, y: 0.0, z: -1.0 }, Vec3 { x: 0.0, y: 1.0, z: 0.0 }, Vec3 { x: 1.0, y: 0.0, z: 0.0 }),
+];
+
+/// Flat color of a material for the preview: texture average, alpha from transparency
+#[derive(Clone, Copy)]
+struct PreviewColor {
+    color: color::Color,
+    alpha: f32,
+}
+
+impl PreviewColor {
+    fn from_material(material: &Material) -> Self {
+        let color = if material.emissive.r > 0.0 || material.emissive.g > 0.0 || material.emissive.b > 0.0 {
+            material.emissive
+        } else {
+            material.average_color()
+        };
+        Self {
+            color,
+            alpha: 1.0 - material.transparency * 0.7,
+        }
+    }
+}
+
+/// Fast rasterized preview of the scene (raylib 3D) used while the camera moves
+pub struct RasterPreview {
+    cube_colors: Vec<[PreviewColor; 3]>, // (top, sides, bottom) per scene cube, built on first use
+    colors_generation: Option<u64>, // VoxelGrid::generation the colors were built at
+}
+
+impl RasterPreview {
+    pub fn new() -> Self {
+        Self {
+            cube_colors: Vec::new(),
+            colors_generation: None,
+        }
+    }
+
+    /// Forget cached colors (after the scene is rebuilt)
+    pub fn invalidate(&mut self) {
+        self.cube_colors.clear();
+        self.colors_generation = None;
+    }
+
+    fn cube_colors(cube: &Cube, materials: &MaterialPalette) -> [PreviewColor; 3] {
+        let (top, sides, bottom) = cube.face_materials(materials);
+        [
+            PreviewColor::from_material(top),
+            PreviewColor::from_material(sides),
+            PreviewColor::from_material(bottom),
+        ]
+    }
+
+    /// Rasterize into the viewport (clipped, with the FOV widened to match when it's letterboxed)
+    pub fn draw(&mut self, d: &mut RaylibDrawHandle, scene: &Scene, camera: &Camera, viewport: Viewport, window_height: i32) {
+        // Averaging textures is slow, so it happens once per scene instead of every frame
+        if self.colors_generation != Some(scene.grid.generation()) {
+            self.cube_colors = scene.grid.cubes().map(|cube| Self::cube_colors(cube, &scene.materials)).collect();
+            self.colors_generation = Some(scene.grid.generation());
+        }
+
+        // Sky fades from day blue to night
+        let day_time = scene.time.day_fraction;
+        let sky = color::Color::new(0.53, 0.81, 0.92) * (1.0 - day_time) + color::Color::new(0.02, 0.02, 0.08) * day_time;
+        let mut d = d.begin_scissor_mode(viewport.x, viewport.y, viewport.width, viewport.height);
+        d.clear_background(sky.to_raylib());
+
+        let lighting = Lighting::new(scene);
+        let camera_3d = Camera3D::perspective(
+            to_vector3(camera.position),
+            to_vector3(camera.target),
+            to_vector3(camera.up()),
+            Presentation::preview_fov(camera.fov, viewport, window_height).0,
+        );
+
+        let mut d3 = d.begin_mode3D(camera_3d);
+
+        // Opaque geometry first, translucent (glass, water) last so it blends over what is behind it
+        let moving = || scene.entities.iter().chain(&scene.particles);
+        let entity_colors: Vec<[PreviewColor; 3]> = moving().map(|cube| Self::cube_colors(cube, &scene.materials)).collect();
+        let cubes = scene.grid.cubes().zip(&self.cube_colors).chain(moving().zip(&entity_colors));
+        let (opaque, translucent): (Vec<_>, Vec<_>) = cubes.partition(|(_, colors)| colors[1].alpha >= 1.0);
+
+        for (cube, colors) in opaque.into_iter().chain(translucent) {
+            draw_cube(&mut d3, cube, colors, &lighting);
+        }
+
+        for mesh in &scene.meshes {
+            let base = PreviewColor::from_material(&mesh.material);
+            for triangle in &mesh.triangles {
+                // OBJ triangles are hit from both sides by the raytracer, so draw both windings
+                let shade = lighting.shade(triangle.normal).max(lighting.shade(-triangle.normal));
+                let color = shaded(base, shade);
+                let (v0, v1, v2) = (
+                    to_vector3(triangle.v0 + mesh.position),
+                    to_vector3(triangle.v1 + mesh.position),
+                    to_vector3(triangle.v2 + mesh.position),
+                );
+                d3.draw_triangle3D(v0, v1, v2, color);
+                d3.draw_triangle3D(v0, v2, v1, color);
+            }
+        }
+    }
+}
+
+impl Default for RasterPreview {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Simple per-face lighting matching the raytracer's ambient + sun terms (no shadows)
+struct Lighting {
+    ambient: f32,
+    sun_dir: Vec3,
+    sun_intensity: f32,
+}
+
+impl Lighting {
+    fn new(scene: &Scene) -> Self {
+        let ambient = scene.ambient();
+        Self {
+            ambient: (ambient.r + ambient.g + ambient.b) / 3.0,
+            sun_dir: -scene.key_light().0.direction,
+            sun_intensity: scene.key_light().1,
+        }
+    }
+
+    fn shade(&self, normal: Vec3) -> f32 {
+        self.ambient + narrow(normal.dot(&self.sun_dir)).max(0.0) * self.sun_intensity
+    }
+}
+
+fn draw_cube(d3: &mut impl RaylibDraw3D, cube: &Cube, colors: &[PreviewColor; 3], lighting: &Lighting) {
+    let half = cube.size / 2.0;
+    for (normal, u, v) in FACES {
+        let base = if normal.y > 0.5 {
+            colors[0]
+        } else if normal.y < -0.5 {
+            colors[2]
+        } else {
+            colors[1]
+        };
+        let color = shaded(base, lighting.shade(normal));
+
+        let center = cube.position + normal * half;
+        let corner = |su: Real, sv: Real| to_vector3(center + u * (su * half) + v * (sv * half));
+        let (a, b, c, e) = (corner(-1.0, -1.0), corner(1.0, -1.0), corner(1.0, 1.0), corner(-1.0, 1.0));
+        d3.draw_triangle3D(a, b, c, color);
+        d3.draw_triangle3D(a, c, e, color);
+    }
+}
+
+fn shaded(base: PreviewColor, shade: f32) -> Color {
+    let lit = (base.color * shade).to_srgb().to_raylib();
+    Color::new(lit.r, lit.g, lit.b, (base.alpha * 255.0) as u8)
+}
+
+fn to_vector3(v: Vec3) -> Vector3 {
+    Vector3::new(narrow(v.x), narrow(v.y), narrow(v.z))
+}
+
+/// Decides between the raster preview and the raytracer from camera motion.
+/// The camera must stay still for SETTLE_TIME before tracing resumes, then the traced
+/// frame fades in over BLEND_TIME so the switch doesn't pop.
+pub struct HybridSwitch {
+    settle_timer: f32, // Seconds of stillness left before tracing resumes
+    traced_opacity: f32, // 0 = raster only, 1 = traced only
+}
+
+impl HybridSwitch {
+    const SETTLE_TIME: f32 = 0.25;
+    const BLEND_TIME: f32 = 0.3;
+    const MOVE_THRESHOLD: f32 = 0.05; // Camera speed (units/second) that counts as moving
+
+    pub fn new() -> Self {
+        Self {
+            settle_timer: 0.0,
+            traced_opacity: 1.0,
+        }
+    }
+
+    /// Feed this frame's camera speed (position and target movement since the shutter opened)
+    pub fn update(&mut self, camera: &Camera, delta_time: f32) {
+        let speed = camera.shutter_speed(delta_time);
+        if speed > Self::MOVE_THRESHOLD {
+            self.settle_timer = Self::SETTLE_TIME;
+            self.traced_opacity = 0.0;
+        } else if self.settle_timer > 0.0 {
+            self.settle_timer -= delta_time;
+        } else {
+            self.traced_opacity = (self.traced_opacity + delta_time / Self::BLEND_TIME).min(1.0);
+        }
+    }
+
+    /// Raytrace this frame? (false while moving or settling)
+    pub fn should_trace(&self) -> bool {
+        self.settle_timer <= 0.0
+    }
+
+    /// Draw the raster preview this frame? (while moving and during the fade-in)
+    pub fn should_rasterize(&self) -> bool {
+        self.traced_opacity < 1.0
+    }
+
+    pub fn traced_opacity(&self) -> f32 {
+        self.traced_opacity
+    }
+}
+
+impl Default for HybridSwitch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,315 @@
+use std::path::Path;
+
+use crate::color::Color;
+use crate::texture::Texture;
+use crate::utils::{Real, Vec3};
+
+// Minecraft-style resolution: textures are sampled nearest-neighbor, so 16x16 reads as pixel art
+const SIZE: usize = 16;
+
+const UV_GRID_SIZE: usize = 128; // The debug grid is finer so its numbers stay legible
+const UV_GRID_CELLS: usize = 4;
+const DIGIT_SCALE: usize = 3; // Font pixels per glyph pixel
+
+// 3x5 pixel digits, one row per entry (top first), high bit on the left
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+/// Frames in the procedural water/lava strips (scrolling one texel per frame loops after SIZE frames)
+pub const STRIP_FRAMES: usize = SIZE;
+
+/// Procedural stand-in for a missing block texture, chosen from the file name
+/// (e.g. "assets/textures/grass_side.jpg" -> grass side). None for unknown names.
+pub fn fallback_for(path: &str) -> Option<Texture> {
+    let stem = Path::new(path).file_stem()?.to_str()?.to_lowercase();
+
+    let texture = match stem.as_str() {
+        "grass" => generate(grass_top),
+        "grass_side" => generate(grass_side),
+        "dirt" => generate(dirt),
+        "stone" => generate(stone),
+        "wood" => generate(|x, y| planks(x, y, Color::new(0.62, 0.45, 0.27))),
+        "cherry_wood" => generate(|x, y| planks(x, y, Color::new(0.86, 0.62, 0.58))),
+        "cherry_log" => generate(bark),
+        "cherry_leaves" => with_alpha(generate(leaves), |x, y| noise(x, y, 10) <= 0.85),
+        "water" => generate(water),
+        // Animated strips (STRIP_FRAMES frames of 16x16 stacked vertically, see TextureAnimation)
+        "water_still" => generate_strip(water_frame),
+        "lava_still" => generate_strip(lava_frame),
+        "ice" => generate(ice),
+        "snow" => generate(snow),
+        "sand" => generate(sand),
+        "gravel" => generate(gravel),
+        "glass" => with_alpha(generate(glass), |x, y| glass_frame(x, y) || glass_streak(x, y)),
+        "torch" | "emissive_lantern" => generate(glow),
+        // Skybox faces: plain gradients are enough to avoid a checkered sky
+        "side" => generate_sky(|t| Color::new(0.8, 0.9, 1.0) * (1.0 - t) + Color::new(0.4, 0.6, 0.95) * t),
+        "top" => generate_sky(|_| Color::new(0.4, 0.6, 0.95)),
+        "bottom" => generate_sky(|_| Color::new(0.8, 0.9, 1.0)),
+        "side_night" => generate_sky(|t| Color::new(0.1, 0.1, 0.2) * (1.0 - t) + Color::new(0.02, 0.02, 0.1) * t),
+        "top_night" | "bottom_night" => generate_sky(|_| Color::new(0.02, 0.02, 0.1)),
+        "moon" => with_alpha(generate(moon), moon_disk),
+        // Normal maps: bumps from the matching block's brightness, otherwise flat
+        name if name.ends_with("_normal") => match fallback_for(&name.replace("_normal", "")) {
+            Some(base) => normal_map_from_height(&base, 2.0),
+            None => Texture::from_color(Color::new(0.5, 0.5, 1.0)),
+        },
+        _ => return None,
+    };
+    Some(texture)
+}
+
+/// Tangent-space normal map (green = up) from a texture's brightness used as a height field:
+/// dark cracks and grooves sink in, bright spots stand out. Wraps around so tiling stays seamless.
+pub fn normal_map_from_height(texture: &Texture, bumpiness: f32) -> Texture {
+    let (width, height) = (texture.width, texture.height);
+    let height_at = |x: usize, y: usize| {
+        texture.data[(y % height) * width + (x % width)].luminance()
+    };
+
+    let mut normal_map = Texture::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let dx = height_at(x + 1, y) - height_at(x + width - 1, y);
+            let dy = height_at(x, y + 1) - height_at(x, y + height - 1); // Rows grow downwards
+            let normal = Vec3::new((-dx * bumpiness) as Real, (dy * bumpiness) as Real, 1.0).normalize();
+            normal_map.data[y * width + x] = Color::from_vec3(normal * 0.5 + Vec3::new(0.5, 0.5, 0.5));
+        }
+    }
+    normal_map
+}
+
+/// Debug texture for checking UV orientation: a 4x4 grid of cells numbered 0-15 from the top left
+/// (u = 0, v = 0) across and then down, redder along u and greener along v. The numbers read
+/// upright on a face whose uvs are the right way round; mirrored or sideways digits show a flip.
+pub fn uv_grid() -> Texture {
+    let cell = UV_GRID_SIZE / UV_GRID_CELLS;
+    let mut texture = Texture::new(UV_GRID_SIZE, UV_GRID_SIZE);
+    for y in 0..UV_GRID_SIZE {
+        for x in 0..UV_GRID_SIZE {
+            let (column, row) = (x / cell, y / cell);
+            let number = row * UV_GRID_CELLS + column;
+            let (u, v) = ((x as f32 + 0.5) / UV_GRID_SIZE as f32, (y as f32 + 0.5) / UV_GRID_SIZE as f32);
+            let checker = if (column + row) % 2 == 0 { 1.0 } else { 0.8 };
+            texture.data[y * UV_GRID_SIZE + x] = if x % cell == 0 || y % cell == 0 {
+                Color::new(0.1, 0.1, 0.1) // Cell borders
+            } else if number_pixel(number, x % cell, y % cell, cell) {
+                Color::new(0.05, 0.05, 0.05)
+            } else {
+                Color::new(0.35 + 0.6 * u, 0.35 + 0.6 * v, 0.45) * checker
+            };
+        }
+    }
+    texture
+}
+
+// Whether pixel (x, y) of a cell falls on its number, drawn centered in the 3x5 font scaled up 3x
+fn number_pixel(number: usize, x: usize, y: usize, cell: usize) -> bool {
+    let digits: Vec<usize> = if number < 10 { vec![number] } else { vec![number / 10, number % 10] };
+    let width = digits.len() * 4 * DIGIT_SCALE - DIGIT_SCALE;
+    let (left, top) = ((cell - width) / 2, (cell - 5 * DIGIT_SCALE) / 2);
+    if x < left || y < top || y >= top + 5 * DIGIT_SCALE {
+        return false;
+    }
+    let (column, row) = ((x - left) / DIGIT_SCALE, (y - top) / DIGIT_SCALE);
+    let (digit, bit) = (column / 4, column % 4);
+    digit < digits.len() && bit < 3 && DIGITS[digits[digit]][row] & (0b100 >> bit) != 0
+}
+
+fn generate(pixel: impl Fn(usize, usize) -> Color) -> Texture {
+    let mut texture = Texture::new(SIZE, SIZE);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            texture.data[y * SIZE + x] = pixel(x, y);
+        }
+    }
+    texture
+}
+
+// Frames stacked top to bottom, pixel(x, y, frame)
+fn generate_strip(pixel: impl Fn(usize, usize, usize) -> Color) -> Texture {
+    let mut texture = Texture::new(SIZE, SIZE * STRIP_FRAMES);
+    for frame in 0..STRIP_FRAMES {
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                texture.data[(frame * SIZE + y) * SIZE + x] = pixel(x, y, frame);
+            }
+        }
+    }
+    texture
+}
+
+// Cut-out mask for materials with an alpha cutoff (false = hole)
+fn with_alpha(mut texture: Texture, opaque: impl Fn(usize, usize) -> bool) -> Texture {
+    texture.alpha = (0..SIZE * SIZE)
+        .map(|i| if opaque(i % SIZE, i / SIZE) { 1.0 } else { 0.0 })
+        .collect();
+    texture
+}
+
+// Sky faces are larger so the gradient doesn't band (t = 0 at the bottom row, 1 at the top)
+fn generate_sky(gradient: impl Fn(f32) -> Color) -> Texture {
+    let size = 64;
+    let mut texture = Texture::new(size, size);
+    for y in 0..size {
+        let color = gradient(1.0 - y as f32 / (size - 1) as f32);
+        texture.data[y * size..(y + 1) * size].fill(color);
+    }
+    texture
+}
+
+// Deterministic per-texel noise in [0, 1)
+fn noise(x: usize, y: usize, seed: u32) -> f32 {
+    let mut h = (x as u32).wrapping_mul(374_761_393)
+        ^ (y as u32).wrapping_mul(668_265_263)
+        ^ seed.wrapping_mul(2_246_822_519);
+    h = (h ^ (h >> 13)).wrapping_mul(1_274_126_177);
+    h ^= h >> 16;
+    (h & 0xFFFF) as f32 / 65536.0
+}
+
+// Noise averaged with its neighbors for softer blotches (tiles seamlessly)
+fn smooth_noise(x: usize, y: usize, seed: u32) -> f32 {
+    let at = |dx: usize, dy: usize| noise((x + dx) % SIZE, (y + dy) % SIZE, seed);
+    (at(0, 0) * 2.0 + at(1, 0) + at(SIZE - 1, 0) + at(0, 1) + at(0, SIZE - 1)) / 6.0
+}
+
+fn grass_top(x: usize, y: usize) -> Color {
+    Color::new(0.35, 0.65, 0.25) * (0.75 + 0.45 * noise(x, y, 1))
+}
+
+fn dirt(x: usize, y: usize) -> Color {
+    let speck = if noise(x, y, 3) > 0.9 { 0.7 } else { 1.0 }; // Small dark pebbles
+    Color::new(0.47, 0.33, 0.21) * ((0.75 + 0.4 * noise(x, y, 2)) * speck)
+}
+
+fn grass_side(x: usize, y: usize) -> Color {
+    // Green top rows hanging down unevenly into the dirt
+    let overhang = 3 + (noise(x, 0, 4) * 2.0) as usize;
+    if y < overhang {
+        grass_top(x, y)
+    } else {
+        dirt(x, y)
+    }
+}
+
+fn stone(x: usize, y: usize) -> Color {
+    let shade = 0.7 + 0.25 * smooth_noise(x, y, 5) + 0.1 * noise(x, y, 6);
+    Color::new(0.52, 0.52, 0.52) * shade
+}
+
+fn planks(x: usize, y: usize, base: Color) -> Color {
+    // Four horizontal boards with dark seams, staggered joints and grain along each board
+    let board = y / 4;
+    let seam = y % 4 == 3 || (x + board * 5).is_multiple_of(SIZE);
+    let grain = 0.85 + 0.15 * noise(x / 3, y, 7 + board as u32);
+    base * if seam { 0.6 } else { grain }
+}
+
+fn bark(x: usize, y: usize) -> Color {
+    // Vertical ridges: each column gets its own shade, broken up along its length
+    let ridge = 0.7 + 0.3 * noise(x, 0, 8);
+    let crack = if noise(x, y / 3, 9) > 0.8 { 0.65 } else { 1.0 };
+    Color::new(0.3, 0.18, 0.17) * (ridge * crack)
+}
+
+fn leaves(x: usize, y: usize) -> Color {
+    let n = noise(x, y, 10);
+    let shade = if n > 0.85 { 0.55 } else { 0.75 + 0.35 * n }; // Darker gaps between petals (holes when cut out)
+    Color::new(0.95, 0.62, 0.78) * shade
+}
+
+fn water(x: usize, y: usize) -> Color {
+    water_frame(x, y, 0)
+}
+
+fn water_frame(x: usize, y: usize, frame: usize) -> Color {
+    // Wobbly diagonal wave crests, drifting one full wave over the strip
+    let drift = frame as f32 / STRIP_FRAMES as f32 * std::f32::consts::TAU;
+    let phase = (x as f32 + 2.0 * (y as f32 * 0.8).sin()) * 0.8 - drift;
+    let shade = 0.85 + 0.12 * phase.sin() + 0.05 * noise(x, y, 11);
+    Color::new(0.22, 0.42, 0.82) * shade
+}
+
+fn lava_frame(x: usize, y: usize, frame: usize) -> Color {
+    // Molten glow with darker crust blobs, flowing diagonally one texel per frame (tiles and loops)
+    let heat = smooth_noise((x + frame) % SIZE, (y + frame) % SIZE, 13);
+    let glow = Color::new(1.0, 0.45 + 0.35 * heat, 0.08);
+    if heat < 0.3 { glow * 0.55 } else { glow }
+}
+
+fn ice(x: usize, y: usize) -> Color {
+    // Pale blue with a few white fracture lines and cloudy patches
+    let crack = (x * 3 + y * 2).is_multiple_of(23) || (x * 5 + SIZE * 7 - y * 3).is_multiple_of(29);
+    let cloud = 0.9 + 0.1 * smooth_noise(x, y, 14);
+    if crack { Color::new(0.95, 0.98, 1.0) } else { Color::new(0.72, 0.86, 0.98) * cloud }
+}
+
+fn snow(x: usize, y: usize) -> Color {
+    // Nearly white, with soft blue-gray dimples
+    let shade = 0.9 + 0.1 * smooth_noise(x, y, 15);
+    Color::new(0.96, 0.97, 1.0) * shade
+}
+
+fn sand(x: usize, y: usize) -> Color {
+    // Fine light grains with the odd darker one
+    let grain = if noise(x, y, 18) > 0.85 { 0.82 } else { 0.92 + 0.08 * noise(x, y, 19) };
+    Color::new(0.86, 0.8, 0.6) * grain
+}
+
+fn gravel(x: usize, y: usize) -> Color {
+    // Pebbles of a few grays, two-texel blobs with dark gaps between them
+    let pebble = noise(x / 2, y / 2, 20);
+    let gap = noise(x, y, 21) > 0.8;
+    let shade = if gap { 0.35 } else { 0.5 + 0.35 * pebble };
+    Color::new(0.58, 0.56, 0.55) * shade
+}
+
+fn moon(x: usize, y: usize) -> Color {
+    // Pale gray with darker maria and crater pits
+    let mare = smooth_noise(x, y, 16) > 0.6;
+    let crater = noise(x, y, 17) > 0.9;
+    let shade = if crater { 0.6 } else if mare { 0.75 } else { 0.92 };
+    Color::new(0.9, 0.9, 0.95) * shade
+}
+
+fn moon_disk(x: usize, y: usize) -> bool {
+    let (dx, dy) = (x as f32 + 0.5 - SIZE as f32 / 2.0, y as f32 + 0.5 - SIZE as f32 / 2.0);
+    dx * dx + dy * dy <= (SIZE as f32 / 2.0).powi(2)
+}
+
+fn glass_frame(x: usize, y: usize) -> bool {
+    x == 0 || y == 0 || x == SIZE - 1 || y == SIZE - 1
+}
+
+fn glass_streak(x: usize, y: usize) -> bool {
+    (x + y == 5 || x + y == 6 || x + y == 11) && (3..13).contains(&x)
+}
+
+fn glass(x: usize, y: usize) -> Color {
+    if glass_frame(x, y) {
+        Color::new(0.75, 0.82, 0.88)
+    } else if glass_streak(x, y) {
+        Color::new(1.0, 1.0, 1.0)
+    } else {
+        Color::new(0.88, 0.93, 0.97)
+    }
+}
+
+fn glow(x: usize, y: usize) -> Color {
+    // Bright center fading to warm orange at the edges
+    let dx = x as f32 - 7.5;
+    let dy = y as f32 - 7.5;
+    let falloff = 1.0 - (dx * dx + dy * dy).sqrt() / 11.0;
+    Color::new(1.0, 0.75, 0.4) * (0.7 + 0.3 * falloff + 0.1 * noise(x, y, 12))
+}
@@ -0,0 +1,89 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::Instant;
+
+const BAR_WIDTH: usize = 30;
+
+// Set from the Ctrl+C handler, polled by the render threads between tiles
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Catch Ctrl+C for offline renders: the current frame stops after its running tiles
+/// and the caller saves what was finished so far. A second Ctrl+C quits right away.
+pub fn install_interrupt_handler() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let installed = ctrlc::set_handler(|| {
+            if INTERRUPTED.swap(true, Ordering::SeqCst) {
+                std::process::exit(130); // What a shell reports for a process ended by Ctrl+C
+            }
+        });
+        if let Err(e) = installed {
+            eprintln!("Ctrl+C won't stop renders cleanly: {}", e);
+        }
+    });
+}
+
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// `frame_0003.png` -> `frame_0003_partial.png`
+pub fn partial_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("render");
+    let extension = path.extension().and_then(|s| s.to_str()).unwrap_or("png");
+    path.with_file_name(format!("{}_partial.{}", stem, extension))
+}
+
+/// Counters shared by the render threads of one frame, printed as a one-line progress bar
+pub struct RenderProgress {
+    label: String,
+    start: Instant,
+    pub total_tiles: AtomicUsize,
+    pub tiles_done: AtomicUsize,
+    pub tiles_resumed: AtomicUsize, // Already done in a checkpoint, left out of the ETA
+    pub rays: AtomicU64,
+}
+
+impl RenderProgress {
+    pub fn new(label: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            start: Instant::now(),
+            total_tiles: AtomicUsize::new(0),
+            tiles_done: AtomicUsize::new(0),
+            tiles_resumed: AtomicUsize::new(0),
+            rays: AtomicU64::new(0),
+        }
+    }
+
+    /// Redraw the bar in place: tiles done, rays per second and time left
+    pub fn print(&self) {
+        let total = self.total_tiles.load(Ordering::Relaxed).max(1);
+        let done = self.tiles_done.load(Ordering::Relaxed).min(total);
+        let elapsed = self.start.elapsed().as_secs_f32();
+        let rays_per_sec = self.rays.load(Ordering::Relaxed) as f32 / elapsed.max(0.001);
+        let rendered = done.saturating_sub(self.tiles_resumed.load(Ordering::Relaxed));
+        let eta = if rendered > 0 { elapsed / rendered as f32 * (total - done) as f32 } else { 0.0 };
+
+        let filled = done * BAR_WIDTH / total;
+        print!(
+            "\r  {} [{}{}] {}/{} tiles, {:.2}M rays/s, ETA {:.0}s   ",
+            self.label,
+            "#".repeat(filled),
+            "-".repeat(BAR_WIDTH - filled),
+            done,
+            total,
+            rays_per_sec / 1_000_000.0,
+            eta
+        );
+        let _ = std::io::stdout().flush();
+    }
+
+    /// Wipe the bar so the next println starts on a clean line
+    pub fn clear(&self) {
+        print!("\r{:width$}\r", "", width = self.label.len() + BAR_WIDTH + 60);
+        let _ = std::io::stdout().flush();
+    }
+}
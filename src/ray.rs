@@ -1,9 +1,20 @@
-use crate::utils::Vec3;
+use crate::color::Color;
+use crate::utils::{Real, Vec3, narrow};
+
+/// Closest hit distance every primitive accepts; anything nearer is the surface the ray just left.
+/// Rays that start inside a solid (camera in a wall, refraction rays) hit its far side from
+/// within: the exit point, with the normal pointing inward, back toward the ray origin.
+pub const T_MIN: Real = 0.001;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Ray {
     pub origin: Vec3,
     pub direction: Vec3,
+    pub time: f32, // Position within the shutter interval (0.0 = shutter open, 1.0 = close)
+    pub footprint: Real, // Width covered by one pixel at the origin (ray cone, for mip selection)
+    pub spread: Real,    // Footprint growth per unit of distance (pixel angle)
+    pub absorption: Color, // Absorption of the medium the ray travels through (black = air)
+    pub bounce: bool, // Diffuse bounce of the path tracer: lights and the sun were already sampled directly
 }
 
 impl Ray {
@@ -11,10 +22,55 @@ impl Ray {
         Self {
             origin,
             direction: direction.normalize(),
+            time: 1.0,
+            footprint: 0.0,
+            spread: 0.0,
+            absorption: Color::black(),
+            bounce: false,
         }
     }
 
-    pub fn at(&self, t: f32) -> Vec3 {
+    /// Give the ray a cone (footprint at the origin and its growth per unit distance)
+    pub fn with_cone(mut self, footprint: Real, spread: Real) -> Self {
+        self.footprint = footprint;
+        self.spread = spread;
+        self
+    }
+
+    /// Width of the ray cone after travelling distance t
+    pub fn footprint_at(&self, t: Real) -> Real {
+        self.footprint + self.spread * t
+    }
+
+    /// Ray travelling inside glass/water with that material's absorption coefficients
+    pub fn with_medium(mut self, absorption: Color) -> Self {
+        self.absorption = absorption;
+        self
+    }
+
+    /// Mark as a path-traced diffuse bounce, which sees emissive blocks and the sky without their
+    /// direct light (next-event estimation already added it, so it would be counted twice)
+    pub fn as_bounce(mut self) -> Self {
+        self.bounce = true;
+        self
+    }
+
+    /// Share of each channel left after travelling distance t through the ray's medium (Beer-Lambert)
+    pub fn transmittance(&self, t: Real) -> Color {
+        let t = narrow(t);
+        Color::new(
+            (-self.absorption.r * t).exp(),
+            (-self.absorption.g * t).exp(),
+            (-self.absorption.b * t).exp(),
+        )
+    }
+
+    pub fn with_time(mut self, time: f32) -> Self {
+        self.time = time;
+        self
+    }
+
+    pub fn at(&self, t: Real) -> Vec3 {
         self.origin + self.direction * t
     }
 }
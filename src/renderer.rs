@@ -1,9 +1,139 @@
 use crate::scene::Scene;
 use crate::camera::Camera;
 use crate::ray::Ray;
+use crate::intersection::Intersection;
 use crate::color::Color;
+use crate::utils::{Degrees, Real, TAU, Vec3, narrow, random_f32};
+use crate::progress::{self, RenderProgress};
+use crate::checkpoint::{Checkpoint, CheckpointOptions, KeyHasher};
+use crate::watchdog::{FrameStats, Watchdog};
+use crate::denoise::{self, GBuffer, Surface};
+use crate::palette;
+use crate::post;
+use crate::shoreline;
+use crate::waves;
+use crate::weather;
+use crate::light::DirectionalLight;
+use crate::point_light::sample_sphere;
+use crate::tonemap::ToneMap;
+
+use std::cell::Cell;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 const MAX_DEPTH: i32 = 8;  // Increased from 5 to 8 for better water transparency/reflection
+const MAX_SHADOW_LAYERS: usize = 16; // Transparent surfaces a shadow ray passes before giving up
+const AO_RADIUS: Real = 1.5; // Blocks closer than this darken the ambient light (about one block of reach)
+const AO_MAX_DEPTH: i32 = 1; // Camera hits and one bounce (the pond floor through the water) get AO
+const ROULETTE_DEPTH: i32 = 2; // Path tracing: bounces after this many may be cut short (Russian roulette)
+const TILE_SIZE: i32 = 32; // Scaled pixels per tile side, threads pull tiles from a shared counter
+const ROUGH_STEP: i32 = 4;  // Tiles traced after the frame budget ran out use one sample per 4x4 scaled pixels
+const PACKET_SIZE: i32 = 4; // One-ray pixels are traced in packets of 4x4 camera rays
+const PACKET_RAYS: usize = (PACKET_SIZE * PACKET_SIZE) as usize;
+const MAX_ACCUMULATED_FRAMES: u32 = 256; // A still view stops re-tracing once this many frames are averaged
+const SPARKLE_SHININESS: f32 = 400.0; // Crystal glints are tiny mirrors: only an exact line-up flashes
+const SPARKLE_BRIGHTNESS: f32 = 6.0; // Glints several times brighter than white, so bloom catches them
+const GLINT_SHININESS: f32 = 300.0; // Metal flakes: a little broader than snow crystals, so lamps catch them too
+const GLINT_BRIGHTNESS: f32 = 4.0;
+const ADAPTIVE_MIN_SAMPLES: u32 = 2; // Rays every pixel gets before adaptive sampling judges its noise
+pub const OFFLINE_LENS_SAMPLES: u32 = 32; // Exports and batch jobs: rays per pixel through a camera with depth of field
+
+thread_local! {
+    // Rays traced by this thread (camera, bounce and shadow rays), drained per tile for rays/sec
+    static RAYS_TRACED: Cell<u64> = const { Cell::new(0) };
+}
+
+fn count_ray() {
+    RAYS_TRACED.with(|rays| rays.set(rays.get() + 1));
+}
+
+/// Per-frame render options chosen by the UI
+#[derive(Clone, Copy)]
+pub struct RenderSettings {
+    pub render_scale: i32,
+    pub use_threading: bool,
+    pub motion_blur_samples: u32, // Rays per pixel spread over the shutter (0 or 1 = off)
+    pub shutter: f32,             // Fraction of the frame the shutter stays open (0.5 = 180° shutter)
+    pub frame_budget: f32,        // Seconds before the remaining tiles are traced roughly (0 = no limit: offline renders take as long as they need)
+    pub ray_budget: u64,          // Same, counted in rays (0 = no limit)
+    pub transparent_background: bool, // Offline renders: pixels whose camera ray misses everything get alpha 0
+    pub shadow_samples: u32,      // Shadow rays spread over the sun's disk per hit (1 = hard shadows)
+    pub ao_samples: u32,          // Ambient occlusion rays per hit (0 = flat ambient)
+    pub path_tracing: bool,       // Monte Carlo path tracing (diffuse bounces) instead of ambient + AO
+    pub path_samples: u32,        // Paths per pixel when path tracing, jittered inside the pixel
+    pub jitter: bool,             // Spread each pixel's rays over its area (frames that get averaged, antialiasing)
+    pub denoise: bool,            // Edge-preserving filter over the finished frame's float colors (denoise.rs)
+    pub aa_samples: u32,          // Supersampling: jittered rays averaged per pixel (0 or 1 = one ray through the center)
+    pub adaptive_threshold: f32,  // Pixels stop taking samples once their brightness is this certain (std. error, 0 = all samples)
+    pub lens_samples: u32,        // Rays per pixel spread over the lens when the camera has an aperture (the app averages frames instead)
+    pub shadow_preview: bool,     // Lighting design view: gray shadow factors only (white = lit), no materials or bounces
+    pub tone_map: ToneMap,        // Curve from the traced linear colors (unclamped) to the 0-1 screen range
+    pub exposure: f32,            // Brightness multiplier applied before the tone map
+    pub bloom: f32,               // Strength of the glow around pixels brighter than bloom_threshold (0 = off, post.rs)
+    pub bloom_threshold: f32,     // Traced brightness (white = 1.0) where the glow starts
+    pub band: Option<Band>,       // Rows of a taller image the buffer covers (bands.rs), None = the whole image
+}
+
+/// Horizontal strip of a taller image: images bigger than the window (4K and 8K stills) are traced
+/// a band at a time so only one strip is ever in memory
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Band {
+    pub top: i32,          // Image row the buffer's first row lands on
+    pub image_height: i32, // Rows in the whole image
+}
+
+impl Band {
+    /// Image row of a buffer row, and the row count of the whole image (the buffer's own without a band)
+    pub fn locate(band: Option<Band>, row: i32, rows: i32) -> (i32, i32) {
+        match band {
+            Some(band) => (band.top + row, band.image_height),
+            None => (row, rows),
+        }
+    }
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            render_scale: 2,
+            use_threading: true,
+            motion_blur_samples: 0,
+            shutter: 0.5,
+            frame_budget: 2.0,
+            ray_budget: 100_000_000,
+            transparent_background: false,
+            shadow_samples: 1,
+            ao_samples: 0,
+            path_tracing: false,
+            path_samples: 1,
+            jitter: false,
+            denoise: false,
+            aa_samples: 0,
+            adaptive_threshold: 0.0,
+            lens_samples: 0,
+            shadow_preview: false,
+            tone_map: ToneMap::default(),
+            exposure: 1.0,
+            bloom: 0.0,
+            bloom_threshold: 1.0,
+            band: None,
+        }
+    }
+}
+
+impl RenderSettings {
+    /// Screen color for a traced (linear, unclamped) color: tone mapped, then sRGB-encoded
+    pub fn display(&self, color: Color) -> raylib::prelude::Color {
+        self.tone_map.apply(color, self.exposure).to_srgb().to_raylib()
+    }
+
+    // Passes over the finished frame's float colors (denoiser, bloom), which then have to be kept
+    fn post_processing(&self) -> bool {
+        self.denoise || self.bloom > 0.0
+    }
+}
 
 pub fn render_scene(
     scene: &Scene,
@@ -11,54 +141,694 @@ pub fn render_scene(
     buffer: &mut [raylib::prelude::Color],
     width: i32,
     height: i32,
-    render_scale: i32,
-    use_threading: bool,
-    day_time: f32,
-) {
+    settings: &RenderSettings,
+) -> FrameStats {
+    let scaled_width = width / settings.render_scale;
+    let scaled_height = height / settings.render_scale;
+    let mut first_hits = FirstHits::new(Passes { gbuffer: settings.denoise, background: false, colors: false }, scaled_width, scaled_height);
+    render_frame(scene, camera, buffer, width, height, settings, &mut first_hits)
+}
+
+/// render_scene that also hands back the frame's linear colors (scaled resolution, after the post
+/// passes, before tone mapping) for the Accumulator, and with `gbuffer` the frame's G-buffer, taken
+/// from the render's own camera rays: the depth temporal reprojection needs, without tracing it again
+pub fn render_scene_with_colors(
+    scene: &Scene,
+    camera: &Camera,
+    buffer: &mut [raylib::prelude::Color],
+    width: i32,
+    height: i32,
+    settings: &RenderSettings,
+    gbuffer: bool,
+) -> (FrameStats, Vec<Color>, Option<GBuffer>) {
+    let scaled_width = width / settings.render_scale;
+    let scaled_height = height / settings.render_scale;
+    let passes = Passes { gbuffer: gbuffer || settings.denoise, background: false, colors: true };
+    let mut first_hits = FirstHits::new(passes, scaled_width, scaled_height);
+    let stats = render_frame(scene, camera, buffer, width, height, settings, &mut first_hits);
+    (stats, first_hits.colors.unwrap_or_default(), first_hits.gbuffer.filter(|_| gbuffer))
+}
+
+fn render_frame(
+    scene: &Scene,
+    camera: &Camera,
+    buffer: &mut [raylib::prelude::Color],
+    width: i32,
+    height: i32,
+    settings: &RenderSettings,
+    first_hits: &mut FirstHits,
+) -> FrameStats {
+    let scaled_width = width / settings.render_scale;
+    let scaled_height = height / settings.render_scale;
+    if settings.use_threading {
+        render_threaded(scene, camera, buffer, width, height, scaled_width, scaled_height, settings, first_hits, None, None)
+    } else {
+        render_single_threaded(scene, camera, buffer, width, height, scaled_width, scaled_height, settings, first_hits)
+    }
+}
+
+/// Offline variant of render_scene (export, batch): always threaded, prints a progress bar
+/// while the tiles come in. Returns false when Ctrl+C stopped it early, the buffer then only
+/// holds the finished tiles. With a checkpoint file the finished tiles are also saved
+/// periodically (and on Ctrl+C), and a matching checkpoint is resumed from. A finished render
+/// drops its checkpoint, unless the options keep it (bands::render_to_png removes them).
+#[allow(clippy::too_many_arguments)]
+pub fn render_scene_with_progress(
+    scene: &Scene,
+    camera: &Camera,
+    buffer: &mut [raylib::prelude::Color],
+    width: i32,
+    height: i32,
+    settings: &RenderSettings,
+    label: &str,
+    checkpoint: Option<(&Path, &CheckpointOptions)>,
+) -> bool {
+    let scaled_width = width / settings.render_scale;
+    let scaled_height = height / settings.render_scale;
+    let progress = Arc::new(RenderProgress::new(label));
+    let checkpoint = checkpoint.filter(|(_, options)| options.enabled());
+    let checkpoint_options = checkpoint.map(|(_, options)| *options);
+    let checkpoint = checkpoint.map(|(path, options)| {
+        let key = render_key(scene, camera, settings, width, height);
+        Arc::new(Mutex::new(Checkpoint::open(
+            path.to_path_buf(),
+            key,
+            scaled_width.max(0) as usize,
+            scaled_height.max(0) as usize,
+            TILE_SIZE as usize,
+            options,
+        )))
+    });
+
+    let passes = Passes { gbuffer: settings.denoise, background: settings.transparent_background, colors: false };
+    let mut first_hits = FirstHits::new(passes, scaled_width, scaled_height);
+    render_threaded(scene, camera, buffer, width, height, scaled_width, scaled_height, settings, &mut first_hits, Some(&progress), checkpoint.as_ref());
+    progress.clear();
+    if let Some(background) = &first_hits.background {
+        clear_background(background, settings, buffer, width, height);
+    }
+
+    let finished = !progress::interrupted();
+    let keep_finished = checkpoint_options.is_some_and(|options| options.keep_finished);
+    if let Some(checkpoint) = checkpoint {
+        let mut checkpoint = checkpoint.lock().unwrap();
+        if finished && !keep_finished {
+            checkpoint.remove();
+        } else {
+            match checkpoint.save() {
+                Ok(()) if !finished => println!("Checkpoint saved to {} (continue with --resume)", checkpoint.path().display()),
+                Ok(()) => {}
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+    }
+    finished
+}
+
+// Make sky pixels transparent: every (scaled) pixel whose unjittered camera ray missed becomes
+// transparent black for compositing elsewhere. Shadow catchers in front of the sky keep only
+// their shadows and reflections, as partial alpha. `background` is the layer per scaled pixel
+// the frame's first hits left over the sky (None on solid surfaces).
+fn clear_background(background: &[Option<(Color, f32)>], settings: &RenderSettings, buffer: &mut [raylib::prelude::Color], width: i32, height: i32) {
+    let render_scale = settings.render_scale;
     let scaled_width = width / render_scale;
     let scaled_height = height / render_scale;
+    for sy in 0..scaled_height {
+        for sx in 0..scaled_width {
+            let Some((layer, coverage)) = background[(sy * scaled_width + sx) as usize] else {
+                continue;
+            };
+            let pixel = if coverage > 0.0 {
+                let mut pixel = settings.display(layer * (1.0 / coverage));
+                pixel.a = (coverage * 255.0).round() as u8;
+                pixel
+            } else {
+                raylib::prelude::Color::BLANK
+            };
+            for y in (sy * render_scale)..((sy + 1) * render_scale).min(height) {
+                for x in (sx * render_scale)..((sx + 1) * render_scale).min(width) {
+                    buffer[(y * width + x) as usize] = pixel;
+                }
+            }
+        }
+    }
+}
 
-    if use_threading {
-        render_threaded(scene, camera, buffer, width, height, scaled_width, scaled_height, render_scale, day_time);
-    } else {
-        render_single_threaded(scene, camera, buffer, width, height, scaled_width, scaled_height, render_scale, day_time);
+// What a camera ray hitting `hit` first leaves over the background: None when it ends on a
+// solid surface, otherwise the (premultiplied) shadow catcher layer in front of the sky
+fn background_layer(scene: &Scene, camera: &Camera, settings: &RenderSettings, ray: &Ray, hit: Option<&Intersection>) -> Option<(Color, f32)> {
+    let Some(hit) = hit else {
+        return Some((Color::black(), 0.0));
+    };
+    if hit.material.shadow_catcher <= 0.0 {
+        return None;
     }
+    let (_, behind) = skip_shadow_catchers(scene, *ray);
+    if behind.is_some() {
+        return None;
+    }
+    Some(shadow_catcher_layer(ray, hit, scene, camera, settings, 0))
 }
 
-fn render_single_threaded(
+// Which per-pixel records a frame gathers along with its colors
+#[derive(Clone, Copy)]
+struct Passes {
+    gbuffer: bool,    // Denoiser, temporal reprojection
+    background: bool, // Transparent background
+    colors: bool,     // The frame's linear colors, for the Accumulator
+}
+
+impl Passes {
+    fn any(&self) -> bool {
+        self.gbuffer || self.background
+    }
+}
+
+// What one scaled pixel's unjittered camera ray found first
+#[derive(Clone, Copy)]
+struct FirstHit {
+    surface: Surface,
+    background: Option<(Color, f32)>, // Layer left over the sky (see background_layer)
+}
+
+// The records of a whole frame, for the passes that run after the trace: taken by the tiles from
+// the camera rays they trace anyway, so none of those passes traces the frame a second time
+struct FirstHits {
+    passes: Passes,
+    scaled_width: i32,
+    gbuffer: Option<GBuffer>,
+    background: Option<Vec<Option<(Color, f32)>>>,
+    colors: Option<Vec<Color>>, // Filled in once the frame is done (and post processed)
+}
+
+impl FirstHits {
+    fn new(passes: Passes, scaled_width: i32, scaled_height: i32) -> Self {
+        let count = (scaled_width * scaled_height).max(0) as usize;
+        Self {
+            passes,
+            scaled_width,
+            gbuffer: passes.gbuffer.then(|| GBuffer::new(scaled_width, scaled_height)),
+            background: passes.background.then(|| vec![None; count]),
+            colors: None,
+        }
+    }
+
+    fn set(&mut self, sx: i32, sy: i32, hit: FirstHit) {
+        let index = (sy * self.scaled_width + sx) as usize;
+        if let Some(gbuffer) = &mut self.gbuffer {
+            gbuffer.set(index, hit.surface);
+        }
+        if let Some(background) = &mut self.background {
+            background[index] = hit.background;
+        }
+    }
+}
+
+// The records of a pixel whose camera ray `ray` hit `hit` first
+fn first_hit(scene: &Scene, camera: &Camera, settings: &RenderSettings, passes: Passes, ray: &Ray, hit: Option<&Intersection>) -> FirstHit {
+    FirstHit {
+        surface: if passes.gbuffer { Surface::seen(scene, ray, hit) } else { Surface::SKY },
+        background: if passes.background { background_layer(scene, camera, settings, ray, hit) } else { None },
+    }
+}
+
+// The records of scaled pixel (sx, sy) for a pixel whose samples can't be reused (jittered,
+// through the lens, over time): its ray at the end of the shutter through the center of the lens
+#[allow(clippy::too_many_arguments)]
+fn trace_first_hit(scene: &Scene, camera: &Camera, settings: &RenderSettings, passes: Passes, sx: i32, sy: i32, scaled_width: i32, scaled_height: i32) -> FirstHit {
+    let (row, rows) = Band::locate(settings.band, sy, scaled_height);
+    let ray = camera.get_pinhole_ray(sx as f32 / scaled_width as f32, row as f32 / rows as f32).with_cone(0.0, camera.pixel_spread(rows));
+    count_ray();
+    first_hit(scene, camera, settings, passes, &ray, scene.intersect(&ray).as_ref())
+}
+
+// First hit that isn't a shadow catcher, and the ray continued up to the last catcher it passed
+// (catchers are see-through for everything but the layer they add, and only add it once)
+fn skip_shadow_catchers(scene: &Scene, mut ray: Ray) -> (Ray, Option<Intersection>) {
+    loop {
+        count_ray();
+        match scene.intersect(&ray) {
+            Some(hit) if hit.material.shadow_catcher > 0.0 => {
+                ray = Ray { origin: hit.position - hit.normal * 0.001, ..ray };
+            }
+            other => return (ray, other),
+        }
+    }
+}
+
+// Everything that changes the pixels of an offline render, blocks included, so a checkpoint of
+// a scene file edited since (a block moved or given another material) isn't resumed
+fn render_key(scene: &Scene, camera: &Camera, settings: &RenderSettings, width: i32, height: i32) -> u64 {
+    let mut hasher = KeyHasher::new();
+    (view_key(scene, camera, settings, width, height), scene.time.elapsed.to_bits(), blocks_key(scene)).hash(&mut hasher);
+    hasher.finish()
+}
+
+// Every block's place, size and materials. The grid hands its cubes out in no fixed order, so
+// their hashes are summed rather than chained.
+fn blocks_key(scene: &Scene) -> u64 {
+    scene.grid.cubes().fold(0, |sum: u64, cube| {
+        let mut hasher = KeyHasher::new();
+        [cube.position.x, cube.position.y, cube.position.z, cube.size].map(|value| value.to_bits()).hash(&mut hasher);
+        (cube.material, cube.top_material, cube.side_material, cube.bottom_material).hash(&mut hasher);
+        cube.face_material.map(|(normal, id)| ([normal.x, normal.y, normal.z].map(|value| value.to_bits()), id)).hash(&mut hasher);
+        sum.wrapping_add(hasher.finish())
+    })
+}
+
+// Same without the running clock and the blocks: the camera, lighting and settings a still
+// view keeps while frames are averaged together
+fn view_key(scene: &Scene, camera: &Camera, settings: &RenderSettings, width: i32, height: i32) -> u64 {
+    let mut hasher = KeyHasher::new();
+    for value in [
+        camera.position.x, camera.position.y, camera.position.z,
+        camera.target.x, camera.target.y, camera.target.z,
+        camera.aspect as Real, camera.aperture as Real, camera.focus_distance,
+        scene.time.day_fraction as Real, scene.weather.wetness as Real,
+        scene.key_light().0.direction.x, scene.key_light().0.direction.y, scene.key_light().0.direction.z, scene.key_light().1 as Real,
+        scene.ambient().r as Real, scene.ambient().g as Real, scene.ambient().b as Real,
+        settings.shutter as Real,
+    ] {
+        value.to_bits().hash(&mut hasher);
+    }
+    (camera.fov.to_bits(), camera.roll.to_bits()).hash(&mut hasher);
+    (width, height, settings.render_scale, settings.motion_blur_samples, settings.shadow_samples, settings.ao_samples).hash(&mut hasher);
+    (settings.path_tracing, settings.path_samples, settings.denoise, settings.aa_samples, settings.adaptive_threshold.to_bits(), settings.lens_samples, settings.shadow_preview, settings.tone_map, settings.exposure.to_bits()).hash(&mut hasher);
+    (scene.entities.len(), scene.static_lights().count() + scene.spot_lights.len(), scene.lamps_on).hash(&mut hasher);
+    (settings.bloom.to_bits(), settings.bloom_threshold.to_bits(), scene.time.days).hash(&mut hasher);
+    settings.band.map(|band| (band.top, band.image_height)).hash(&mut hasher);
+    // Lamps moved or resized in the light editor
+    for (position, reach) in scene.static_lights().map(|l| (l.position, l.radius)).chain(scene.spot_lights.iter().map(|l| (l.position, l.radius))) {
+        [position.x, position.y, position.z, reach].map(|value| value.to_bits()).hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Progressive rendering in the app: while the camera, time of day and scene stay the same, each
+/// new frame is averaged with the previous ones (per scaled pixel sums of the traced linear colors,
+/// tone mapped only for display), so soft shadows, AO and
+/// path tracing noise clear up and edges get antialiased the longer the camera rests
+pub struct Accumulator {
+    sum: Vec<Color>,
+    frames: u32,
+    key: u64,
+}
+
+impl Accumulator {
+    pub fn new() -> Self {
+        Self {
+            sum: Vec::new(),
+            frames: 0,
+            key: 0,
+        }
+    }
+
+    /// Add a freshly traced frame, its linear colors from render_scene_with_colors, and replace the
+    /// display buffer with the average so far (starts over when the view, lighting or settings changed)
+    #[allow(clippy::too_many_arguments)]
+    pub fn add(
+        &mut self,
+        buffer: &mut [raylib::prelude::Color],
+        colors: &[Color],
+        scene: &Scene,
+        camera: &Camera,
+        settings: &RenderSettings,
+        width: i32,
+        height: i32,
+    ) {
+        let key = Self::key(scene, camera, settings, width, height);
+        if key != self.key || self.sum.len() != colors.len() {
+            self.sum = vec![Color::black(); colors.len()];
+            self.frames = 0;
+            self.key = key;
+        }
+        self.frames += 1;
+        let weight = 1.0 / self.frames as f32;
+        // Averaged before tone mapping, so clamped highlights and 8-bit banding don't end up in the average
+        for (sum, &color) in self.sum.iter_mut().zip(colors) {
+            *sum = *sum + color;
+        }
+        let render_scale = settings.render_scale;
+        let scaled_width = width / render_scale;
+        for (index, sum) in self.sum.iter().enumerate() {
+            let (sx, sy) = (index as i32 % scaled_width, index as i32 / scaled_width);
+            let color = settings.display(*sum * weight);
+            for y in (sy * render_scale)..((sy + 1) * render_scale).min(height) {
+                for x in (sx * render_scale)..((sx + 1) * render_scale).min(width) {
+                    buffer[(y * width + x) as usize] = color;
+                }
+            }
+        }
+    }
+
+    /// Whether the next frame would be averaged into the current image (nothing changed since the last one)
+    pub fn is_still(&self, scene: &Scene, camera: &Camera, settings: &RenderSettings, width: i32, height: i32) -> bool {
+        self.frames > 0
+            && self.sum.len() == ((width / settings.render_scale) * (height / settings.render_scale)).max(0) as usize
+            && Self::key(scene, camera, settings, width, height) == self.key
+    }
+
+    // The view key, and the grid's edit counter for the blocks (cheap, and only compared within this run)
+    fn key(scene: &Scene, camera: &Camera, settings: &RenderSettings, width: i32, height: i32) -> u64 {
+        let mut hasher = KeyHasher::new();
+        (view_key(scene, camera, settings, width, height), scene.grid.generation()).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Start averaging over (the scene changed in a way the view key can't see, like a material swap)
+    pub fn reset(&mut self) {
+        self.sum.clear();
+        self.frames = 0;
+    }
+
+    /// Enough frames averaged that more won't visibly change the image
+    pub fn converged(&self) -> bool {
+        self.frames >= MAX_ACCUMULATED_FRAMES
+    }
+
+    /// Frames averaged into the current image
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+}
+
+impl Default for Accumulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Trace one (downscaled) pixel, averaging several shutter-time samples when motion blur is on
+fn render_pixel(
+    scene: &Scene,
+    camera: &Camera,
+    sx: i32,
+    sy: i32,
+    scaled_width: i32,
+    scaled_height: i32,
+    settings: &RenderSettings,
+) -> Color {
+    let (row, rows) = Band::locate(settings.band, sy, scaled_height);
+    let u = sx as f32 / scaled_width as f32;
+    let v = row as f32 / rows as f32;
+
+    // Angle covered by one (scaled) pixel, so lower render scales pick blurrier mips
+    let spread = camera.pixel_spread(rows);
+
+    let (samples, jitter) = pixel_samples(camera, settings);
+    if samples <= 1 && !jitter {
+        let ray = camera.get_ray(u, v).with_cone(0.0, spread);
+        return trace_ray(&ray, scene, camera, settings, 0);
+    }
+
+    // Adaptive sampling: flat pixels stop after a couple of rays that agree, edges and noisy
+    // soft shadows / paths keep going up to the full count
+    let min_samples = if settings.adaptive_threshold > 0.0 { ADAPTIVE_MIN_SAMPLES.min(samples) } else { samples };
+    let mut accumulated = Color::black();
+    let (mut sum, mut sum_squares) = (0.0, 0.0);
+    let mut taken = 0;
+    while taken < samples {
+        // Random time inside the open part of the shutter, ending at the current pose
+        let time = if settings.motion_blur_samples > 1 { 1.0 - settings.shutter * random_f32() } else { 1.0 };
+        // Supersampling, path tracing and averaged frames spread the samples over the pixel (antialiasing)
+        let (u, v) = if jitter {
+            (u + random_f32() / scaled_width as f32, v + random_f32() / rows as f32)
+        } else {
+            (u, v)
+        };
+        let ray = camera.get_ray_at_time(u, v, time).with_cone(0.0, spread);
+        let color = trace_ray(&ray, scene, camera, settings, 0);
+        accumulated = accumulated + color;
+        taken += 1;
+
+        let luminance = color.luminance();
+        sum += luminance;
+        sum_squares += luminance * luminance;
+        if taken >= min_samples && taken < samples {
+            let count = taken as f32;
+            let variance = (sum_squares / count - (sum / count).powi(2)).max(0.0);
+            if (variance / count).sqrt() <= settings.adaptive_threshold {
+                break;
+            }
+        }
+    }
+    accumulated * (1.0 / taken as f32)
+}
+
+// Rays per pixel (motion blur, path, supersampling and lens samples share them), and whether they
+// are jittered across the pixel instead of all going through its corner
+fn pixel_samples(camera: &Camera, settings: &RenderSettings) -> (u32, bool) {
+    let path_samples = if settings.path_tracing { settings.path_samples.max(1) } else { 1 };
+    let lens_samples = if camera.aperture > 0.0 { settings.lens_samples } else { 0 };
+    let samples = settings.motion_blur_samples.max(path_samples).max(settings.aa_samples).max(lens_samples);
+    let jitter = settings.path_tracing || settings.jitter || settings.aa_samples > 1;
+    (samples, jitter)
+}
+
+// Trace the PACKET_SIZE x PACKET_SIZE block of one-ray pixels starting at (px, py) as a packet:
+// neighbouring camera rays head the same way, so they walk the scene's chunks together. Blocks
+// cut off by the edge of the tile are traced pixel by pixel, and so is everything after the
+// first hit (shadow, reflection and refraction rays scatter). `record` gets each pixel's camera
+// ray and first hit when they can stand for its unjittered ray (a pinhole camera).
+#[allow(clippy::too_many_arguments)]
+fn render_packet(
     scene: &Scene,
     camera: &Camera,
+    px: i32,
+    py: i32,
+    end_x: i32,
+    end_y: i32,
+    scaled_width: i32,
+    scaled_height: i32,
+    settings: &RenderSettings,
+    put: &mut impl FnMut(i32, i32, Color),
+    record: &mut impl FnMut(i32, i32, Option<(&Ray, Option<&Intersection>)>),
+) {
+    if px + PACKET_SIZE > end_x || py + PACKET_SIZE > end_y {
+        for sy in py..(py + PACKET_SIZE).min(end_y) {
+            for sx in px..(px + PACKET_SIZE).min(end_x) {
+                put(sx, sy, render_pixel(scene, camera, sx, sy, scaled_width, scaled_height, settings));
+                record(sx, sy, None);
+            }
+        }
+        return;
+    }
+    let pixel = |i: usize| (px + i as i32 % PACKET_SIZE, py + i as i32 / PACKET_SIZE);
+    let rays: [Ray; PACKET_RAYS] = std::array::from_fn(|i| {
+        let (sx, sy) = pixel(i);
+        let (row, rows) = Band::locate(settings.band, sy, scaled_height);
+        camera.get_ray(sx as f32 / scaled_width as f32, row as f32 / rows as f32).with_cone(0.0, camera.pixel_spread(rows))
+    });
+    for (i, hit) in scene.intersect_packet(&rays).into_iter().enumerate() {
+        let (sx, sy) = pixel(i);
+        record(sx, sy, (camera.aperture <= 0.0).then_some((&rays[i], hit.as_ref())));
+        put(sx, sy, shade(&rays[i], hit, scene, camera, settings, 0));
+    }
+}
+
+// Cheap stand-in for render_pixel once the frame is over budget: one ray, one bounce, no motion blur
+fn rough_pixel(scene: &Scene, camera: &Camera, sx: i32, sy: i32, scaled_width: i32, scaled_height: i32, settings: &RenderSettings) -> Color {
+    let settings = RenderSettings { shadow_samples: 1, ..*settings };
+    let (row, rows) = Band::locate(settings.band, sy, scaled_height);
+    let u = sx as f32 / scaled_width as f32;
+    let v = row as f32 / rows as f32;
+    let ray = camera.get_ray(u, v).with_cone(0.0, camera.pixel_spread(rows));
+    trace_ray(&ray, scene, camera, &settings, MAX_DEPTH - 2)
+}
+
+// Run the post passes over the frame's float colors (scaled resolution): the denoiser against the
+// frame's G-buffer, then bloom. The result is written over the display buffer.
+#[allow(clippy::too_many_arguments)]
+fn post_process_into(
+    settings: &RenderSettings,
+    gbuffer: Option<&GBuffer>,
+    floats: &mut [Color],
     buffer: &mut [raylib::prelude::Color],
     width: i32,
     height: i32,
     scaled_width: i32,
     scaled_height: i32,
-    render_scale: i32,
-    day_time: f32,
 ) {
+    if let Some(gbuffer) = gbuffer.filter(|_| settings.denoise) {
+        denoise::denoise(floats, gbuffer, scaled_width, scaled_height);
+    }
+    let (_, image_height) = Band::locate(settings.band, 0, scaled_height);
+    post::bloom(floats, scaled_width, scaled_height, image_height, settings.bloom_threshold, settings.bloom);
+    let render_scale = settings.render_scale;
     for sy in 0..scaled_height {
         for sx in 0..scaled_width {
-            let u = sx as f32 / scaled_width as f32;
-            let v = sy as f32 / scaled_height as f32;
-
-            let ray = camera.get_ray(u, v);
-            let color = trace_ray(&ray, scene, 0, day_time);
-
-            // Fill the scaled pixels
-            for dy in 0..render_scale {
-                for dx in 0..render_scale {
-                    let x = sx * render_scale + dx;
-                    let y = sy * render_scale + dy;
-                    if x < width && y < height {
-                        let idx = (y * width + x) as usize;
-                        buffer[idx] = color.to_raylib();
-                    }
+            let color = settings.display(floats[(sy * scaled_width + sx) as usize]);
+            for y in (sy * render_scale)..((sy + 1) * render_scale).min(height) {
+                for x in (sx * render_scale)..((sx + 1) * render_scale).min(width) {
+                    buffer[(y * width + x) as usize] = color;
                 }
             }
         }
     }
 }
 
+// Tiles across, and in total, for a scaled image
+fn tile_grid(scaled_width: i32, scaled_height: i32) -> (i32, usize) {
+    let tiles_x = (scaled_width + TILE_SIZE - 1) / TILE_SIZE;
+    let tiles_y = (scaled_height + TILE_SIZE - 1) / TILE_SIZE;
+    (tiles_x, (tiles_x * tiles_y).max(0) as usize)
+}
+
+// Trace one tile into (buffer index, color) pairs, plus the per-pixel colors when `colors` is given
+// and the first hits of `passes` into `first_hits`. Rough tiles trace one pixel per
+// ROUGH_STEP x ROUGH_STEP block and fill the block with it.
+#[allow(clippy::too_many_arguments)]
+fn render_tile(
+    scene: &Scene,
+    camera: &Camera,
+    tile: usize,
+    tiles_x: i32,
+    width: i32,
+    height: i32,
+    scaled_width: i32,
+    scaled_height: i32,
+    settings: &RenderSettings,
+    rough: bool,
+    pixels: &mut Vec<(usize, raylib::prelude::Color)>,
+    mut colors: Option<&mut Vec<(i32, i32, Color)>>,
+    passes: Passes,
+    first_hits: &mut Vec<(i32, i32, FirstHit)>,
+) {
+    let render_scale = settings.render_scale;
+    let tile_x = (tile as i32 % tiles_x) * TILE_SIZE;
+    let tile_y = (tile as i32 / tiles_x) * TILE_SIZE;
+    let end_x = (tile_x + TILE_SIZE).min(scaled_width);
+    let end_y = (tile_y + TILE_SIZE).min(scaled_height);
+    let step = if rough { ROUGH_STEP } else { 1 };
+
+    let mut put = |sx: i32, sy: i32, color: Color| {
+        if let Some(colors) = colors.as_deref_mut() {
+            for y in sy..(sy + step).min(end_y) {
+                for x in sx..(sx + step).min(end_x) {
+                    colors.push((x, y, color));
+                }
+            }
+        }
+
+        // Fill the scaled pixels
+        let color = settings.display(color);
+        for y in (sy * render_scale)..((sy + step).min(end_y) * render_scale).min(height) {
+            for x in (sx * render_scale)..((sx + step).min(end_x) * render_scale).min(width) {
+                pixels.push(((y * width + x) as usize, color));
+            }
+        }
+    };
+
+    // The first hits from the pixel's own camera ray when it is the unjittered one, else traced
+    let mut record = |sx: i32, sy: i32, primary: Option<(&Ray, Option<&Intersection>)>| {
+        if !passes.any() {
+            return;
+        }
+        let hit = match primary {
+            Some((ray, hit)) => first_hit(scene, camera, settings, passes, ray, hit),
+            None => trace_first_hit(scene, camera, settings, passes, sx, sy, scaled_width, scaled_height),
+        };
+        for y in sy..(sy + step).min(end_y) {
+            for x in sx..(sx + step).min(end_x) {
+                first_hits.push((x, y, hit));
+            }
+        }
+    };
+
+    // One coherent camera ray per pixel: traced in packets
+    if !rough && !settings.shadow_preview && matches!(pixel_samples(camera, settings), (..=1, false)) {
+        for py in (tile_y..end_y).step_by(PACKET_SIZE as usize) {
+            for px in (tile_x..end_x).step_by(PACKET_SIZE as usize) {
+                render_packet(scene, camera, px, py, end_x, end_y, scaled_width, scaled_height, settings, &mut put, &mut record);
+            }
+        }
+        return;
+    }
+
+    for sy in (tile_y..end_y).step_by(step as usize) {
+        for sx in (tile_x..end_x).step_by(step as usize) {
+            let color = if rough {
+                rough_pixel(scene, camera, sx, sy, scaled_width, scaled_height, settings)
+            } else {
+                render_pixel(scene, camera, sx, sy, scaled_width, scaled_height, settings)
+            };
+            put(sx, sy, color);
+            record(sx, sy, None);
+        }
+    }
+}
+
+// The first hits of `passes` for a tile that isn't traced (resumed from a checkpoint)
+#[allow(clippy::too_many_arguments)]
+fn tile_first_hits(
+    scene: &Scene,
+    camera: &Camera,
+    tile: usize,
+    tiles_x: i32,
+    scaled_width: i32,
+    scaled_height: i32,
+    settings: &RenderSettings,
+    passes: Passes,
+    first_hits: &mut Vec<(i32, i32, FirstHit)>,
+) {
+    let tile_x = (tile as i32 % tiles_x) * TILE_SIZE;
+    let tile_y = (tile as i32 / tiles_x) * TILE_SIZE;
+    for sy in tile_y..(tile_y + TILE_SIZE).min(scaled_height) {
+        for sx in tile_x..(tile_x + TILE_SIZE).min(scaled_width) {
+            first_hits.push((sx, sy, trace_first_hit(scene, camera, settings, passes, sx, sy, scaled_width, scaled_height)));
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn render_single_threaded(
+    scene: &Scene,
+    camera: &Camera,
+    buffer: &mut [raylib::prelude::Color],
+    width: i32,
+    height: i32,
+    scaled_width: i32,
+    scaled_height: i32,
+    settings: &RenderSettings,
+    first_hits: &mut FirstHits,
+) -> FrameStats {
+    let (tiles_x, total_tiles) = tile_grid(scaled_width, scaled_height);
+    let watchdog = Watchdog::new(settings);
+    let mut pixels = vec![];
+    let mut colors = vec![];
+    let mut tile_hits = vec![];
+    let keep_colors = settings.post_processing() || first_hits.passes.colors;
+    let mut floats = vec![Color::black(); if keep_colors { (scaled_width * scaled_height).max(0) as usize } else { 0 }];
+
+    for tile in 0..total_tiles {
+        let rough = watchdog.exceeded();
+        if rough {
+            watchdog.rough_tile();
+        }
+        let tile_colors = if keep_colors { Some(&mut colors) } else { None };
+        render_tile(scene, camera, tile, tiles_x, width, height, scaled_width, scaled_height, settings, rough, &mut pixels, tile_colors, first_hits.passes, &mut tile_hits);
+        watchdog.add_rays(RAYS_TRACED.with(|rays| rays.replace(0)));
+        for (idx, color) in pixels.drain(..) {
+            buffer[idx] = color;
+        }
+        for (sx, sy, color) in colors.drain(..) {
+            floats[(sy * scaled_width + sx) as usize] = color;
+        }
+        for (sx, sy, hit) in tile_hits.drain(..) {
+            first_hits.set(sx, sy, hit);
+        }
+    }
+    if settings.post_processing() {
+        post_process_into(settings, first_hits.gbuffer.as_ref(), &mut floats, buffer, width, height, scaled_width, scaled_height);
+    }
+    if first_hits.passes.colors {
+        first_hits.colors = Some(floats);
+    }
+
+    watchdog.stats(total_tiles, (scaled_width * scaled_height).max(0) as usize)
+}
+
+#[allow(clippy::too_many_arguments)]
 fn render_threaded(
     scene: &Scene,
     camera: &Camera,
@@ -67,128 +837,330 @@ fn render_threaded(
     height: i32,
     scaled_width: i32,
     scaled_height: i32,
-    render_scale: i32,
-    day_time: f32,
-) {
-    use std::sync::{Arc, Mutex};
+    settings: &RenderSettings,
+    first_hits: &mut FirstHits,
+    progress: Option<&Arc<RenderProgress>>,
+    checkpoint: Option<&Arc<Mutex<Checkpoint>>>,
+) -> FrameStats {
     use std::thread;
+    use std::time::Duration;
 
     let num_threads = 4;
-    let buffer = Arc::new(Mutex::new(buffer));
-    let scene = Arc::new(scene.clone());
-    let camera = Arc::new(*camera);
-
-    let rows_per_thread = (scaled_height + num_threads - 1) / num_threads;
+    let settings = *settings;
+    let render_scale = settings.render_scale;
+    let passes = first_hits.passes;
 
-    let mut handles = vec![];
+    let (tiles_x, total_tiles) = tile_grid(scaled_width, scaled_height);
+    let watchdog = Watchdog::new(&settings);
+    let next_tile = AtomicUsize::new(0);
+    if let Some(progress) = progress {
+        progress.total_tiles.store(total_tiles, Ordering::Relaxed);
+    }
 
-    for thread_id in 0..num_threads {
-        let scene = Arc::clone(&scene);
-        let camera = Arc::clone(&camera);
+    // Float colors of the whole frame, kept only for the post passes and the Accumulator
+    let keep_colors = settings.post_processing() || passes.colors;
+    let mut floats = vec![Color::black(); if keep_colors { (scaled_width * scaled_height).max(0) as usize } else { 0 }];
 
-        let start_row = thread_id * rows_per_thread;
-        let end_row = ((thread_id + 1) * rows_per_thread).min(scaled_height);
+    // Tiles finished in a resumed checkpoint go straight into the buffer
+    if let Some(checkpoint) = checkpoint {
+        let checkpoint = checkpoint.lock().unwrap();
+        for tile in (0..total_tiles).filter(|&tile| checkpoint.tile_done(tile)) {
+            let tile_x = (tile as i32 % tiles_x) * TILE_SIZE;
+            let tile_y = (tile as i32 / tiles_x) * TILE_SIZE;
+            for sy in tile_y..(tile_y + TILE_SIZE).min(scaled_height) {
+                for sx in tile_x..(tile_x + TILE_SIZE).min(scaled_width) {
+                    let color = checkpoint.pixel(sx, sy, tile);
+                    if keep_colors {
+                        floats[(sy * scaled_width + sx) as usize] = color;
+                    }
+                    let color = settings.display(color);
+                    for y in (sy * render_scale)..((sy + 1) * render_scale).min(height) {
+                        for x in (sx * render_scale)..((sx + 1) * render_scale).min(width) {
+                            buffer[(y * width + x) as usize] = color;
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(progress) = progress {
+            progress.tiles_done.store(checkpoint.tiles_done(), Ordering::Relaxed);
+            progress.tiles_resumed.store(checkpoint.tiles_done(), Ordering::Relaxed);
+        }
+    }
 
-        let handle = thread::spawn(move || {
+    // The workers borrow the scene for the frame rather than each frame copying the whole world
+    let results = thread::scope(|workers| {
+        let worker = || {
             let mut local_pixels = vec![];
+            let mut local_colors = vec![];
+            let mut tile_colors = vec![];
+            let mut local_hits = vec![];
 
-            for sy in start_row..end_row {
-                for sx in 0..scaled_width {
-                    let u = sx as f32 / scaled_width as f32;
-                    let v = sy as f32 / scaled_height as f32;
-
-                    let ray = camera.get_ray(u, v);
-                    let color = trace_ray(&ray, &scene, 0, day_time);
-
-                    for dy in 0..render_scale {
-                        for dx in 0..render_scale {
-                            let x = sx * render_scale + dx;
-                            let y = sy * render_scale + dy;
-                            if x < width && y < height {
-                                let idx = (y * width + x) as usize;
-                                local_pixels.push((idx, color.to_raylib()));
-                            }
-                        }
+            loop {
+                let tile = next_tile.fetch_add(1, Ordering::Relaxed);
+                if tile >= total_tiles || progress::interrupted() {
+                    break;
+                }
+                if let Some(checkpoint) = checkpoint
+                    && checkpoint.lock().unwrap().tile_done(tile)
+                {
+                    if passes.any() {
+                        tile_first_hits(scene, camera, tile, tiles_x, scaled_width, scaled_height, &settings, passes, &mut local_hits);
                     }
+                    continue;
                 }
-            }
+                let rough = watchdog.exceeded();
+                if rough {
+                    watchdog.rough_tile();
+                }
+                let colors = if checkpoint.is_some() || keep_colors { Some(&mut tile_colors) } else { None };
+                render_tile(
+                    scene,
+                    camera,
+                    tile,
+                    tiles_x,
+                    width,
+                    height,
+                    scaled_width,
+                    scaled_height,
+                    &settings,
+                    rough,
+                    &mut local_pixels,
+                    colors,
+                    passes,
+                    &mut local_hits,
+                );
 
-            local_pixels
-        });
+                // Rough tiles are never checkpointed (offline renders run without a budget anyway)
+                if let Some(checkpoint) = checkpoint
+                    && !rough
+                {
+                    checkpoint.lock().unwrap().add_tile(tile, &tile_colors, settings.motion_blur_samples.max(1));
+                }
+                if keep_colors {
+                    local_colors.append(&mut tile_colors);
+                }
+                tile_colors.clear();
 
-        handles.push(handle);
-    }
+                let rays = RAYS_TRACED.with(|rays| rays.replace(0));
+                watchdog.add_rays(rays);
+                if let Some(progress) = progress {
+                    progress.rays.fetch_add(rays, Ordering::Relaxed);
+                    progress.tiles_done.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+
+            (local_pixels, local_colors, local_hits)
+        };
+        let handles: Vec<_> = (0..num_threads).map(|_| workers.spawn(worker)).collect();
 
-    for handle in handles {
-        if let Ok(pixels) = handle.join() {
-            let mut buffer = buffer.lock().unwrap();
-            for (idx, color) in pixels {
-                buffer[idx] = color;
+        // Offline renders redraw the progress bar while the workers run
+        if let Some(progress) = progress {
+            while !handles.iter().all(|handle| handle.is_finished()) {
+                progress.print();
+                if let Some(checkpoint) = checkpoint {
+                    checkpoint.lock().unwrap().maybe_save();
+                }
+                thread::sleep(Duration::from_millis(200));
             }
+            progress.print();
+        }
+        handles.into_iter().filter_map(|handle| handle.join().ok()).collect::<Vec<_>>()
+    });
+
+    for (pixels, colors, hits) in results {
+        for (idx, color) in pixels {
+            buffer[idx] = color;
         }
+        for (sx, sy, color) in colors {
+            floats[(sy * scaled_width + sx) as usize] = color;
+        }
+        for (sx, sy, hit) in hits {
+            first_hits.set(sx, sy, hit);
+        }
+    }
+    // An interrupted frame has holes the filters would smear, so it's saved as traced
+    if settings.post_processing() && !progress::interrupted() {
+        post_process_into(&settings, first_hits.gbuffer.as_ref(), &mut floats, buffer, width, height, scaled_width, scaled_height);
     }
+    if passes.colors {
+        first_hits.colors = Some(floats);
+    }
+
+    watchdog.stats(total_tiles, (scaled_width * scaled_height).max(0) as usize)
 }
 
-fn trace_ray(ray: &Ray, scene: &Scene, depth: i32, day_time: f32) -> Color {
+/// Traced (linear, unclamped) color seen along one camera ray, for checks outside the frame loop
+pub fn trace(ray: &Ray, scene: &Scene, camera: &Camera, settings: &RenderSettings) -> Color {
+    trace_ray(ray, scene, camera, settings, 0)
+}
+
+// The camera is only needed to pin a screen-mapped backdrop behind the view
+fn trace_ray(ray: &Ray, scene: &Scene, camera: &Camera, settings: &RenderSettings, depth: i32) -> Color {
     if depth >= MAX_DEPTH {
         return Color::black();
     }
+    if settings.shadow_preview {
+        count_ray();
+        return shadow_preview(ray, scene, settings);
+    }
+    shade(ray, scene.intersect(ray), scene, camera, settings, depth)
+}
+
+// Color seen along a ray whose closest hit was already found (None: it hit nothing)
+fn shade(ray: &Ray, hit: Option<Intersection>, scene: &Scene, camera: &Camera, settings: &RenderSettings, depth: i32) -> Color {
+    count_ray();
 
-    if let Some(intersection) = scene.intersect(ray) {
+    if let Some(intersection) = hit {
         let material = &intersection.material;
-        let normal = intersection.normal;
         let hit_point = intersection.position;
+        // Geometric normal offsets secondary rays, the (normal mapped) shading normal lights the surface
+        let geometric_normal = intersection.normal;
+        let normal = material.shading_normal(
+            geometric_normal,
+            intersection.tangent,
+            intersection.bitangent,
+            intersection.u,
+            intersection.v,
+        );
+        // Water waves ripple the surface over time
+        let normal = if material.waves > 0.0 {
+            waves::normal(hit_point, normal, scene.time.elapsed, material.waves)
+        } else {
+            normal
+        };
+        // A strong normal map can tilt past the horizon; then it would be lit from behind
+        let normal = if normal.dot(&ray.direction) < 0.0 { normal } else { geometric_normal };
+
+        // Rain puddles on top faces: standing water is flat, so the normal map fades out under it
+        let puddle = if material.puddles > 0.0 && intersection.front_face && geometric_normal.y > 0.9 {
+            weather::puddle(hit_point, scene.weather.wetness) * material.puddles
+        } else {
+            0.0
+        };
+        let normal = if puddle > 0.0 { (normal * (1.0 - puddle) as Real + geometric_normal * puddle as Real).normalize() } else { normal };
 
-        // Get surface color
-        let surface_color = material.get_color(intersection.u, intersection.v);
+        let cone_width = ray.footprint_at(intersection.t);
+
+        // Leaving a transparent block straight into another one of the same kind (pond water,
+        // stacked glass) isn't a real boundary: carry on inside without bending or reflecting
+        if !intersection.front_face && material.transparency > 0.0 {
+            let probe = Ray::new(hit_point - geometric_normal * 0.001, ray.direction).with_time(ray.time);
+            count_ray();
+            if let Some(next) = scene.intersect(&probe)
+                && next.t < 0.01
+                && next.front_face
+                && next.material.transparency > 0.0
+                && next.material.refractive_index == material.refractive_index
+            {
+                let through = Ray::new(next.position - next.normal * 0.001, ray.direction)
+                    .with_time(ray.time)
+                    .with_cone(cone_width, ray.spread)
+                    .with_medium(ray.absorption);
+                // Counted as a bounce, so the recursion stays bounded however many blocks are stacked
+                return trace_ray(&through, scene, camera, settings, depth + 1) * ray.transmittance(intersection.t);
+            }
+        }
+
+        // Shadow catchers show what's behind them, with their shadows and reflections laid on top
+        if material.shadow_catcher > 0.0 {
+            let through = Ray::new(hit_point - geometric_normal * 0.001, ray.direction)
+                .with_time(ray.time)
+                .with_cone(cone_width, ray.spread)
+                .with_medium(ray.absorption);
+            let (through, _) = skip_shadow_catchers(scene, through);
+            let background = trace_ray(&through, scene, camera, settings, depth);
+            let (layer, coverage) = shadow_catcher_layer(ray, &intersection, scene, camera, settings, depth);
+            return layer + background * (1.0 - coverage);
+        }
+
+        // Get surface color, with the texture mip picked from the ray cone footprint
+        // (tilted surfaces stretch the footprint; clamped so grazing angles don't blur everything)
+        let slant = geometric_normal.dot(&ray.direction).abs().max(0.3);
+        let footprint = cone_width / slant / intersection.uv_size;
+        let surface_color = material.get_color(intersection.u, intersection.v, narrow(footprint), scene.time.elapsed);
+
+        // Shoreline foam: lighter than the water and hiding what's under it
+        let foam = if material.foam > 0.0 && intersection.front_face {
+            shoreline::foam(scene, hit_point, geometric_normal, scene.time.elapsed) * material.foam
+        } else {
+            0.0
+        };
+        let surface_color = surface_color * (1.0 - foam) + palette::FOAM_WHITE * foam;
+        let surface_color = if material.puddles > 0.0 && geometric_normal.y > 0.9 {
+            surface_color * weather::darkening(scene.weather.wetness * material.puddles, puddle)
+        } else {
+            surface_color
+        };
+        let transparency = material.transparency * (1.0 - foam);
 
         // Emissive
-        if material.emissive.r > 0.0 || material.emissive.g > 0.0 || material.emissive.b > 0.0 {
-            return material.emissive;
+        if material.is_emissive() {
+            if ray.bounce {
+                return Color::black(); // Its light was already added as a block light
+            }
+            return material.emissive * ray.transmittance(intersection.t);
         }
 
-        // Ambient lighting - varies with day/night cycle
-        // Day (day_time=0.0): Bright ambient light
-        // Night (day_time=1.0): Very dark ambient light
-        let day_ambient = Color::new(0.45, 0.45, 0.52);
-        let night_ambient = Color::new(0.05, 0.05, 0.08); // Very dark at night
-        let ambient = day_ambient * (1.0 - day_time) + night_ambient * day_time;
+        // Ambient lighting - varies with day/night cycle (or fixed by a lighting preset),
+        // darkened in corners and crevices by ambient occlusion
+        let ambient = if settings.path_tracing {
+            indirect_light(scene, camera, settings, ray, hit_point, geometric_normal, normal, surface_color, depth)
+        } else if depth <= AO_MAX_DEPTH {
+            scene.ambient_light(normal) * ambient_occlusion(scene, hit_point, geometric_normal, ray.time, settings.ao_samples)
+        } else {
+            scene.ambient_light(normal)
+        };
 
         // View direction for specular calculations
         let view_dir = -ray.direction;
 
-        // Sun/moon intensity varies with day/night
-        // During day (day_time=0.0): Full sun intensity
-        // During night (day_time=1.0): Very weak moonlight
-        let celestial_intensity = scene.sun.intensity * (1.0 - day_time * 0.95); // Reduce to 5% at night
+        // Sun by day, the dimmer blue moonlight once the sun is down
+        let (celestial, celestial_intensity) = scene.key_light();
 
         // Diffuse lighting from sun
-        let light_dir = -scene.sun.direction;
-        let diffuse_strength = normal.dot(&light_dir).max(0.0);
-
-        // Shadow check
-        let shadow_ray = Ray::new(hit_point + normal * 0.001, light_dir);
-        let in_shadow = scene.intersect(&shadow_ray).is_some();
+        let light_dir = -celestial.direction;
+        let diffuse_strength = material.diffuse(narrow(normal.dot(&light_dir)));
 
-        let diffuse = if in_shadow {
-            Color::black()
+        // Shadow check (partly lit in the penumbra of a soft shadow)
+        let sun_visible = if diffuse_strength > 0.0 {
+            sun_visibility(scene, celestial, hit_point + geometric_normal * 0.001, ray.time, settings)
         } else {
-            scene.sun.color * (diffuse_strength * celestial_intensity)
+            Color::black()
         };
 
+        let sunlight = celestial.color * sun_visible;
+        let diffuse = sunlight * (diffuse_strength * celestial_intensity);
+
         // Specular lighting from sun (Blinn-Phong)
         let mut specular = Color::black();
-        if !in_shadow && material.specular > 0.0 && diffuse_strength > 0.0 {
+        if is_lit(sun_visible) && material.specular > 0.0 {
+            let halfway = (light_dir + view_dir).normalize();
+            let spec_strength = narrow(normal.dot(&halfway)).max(0.0).powf(material.shininess);
+            specular = sunlight * (material.specular * spec_strength * celestial_intensity);
+        }
+        // Snow crystals: each one mirrors the sun off its own tilted facet (looked up just inside
+        // the surface, so the face plane doesn't flicker between two cells)
+        if is_lit(sun_visible)
+            && let Some(facet) = material.sparkle_normal(hit_point - geometric_normal * 0.01, normal)
+        {
             let halfway = (light_dir + view_dir).normalize();
-            let spec_strength = normal.dot(&halfway).max(0.0).powf(material.shininess);
-            specular = scene.sun.color * (material.specular * spec_strength * celestial_intensity);
+            let glint = narrow(facet.dot(&halfway)).max(0.0).powf(SPARKLE_SHININESS);
+            specular = specular + sunlight * (material.sparkle * glint * SPARKLE_BRIGHTNESS * celestial_intensity);
+        }
+        // Metal flakes (gold, ores): like the crystals, but in the metal's own color and for lamps too
+        let glint_facet = material.glint_normal(hit_point - geometric_normal * 0.01, normal);
+        let glint_color = surface_color * (material.glint * GLINT_BRIGHTNESS);
+        if let Some(facet) = glint_facet
+            && is_lit(sun_visible)
+        {
+            specular = specular + sunlight * glint_color * (glint(facet, light_dir, view_dir) * celestial_intensity);
         }
 
-        // Add point light contributions (diffuse + specular)
+        // Add point and spot light contributions (diffuse + specular)
         let mut point_light_contribution = Color::black();
         let mut point_light_specular = Color::black();
-        for point_light in &scene.point_lights {
-            let (light_direction, light_color) = point_light.illuminate(&hit_point);
+        for (light_position, source_radius, light_direction, light_color) in scene.light_samples(hit_point) {
 
             // Skip if light is too far or has no contribution
             if light_color.r <= 0.0 && light_color.g <= 0.0 && light_color.b <= 0.0 {
@@ -196,79 +1168,315 @@ fn trace_ray(ray: &Ray, scene: &Scene, depth: i32, day_time: f32) -> Color {
             }
 
             // Calculate diffuse strength for this point light
-            let point_diffuse_strength = normal.dot(&light_direction).max(0.0);
-
-            // Shadow check for this point light
-            let point_shadow_ray = Ray::new(hit_point + normal * 0.001, light_direction);
-            let point_in_shadow = if let Some(shadow_hit) = scene.intersect(&point_shadow_ray) {
-                // Check if the shadow hit is closer than the light source
-                let light_distance = (point_light.position - hit_point).length();
-                shadow_hit.t < light_distance
-            } else {
-                false
-            };
+            let point_diffuse_strength = material.diffuse(narrow(normal.dot(&light_direction)));
 
-            if !point_in_shadow && point_diffuse_strength > 0.0 {
+            // Shadow check for this point light (skipped for surfaces facing away from it).
+            if point_diffuse_strength <= 0.0 {
+                continue;
+            }
+            let light_color = light_color
+                * light_visibility(scene, hit_point + geometric_normal * 0.001, light_position, source_radius, ray.time, settings);
+
+            if is_lit(light_color) {
                 // Diffuse contribution
                 point_light_contribution = point_light_contribution + light_color * point_diffuse_strength;
 
                 // Specular contribution (Blinn-Phong)
                 if material.specular > 0.0 {
                     let halfway = (light_direction + view_dir).normalize();
-                    let spec_strength = normal.dot(&halfway).max(0.0).powf(material.shininess);
+                    let spec_strength = narrow(normal.dot(&halfway)).max(0.0).powf(material.shininess);
                     point_light_specular = point_light_specular + light_color * (material.specular * spec_strength);
                 }
+                if let Some(facet) = glint_facet {
+                    point_light_specular = point_light_specular + light_color * glint_color * glint(facet, light_direction, view_dir);
+                }
             }
         }
 
-        let mut color = (ambient + diffuse + point_light_contribution) * surface_color + specular + point_light_specular;
+        let local_color = (ambient + diffuse + point_light_contribution) * surface_color + specular + point_light_specular;
+
+        // Fresnel (Schlick) from the view angle: more mirror-like toward grazing angles.
+        // The light is split three ways: reflected, refracted (transparency of the rest) and the surface itself.
+        let cos_theta = narrow(view_dir.dot(&normal)).clamp(0.0, 1.0);
+        let fresnel = material.fresnel(cos_theta, intersection.front_face).max(weather::puddle_reflectance(cos_theta, puddle));
+        let transmitted = (1.0 - fresnel) * transparency;
+        let mut color = local_color * ((1.0 - fresnel) * (1.0 - transparency));
 
-        // Calculate Fresnel effect for more realistic reflections (especially for water)
-        let cos_theta = view_dir.dot(&normal).abs().max(0.0).min(1.0);
-        
-        // Schlick's approximation for Fresnel reflectance
-        let r0 = if material.refractive_index > 1.0 {
-            ((1.0 - material.refractive_index) / (1.0 + material.refractive_index)).powi(2)
+        let refract_color = if transmitted > 0.0 {
+            // Entering goes from air into the material, leaving (front_face = false) the other way round
+            let eta = if intersection.front_face { 1.0 / material.refractive_index } else { material.refractive_index };
+            ray.direction.refract(&normal, eta as Real).map(|refract_dir| {
+                let refract_dir = frost(refract_dir, material.frost, geometric_normal);
+                // Going in, the ray picks up the material's absorption; coming out it's back in clear air
+                let medium = if intersection.front_face { material.absorption } else { Color::black() };
+                let refract_ray = Ray::new(hit_point - geometric_normal * 0.001, refract_dir)
+                    .with_time(ray.time)
+                    .with_cone(cone_width, ray.spread)
+                    .with_medium(medium);
+                trace_ray(&refract_ray, scene, camera, settings, depth + 1)
+            })
         } else {
-            0.04 // Default for non-refractive materials
+            None
         };
-        let fresnel = r0 + (1.0 - r0) * (1.0 - cos_theta).powi(5);
+        // Whatever still fails to refract (the shading normal tilted past the critical angle) is reflected too
+        let reflect_weight = if refract_color.is_some() { fresnel } else { fresnel + transmitted };
 
-        // Reflection (enhanced with Fresnel for transparent materials)
-        if material.reflectivity > 0.0 || material.transparency > 0.0 {
+        if reflect_weight > 0.0 {
             let reflect_dir = ray.direction.reflect(&normal);
-            let reflect_ray = Ray::new(hit_point + normal * 0.001, reflect_dir);
-            let reflect_color = trace_ray(&reflect_ray, scene, depth + 1, day_time);
+            let reflect_ray = Ray::new(hit_point + geometric_normal * 0.001, reflect_dir)
+                .with_time(ray.time)
+                .with_cone(cone_width, ray.spread)
+                .with_medium(ray.absorption); // Reflections stay on the side they came from
+            color = color + trace_ray(&reflect_ray, scene, camera, settings, depth + 1) * reflect_weight;
+        }
+        if let Some(refract_color) = refract_color {
+            color = color + refract_color * transmitted;
+        }
 
-            // Use Fresnel for transparent materials, otherwise use base reflectivity
-            let effective_reflectivity = if material.transparency > 0.0 {
-                fresnel.max(material.reflectivity)
-            } else {
-                material.reflectivity
-            };
+        // Beer-Lambert: whatever the ray found is dimmed by the distance it travelled through water/glass.
+        // Left unclamped: bright highlights are squeezed into the screen range by the tone map.
+        color * ray.transmittance(intersection.t)
+    } else if ray.bounce {
+        // Sky light at the ambient level, so open ground matches the Whitted look
+        scene.sky_light(ray.direction)
+    } else if let Some(color) = scene.backdrop.as_ref().and_then(|backdrop| backdrop.sample(ray, camera)) {
+        color
+    } else {
+        // Sky - use the scene clock for skybox texture blending
+        // Pass sun parameters so the skybox can render a visible sun disk
+        scene.skybox.sample(ray, &scene.time, -scene.sun.direction, -scene.moon.direction, scene.sun.color, scene.sun.intensity)
+    }
+}
 
-            color = color * (1.0 - effective_reflectivity) + reflect_color * effective_reflectivity;
+// Shadow preview: how lit the first surface is, in gray. Every light that faces it counts fully
+// (no falloff, colors or materials), so white = reached by some light, black = in shadow of all
+// of them and grays = penumbra or light filtered through glass/water. Lamps and the sky stand out.
+fn shadow_preview(ray: &Ray, scene: &Scene, settings: &RenderSettings) -> Color {
+    let Some(intersection) = scene.intersect(ray) else {
+        return Color::new(0.35, 0.5, 0.75); // Flat blue, so open sky doesn't read as lit ground
+    };
+    if intersection.material.is_emissive() {
+        return Color::new(1.0, 0.8, 0.2); // Lamp blocks in yellow, to see where the lights sit
+    }
+    let normal = intersection.normal;
+    let origin = intersection.position + normal * 0.001;
+    let gray = |visible: Color| (visible.r + visible.g + visible.b) / 3.0;
+
+    let mut lit = 0.0f32;
+    let (celestial, _) = scene.key_light();
+    if normal.dot(&-celestial.direction) > 0.0 {
+        lit = gray(sun_visibility(scene, celestial, origin, ray.time, settings));
+    }
+    for (light_position, source_radius, light_direction, light_color) in scene.light_samples(intersection.position) {
+        if lit >= 1.0 {
+            break;
         }
+        if !is_lit(light_color) || normal.dot(&light_direction) <= 0.0 {
+            continue;
+        }
+        lit = lit.max(gray(light_visibility(scene, origin, light_position, source_radius, ray.time, settings)));
+    }
+    Color::new(lit, lit, lit)
+}
+
+// Share of the sun's (or moon's) light reaching a point, per channel: one shadow ray toward a
+// point-like light, otherwise `shadow_samples` rays jittered across its disk, for penumbrae at shadow edges
+fn sun_visibility(scene: &Scene, sun: &DirectionalLight, origin: Vec3, time: f32, settings: &RenderSettings) -> Color {
+    // Path tracing averages many paths anyway, so one jittered ray per hit is enough there
+    let samples = if sun.angular_radius > Degrees(0.0) && !settings.path_tracing { settings.shadow_samples.max(1) } else { 1 };
+    let total = (0..samples).fold(Color::black(), |total, _| {
+        let direction = if samples == 1 && !settings.path_tracing { -sun.direction } else { sun.sample_toward() };
+        total + shadow_transmission(scene, origin, direction, time, Real::INFINITY, None)
+    });
+    total * (1.0 / samples as f32)
+}
+
+// Same as sun_visibility for a point or spot light: one shadow ray to its center, or `shadow_samples`
+// rays to random points of its glowing sphere for penumbrae around lantern-lit blocks
+fn light_visibility(scene: &Scene, origin: Vec3, position: Vec3, source_radius: Real, time: f32, settings: &RenderSettings) -> Color {
+    let samples = if source_radius > 0.0 && !settings.path_tracing { settings.shadow_samples.max(1) } else { 1 };
+    let total = (0..samples).fold(Color::black(), |total, _| {
+        let target = if samples == 1 && !settings.path_tracing { position } else { sample_sphere(position, source_radius, origin) };
+        let toward = target - origin;
+        total + shadow_transmission(scene, origin, toward.normalize(), time, toward.length(), Some(position))
+    });
+    total * (1.0 / samples as f32)
+}
+
+// Whether a shadow ray's hit is on the glowing cube holding the light at `lamp`: the cube behind
+// the hit face (uv_size across) has the light inside it
+fn around_lamp(hit: &Intersection, lamp: Vec3) -> bool {
+    let half = hit.uv_size * 0.5;
+    let outward = if hit.front_face { hit.normal } else { -hit.normal };
+    let offset = lamp - (hit.position - outward * half);
+    hit.material.is_emissive() && offset.x.abs().max(offset.y.abs()).max(offset.z.abs()) <= half + 0.001
+}
+
+// Light that makes it from `origin` to a light `distance` away (infinite for the sun), per channel.
+// Opaque blocks stop it; glass and water let it through tinted by their color x transparency on
+// the way in and dimmed by their absorption inside, so the pond casts a colored, partial shadow.
+// A lamp's own glowing block is see-through for the light at `lamp` inside it; other emissive
+// blocks cast shadows like any other.
+fn shadow_transmission(scene: &Scene, origin: Vec3, direction: Vec3, time: f32, distance: Real, lamp: Option<Vec3>) -> Color {
+    let mut filter = Color::white();
+    let mut ray = Ray::new(origin, direction).with_time(time);
+    let mut remaining = distance;
+    let mut left_volume: Option<f32> = None; // IOR of the transparent block the ray just came out of
+    for _ in 0..MAX_SHADOW_LAYERS {
+        count_ray();
+        let Some(hit) = scene.intersect(&ray) else {
+            return filter;
+        };
+        let travelled = (hit.position - ray.origin).length();
+        if travelled >= remaining {
+            return filter;
+        }
+        let material = &hit.material;
+        let passes = material.shadow_catcher > 0.0 || lamp.is_some_and(|lamp| around_lamp(&hit, lamp));
+        if !passes {
+            if material.transparency <= 0.0 {
+                return Color::black();
+            }
+            if hit.front_face {
+                // Straight from one block into a neighbour of the same kind is one volume: tint once
+                let joined = left_volume == Some(material.refractive_index) && travelled < 0.01;
+                if !joined {
+                    let surface = material.get_color(hit.u, hit.v, 0.0, scene.time.elapsed);
+                    filter = filter * surface * material.transparency;
+                }
+                left_volume = None;
+            } else {
+                // Coming out the far side: absorbed over the way through
+                let inside = Ray::new(ray.origin, direction).with_medium(material.absorption);
+                filter = filter * inside.transmittance(travelled);
+                left_volume = Some(material.refractive_index);
+            }
+            if !is_lit(filter) {
+                return Color::black();
+            }
+        }
+        remaining -= travelled;
+        ray = Ray::new(hit.position - hit.normal * 0.001, direction).with_time(time);
+    }
+    filter
+}
 
-        // Refraction
-        if material.transparency > 0.0 {
-            let eta = 1.0 / material.refractive_index;
-            if let Some(refract_dir) = ray.direction.refract(&normal, eta) {
-                let refract_ray = Ray::new(hit_point - normal * 0.001, refract_dir);
-                let refract_color = trace_ray(&refract_ray, scene, depth + 1, day_time);
+// Path tracing: light arriving from everything around a point (sky, and whatever nearby surfaces
+// reflect, like the pink leaves onto the ground), from one cosine-weighted diffuse bounce.
+// Next-event estimation already gave the sun and lights, so the bounce skips them. After a few
+// bounces paths are ended at random (Russian roulette) and the survivors weighted up to stay unbiased.
+#[allow(clippy::too_many_arguments)]
+fn indirect_light(
+    scene: &Scene,
+    camera: &Camera,
+    settings: &RenderSettings,
+    ray: &Ray,
+    point: Vec3,
+    geometric_normal: Vec3,
+    normal: Vec3,
+    albedo: Color,
+    depth: i32,
+) -> Color {
+    let survival = if depth >= ROULETTE_DEPTH { albedo.r.max(albedo.g).max(albedo.b).clamp(0.1, 0.9) } else { 1.0 };
+    if depth + 1 >= MAX_DEPTH || random_f32() >= survival {
+        return Color::black();
+    }
+    let helper = if normal.y.abs() < 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+    let tangent = normal.cross(&helper).normalize();
+    let bitangent = normal.cross(&tangent);
+    let (radius, angle) = (random_f32().sqrt() as Real, TAU * random_f32() as Real);
+    let height = (1.0 - radius * radius).max(0.0).sqrt();
+    let direction = tangent * (radius * angle.cos()) + bitangent * (radius * angle.sin()) + normal * height;
+    if direction.dot(&geometric_normal) <= 0.0 {
+        return Color::black(); // The normal map tilted it into the block
+    }
+    // Cosine-weighted directions cancel the cosine of the diffuse surface, so the sample is used as is
+    let bounce = Ray::new(point + geometric_normal * 0.001, direction)
+        .with_time(ray.time)
+        .with_medium(ray.absorption)
+        .as_bounce();
+    trace_ray(&bounce, scene, camera, settings, depth + 1) * (1.0 / survival)
+}
 
-                // Blend refraction with existing color (accounting for Fresnel in reflection above)
-                let refract_amount = material.transparency * (1.0 - fresnel);
-                color = color * (1.0 - refract_amount) + refract_color * refract_amount;
+// Share of the sky above a point that isn't blocked by nearby blocks (1.0 = fully open).
+// Cosine-weighted rays over the hemisphere; a hit right next to the point blocks fully,
+// one almost AO_RADIUS away barely, so corners fade in smoothly like Minecraft's smooth lighting.
+fn ambient_occlusion(scene: &Scene, point: Vec3, normal: Vec3, time: f32, samples: u32) -> f32 {
+    if samples == 0 {
+        return 1.0;
+    }
+    let helper = if normal.y.abs() < 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+    let tangent = normal.cross(&helper).normalize();
+    let bitangent = normal.cross(&tangent);
+    let origin = point + normal * 0.001;
+
+    let mut occlusion = 0.0;
+    for _ in 0..samples {
+        let (radius, angle) = (random_f32().sqrt() as Real, TAU * random_f32() as Real);
+        let height = (1.0 - radius * radius).max(0.0).sqrt();
+        let direction = tangent * (radius * angle.cos()) + bitangent * (radius * angle.sin()) + normal * height;
+        count_ray();
+        if let Some(hit) = scene.intersect(&Ray::new(origin, direction).with_time(time)) {
+            let material = &hit.material;
+            if hit.t < AO_RADIUS && material.shadow_catcher <= 0.0 && !material.is_emissive() {
+                // Glass and water only block the share of light they don't let through
+                occlusion += narrow(1.0 - hit.t / AO_RADIUS) * (1.0 - material.transparency);
             }
         }
+    }
+    1.0 - occlusion / samples as f32
+}
 
-        color.clamp()
+// Frosted surfaces (ice) scatter refracted rays randomly around the sharp direction; a scattered
+// ray that would turn back out of the surface keeps the sharp one
+fn frost(direction: Vec3, roughness: f32, geometric_normal: Vec3) -> Vec3 {
+    if roughness <= 0.0 {
+        return direction;
+    }
+    let jitter = || (random_f32() - 0.5) as Real * 2.0 * roughness as Real;
+    let scattered = (direction + Vec3::new(jitter(), jitter(), jitter())).normalize();
+    if scattered.dot(&geometric_normal) < 0.0 { scattered } else { direction }
+}
+
+// How brightly a metal flake with this facet normal mirrors the light toward the viewer
+fn glint(facet: Vec3, light_dir: Vec3, view_dir: Vec3) -> f32 {
+    let halfway = (light_dir + view_dir).normalize();
+    narrow(facet.dot(&halfway)).max(0.0).powf(GLINT_SHININESS)
+}
+
+// Whether any light gets through a shadow filter
+fn is_lit(filter: Color) -> bool {
+    filter.r.max(filter.g).max(filter.b) > 0.001
+}
+
+// What a shadow catcher lays over the background at a hit: black at its opacity where the sun
+// is blocked, and the blocks it reflects at its reflectivity (premultiplied color, coverage)
+fn shadow_catcher_layer(ray: &Ray, intersection: &Intersection, scene: &Scene, camera: &Camera, settings: &RenderSettings, depth: i32) -> (Color, f32) {
+    let material = &intersection.material;
+    let normal = intersection.normal;
+    let hit_point = intersection.position;
+
+    let (celestial, _) = scene.key_light();
+    let shadow = if normal.dot(&-celestial.direction) > 0.0 {
+        let visible = sun_visibility(scene, celestial, hit_point + normal * 0.001, ray.time, settings);
+        material.shadow_catcher * (1.0 - (visible.r + visible.g + visible.b) / 3.0)
     } else {
-        // Sky - use actual day_time for skybox texture blending
-        // Pass sun parameters so the skybox can render a visible sun disk
-        scene.skybox.sample(ray, day_time, -scene.sun.direction, scene.sun.color, scene.sun.intensity)
+        0.0
+    };
+
+    // Only reflections of actual blocks count; the reflected sky stays transparent
+    let mut layer = Color::black();
+    let mut reflection = 0.0;
+    if material.reflectivity > 0.0 && depth + 1 < MAX_DEPTH {
+        let reflect_ray = Ray::new(hit_point + normal * 0.001, ray.direction.reflect(&normal))
+            .with_time(ray.time)
+            .with_cone(ray.footprint_at(intersection.t), ray.spread);
+        if skip_shadow_catchers(scene, reflect_ray).1.is_some() {
+            layer = trace_ray(&reflect_ray, scene, camera, settings, depth + 1) * material.reflectivity;
+            reflection = material.reflectivity;
+        }
     }
+    (layer, reflection + shadow * (1.0 - reflection))
 }
 
 // Copy trait for Camera
@@ -279,29 +1487,45 @@ impl Clone for Camera {
     }
 }
 
-// Clone trait for Scene (needed for threading)
+// Clone trait for Scene (a copy of the whole world; the render threads borrow the scene instead)
 impl Clone for Scene {
     fn clone(&self) -> Self {
         Self {
-            cubes: self.cubes.iter().map(|c| c.clone()).collect(),
+            materials: self.materials.clone(),
+            entities: self.entities.clone(),
+            smoke: self.smoke.clone(),
+            swarms: self.swarms.clone(),
+            petal_bursts: self.petal_bursts.clone(),
+            critters: self.critters,
+            particles: self.particles.clone(),
+            critter_lights: self.critter_lights.clone(),
             meshes: self.meshes.iter().map(|m| m.clone()).collect(),
             sun: self.sun.clone(),
+            moon: self.moon.clone(),
             point_lights: self.point_lights.iter().map(|l| l.clone()).collect(),
+            spot_lights: self.spot_lights.clone(),
+            block_lights: self.block_lights.clone(),
+            lamps_on: self.lamps_on,
             skybox: self.skybox.clone(),
+            backdrop: self.backdrop.clone(),
+            time: self.time,
+            weather: self.weather,
+            events: self.events.clone(),
+            grid: self.grid.clone(),
+            streaming: self.streaming.clone(),
+            viewpoints: self.viewpoints.clone(),
+            lighting: self.lighting.clone(),
+            sun_pin: self.sun_pin,
+            origin: self.origin,
         }
     }
 }
 
+// Copy trait for Cube (materials are palette ids)
+impl Copy for crate::cube::Cube {}
 impl Clone for crate::cube::Cube {
     fn clone(&self) -> Self {
-        Self {
-            position: self.position,
-            size: self.size,
-            material: self.material.clone(),
-            top_material: self.top_material.clone(),
-            side_material: self.side_material.clone(),
-            bottom_material: self.bottom_material.clone(),
-        }
+        *self
     }
 }
 
@@ -310,6 +1534,7 @@ impl Clone for crate::obj_loader::Mesh {
         Self {
             triangles: self.triangles.iter().map(|t| t.clone()).collect(),
             position: self.position,
+            previous_position: self.previous_position,
             scale: self.scale,
             material: self.material.clone(),
         }
@@ -327,12 +1552,13 @@ impl Clone for crate::obj_loader::Triangle {
     }
 }
 
-impl Clone for crate::light::DirectionalLight {
+impl Clone for DirectionalLight {
     fn clone(&self) -> Self {
         Self {
             direction: self.direction,
             color: self.color,
             intensity: self.intensity,
+            angular_radius: self.angular_radius,
         }
     }
 }
@@ -352,6 +1578,105 @@ impl Clone for crate::skybox::Skybox {
             bottom_night: self.bottom_night.clone(),
             front_night: self.front_night.clone(),
             back_night: self.back_night.clone(),
+            moon: self.moon.clone(),
+            cloud_coverage: self.cloud_coverage,
+            environment: self.environment.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::Cube;
+    use crate::material::Material;
+
+    const SIZE: i32 = 24;
+
+    #[test]
+    fn the_gbuffer_matches_the_camera_rays() {
+        let mut scene = Scene::new();
+        let stone = scene.materials.add(Material::new(palette::STONE_GRAY));
+        for x in -3..=3 {
+            scene.grid.add(Cube::new(Vec3::new(x as Real, 0.0, -(x * x) as Real), 1.0, stone));
         }
+        scene.update_grid();
+        scene.entities.push(Cube::new(Vec3::new(0.5, 1.5, 0.0), 0.6, stone));
+        let camera = Camera::new(Vec3::new(0.0, 4.0, 8.0), Vec3::new(0.0, 0.0, -2.0), Degrees(60.0), 1.0);
+
+        for use_threading in [false, true] {
+            // Packets (one ray per pixel) and the per-pixel path (supersampled)
+            for aa_samples in [1, 4] {
+                let settings = RenderSettings { render_scale: 1, use_threading, aa_samples, ..RenderSettings::default() };
+                let mut buffer = vec![raylib::prelude::Color::BLACK; (SIZE * SIZE) as usize];
+                let (_, _, gbuffer) = render_scene_with_colors(&scene, &camera, &mut buffer, SIZE, SIZE, &settings, true);
+                let gbuffer = gbuffer.unwrap();
+                let mut hits = 0;
+                for sy in 0..SIZE {
+                    for sx in 0..SIZE {
+                        let ray = camera.get_pinhole_ray(sx as f32 / SIZE as f32, sy as f32 / SIZE as f32);
+                        let depth = scene.intersect(&ray).map_or(Real::INFINITY, |hit| hit.t);
+                        assert_eq!(gbuffer.depth()[(sy * SIZE + sx) as usize], depth, "pixel ({}, {})", sx, sy);
+                        hits += depth.is_finite() as usize;
+                    }
+                }
+                assert!(hits > 0 && hits < (SIZE * SIZE) as usize);
+            }
+        }
+    }
+
+    #[test]
+    fn the_accumulator_averages_before_tone_mapping() {
+        let scene = Scene::new();
+        let camera = Camera::new(Vec3::new(0.0, 4.0, 8.0), Vec3::zero(), Degrees(60.0), 1.0);
+        let settings = RenderSettings::default();
+        let (width, height) = (4 * settings.render_scale, 2 * settings.render_scale);
+        let mut buffer = vec![raylib::prelude::Color::BLACK; (width * height) as usize];
+        let mut accumulator = Accumulator::new();
+        // Too bright to display, then black: the average is the color half as bright, not clamped white halved
+        for color in [Color::new(4.0, 2.0, 1.0), Color::black()] {
+            accumulator.add(&mut buffer, &[color; 8], &scene, &camera, &settings, width, height);
+        }
+        assert_eq!(accumulator.frames(), 2);
+        let expected = settings.display(Color::new(2.0, 1.0, 0.5));
+        assert!(buffer.iter().all(|&pixel| pixel == expected));
+    }
+
+    #[test]
+    fn only_the_lamps_own_block_lets_its_light_through() {
+        let mut scene = Scene::new();
+        let glow = scene.materials.add(Material::new(palette::GLOWSTONE_YELLOW).with_emissive(palette::GLOWSTONE_YELLOW));
+        let lamp = Vec3::new(0.0, 3.0, 0.0);
+        scene.grid.add(Cube::new(lamp, 0.4, glow));
+        scene.update_grid();
+        let settings = RenderSettings::default();
+        let visibility = |scene: &Scene| light_visibility(scene, Vec3::zero(), lamp, 0.0, 0.0, &settings).r;
+        assert_eq!(visibility(&scene), 1.0);
+
+        // Another glowing block in the way shadows it
+        scene.grid.add(Cube::new(Vec3::new(0.0, 1.5, 0.0), 0.4, glow));
+        scene.update_grid();
+        assert_eq!(visibility(&scene), 0.0);
+    }
+
+    #[test]
+    fn checkpoints_of_edited_blocks_dont_match() {
+        let mut scene = Scene::new();
+        let stone = scene.materials.add(Material::new(palette::STONE_GRAY));
+        let glow = scene.materials.add(Material::new(palette::GLOWSTONE_YELLOW).with_emissive(palette::GLOWSTONE_YELLOW));
+        scene.grid.add(Cube::new(Vec3::new(0.0, 0.0, 0.0), 1.0, stone));
+        scene.update_grid();
+        let camera = Camera::new(Vec3::new(0.0, 4.0, 8.0), Vec3::zero(), Degrees(60.0), 1.0);
+        let settings = RenderSettings::default();
+        let key = |scene: &Scene| render_key(scene, &camera, &settings, SIZE, SIZE);
+        let before = key(&scene);
+
+        // Same number of blocks, another material
+        scene.grid.get_mut((0, 0, 0)).unwrap().material = glow;
+        scene.update_grid();
+        assert_ne!(key(&scene), before);
+        scene.grid.get_mut((0, 0, 0)).unwrap().material = stone;
+        scene.update_grid();
+        assert_eq!(key(&scene), before);
     }
 }
@@ -1,70 +1,143 @@
+use crate::backdrop::{Backdrop, BackdropMapping};
+use crate::chunk::CHUNK_SIZE;
 use crate::color::Color;
 use crate::cube::Cube;
 use crate::intersection::Intersection;
-use crate::light::DirectionalLight;
+use crate::light::{DirectionalLight, GLOWSTONE_LUMENS, LANTERN_LUMENS, MOONLIGHT_LUX, NOON_SUN_LUX, SpotLight, SunAngles, lumens, lux};
+use crate::lighting::LightingPreset;
 use crate::material::Material;
+use crate::material_palette::MaterialPalette;
 use crate::obj_loader::Mesh;
+use crate::palette;
+use crate::particles::{PetalBurst, SmokeEmitter, Swarm};
 use crate::point_light::PointLight;
 use crate::ray::Ray;
-use crate::skybox::Skybox;
-use crate::texture::Texture;
-use crate::utils::Vec3;
+use crate::scene_events::{EventAction, EventScheduler};
+use crate::scene_time::SceneTime;
+use crate::skybox::{self, Skybox};
+use crate::streaming::ChunkStreamer;
+use crate::texture::{TextureAnimation, TextureFilter};
+use crate::texture_cache;
+use crate::utils::{PI, Real, Vec3, narrow};
+use crate::viewpoint::Viewpoint;
+use crate::weather::Weather;
+use crate::voxel_grid::VoxelGrid;
+
+// Point light intensity of a fully glowing 1x1x1 block (emissive 1.0); smaller or dimmer blocks scale down
+const BLOCK_LIGHT_STRENGTH: f32 = lumens(GLOWSTONE_LUMENS);
+const MIN_BLOCK_LIGHT: f32 = lumens(2.0);
+
+// Floating origin: f32 positions get coarse far from (0,0,0) (about 1/1000 of a block at 10k blocks,
+// the size of the shadow ray offsets), so render space is moved back near the camera past this distance
+const RECENTER_DISTANCE: Real = 512.0;
+const RECENTER_STEP: Real = CHUNK_SIZE as Real; // The origin moves in whole chunks, so blocks and chunk borders stay aligned
 
 pub struct Scene {
-    pub cubes: Vec<Cube>,
+    pub materials: MaterialPalette, // Every material the cubes (static, entities and particles) refer to by id
+    pub entities: Vec<Cube>, // Dynamic cubes rebuilt every frame (player model, moving things)
+    pub smoke: Vec<SmokeEmitter>, // Chimneys and other smoke sources (particles.rs)
+    pub swarms: Vec<Swarm>, // Butterflies by the trees, fireflies over the pond (particles.rs)
+    pub critters: bool, // The swarms come out (scene file `critters = false` keeps them away)
+    pub petal_bursts: Vec<PetalBurst>, // Petals still falling from "petals" events
+    pub particles: Vec<Cube>, // Smoke puffs and critters at the current time, rebuilt by update_particles
+    pub critter_lights: Vec<PointLight>, // Glowing critters' lights, rebuilt with the particles
     pub meshes: Vec<Mesh>,
     pub sun: DirectionalLight,
+    pub moon: DirectionalLight, // Opposite the sun, lights the day/night cycle's nights (Scene::key_light)
     pub point_lights: Vec<PointLight>,
+    pub spot_lights: Vec<SpotLight>,
+    pub block_lights: Vec<PointLight>, // Implicit lights of glowing blocks, collected by update_grid
+    pub lamps_on: bool, // The scene's own point and spot lights shine (events switch them at dusk and dawn)
     pub skybox: Skybox,
+    pub backdrop: Option<Backdrop>, // Image shown instead of the skybox (scene file [backdrop])
+    pub time: SceneTime,
+    pub weather: Weather, // Rain and ground wetness, for puddles on materials that take them
+    pub events: EventScheduler, // Time-based triggers (scene file [[events]]), run by update
+    pub grid: VoxelGrid, // The static cubes, stored in 16x16x16 chunks (update_grid after changing them)
+    pub streaming: Option<ChunkStreamer>, // Procedural hills loaded around the camera (scene file [streaming])
+    pub viewpoints: Vec<Viewpoint>, // Named cameras from the scene file (C cycles through them)
+    pub lighting: Option<LightingPreset>, // Replaces the day/night cycle's lighting while set (G cycles)
+    pub sun_pin: Option<SunAngles>, // Sun placed by dragging on the sky (F7), overrides the clock's or preset's sun direction
+    pub origin: Vec3, // World position of render space's (0,0,0); everything above is stored relative to it
 }
 
 impl Scene {
     pub fn new() -> Self {
         Self {
-            cubes: Vec::new(),
+            materials: MaterialPalette::new(),
+            entities: Vec::new(),
+            smoke: Vec::new(),
+            swarms: Vec::new(),
+            critters: true,
+            petal_bursts: Vec::new(),
+            particles: Vec::new(),
+            critter_lights: Vec::new(),
             meshes: Vec::new(),
             // Sun direction points downward at 45° angle (will be negated in renderer)
             // When negated: points up and to the right at 45°, lighting both tops and sides
-            sun: DirectionalLight::sun(Vec3::new(-1.0, -1.0, -0.5).normalize(), 1.2),
+            sun: DirectionalLight::sun(Vec3::new(-1.0, -1.0, -0.5).normalize(), lux(NOON_SUN_LUX)),
+            moon: DirectionalLight::moon(Vec3::new(1.0, -1.0, 0.5).normalize(), 0.0),
             point_lights: Vec::new(),
+            spot_lights: Vec::new(),
+            block_lights: Vec::new(),
+            lamps_on: true,
             skybox: Skybox::new(),
+            backdrop: None,
+            time: SceneTime::new(),
+            weather: Weather::new(),
+            events: EventScheduler::new(),
+            grid: VoxelGrid::new(),
+            streaming: None,
+            viewpoints: Vec::new(),
+            lighting: None,
+            sun_pin: None,
+            origin: Vec3::zero(),
         }
     }
 
-    pub fn build_cherry_tree_diorama(&mut self) {
+    /// The bundled diorama (ground, trees, pond, house, axolotl), moved by `offset`
+    pub fn build_cherry_tree_diorama(&mut self, offset: Vec3) {
         // === ADD DIRT LAYER UNDER GRASS ===
         // Create dirt blocks underneath the entire diorama
-        let dirt_mat = Material::new(Color::new(0.4, 0.3, 0.2))
-            .with_texture(Texture::load("assets/textures/dirt.jpg"));
+        let dirt_mat = self.materials.add(
+            Material::new(palette::DIRT_BROWN)
+                .with_texture(texture_cache::load("assets/textures/dirt.jpg")),
+        );
 
         for x in -10..10 {
             for z in -15..6 {
-                self.cubes.push(Cube::new(
-                    Vec3::new(x as f32, -1.5, z as f32),
+                self.grid.add(Cube::new(
+                    offset + Vec3::new(x as Real, -1.5, z as Real),
                     1.0,
-                    dirt_mat.clone(),
+                    dirt_mat,
                 ));
             }
         }
 
         // Create ground plane with grass blocks (different textures per face)
         // Asymmetric: more grass in front of house (negative z), less behind axolotl (positive z)
-        for x in -10..10 {
-            for z in -15..6 {
-                // Top face: grass texture
-                let grass_top = Material::new(Color::new(0.3, 0.7, 0.3))
-                    .with_texture(Texture::load("assets/textures/grass.jpg"));
+        // Top face: grass texture
+        let grass_top = self.materials.add(
+            Material::new(palette::GRASS_GREEN)
+                .with_texture(texture_cache::load("assets/textures/grass.jpg")),
+        );
 
-                // Side faces: grass side texture
-                let grass_side = Material::new(Color::new(0.5, 0.6, 0.4))
-                    .with_texture(Texture::load("assets/textures/grass_side.jpg"));
+        // Side faces: grass side texture
+        let grass_side = self.materials.add(
+            Material::new(palette::GRASS_SIDE_GREEN)
+                .with_texture(texture_cache::load("assets/textures/grass_side.jpg")),
+        );
 
-                // Bottom face: dirt texture (use grass_side as fallback if dirt doesn't exist)
-                let grass_bottom = Material::new(Color::new(0.4, 0.3, 0.2))
-                    .with_texture(Texture::load("assets/textures/grass_side.jpg"));
+        // Bottom face: dirt texture (use grass_side as fallback if dirt doesn't exist)
+        let grass_bottom = self.materials.add(
+            Material::new(palette::DIRT_BROWN)
+                .with_texture(texture_cache::load("assets/textures/grass_side.jpg")),
+        );
 
-                self.cubes.push(Cube::new_multi_texture(
-                    Vec3::new(x as f32, -0.5, z as f32),
+        for x in -10..10 {
+            for z in -15..6 {
+                self.grid.add(Cube::new_multi_texture(
+                    offset + Vec3::new(x as Real, -0.5, z as Real),
                     1.0,
                     grass_top,
                     grass_side,
@@ -74,14 +147,18 @@ impl Scene {
         }
 
         // Build cherry trees
-        self.build_cherry_tree(0.0, -1.0);  // Original tree at the center
-        self.build_cherry_tree(7.0, -4.0);  // Second tree behind the pond
+        self.build_cherry_tree(offset + Vec3::new(0.0, 0.0, -1.0)); // Original tree at the center
+        self.build_cherry_tree(offset + Vec3::new(7.0, 0.0, -4.0)); // Second tree behind the pond
 
         // === BUILD CEMENT SIDEWALK NEAR HOUSE ===
-        let stone_mat = Material::new(Color::new(0.6, 0.6, 0.6))
-            .with_texture(Texture::load("assets/textures/stone.jpg"))
-            .with_reflectivity(0.02)
-            .with_specular(0.2, 16.0);  // Dull, soft highlights on stone
+        let stone_mat = self.materials.add(
+            Material::new(palette::STONE_GRAY)
+                .with_texture(texture_cache::load("assets/textures/stone.jpg"))
+                .with_normal_map(texture_cache::load_normal_map("assets/textures/stone_normal.png"), 1.0)
+                .with_reflectivity(0.02)
+                .with_specular(0.2, 16.0)  // Dull, soft highlights on stone
+                .with_puddles(1.0), // Rain pools on the paths
+        );
 
         // House is at x=-10 to -4, z=-10 to -4
         // Create sidewalk around the house (2 blocks wide)
@@ -89,10 +166,10 @@ impl Scene {
         // Front sidewalk (along z = -10 side, extending to grass edge)
         for x in -12..=10 {
             for z in -14..=-11 {
-                self.cubes.push(Cube::new(
-                    Vec3::new(x as f32, 0.0, z as f32),
+                self.grid.add(Cube::new(
+                    offset + Vec3::new(x as Real, 0.0, z as Real),
                     1.0,
-                    stone_mat.clone(),
+                    stone_mat,
                 ));
             }
         }
@@ -100,10 +177,10 @@ impl Scene {
         // Right side sidewalk (along x = -3 side)
         for x in -4..=-2 {
             for z in -10..=-2 {
-                self.cubes.push(Cube::new(
-                    Vec3::new(x as f32, 0.0, z as f32),
+                self.grid.add(Cube::new(
+                    offset + Vec3::new(x as Real, 0.0, z as Real),
                     1.0,
-                    stone_mat.clone(),
+                    stone_mat,
                 ));
             }
         }
@@ -111,132 +188,164 @@ impl Scene {
         // Back sidewalk (along z = -3 side)
         for x in -10..=-2 {
             for z in -3..=-2 {
-                self.cubes.push(Cube::new(
-                    Vec3::new(x as f32, 0.0, z as f32),
+                self.grid.add(Cube::new(
+                    offset + Vec3::new(x as Real, 0.0, z as Real),
                     1.0,
-                    stone_mat.clone(),
+                    stone_mat,
                 ));
             }
         }
 
         // === ADD GRASS UNDER HOUSE ===
         // Fill in grass blocks under the house area so it doesn't look floating
-        let grass_mat = Material::new(Color::new(0.3, 0.7, 0.3))
-            .with_texture(Texture::load("assets/textures/grass.jpg"));
+        let grass_mat = self.materials.add(
+            Material::new(palette::GRASS_GREEN)
+                .with_texture(texture_cache::load("assets/textures/grass.jpg")),
+        );
         
         // House occupies x: -10 to -4, z: -10 to -4
         for x in -10..=-4 {
             for z in -10..=-4 {
-                self.cubes.push(Cube::new(
-                    Vec3::new(x as f32, -0.5, z as f32),
+                self.grid.add(Cube::new(
+                    offset + Vec3::new(x as Real, -0.5, z as Real),
                     1.0,
-                    grass_mat.clone(),
+                    grass_mat,
                 ));
             }
         }
 
         // Add glass block
-        let glass_mat = Material::new(Color::new(0.9, 0.9, 1.0))
-            .with_texture(Texture::load("assets/textures/glass.png"))
-            .with_transparency(0.9, 1.5)
-            .with_reflectivity(0.1)
-            .with_specular(0.9, 128.0);  // Very sharp, bright highlights on glass
+        let glass_mat = self.materials.add(
+            Material::new(palette::GLASS_WHITE)
+                .with_texture(texture_cache::load("assets/textures/glass.png"))
+                .with_alpha_cutoff(0.5) // Clear pane between the frame and streaks
+                .with_transparency(0.9, 1.5)
+                .with_absorption(palette::GLASS_GREEN_TINT, 0.5) // Faint green through the thick block
+                .with_reflectivity(0.1)
+                .with_specular(0.9, 128.0), // Very sharp, bright highlights on glass
+        );
 
-        self.cubes
-            .push(Cube::new(Vec3::new(2.0, 0.0, -2.0), 1.0, glass_mat));
+        self.grid
+            .add(Cube::new(offset + Vec3::new(2.0, 0.0, -2.0), 1.0, glass_mat));
 
         // === ADD METALLIC/GOLD DECORATIVE BLOCKS ===
         // Gold material: Very high specular for shiny metal appearance
-        let gold_mat = Material::new(Color::new(1.0, 0.84, 0.0))
-            .with_texture(Texture::load("assets/textures/wood.png"))  // Using wood texture as fallback
-            .with_reflectivity(0.4)
-            .with_specular(1.0, 256.0);  // Very sharp, intense highlights for metallic look
+        let gold_mat = self.materials.add(
+            Material::new(palette::GOLD)
+                .with_texture(texture_cache::load("assets/textures/wood.png"))  // Using wood texture as fallback
+                .with_reflectivity(0.4)
+                .with_specular(1.0, 256.0)  // Very sharp, intense highlights for metallic look
+                .with_glint(1.0), // Flakes that twinkle as the camera moves
+        );
 
         // Place decorative gold blocks (removed the one at 4,0,0 that was near pond)
-        self.cubes.push(Cube::new(Vec3::new(4.0, 1.0, 0.0), 1.0, gold_mat.clone()));
-        self.cubes.push(Cube::new(Vec3::new(-4.0, 0.0, -4.0), 1.0, gold_mat));
+        self.grid.add(Cube::new(offset + Vec3::new(4.0, 1.0, 0.0), 1.0, gold_mat));
+        self.grid.add(Cube::new(offset + Vec3::new(-4.0, 0.0, -4.0), 1.0, gold_mat));
+
+        // === AXOLOTL BY THE POND ===
+        self.build_axolotl(offset);
+
+        // === BUILD POND AND FOUNTAIN ===
+        self.build_pond(offset);
+
+        // === BUILD A HOUSE ===
+        self.build_house(offset);
+    }
 
+    /// The axolotl by the pond, moved by `offset` from its place in the diorama
+    pub fn build_axolotl(&mut self, offset: Vec3) {
         // Load axolotl model with pink material (smaller size, rotated 180°)
-        let axolotl_body_mat = Material::new(Color::new(1.0, 0.7, 0.8)); // Pink/rosado body color
+        let axolotl_body_mat = Material::new(palette::AXOLOTL_PINK);
         let mut axolotl = Mesh::load_obj(
             "assets/models/axolotl.obj",
-            Vec3::new(-1.0, 0.2, 4.0), // Position: near the tree
+            offset + Vec3::new(-1.0, 0.2, 4.0), // Position: near the tree
             0.15,                     // Scale: 0.15 = 15% of original size (much smaller!)
             axolotl_body_mat,
         );
         // Rotate 180 degrees around Y axis
-        axolotl.rotate_y(std::f32::consts::PI);
+        axolotl.rotate_y(PI);
         self.meshes.push(axolotl);
 
         // === ADD AXOLOTL FEATURES ===
         // Eyes (big, bright, and emissive so they're clearly visible!)
-        let eye_mat = Material::new(Color::new(0.05, 0.05, 0.05)) // Very dark
-            .with_emissive(Color::new(0.1, 0.1, 0.1)); // Slight glow to stand out
+        let eye_mat = self.materials.add(
+            Material::new(palette::EYE_BLACK)
+                .with_emissive(palette::EYE_GLOW), // Slight glow to stand out
+        );
         
         // Make eyes MUCH bigger and position them at the front
-        self.cubes.push(Cube::new(Vec3::new(-1.15, 0.5, 3.75), 0.18, eye_mat.clone())); // Left eye - bigger!
-        self.cubes.push(Cube::new(Vec3::new(-0.85, 0.5, 3.75), 0.18, eye_mat));         // Right eye - bigger!
+        self.grid.add(Cube::new(offset + Vec3::new(-1.15, 0.5, 3.75), 0.18, eye_mat)); // Left eye - bigger!
+        self.grid.add(Cube::new(offset + Vec3::new(-0.85, 0.5, 3.75), 0.18, eye_mat));         // Right eye - bigger!
 
         // Mouth (darker pink, more visible) 
-        let mouth_mat = Material::new(Color::new(0.7, 0.3, 0.4)) // Darker, more contrast
-            .with_emissive(Color::new(0.1, 0.05, 0.05)); // Slight glow
-        self.cubes.push(Cube::new(Vec3::new(-1.0, 0.35, 3.65), 0.15, mouth_mat));
+        let mouth_mat = self.materials.add(
+            Material::new(palette::AXOLOTL_MOUTH) // Darker, more contrast
+                .with_emissive(palette::AXOLOTL_MOUTH_GLOW), // Slight glow
+        );
+        self.grid.add(Cube::new(offset + Vec3::new(-1.0, 0.35, 3.65), 0.15, mouth_mat));
 
         // Scales/Gills (bright pink frills on sides) - adjusted positions
-        let scale_mat = Material::new(Color::new(1.0, 0.4, 0.6)) // Brighter pink for gills
-            .with_emissive(Color::new(0.3, 0.1, 0.15)); // More visible glow
+        let scale_mat = self.materials.add(
+            Material::new(palette::AXOLOTL_GILLS) // Brighter pink for gills
+                .with_emissive(palette::AXOLOTL_GILLS_GLOW), // Brighter than white, so bloom gives them a halo
+        );
         
         // Left gills (3 small cubes) - adjusted for rotation
-        self.cubes.push(Cube::new(Vec3::new(-1.3, 0.4, 4.0), 0.08, scale_mat.clone()));
-        self.cubes.push(Cube::new(Vec3::new(-1.35, 0.45, 4.0), 0.07, scale_mat.clone()));
-        self.cubes.push(Cube::new(Vec3::new(-1.35, 0.35, 4.0), 0.07, scale_mat.clone()));
+        self.grid.add(Cube::new(offset + Vec3::new(-1.3, 0.4, 4.0), 0.08, scale_mat));
+        self.grid.add(Cube::new(offset + Vec3::new(-1.35, 0.45, 4.0), 0.07, scale_mat));
+        self.grid.add(Cube::new(offset + Vec3::new(-1.35, 0.35, 4.0), 0.07, scale_mat));
         
         // Right gills (3 small cubes) - adjusted for rotation
-        self.cubes.push(Cube::new(Vec3::new(-0.7, 0.4, 4.0), 0.08, scale_mat.clone()));
-        self.cubes.push(Cube::new(Vec3::new(-0.65, 0.45, 4.0), 0.07, scale_mat.clone()));
-        self.cubes.push(Cube::new(Vec3::new(-0.65, 0.35, 4.0), 0.07, scale_mat));
-
-        // === BUILD POND AND FOUNTAIN ===
-        self.build_pond();
-
-        // === BUILD A HOUSE ===
-        self.build_house();
+        self.grid.add(Cube::new(offset + Vec3::new(-0.7, 0.4, 4.0), 0.08, scale_mat));
+        self.grid.add(Cube::new(offset + Vec3::new(-0.65, 0.45, 4.0), 0.07, scale_mat));
+        self.grid.add(Cube::new(offset + Vec3::new(-0.65, 0.35, 4.0), 0.07, scale_mat));
     }
 
-    fn build_cherry_tree(&mut self, base_x: f32, base_z: f32) {
+    /// Cherry tree whose lowest trunk block is centered on `base`
+    pub fn build_cherry_tree(&mut self, base: Vec3) {
         // Create cherry tree trunk
-        let wood_mat = Material::new(Color::new(0.5, 0.3, 0.2))
-            .with_texture(Texture::load("assets/textures/cherry_wood.jpg"))
-            .with_specular(0.1, 32.0);  // Minimal, soft highlights on wood
+        let wood_mat = self.materials.add(
+            Material::new(palette::CHERRY_LOG_BROWN)
+                .with_texture(texture_cache::load("assets/textures/cherry_wood.jpg"))
+                .with_normal_map(texture_cache::load_normal_map("assets/textures/cherry_wood_normal.png"), 1.0)
+                .with_specular(0.1, 32.0), // Minimal, soft highlights on wood
+        );
 
         for y in 0..4 {
-            self.cubes.push(Cube::new(
-                Vec3::new(base_x, y as f32, base_z),
+            self.grid.add(Cube::new(
+                base + Vec3::new(0.0, y as Real, 0.0),
                 1.0,
-                wood_mat.clone(),
+                wood_mat,
             ));
         }
 
         // Create cherry tree leaves
-        let leaves_mat = Material::new(Color::new(1.0, 0.7, 0.8))
-            .with_texture(Texture::load("assets/textures/cherry_leaves.png"));
+        let leaves_mat = self.materials.add(
+            Material::new(palette::CHERRY_PINK)
+                .with_texture(texture_cache::load("assets/textures/cherry_leaves.png"))
+                .with_alpha_cutoff(0.5), // Gaps between the leaves (where the texture has alpha)
+        );
 
         for x in -2i32..=2 {
             for y in 3i32..=5 {
                 for z in -2i32..=2 {
                     if (x.abs() + z.abs()) < 4 && y < 6 {
-                        self.cubes.push(Cube::new(
-                            Vec3::new(base_x + x as f32, y as f32, base_z + z as f32),
+                        self.grid.add(Cube::new(
+                            base + Vec3::new(x as Real, y as Real, z as Real),
                             1.0,
-                            leaves_mat.clone(),
+                            leaves_mat,
                         ));
                     }
                 }
             }
         }
+
+        // Butterflies flutter around under the canopy by day
+        self.swarms.push(Swarm::butterflies(base + Vec3::new(0.0, 1.5, 0.0), 3.0));
     }
 
-    fn build_pond(&mut self) {
+    /// Fountain pond next to the diorama's center tree, moved by `offset`
+    pub fn build_pond(&mut self, offset: Vec3) {
         // Pond position (rectangular pond near the tree and axolotl)
         let pond_center_x = 5.0;
         let pond_center_z = 2.0;
@@ -246,37 +355,49 @@ impl Scene {
         let pond_depth = 4;  // Depth along z-axis
 
         // === POND MATERIALS ===
-        let water_mat = Material::new(Color::new(0.2, 0.5, 0.9))
-            .with_texture(Texture::load("assets/textures/water.jpeg"))
-            .with_transparency(0.85, 1.33)
-            .with_reflectivity(0.3)
-            .with_specular(0.8, 64.0);  // Strong, sharp highlights on water
+        let water_mat = self.materials.add(
+            Material::new(palette::WATER_BLUE)
+                .with_texture(texture_cache::load("assets/textures/water_still.png"))
+                .with_animation(TextureAnimation::new(16, 0.1)) // 16-frame strip, flows in a 1.6s loop
+                .with_filter(TextureFilter::Bilinear) // Smooth ripples instead of blocky texels
+                .with_transparency(0.85, 1.33)
+                .with_absorption(palette::WATER_DEPTH_TINT, 0.8) // Deeper water reads blue-green
+                .with_reflectivity(0.3)
+                .with_specular(0.8, 64.0)  // Strong, sharp highlights on water
+                .with_waves(1.0) // Ripples, so reflections aren't mirror flat
+                .with_foam(0.8), // White band where the water laps against the stone rim
+        );
 
-        let stone_mat = Material::new(Color::new(0.5, 0.5, 0.5))
-            .with_texture(Texture::load("assets/textures/stone.jpg"))
-            .with_reflectivity(0.05);
+        let stone_mat = self.materials.add(
+            Material::new(palette::NEUTRAL_GRAY)
+                .with_texture(texture_cache::load("assets/textures/stone.jpg"))
+                .with_normal_map(texture_cache::load_normal_map("assets/textures/stone_normal.png"), 1.0)
+                .with_reflectivity(0.05),
+        );
 
         // Lily pad material (green, for decoration)
-        let lily_mat = Material::new(Color::new(0.3, 0.7, 0.3))
-            .with_texture(Texture::load("assets/textures/grass.jpg"));
+        let lily_mat = self.materials.add(
+            Material::new(palette::GRASS_GREEN)
+                .with_texture(texture_cache::load("assets/textures/grass.jpg")),
+        );
 
         // === CREATE RECTANGULAR POND ===
         // Calculate starting corner
-        let start_x = pond_center_x - (pond_width as f32 / 2.0);
-        let start_z = pond_center_z - (pond_depth as f32 / 2.0);
+        let start_x = pond_center_x - (pond_width as Real / 2.0);
+        let start_z = pond_center_z - (pond_depth as Real / 2.0);
 
         // Create stone border (outer ring)
         for x in -1..=pond_width {
             for z in -1..=pond_depth {
-                let x_pos = start_x + x as f32;
-                let z_pos = start_z + z as f32;
+                let x_pos = start_x + x as Real;
+                let z_pos = start_z + z as Real;
                 
                 // Only place stones on the border
                 if x == -1 || x == pond_width || z == -1 || z == pond_depth {
-                    self.cubes.push(Cube::new(
-                        Vec3::new(x_pos, 0.0, z_pos),
+                    self.grid.add(Cube::new(
+                        offset + Vec3::new(x_pos, 0.0, z_pos),
                         1.0,
-                        stone_mat.clone(),
+                        stone_mat,
                     ));
                 }
             }
@@ -285,53 +406,68 @@ impl Scene {
         // Fill interior with water
         for x in 0..pond_width {
             for z in 0..pond_depth {
-                let x_pos = start_x + x as f32;
-                let z_pos = start_z + z as f32;
+                let x_pos = start_x + x as Real;
+                let z_pos = start_z + z as Real;
                 
-                self.cubes.push(Cube::new(
-                    Vec3::new(x_pos, 0.0, z_pos),
+                self.grid.add(Cube::new(
+                    offset + Vec3::new(x_pos, 0.0, z_pos),
                     1.0,
-                    water_mat.clone(),
+                    water_mat,
                 ));
             }
         }
 
         // === ADD LILY PADS (optional decoration) ===
         // Place a few lily pads floating on the water surface
-        self.cubes.push(Cube::new(
-            Vec3::new(pond_center_x - 1.0, 0.9, pond_center_z - 0.5),
+        self.grid.add(Cube::new(
+            offset + Vec3::new(pond_center_x - 1.0, 0.9, pond_center_z - 0.5),
             0.4,
-            lily_mat.clone(),
+            lily_mat,
         ));
 
-        self.cubes.push(Cube::new(
-            Vec3::new(pond_center_x + 1.0, 0.9, pond_center_z + 0.5),
+        self.grid.add(Cube::new(
+            offset + Vec3::new(pond_center_x + 1.0, 0.9, pond_center_z + 0.5),
             0.4,
-            lily_mat.clone(),
+            lily_mat,
         ));
         
-        self.cubes.push(Cube::new(
-            Vec3::new(pond_center_x, 0.9, pond_center_z),
+        self.grid.add(Cube::new(
+            offset + Vec3::new(pond_center_x, 0.9, pond_center_z),
             0.4,
             lily_mat,
         ));
+
+        // Fireflies drift low over the water at night
+        self.swarms.push(Swarm::fireflies(offset + Vec3::new(pond_center_x, 1.6, pond_center_z), 2.5));
     }
 
-    fn build_house(&mut self) {
+    /// Lantern-lit house in the diorama's back corner, moved by `offset`
+    pub fn build_house(&mut self, offset: Vec3) {
         // House materials
-        let wall_mat = Material::new(Color::new(0.6, 0.4, 0.3))
-            .with_texture(Texture::load("assets/textures/cherry_log.png"));
+        let wall_mat = self.materials.add(
+            Material::new(palette::PLANKS_BROWN)
+                .with_texture(texture_cache::load("assets/textures/cherry_log.png")),
+        );
 
-        let window_mat = Material::new(Color::new(0.8, 0.9, 1.0))
-            .with_texture(Texture::load("assets/textures/glass.png"))
-            .with_transparency(0.8, 1.5)
-            .with_reflectivity(0.1);
+        let window_mat = self.materials.add(
+            Material::new(palette::WINDOW_BLUE)
+                .with_texture(texture_cache::load("assets/textures/glass.png"))
+                .with_alpha_cutoff(0.5)
+                .with_transparency(0.8, 1.5)
+                .with_reflectivity(0.1),
+        );
 
-        let roof_mat = Material::new(Color::new(0.5, 0.5, 0.5))
-            .with_texture(Texture::load("assets/textures/stone.jpg"));
+        let roof_mat = self.materials.add(
+            Material::new(palette::NEUTRAL_GRAY)
+                .with_texture(texture_cache::load("assets/textures/stone.jpg"))
+                .with_normal_map(texture_cache::load_normal_map("assets/textures/stone_normal.png"), 1.0),
+        );
 
-        let door_mat = Material::new(Color::new(0.5, 0.5, 0.5))
-            .with_texture(Texture::load("assets/textures/wood.png"));
+        let door_mat = self.materials.add(
+            Material::new(palette::NEUTRAL_GRAY)
+                .with_texture(texture_cache::load("assets/textures/wood.png"))
+                .with_normal_map(texture_cache::load_normal_map("assets/textures/wood_normal.png"), 1.0),
+        );
 
         // House position and size
         let house_x = -10.0;
@@ -344,106 +480,409 @@ impl Scene {
 
         // Build walls (all 4 sides)
         for y in 0..house_height {
-            let y_pos = y as f32;
+            let y_pos = y as Real;
 
             // Front wall (z = house_z) with windows
             for x in 0..house_width {
-                let x_pos = house_x + x as f32;
+                let x_pos = house_x + x as Real;
                 let is_window = y >= 2 && y <= 3 && (x == 2 || x == 4);
 
                 if is_window {
-                    self.cubes.push(Cube::new(
-                        Vec3::new(x_pos, y_pos, house_z),
+                    self.grid.add(Cube::new(
+                        offset + Vec3::new(x_pos, y_pos, house_z),
                         1.0,
-                        window_mat.clone(),
+                        window_mat,
                     ));
                 } else {
-                    self.cubes.push(Cube::new(
-                        Vec3::new(x_pos, y_pos, house_z),
+                    self.grid.add(Cube::new(
+                        offset + Vec3::new(x_pos, y_pos, house_z),
                         1.0,
-                        wall_mat.clone(),
+                        wall_mat,
                     ));
                 }
             }
 
             // Back wall (z = house_z + depth) with door
             for x in 0..house_width {
-                let x_pos = house_x + x as f32;
+                let x_pos = house_x + x as Real;
                 let is_door = y < 3 && x >= 2 && x <= 4; // Door opening (3 blocks wide, 3 blocks tall)
 
                 if !is_door {
-                    self.cubes.push(Cube::new(
-                        Vec3::new(x_pos, y_pos, house_z + house_depth as f32 - 1.0),
+                    self.grid.add(Cube::new(
+                        offset + Vec3::new(x_pos, y_pos, house_z + house_depth as Real - 1.0),
                         1.0,
-                        wall_mat.clone(),
+                        wall_mat,
                     ));
                 } else {
                     // Door blocks filling entire 3x3 opening
-                    self.cubes.push(Cube::new(
-                        Vec3::new(x_pos, y_pos, house_z + house_depth as f32 - 1.0),
+                    self.grid.add(Cube::new(
+                        offset + Vec3::new(x_pos, y_pos, house_z + house_depth as Real - 1.0),
                         1.0,
-                        door_mat.clone(),
+                        door_mat,
                     ));
                 }
             }
 
             // Left wall (x = house_x) with window
             for z in 1..(house_depth - 1) {
-                let z_pos = house_z + z as f32;
+                let z_pos = house_z + z as Real;
                 let is_window = y >= 2 && y <= 3 && z == 3;
 
                 if is_window {
-                    self.cubes.push(Cube::new(
-                        Vec3::new(house_x, y_pos, z_pos),
+                    self.grid.add(Cube::new(
+                        offset + Vec3::new(house_x, y_pos, z_pos),
                         1.0,
-                        window_mat.clone(),
+                        window_mat,
                     ));
                 } else {
-                    self.cubes.push(Cube::new(
-                        Vec3::new(house_x, y_pos, z_pos),
+                    self.grid.add(Cube::new(
+                        offset + Vec3::new(house_x, y_pos, z_pos),
                         1.0,
-                        wall_mat.clone(),
+                        wall_mat,
                     ));
                 }
             }
 
             // Right wall (x = house_x + width) with window
             for z in 1..(house_depth - 1) {
-                let z_pos = house_z + z as f32;
+                let z_pos = house_z + z as Real;
                 let is_window = y >= 2 && y <= 3 && z == 3;
 
                 if is_window {
-                    self.cubes.push(Cube::new(
-                        Vec3::new(house_x + house_width as f32 - 1.0, y_pos, z_pos),
+                    self.grid.add(Cube::new(
+                        offset + Vec3::new(house_x + house_width as Real - 1.0, y_pos, z_pos),
                         1.0,
-                        window_mat.clone(),
+                        window_mat,
                     ));
                 } else {
-                    self.cubes.push(Cube::new(
-                        Vec3::new(house_x + house_width as f32 - 1.0, y_pos, z_pos),
+                    self.grid.add(Cube::new(
+                        offset + Vec3::new(house_x + house_width as Real - 1.0, y_pos, z_pos),
                         1.0,
-                        wall_mat.clone(),
+                        wall_mat,
                     ));
                 }
             }
         }
 
         // Build roof (flat roof made of stone)
-        let roof_y = house_height as f32;
+        let roof_y = house_height as Real;
         for x in 0..house_width {
             for z in 0..house_depth {
-                self.cubes.push(Cube::new(
-                    Vec3::new(house_x + x as f32, roof_y, house_z + z as f32),
+                self.grid.add(Cube::new(
+                    offset + Vec3::new(house_x + x as Real, roof_y, house_z + z as Real),
                     1.0,
-                    roof_mat.clone(),
+                    roof_mat,
                 ));
             }
         }
+
+        // Stone chimney on the roof's back corner, smoking away
+        let chimney_x = house_x + house_width as Real - 2.0;
+        let chimney_z = house_z + 1.0;
+        for y in 1..=2 {
+            self.grid.add(Cube::new(offset + Vec3::new(chimney_x, roof_y + y as Real, chimney_z), 1.0, roof_mat));
+        }
+        self.smoke.push(SmokeEmitter::chimney(offset + Vec3::new(chimney_x, roof_y + 2.6, chimney_z)));
+
+        // Two lanterns hanging under the ceiling, each a glowing block with a warm point light inside
+        let lantern_mat = self.materials.add(
+            Material::new(palette::LANTERN_AMBER)
+                .with_texture(texture_cache::load("assets/textures/emissive_lantern.png"))
+                .with_emissive(palette::LANTERN_AMBER),
+        );
+        for x in [2.0, 4.0] {
+            let position = offset + Vec3::new(house_x + x, house_height as Real - 1.3, house_z + house_depth as Real / 2.0 - 0.5);
+            self.grid.add(Cube::new(position, 0.4, lantern_mat));
+            self.point_lights.push(PointLight::new(position, palette::LANTERN_LIGHT, lumens(LANTERN_LUMENS), 8.0).with_source_radius(0.2));
+        }
+
+        // Porch lamp above the door, shining down and out over the doorstep
+        let porch_z = house_z + house_depth as Real - 0.35;
+        self.grid.add(Cube::new(offset + Vec3::new(house_x + 3.0, 3.2, porch_z), 0.3, lantern_mat));
+        self.spot_lights.push(
+            SpotLight::new(
+                offset + Vec3::new(house_x + 3.0, 3.0, porch_z + 0.1),
+                Vec3::new(0.0, -1.0, 0.6),
+                palette::PORCH_LIGHT,
+                lumens(600.0),
+                25.0,
+                45.0,
+            )
+            .with_radius(12.0),
+        );
+    }
+
+    /// Bring the chunks edited since the last call up to date for tracing and collect the
+    /// glowing-block lights again (call after building or editing blocks)
+    pub fn update_grid(&mut self) {
+        self.grid.update(&self.materials);
+        self.collect_block_lights();
+    }
+
+    // Every emissive cube becomes a point light at its center, brighter the bigger and hotter it is.
+    // Blocks that already have a lamp inside them (the house lanterns) are skipped, as are glows
+    // too faint to light anything.
+    fn collect_block_lights(&mut self) {
+        let lamp_positions: Vec<Vec3> = self
+            .point_lights
+            .iter()
+            .map(|light| light.position)
+            .chain(self.spot_lights.iter().map(|light| light.position))
+            .collect();
+        self.block_lights = self
+            .grid
+            .cubes()
+            .filter(|cube| self.materials[cube.material].is_emissive())
+            .filter(|cube| !lamp_positions.iter().any(|lamp| (*lamp - cube.position).length() <= cube.size))
+            .filter_map(|cube| {
+                let glow = self.materials[cube.material].emissive;
+                let intensity = BLOCK_LIGHT_STRENGTH * glow.r.max(glow.g).max(glow.b) * narrow(cube.size);
+                if intensity < MIN_BLOCK_LIGHT {
+                    return None;
+                }
+                // Reach: where the falloff 1 / (1 + d^2 / 2) drops the light below 1%
+                let radius = ((intensity * 100.0 - 1.0) * 2.0).sqrt() as Real;
+                Some(PointLight::new(cube.position, glow, intensity, radius).with_source_radius(cube.size / 2.0))
+            })
+            .collect();
+    }
+
+    /// Camera-relative rendering for worlds far from (0,0,0): when `point` (render space, usually the
+    /// camera target) is more than RECENTER_DISTANCE out, the whole scene moves so it's near the origin
+    /// again. Returns the shift, which the caller adds to whatever else it keeps in render space
+    /// (camera, recorded camera path).
+    pub fn recenter_around(&mut self, point: Vec3) -> Option<Vec3> {
+        if point.x.abs().max(point.y.abs()).max(point.z.abs()) <= RECENTER_DISTANCE {
+            return None;
+        }
+        let snap = |v: Real| (v / RECENTER_STEP).round() * RECENTER_STEP;
+        let shift = -Vec3::new(snap(point.x), snap(point.y), snap(point.z));
+
+        self.grid.translate(shift);
+        for mesh in &mut self.meshes {
+            mesh.position = mesh.position + shift;
+            mesh.previous_position = mesh.previous_position + shift;
+        }
+        for emitter in &mut self.smoke {
+            emitter.position = emitter.position + shift;
+        }
+        for burst in &mut self.petal_bursts {
+            burst.position = burst.position + shift;
+        }
+        for swarm in &mut self.swarms {
+            swarm.center = swarm.center + shift;
+        }
+        for light in &mut self.point_lights {
+            light.position = light.position + shift;
+        }
+        for light in &mut self.spot_lights {
+            light.position = light.position + shift;
+        }
+        for light in self.lighting.iter_mut().flat_map(|lighting| &mut lighting.lights) {
+            light.position = light.position + shift;
+        }
+        for view in &mut self.viewpoints {
+            view.position = view.position + shift;
+            view.target = view.target + shift;
+        }
+        if let Some(Backdrop { mapping: BackdropMapping::Plane { center, .. }, .. }) = &mut self.backdrop {
+            *center = *center + shift;
+        }
+        self.entities.clear(); // Rebuilt every frame anyway
+        self.update_particles();
+        self.origin = self.origin - shift;
+        self.update_grid();
+        Some(shift)
+    }
+
+    /// Load the streamed world's chunks around `point` (render space, usually the camera target)
+    /// and drop the far ones. Returns true when blocks came or went; the grid is updated then.
+    pub fn stream_around(&mut self, point: Vec3) -> bool {
+        let center = point + self.origin;
+        let Some(streaming) = &mut self.streaming else {
+            return false;
+        };
+        if !streaming.update(&mut self.grid, &mut self.materials, center, self.origin) {
+            return false;
+        }
+        self.update_grid();
+        true
+    }
+
+    /// Freshly built scenes far from (0,0,0) are rendered relative to their center (see recenter_around)
+    pub fn center_origin(&mut self) -> Option<Vec3> {
+        let center = self.bounds().map(|(min, max)| (min + max) * 0.5)?;
+        self.recenter_around(center)
+    }
+
+    /// Scene-file / command-line coordinates into render space
+    pub fn to_render(&self, world: Vec3) -> Vec3 {
+        world - self.origin
+    }
+
+    /// Advance the scene clock by real frame time (in fixed ticks) and update time-driven state
+    pub fn update(&mut self, delta_time: f32) {
+        let ticks = self.time.advance(delta_time);
+        for action in self.events.due(&self.time) {
+            self.apply_event(action);
+        }
+        self.weather.step(ticks as f32 * SceneTime::TICK_DURATION);
+        self.update_sun_position();
+        self.update_particles();
+    }
+
+    /// Rebuild the smoke puffs, falling petals and critters for the current scene time (done by update; call after
+    /// setting the time directly)
+    pub fn update_particles(&mut self) {
+        let elapsed = self.time.elapsed;
+        let sun_is_up = self.sun_is_up();
+        let swarms: Vec<&Swarm> = if self.critters {
+            self.swarms.iter().filter(|swarm| swarm.is_out(sun_is_up)).collect()
+        } else {
+            Vec::new()
+        };
+        let mut particles = Vec::new();
+        for emitter in &self.smoke {
+            particles.extend(emitter.puffs(elapsed, &mut self.materials));
+        }
+        self.petal_bursts.retain(|burst| !burst.is_over(elapsed));
+        for burst in &self.petal_bursts {
+            particles.extend(burst.petals(elapsed, &mut self.materials));
+        }
+        for swarm in &swarms {
+            particles.extend(swarm.critters(elapsed, &mut self.materials));
+        }
+        self.particles = particles;
+        self.critter_lights = swarms.iter().flat_map(|swarm| swarm.lights(elapsed)).collect();
+    }
+
+    /// Carry out a scheduled event's action (scene_events.rs)
+    pub fn apply_event(&mut self, action: EventAction) {
+        match action {
+            EventAction::LampsOn => self.lamps_on = true,
+            EventAction::LampsOff => self.lamps_on = false,
+            EventAction::RainStart => self.weather.raining = true,
+            EventAction::RainStop => self.weather.raining = false,
+            EventAction::Clouds(coverage) => self.skybox.cloud_coverage = coverage,
+            EventAction::Petals(position) => self.petal_bursts.push(PetalBurst::new(position - self.origin, self.time.elapsed)),
+        }
+    }
+
+    /// Switch to a lighting preset, or back to the day/night cycle with None. The preset's lamps
+    /// are placed in world coordinates, like the scene file's.
+    pub fn set_lighting(&mut self, mut lighting: Option<LightingPreset>) {
+        for light in lighting.iter_mut().flat_map(|lighting| &mut lighting.lights) {
+            light.position = self.to_render(light.position);
+        }
+        self.lighting = lighting;
+        self.update_sun_position();
+    }
+
+    /// Ambient light: the preset's, or blended from day to night
+    pub fn ambient(&self) -> Color {
+        if let Some(lighting) = &self.lighting {
+            return lighting.ambient;
+        }
+        // Day (day_fraction=0.0): Bright ambient light
+        // Night (day_fraction=1.0): Very dark ambient light
+        let day_time = self.time.day_fraction;
+        let day_ambient = palette::DAY_AMBIENT;
+        let night_ambient = palette::NIGHT_AMBIENT; // Very dark at night
+        day_ambient * (1.0 - day_time) + night_ambient * day_time
+    }
+
+    /// Ambient light on a surface facing `normal`: from the environment image when it lights the
+    /// scene, otherwise the same flat color in every direction
+    pub fn ambient_light(&self, normal: Vec3) -> Color {
+        match &self.skybox.environment {
+            Some(environment) if environment.lighting => environment.ambient(normal),
+            _ => self.ambient(),
+        }
+    }
+
+    /// What a bounce ray that escapes to the sky brings back: the environment image when it lights
+    /// the scene, otherwise the flat ambient level (the skybox texture and its sun disk would
+    /// count the sun a second time)
+    pub fn sky_light(&self, direction: Vec3) -> Color {
+        match &self.skybox.environment {
+            Some(environment) if environment.lighting => environment.sample(direction),
+            _ => self.ambient(),
+        }
     }
 
-    pub fn update_sun_position(&mut self, day_time: f32) {
+    /// Sun intensity used for shading; a preset's sun is used as is
+    pub fn sun_strength(&self) -> f32 {
+        if self.lighting.is_some() {
+            return self.sun.intensity;
+        }
+        // Sun/Moon intensity decreases at night
+        self.sun.intensity * (1.0 - self.time.day_fraction * 0.95) // Reduce to 5% at night
+    }
+
+    /// Whether the sun is up: the day half of the cycle, when the sky shows the sun disk rather than
+    /// the moon. A preset's sun always is (the night preset's "sun" is its moonlight).
+    pub fn sun_is_up(&self) -> bool {
+        self.lighting.is_some() || self.time.day_fraction < 0.5
+    }
+
+    /// The directional light shading uses, with its strength: the sun while it's up, the moon
+    /// once it's below the horizon, so night scenes keep readable shading and shadows
+    pub fn key_light(&self) -> (&DirectionalLight, f32) {
+        if self.sun_is_up() {
+            (&self.sun, self.sun_strength())
+        } else {
+            (&self.moon, self.moon.intensity)
+        }
+    }
+
+    /// The scene's point lights (while the lamps are on), glowing blocks', critters' and the preset's
+    pub fn lights(&self) -> impl Iterator<Item = &PointLight> {
+        self.static_lights().chain(&self.critter_lights)
+    }
+
+    /// The point lights that stay put between frames: all but the critters', which move and blink
+    /// every frame and so are left out of the keys that decide when a view changed
+    pub fn static_lights(&self) -> impl Iterator<Item = &PointLight> {
+        let preset_lights = self.lighting.iter().flat_map(|lighting| lighting.lights.iter());
+        let lamps: &[PointLight] = if self.lamps_on { &self.point_lights } else { &[] };
+        lamps.iter().chain(&self.block_lights).chain(preset_lights)
+    }
+
+    /// (light position, source radius, direction to the light, attenuated color) of every point and
+    /// spot light at a point. Spot lights are points (source radius 0).
+    pub fn light_samples(&self, point: Vec3) -> impl Iterator<Item = (Vec3, Real, Vec3, Color)> {
+        let points = self.lights().map(move |light| {
+            let (direction, color) = light.illuminate(&point);
+            (light.position, light.source_radius, direction, color)
+        });
+        let spots: &[SpotLight] = if self.lamps_on { &self.spot_lights } else { &[] };
+        let spots = spots.iter().map(move |light| {
+            let (direction, color) = light.illuminate(&point);
+            (light.position, 0.0, direction, color)
+        });
+        points.chain(spots)
+    }
+
+    pub fn update_sun_position(&mut self) {
+        if let Some(lighting) = &self.lighting {
+            // Presets hold the clock still so the sky matches the look
+            self.time.day_fraction = lighting.day_fraction;
+            self.sun = lighting.sun.clone();
+        } else {
+            self.sun = self.cycle_sun();
+            self.moon = self.cycle_moon();
+        }
+        // A pinned sun only moves the light; brightness and sky still follow the clock or preset
+        if let Some(pin) = self.sun_pin {
+            self.sun.direction = pin.light_direction();
+        }
+    }
+
+    // The day/night cycle's sun at the current time of day
+    fn cycle_sun(&self) -> DirectionalLight {
+
         // Animate sun from east to west, arcing overhead
-        let angle = day_time * std::f32::consts::PI * 2.0;
+        let angle = self.time.day_fraction as Real * PI * 2.0;
 
         // Sun direction at 45° angle - points DOWN and at an angle
         // When negated in renderer, points UP and at an angle
@@ -457,18 +896,97 @@ impl Scene {
 
         // Intensity based on sun height
         let sun_height = (angle.cos() + 0.5).max(0.0);
-        let intensity = (sun_height * 1.2).min(1.2).max(0.3);
+        let noon = lux(NOON_SUN_LUX) as Real;
+        let intensity = (sun_height * noon).min(noon).max(0.3);
 
-        self.sun = DirectionalLight::sun(sun_dir, intensity);
+        let mut sun = DirectionalLight::sun(sun_dir, narrow(intensity));
+        sun.color = skybox::sun_color(self.time.day_fraction); // Warmer toward sunrise and sunset
+        sun
+    }
+
+    // The day/night cycle's moon: across the sky from the sun, highest at midnight, kept above the
+    // horizon like the sun. Dimmer than the night preset's moonlight, and dimmer still near the horizon.
+    fn cycle_moon(&self) -> DirectionalLight {
+        let angle = self.time.day_fraction as Real * PI * 2.0;
+        let moon_dir = Vec3::new(angle.sin(), -(0.5 - angle.cos()).max(0.3), 0.5).normalize();
+        let moon_height = narrow((0.5 - angle.cos()).clamp(0.0, 1.0));
+        DirectionalLight::moon(moon_dir, lux(MOONLIGHT_LUX) * (0.2 + 0.4 * moon_height))
+    }
+
+    /// Start a new shutter interval: current mesh positions become the motion blur start poses
+    pub fn begin_shutter(&mut self) {
+        for mesh in &mut self.meshes {
+            mesh.previous_position = mesh.position;
+        }
+    }
+
+    /// Smallest box (min, max) around every block and mesh, None for an empty scene
+    pub fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        let cubes = self.grid.cubes().map(|cube| {
+            let half = Vec3::new(cube.size, cube.size, cube.size) * 0.5;
+            (cube.position - half, cube.position + half)
+        });
+        let triangles = self.meshes.iter().flat_map(|mesh| {
+            mesh.triangles
+                .iter()
+                .flat_map(|tri| [tri.v0, tri.v1, tri.v2])
+                .map(move |v| (v + mesh.position, v + mesh.position))
+        });
+        cubes.chain(triangles).reduce(|(min_a, max_a), (min_b, max_b)| {
+            (
+                Vec3::new(min_a.x.min(min_b.x), min_a.y.min(min_b.y), min_a.z.min(min_b.z)),
+                Vec3::new(max_a.x.max(max_b.x), max_a.y.max(max_b.y), max_a.z.max(max_b.z)),
+            )
+        })
+    }
+
+    /// Height of the first static surface below (x, from_y, z), for placing things on the ground
+    pub fn ground_height(&self, x: Real, z: Real, from_y: Real) -> Option<Real> {
+        let down = Ray::new(Vec3::new(x, from_y, z), Vec3::new(0.0, -1.0, 0.0));
+        self.intersect_blocks(&down).map(|hit| hit.position.y)
     }
 
     pub fn intersect(&self, ray: &Ray) -> Option<Intersection> {
+        self.intersect_dynamic(ray, self.intersect_blocks(ray))
+    }
+
+    /// Closest hit among the static cubes only, through their chunks; a grid with edits not yet
+    /// updated (update_grid) is checked cube by cube
+    pub fn intersect_blocks(&self, ray: &Ray) -> Option<Intersection> {
+        if !self.grid.is_stale() {
+            return self.grid.intersect(&self.materials, ray, Real::INFINITY);
+        }
         let mut closest: Option<Intersection> = None;
-        let mut closest_t = f32::INFINITY;
+        let mut closest_t = Real::INFINITY;
+        for cube in self.grid.cubes() {
+            if let Some(intersection) = cube.intersect(ray, &self.materials)
+                && intersection.t < closest_t
+            {
+                closest_t = intersection.t;
+                closest = Some(intersection);
+            }
+        }
+        closest
+    }
+
+    /// Closest hits for a packet of neighbouring camera rays, the same ones intersect finds ray by
+    /// ray: the static world's chunks are walked once for the whole packet, entities, smoke and
+    /// meshes ray by ray
+    pub fn intersect_packet<const N: usize>(&self, rays: &[Ray; N]) -> [Option<Intersection>; N] {
+        if self.grid.is_stale() {
+            return rays.each_ref().map(|ray| self.intersect(ray));
+        }
+        let mut hits = self.grid.intersect_packet(&self.materials, rays, Real::INFINITY);
+        std::array::from_fn(|i| self.intersect_dynamic(&rays[i], hits[i].take()))
+    }
 
-        // Check cubes
-        for cube in &self.cubes {
-            if let Some(intersection) = cube.intersect(ray) {
+    // Entities, smoke and meshes in front of `closest`, the ray's hit in the static world
+    fn intersect_dynamic(&self, ray: &Ray, mut closest: Option<Intersection>) -> Option<Intersection> {
+        let mut closest_t = closest.as_ref().map_or(Real::INFINITY, |hit| hit.t);
+
+        // Dynamic entities and smoke
+        for cube in self.entities.iter().chain(&self.particles) {
+            if let Some(intersection) = cube.intersect(ray, &self.materials) {
                 if intersection.t < closest_t {
                     closest_t = intersection.t;
                     closest = Some(intersection);
@@ -495,3 +1013,88 @@ impl Default for Scene {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::Camera;
+    use crate::conformance::box_mesh;
+    use crate::utils::Degrees;
+
+    const PACKET: usize = 4;
+
+    // Streamed hills with a few cubes of the scene's own, an entity and a mesh in front of the camera
+    fn scene() -> Scene {
+        let mut scene = Scene::new();
+        let stone = scene.materials.add(Material::new(palette::STONE_GRAY));
+        scene.streaming = Some(ChunkStreamer::new(11, 1));
+        scene.stream_around(Vec3::zero());
+        for x in -3..3 {
+            scene.grid.add(Cube::new(Vec3::new(x as Real, 12.0, -4.0), 1.0, stone));
+            scene.grid.add(Cube::new(Vec3::new(x as Real + 0.25, 9.5, -6.0), 0.4, stone)); // Detail cubes
+        }
+        scene.update_grid();
+        scene.entities.push(Cube::new(Vec3::new(2.0, 10.0, 0.0), 0.8, stone));
+        scene.meshes.push(box_mesh(Vec3::new(-3.0, 8.0, -2.0), Material::new(palette::PLANKS_BROWN)));
+        scene
+    }
+
+    // Every hit of the packets of a 32x32 view, next to the same rays traced one by one
+    fn compare(scene: &Scene, camera: &Camera) -> usize {
+        let size = 32;
+        let mut hits = 0;
+        for py in (0..size).step_by(PACKET) {
+            for px in (0..size).step_by(PACKET) {
+                let rays: [Ray; PACKET * PACKET] = std::array::from_fn(|i| {
+                    let (x, y) = (px + i % PACKET, py + i / PACKET);
+                    camera.get_pinhole_ray(x as f32 / size as f32, y as f32 / size as f32)
+                });
+                for (ray, packet_hit) in rays.iter().zip(scene.intersect_packet(&rays)) {
+                    let single = scene.intersect(ray);
+                    assert_eq!(packet_hit.as_ref().map(|hit| (hit.t, hit.normal)), single.as_ref().map(|hit| (hit.t, hit.normal)), "{:?}", ray);
+                    hits += single.is_some() as usize;
+                }
+            }
+        }
+        hits
+    }
+
+    #[test]
+    fn packets_find_the_same_hits_as_single_rays() {
+        let mut scene = scene();
+        let views = [
+            (Vec3::new(0.0, 20.0, 14.0), Vec3::new(0.0, 8.0, -4.0)),    // Down over the hills, rays both ways along x
+            (Vec3::new(-20.0, 14.0, -20.0), Vec3::new(0.0, 9.0, 0.0)),  // Along a diagonal
+            (Vec3::new(1.5, 25.0, -3.5), Vec3::new(1.5, 0.0, -3.6)),    // Straight down
+            (Vec3::new(-0.5, 12.0, 10.0), Vec3::new(-0.5, 12.0, -10.0)), // Level, along the row of blocks
+        ];
+        for (position, target) in views {
+            let camera = Camera::new(position, target, Degrees(70.0), 1.0);
+            assert!(compare(&scene, &camera) > 0);
+        }
+
+        // Edited since the grid was updated: both go ray by ray and still agree
+        let cell = (0, 12, -4);
+        scene.grid.remove(cell);
+        assert!(scene.grid.is_stale());
+        let (position, target) = views[0];
+        compare(&scene, &Camera::new(position, target, Degrees(70.0), 1.0));
+    }
+
+    #[test]
+    fn preset_lamps_stay_put_in_the_world_when_the_origin_moves() {
+        let world_lamps = |scene: &Scene| -> Vec<Vec3> {
+            scene.lighting.iter().flat_map(|lighting| &lighting.lights).map(|light| light.position + scene.origin).collect()
+        };
+        let mut scene = Scene::new();
+        scene.set_lighting(LightingPreset::by_name("studio"));
+        let before = world_lamps(&scene);
+        assert!(!before.is_empty());
+
+        assert!(scene.recenter_around(Vec3::new(5000.0, 0.0, -3000.0)).is_some());
+        assert_eq!(world_lamps(&scene), before);
+        // A preset picked afterwards goes to the same place
+        scene.set_lighting(LightingPreset::by_name("studio"));
+        assert_eq!(world_lamps(&scene), before);
+    }
+}
@@ -0,0 +1,260 @@
+use crate::backdrop::Backdrop;
+use crate::cube::Cube;
+use crate::decorations::Decorator;
+use crate::environment::Environment;
+use crate::light::SpotLight;
+use crate::lighting::LightingPreset;
+use crate::material::Material;
+use crate::material_palette::MaterialId;
+use crate::obj_loader::Mesh;
+use crate::palette;
+use crate::particles::Swarm;
+use crate::point_light::PointLight;
+use crate::scene::Scene;
+use crate::scene_events::{EventAction, Trigger};
+use crate::sign::Sign;
+use crate::skybox::Skybox;
+use crate::texture_cache;
+use crate::utils::{Real, Vec3};
+use crate::viewpoint::Viewpoint;
+use crate::weather::Weather;
+
+/// Built-in structures, the same ones scene files place with `[[prefabs]] kind = "..."`. A cherry
+/// tree stands at its position (lowest trunk block, on y = 0 for the diorama's ground); the
+/// diorama and its parts are moved by theirs from where they sit in the diorama, so zero puts
+/// them back in place.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Prefab {
+    CherryTreeDiorama(Vec3), // The whole bundled diorama: ground, both trees, pond, house and axolotl
+    CherryTree(Vec3),        // Trunk with the leaves around its top
+    Pond(Vec3),              // Fountain pond next to the diorama's center tree
+    House(Vec3),             // Lantern-lit house in the diorama's back corner
+    Axolotl(Vec3),           // OBJ model by the pond
+}
+
+impl Prefab {
+    /// Prefab for a scene file `kind` name, placed at `position`
+    pub fn parse(kind: &str, position: Vec3) -> Result<Self, String> {
+        match kind {
+            "cherry_tree_diorama" => Ok(Prefab::CherryTreeDiorama(position)),
+            "cherry_tree" => Ok(Prefab::CherryTree(position)),
+            "pond" => Ok(Prefab::Pond(position)),
+            "house" => Ok(Prefab::House(position)),
+            "axolotl" => Ok(Prefab::Axolotl(position)),
+            other => Err(format!("Unknown prefab kind '{}'", other)),
+        }
+    }
+
+    pub fn build(&self, scene: &mut Scene) {
+        match *self {
+            Prefab::CherryTreeDiorama(offset) => scene.build_cherry_tree_diorama(offset),
+            Prefab::CherryTree(base) => scene.build_cherry_tree(base),
+            Prefab::Pond(offset) => scene.build_pond(offset),
+            Prefab::House(offset) => scene.build_house(offset),
+            Prefab::Axolotl(offset) => scene.build_axolotl(offset),
+        }
+    }
+}
+
+/// Flat ground of unit blocks covering x in min_x..max_x and z in min_z..max_z (max excluded),
+/// with its top at y = 0 like the diorama's, so prefabs stand on it
+#[derive(Clone)]
+pub struct Terrain {
+    pub min_x: i32,
+    pub min_z: i32,
+    pub max_x: i32,
+    pub max_z: i32,
+    pub depth: u32, // Block layers, the top one included
+    top: Material,  // Top layer, seen from above
+    side: Material, // Top layer's sides
+    fill: Material, // Every layer under it
+}
+
+impl Terrain {
+    /// Grass over one layer of dirt, textured like the diorama's ground
+    pub fn grass(min_x: i32, min_z: i32, max_x: i32, max_z: i32) -> Self {
+        let dirt = Material::new(palette::DIRT_BROWN).with_texture(texture_cache::load("assets/textures/dirt.jpg"));
+        Self {
+            min_x,
+            min_z,
+            max_x,
+            max_z,
+            depth: 2,
+            top: Material::new(palette::GRASS_GREEN).with_texture(texture_cache::load("assets/textures/grass.jpg")),
+            side: Material::new(palette::GRASS_SIDE_GREEN).with_texture(texture_cache::load("assets/textures/grass_side.jpg")),
+            fill: dirt,
+        }
+    }
+
+    /// One material all the way through
+    pub fn new(min_x: i32, min_z: i32, max_x: i32, max_z: i32, material: Material) -> Self {
+        Self {
+            min_x,
+            min_z,
+            max_x,
+            max_z,
+            depth: 1,
+            top: material.clone(),
+            side: material.clone(),
+            fill: material,
+        }
+    }
+
+    pub fn with_depth(mut self, depth: u32) -> Self {
+        self.depth = depth.max(1);
+        self
+    }
+
+    fn build(&self, scene: &mut Scene) {
+        let top = scene.materials.add(self.top.clone());
+        let side = scene.materials.add(self.side.clone());
+        let fill = scene.materials.add(self.fill.clone());
+        for x in self.min_x..self.max_x {
+            for z in self.min_z..self.max_z {
+                let column = |layer: u32| Vec3::new(x as Real, -0.5 - layer as Real, z as Real);
+                scene.grid.add(Cube::new_multi_texture(column(0), 1.0, top, side, fill));
+                for layer in 1..self.depth {
+                    scene.grid.add(Cube::new(column(layer), 1.0, fill));
+                }
+            }
+        }
+    }
+}
+
+/// Builds a Scene in code instead of from a scene file, e.g.
+/// `SceneBuilder::new().with_terrain(Terrain::grass(-8, -8, 8, 8)).with_prefab(Prefab::CherryTree(Vec3::zero()))
+/// .with_light(lamp).build()`. Positions are in world coordinates, like a scene file's.
+pub struct SceneBuilder {
+    scene: Scene,
+}
+
+impl SceneBuilder {
+    /// Empty scene on the day/night cycle's sun and sky
+    pub fn new() -> Self {
+        Self { scene: Scene::new() }
+    }
+
+    pub fn with_terrain(mut self, terrain: Terrain) -> Self {
+        terrain.build(&mut self.scene);
+        self
+    }
+
+    pub fn with_prefab(mut self, prefab: Prefab) -> Self {
+        prefab.build(&mut self.scene);
+        self
+    }
+
+    /// Add a material to the scene's palette, for the cubes passed to with_cube, e.g.
+    /// `let stone = builder.material(Material::new(palette::STONE_GRAY));`
+    pub fn material(&mut self, material: Material) -> MaterialId {
+        self.scene.materials.add(material)
+    }
+
+    /// A cube, its material ids from `material`
+    pub fn with_cube(mut self, cube: Cube) -> Self {
+        self.scene.grid.add(cube);
+        self
+    }
+
+    /// Sign or banner blocks showing text, with planks on the back (sign.rs)
+    pub fn with_sign(mut self, sign: Sign) -> Self {
+        let board = self.scene.materials.intern("sign board", Sign::default_board);
+        self.scene.grid.extend(sign.cubes(board, &mut self.scene.materials));
+        self
+    }
+
+    /// Flowers, bushes and a fenced garden around what has been built so far (decorations.rs)
+    pub fn with_decorations(mut self, decorator: Decorator) -> Self {
+        decorator.decorate(&mut self.scene);
+        self
+    }
+
+    pub fn with_mesh(mut self, mesh: Mesh) -> Self {
+        self.scene.meshes.push(mesh);
+        self
+    }
+
+    pub fn with_light(mut self, light: PointLight) -> Self {
+        self.scene.point_lights.push(light);
+        self
+    }
+
+    pub fn with_spot_light(mut self, light: SpotLight) -> Self {
+        self.scene.spot_lights.push(light);
+        self
+    }
+
+    /// Fixed sun, ambient and sky instead of the day/night cycle's
+    pub fn with_lighting(mut self, lighting: LightingPreset) -> Self {
+        self.scene.set_lighting(Some(lighting));
+        self
+    }
+
+    /// Named camera (C cycles through them in the app, `--view` and `--views` pick them)
+    pub fn with_viewpoint(mut self, viewpoint: Viewpoint) -> Self {
+        self.scene.viewpoints.push(viewpoint);
+        self
+    }
+
+    /// Different sky, usually Skybox::from_cubemap; clouds and environment set on the builder stay
+    pub fn with_skybox(mut self, skybox: Skybox) -> Self {
+        let current = &mut self.scene.skybox;
+        self.scene.skybox = Skybox { cloud_coverage: current.cloud_coverage, environment: current.environment.take(), ..skybox };
+        self
+    }
+
+    /// Sky cloud coverage, 0 (clear) to 1 (overcast)
+    pub fn with_clouds(mut self, coverage: f32) -> Self {
+        self.scene.skybox.cloud_coverage = coverage.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Raining from the start, with the ground already soaked (weather.rs)
+    pub fn with_rain(mut self) -> Self {
+        self.scene.weather = Weather::rain();
+        self
+    }
+
+    /// Whether the prefabs' butterflies and fireflies come out (on unless turned off)
+    pub fn with_critters(mut self, critters: bool) -> Self {
+        self.scene.critters = critters;
+        self
+    }
+
+    /// Ambient critters around a spot, e.g. `Swarm::fireflies(center, 2.0)` (particles.rs)
+    pub fn with_swarm(mut self, swarm: Swarm) -> Self {
+        self.scene.swarms.push(swarm);
+        self
+    }
+
+    /// Scheduled event, e.g. `with_event(Trigger::Day(scene_events::DUSK), EventAction::LampsOn)`
+    pub fn with_event(mut self, trigger: Trigger, action: EventAction) -> Self {
+        self.scene.events.add(trigger, action);
+        self
+    }
+
+    /// HDR sky image, also lighting the scene when its `lighting` is on
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.scene.skybox.environment = Some(environment);
+        self
+    }
+
+    pub fn with_backdrop(mut self, backdrop: Backdrop) -> Self {
+        self.scene.backdrop = Some(backdrop);
+        self
+    }
+
+    /// Finish the scene: block lookup grid, glowing-block lights, smoke, and a floating origin if it's far out
+    pub fn build(mut self) -> Scene {
+        self.scene.update_grid();
+        self.scene.update_particles();
+        self.scene.center_origin();
+        self.scene
+    }
+}
+
+impl Default for SceneBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,374 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::scene_file::{AtlasDef, CameraDef, MaterialDef, PlacedBlock, PointLightDef, SceneFile, SignDef, SpotLightDef};
+use crate::utils::narrow;
+
+// Longest list printed per section before it is summarized
+const MAX_LISTED: usize = 20;
+
+// Positions are compared on a 1/100 unit grid so float noise doesn't show up as a change
+type PositionKey = (i32, i32, i32);
+
+fn position_key(p: [f32; 3]) -> PositionKey {
+    ((p[0] * 100.0).round() as i32, (p[1] * 100.0).round() as i32, (p[2] * 100.0).round() as i32)
+}
+
+fn format_position(key: PositionKey) -> String {
+    format!("({}, {}, {})", key.0 as f32 / 100.0, key.1 as f32 / 100.0, key.2 as f32 / 100.0)
+}
+
+/// Added / removed / changed entries of one section (materials, blocks, ...)
+struct Section {
+    title: &'static str,
+    added: Vec<String>,
+    removed: Vec<String>,
+    changed: Vec<String>,
+}
+
+impl Section {
+    fn new(title: &'static str) -> Self {
+        Self {
+            title,
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+        }
+    }
+
+    /// Compare two keyed collections: describe(value) for adds/removes, change(old, new) for edits
+    fn compare<K: Ord + Copy, V>(
+        title: &'static str,
+        old: &BTreeMap<K, V>,
+        new: &BTreeMap<K, V>,
+        describe: impl Fn(K, &V) -> String,
+        change: impl Fn(K, &V, &V) -> Option<String>,
+    ) -> Self {
+        let mut section = Self::new(title);
+        for (&key, old_value) in old {
+            match new.get(&key) {
+                Some(new_value) => section.changed.extend(change(key, old_value, new_value)),
+                None => section.removed.push(describe(key, old_value)),
+            }
+        }
+        for (&key, new_value) in new {
+            if !old.contains_key(&key) {
+                section.added.push(describe(key, new_value));
+            }
+        }
+        section
+    }
+
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    fn print(&self) {
+        println!(
+            "{}: +{} -{} ~{}",
+            self.title,
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        );
+        let lines = self.added.iter().map(|l| ('+', l))
+            .chain(self.removed.iter().map(|l| ('-', l)))
+            .chain(self.changed.iter().map(|l| ('~', l)));
+        let total = self.added.len() + self.removed.len() + self.changed.len();
+        for (sign, line) in lines.take(MAX_LISTED) {
+            println!("  {} {}", sign, line);
+        }
+        if total > MAX_LISTED {
+            println!("  ... and {} more", total - MAX_LISTED);
+        }
+    }
+}
+
+/// Differences between two scene files, section by section
+pub struct SceneDiff {
+    sections: Vec<Section>,
+}
+
+impl SceneDiff {
+    pub fn load(old_path: &Path, new_path: &Path) -> Result<Self, String> {
+        Ok(Self::between(&SceneFile::load(old_path)?, &SceneFile::load(new_path)?))
+    }
+
+    pub fn between(old: &SceneFile, new: &SceneFile) -> Self {
+        let sections = vec![
+            diff_atlases(old, new),
+            diff_materials(old, new),
+            diff_prefabs(old, new),
+            diff_blocks(old, new),
+            diff_lights(old, new),
+            diff_spot_lights(old, new),
+            diff_signs(old, new),
+            diff_cameras(old, new),
+        ];
+        Self { sections }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sections.iter().all(Section::is_empty)
+    }
+
+    pub fn print(&self) {
+        if self.is_empty() {
+            println!("Scenes are identical");
+            return;
+        }
+        for section in self.sections.iter().filter(|s| !s.is_empty()) {
+            section.print();
+        }
+    }
+}
+
+fn atlases_by_name(file: &SceneFile) -> BTreeMap<&str, &AtlasDef> {
+    file.atlases.iter().map(|(name, def)| (name.as_str(), def)).collect()
+}
+
+fn describe_atlas(atlas: &AtlasDef) -> String {
+    format!("{} ({}x{} tiles)", atlas.texture, atlas.columns, atlas.rows)
+}
+
+fn diff_atlases(old: &SceneFile, new: &SceneFile) -> Section {
+    Section::compare(
+        "Atlases",
+        &atlases_by_name(old),
+        &atlases_by_name(new),
+        |name, atlas| format!("{}: {}", name, describe_atlas(atlas)),
+        |name, a, b| (a != b).then(|| format!("{}: {} -> {}", name, describe_atlas(a), describe_atlas(b))),
+    )
+}
+
+fn materials_by_name(file: &SceneFile) -> BTreeMap<&str, &MaterialDef> {
+    file.materials.iter().map(|(name, def)| (name.as_str(), def)).collect()
+}
+
+fn diff_materials(old: &SceneFile, new: &SceneFile) -> Section {
+    Section::compare(
+        "Materials",
+        &materials_by_name(old),
+        &materials_by_name(new),
+        |name, _| name.to_string(),
+        |name, a, b| {
+            let fields = material_changes(a, b);
+            (!fields.is_empty()).then(|| format!("{}: {}", name, fields.join(", ")))
+        },
+    )
+}
+
+// "field old -> new" for every material property that differs
+fn material_changes(a: &MaterialDef, b: &MaterialDef) -> Vec<String> {
+    let fields = [
+        ("albedo", format!("{:?}", a.albedo), format!("{:?}", b.albedo)),
+        ("texture", format!("{:?}", a.texture), format!("{:?}", b.texture)),
+        ("texture_frames", format!("{:?}", a.texture_frames), format!("{:?}", b.texture_frames)),
+        ("animation", format!("{:?}", a.animation), format!("{:?}", b.animation)),
+        ("atlas", format!("{:?}", a.atlas), format!("{:?}", b.atlas)),
+        ("tile", format!("{:?}", a.tile), format!("{:?}", b.tile)),
+        ("filter", format!("{:?}", a.filter), format!("{:?}", b.filter)),
+        ("reflectivity", a.reflectivity.to_string(), b.reflectivity.to_string()),
+        ("specular", a.specular.to_string(), b.specular.to_string()),
+        ("shininess", a.shininess.to_string(), b.shininess.to_string()),
+        ("emissive", format!("{:?}", a.emissive), format!("{:?}", b.emissive)),
+        ("transparency", a.transparency.to_string(), b.transparency.to_string()),
+        ("refractive_index", a.refractive_index.to_string(), b.refractive_index.to_string()),
+        ("alpha_cutoff", a.alpha_cutoff.to_string(), b.alpha_cutoff.to_string()),
+        ("normal_map", format!("{:?}", a.normal_map), format!("{:?}", b.normal_map)),
+        ("normal_strength", a.normal_strength.to_string(), b.normal_strength.to_string()),
+        ("absorption", format!("{:?}", a.absorption), format!("{:?}", b.absorption)),
+        ("absorption_density", a.absorption_density.to_string(), b.absorption_density.to_string()),
+        ("frost", a.frost.to_string(), b.frost.to_string()),
+        ("subsurface", a.subsurface.to_string(), b.subsurface.to_string()),
+        ("sparkle", a.sparkle.to_string(), b.sparkle.to_string()),
+        ("glint", a.glint.to_string(), b.glint.to_string()),
+        ("puddles", a.puddles.to_string(), b.puddles.to_string()),
+        ("waves", a.waves.to_string(), b.waves.to_string()),
+        ("foam", a.foam.to_string(), b.foam.to_string()),
+        ("gravity", a.gravity.to_string(), b.gravity.to_string()),
+        ("shadow_catcher", a.shadow_catcher.to_string(), b.shadow_catcher.to_string()),
+    ];
+    fields
+        .into_iter()
+        .filter(|(_, before, after)| before != after)
+        .map(|(field, before, after)| format!("{} {} -> {}", field, before, after))
+        .collect()
+}
+
+// Prefabs have no identity besides kind + position, so identical placements are counted
+fn prefabs_by_placement(file: &SceneFile) -> BTreeMap<(&str, PositionKey), usize> {
+    let mut placements = BTreeMap::new();
+    for prefab in &file.prefabs {
+        *placements.entry((prefab.kind.as_str(), position_key(prefab.position))).or_insert(0) += 1;
+    }
+    placements
+}
+
+fn diff_prefabs(old: &SceneFile, new: &SceneFile) -> Section {
+    Section::compare(
+        "Prefabs",
+        &prefabs_by_placement(old),
+        &prefabs_by_placement(new),
+        |(kind, key), _| format!("{} at {}", kind, format_position(key)),
+        |(kind, key), a, b| (a != b).then(|| format!("{} at {}: {} -> {} copies", kind, format_position(key), a, b)),
+    )
+}
+
+fn describe_block(block: &PlacedBlock) -> String {
+    let mut text = block.material.to_string();
+    if let Some(top) = block.top {
+        text += &format!(" (top {})", top);
+    }
+    if let Some(bottom) = block.bottom {
+        text += &format!(" (bottom {})", bottom);
+    }
+    if block.size != 1.0 {
+        text += &format!(" size {}", block.size);
+    }
+    text
+}
+
+fn diff_blocks(old: &SceneFile, new: &SceneFile) -> Section {
+    // Later blocks replace earlier ones at the same position, like they do in the built scene
+    let by_position = |file: &SceneFile| {
+        file.placed_blocks()
+            .iter()
+            .map(|b| (position_key([narrow(b.position.x), narrow(b.position.y), narrow(b.position.z)]), describe_block(b)))
+            .collect::<BTreeMap<_, _>>()
+    };
+    Section::compare(
+        "Blocks",
+        &by_position(old),
+        &by_position(new),
+        |key, block| format!("{} {}", format_position(key), block),
+        |key, a, b| (a != b).then(|| format!("{} {} -> {}", format_position(key), a, b)),
+    )
+}
+
+fn lights_by_position(file: &SceneFile) -> BTreeMap<PositionKey, &PointLightDef> {
+    file.point_lights
+        .iter()
+        .map(|l| (position_key(l.position), l))
+        .collect()
+}
+
+fn describe_light(light: &PointLightDef) -> String {
+    format!(
+        "color {:?}, {}, radius {}, source radius {}, falloff {:?}",
+        light.color, describe_brightness(light.intensity, light.lumens), light.radius, light.source_radius, light.falloff
+    )
+}
+
+fn describe_brightness(intensity: Option<f32>, lumens: Option<f32>) -> String {
+    match (intensity, lumens) {
+        (Some(intensity), _) => format!("intensity {}", intensity),
+        (None, Some(lumens)) => format!("{} lm", lumens),
+        (None, None) => "default brightness".to_string(),
+    }
+}
+
+fn diff_lights(old: &SceneFile, new: &SceneFile) -> Section {
+    Section::compare(
+        "Point lights",
+        &lights_by_position(old),
+        &lights_by_position(new),
+        |key, light| format!("{} {}", format_position(key), describe_light(light)),
+        |key, a, b| (a != b).then(|| format!("{} {} -> {}", format_position(key), describe_light(a), describe_light(b))),
+    )
+}
+
+fn spot_lights_by_position(file: &SceneFile) -> BTreeMap<PositionKey, &SpotLightDef> {
+    file.spot_lights
+        .iter()
+        .map(|l| (position_key(l.position), l))
+        .collect()
+}
+
+fn describe_spot_light(light: &SpotLightDef) -> String {
+    format!(
+        "direction {:?}, color {:?}, {}, cone {}-{} deg, radius {}",
+        light.direction, light.color, describe_brightness(light.intensity, light.lumens), light.inner_angle, light.outer_angle, light.radius
+    )
+}
+
+fn diff_spot_lights(old: &SceneFile, new: &SceneFile) -> Section {
+    Section::compare(
+        "Spot lights",
+        &spot_lights_by_position(old),
+        &spot_lights_by_position(new),
+        |key, light| format!("{} {}", format_position(key), describe_spot_light(light)),
+        |key, a, b| (a != b).then(|| format!("{} {} -> {}", format_position(key), describe_spot_light(a), describe_spot_light(b))),
+    )
+}
+
+fn signs_by_position(file: &SceneFile) -> BTreeMap<PositionKey, &SignDef> {
+    file.signs
+        .iter()
+        .map(|s| (position_key(s.position), s))
+        .collect()
+}
+
+fn describe_sign(sign: &SignDef) -> String {
+    format!(
+        "{:?} facing {}, ink {:?}, background {:?}, board {:?}",
+        sign.text, sign.facing, sign.ink, sign.background, sign.material
+    )
+}
+
+fn diff_signs(old: &SceneFile, new: &SceneFile) -> Section {
+    Section::compare(
+        "Signs",
+        &signs_by_position(old),
+        &signs_by_position(new),
+        |key, sign| format!("{} {}", format_position(key), describe_sign(sign)),
+        |key, a, b| (a != b).then(|| format!("{} {} -> {}", format_position(key), describe_sign(a), describe_sign(b))),
+    )
+}
+
+fn cameras_by_name(file: &SceneFile) -> BTreeMap<&str, &CameraDef> {
+    file.cameras.iter().map(|c| (c.name.as_str(), c)).collect()
+}
+
+fn describe_camera(camera: &CameraDef) -> String {
+    let mut text = format!("at {:?} looking at {:?}, fov {}", camera.position, camera.target, camera.fov);
+    if camera.roll != 0.0 {
+        text += &format!(", roll {}", camera.roll);
+    }
+    if camera.aperture > 0.0 {
+        text += &format!(", aperture {}", camera.aperture);
+    }
+    if let Some(distance) = camera.focus_distance {
+        text += &format!(", focus {}", distance);
+    }
+    text
+}
+
+fn diff_cameras(old: &SceneFile, new: &SceneFile) -> Section {
+    Section::compare(
+        "Cameras",
+        &cameras_by_name(old),
+        &cameras_by_name(new),
+        |name, camera| format!("{} {}", name, describe_camera(camera)),
+        |name, a, b| (a != b).then(|| format!("{} {} -> {}", name, describe_camera(a), describe_camera(b))),
+    )
+}
+
+/// `diff <old.toml> <new.toml>`: print the differences, exit status 0 when identical, 1 when not
+pub fn run(args: &[String]) -> i32 {
+    let (Some(old), Some(new)) = (args.first(), args.get(1)) else {
+        eprintln!("Usage: diff <old scene.toml> <new scene.toml>");
+        return 2;
+    };
+    match SceneDiff::load(Path::new(old), Path::new(new)) {
+        Ok(diff) => {
+            println!("Scene diff: {} -> {}", old, new);
+            diff.print();
+            if diff.is_empty() { 0 } else { 1 }
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            2
+        }
+    }
+}
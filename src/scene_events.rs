@@ -0,0 +1,192 @@
+use crate::scene_time::SceneTime;
+use crate::utils::Vec3;
+
+pub const DUSK: f32 = 0.45; // Day fraction of dusk: the sun is nearly down and the sky turns pink
+pub const DAWN: f32 = 0.95; // Day fraction of dawn, just before the cycle wraps back to day
+
+/// When a scheduled event fires, checked against the scene clock every update
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trigger {
+    Day(f32),   // Each time the clock passes this day fraction (DUSK, DAWN or any other)
+    Every(f32), // Every so many seconds of scene time
+    At(f32),    // Once, when the scene time reaches this many seconds
+}
+
+/// What an event does to the scene
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventAction {
+    LampsOn,
+    LampsOff,
+    RainStart,
+    RainStop,
+    Clouds(f32), // Sky cloud coverage, 0 (clear) to 1 (overcast)
+    Petals(Vec3), // Burst of cherry petals from this world position (particles::PetalBurst)
+}
+
+impl Trigger {
+    /// "dusk" or "dawn"
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "dusk" => Ok(Self::Day(DUSK)),
+            "dawn" => Ok(Self::Day(DAWN)),
+            _ => Err(format!("Unknown event time '{}', expected dusk or dawn", name)),
+        }
+    }
+
+    // Whether the clock passed this trigger going from (day fraction, elapsed) `from` to `time`
+    fn fired(&self, from: (f32, f32), time: &SceneTime) -> bool {
+        let (day, elapsed) = from;
+        match *self {
+            Self::Day(at) if time.day_fraction >= day => day < at && at <= time.day_fraction,
+            Self::Day(at) => at > day || at <= time.day_fraction, // Wrapped from night back to day
+            Self::Every(seconds) => (time.elapsed / seconds).floor() > (elapsed / seconds).floor(),
+            Self::At(seconds) => elapsed < seconds && seconds <= time.elapsed,
+        }
+    }
+}
+
+impl EventAction {
+    /// "lamps_on", "lamps_off", "rain", "dry", "clouds" (which takes the coverage as `value`) or
+    /// "petals" (which bursts from `position`)
+    pub fn parse(name: &str, value: Option<f32>, position: Option<Vec3>) -> Result<Self, String> {
+        if name == "petals" {
+            return position.map(Self::Petals).ok_or_else(|| "Event action 'petals' needs a position".to_string());
+        }
+        match (name, value) {
+            ("lamps_on", _) => Ok(Self::LampsOn),
+            ("lamps_off", _) => Ok(Self::LampsOff),
+            ("rain", _) => Ok(Self::RainStart),
+            ("dry", _) => Ok(Self::RainStop),
+            ("clouds", Some(coverage)) if (0.0..=1.0).contains(&coverage) => Ok(Self::Clouds(coverage)),
+            ("clouds", _) => Err("Event action 'clouds' needs a value between 0 and 1".to_string()),
+            _ => Err(format!("Unknown event action '{}', expected lamps_on, lamps_off, rain, dry, clouds or petals", name)),
+        }
+    }
+}
+
+/// Time-based triggers for the scene's dynamic systems (lamps at dusk and dawn, rain showers,
+/// clouds rolling in), from the scene file's [[events]] or SceneBuilder::with_event. The scene
+/// asks it what's due after every clock update and applies the actions (Scene::update), so the
+/// clock running fast (N) or slow doesn't skip any. Only the running clock fires events; setting
+/// the time directly (exports, batch jobs) doesn't.
+#[derive(Clone, Default)]
+pub struct EventScheduler {
+    events: Vec<(Trigger, EventAction)>,
+    last: Option<(f32, f32)>, // Clock (day fraction, elapsed) at the previous check
+}
+
+impl EventScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, trigger: Trigger, action: EventAction) {
+        self.events.push((trigger, action));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Actions whose triggers the clock passed since the previous call, in the order they were
+    /// added. The first call only notes the time.
+    pub fn due(&mut self, time: &SceneTime) -> Vec<EventAction> {
+        let Some(from) = self.last.replace((time.day_fraction, time.elapsed)) else {
+            return Vec::new();
+        };
+        if from == (time.day_fraction, time.elapsed) {
+            return Vec::new();
+        }
+        self.events.iter().filter(|(trigger, _)| trigger.fired(from, time)).map(|(_, action)| *action).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock(day_fraction: f32, elapsed: f32) -> SceneTime {
+        let mut time = SceneTime::new();
+        (time.day_fraction, time.elapsed) = (day_fraction, elapsed);
+        time
+    }
+
+    // Scheduler with one event, already past its first (time-noting) check at `from`
+    fn scheduler(trigger: Trigger, from: (f32, f32)) -> EventScheduler {
+        let mut events = EventScheduler::new();
+        events.add(trigger, EventAction::LampsOn);
+        assert!(events.due(&clock(from.0, from.1)).is_empty());
+        events
+    }
+
+    #[test]
+    fn trigger_names() {
+        assert_eq!(Trigger::parse("dusk"), Ok(Trigger::Day(DUSK)));
+        assert_eq!(Trigger::parse("dawn"), Ok(Trigger::Day(DAWN)));
+        assert!(Trigger::parse("noon").is_err());
+    }
+
+    #[test]
+    fn first_check_only_notes_the_time() {
+        let mut events = EventScheduler::new();
+        events.add(Trigger::At(0.0), EventAction::LampsOn);
+        assert!(events.due(&clock(0.0, 5.0)).is_empty());
+        assert!(events.due(&clock(0.0, 5.0)).is_empty());
+    }
+
+    #[test]
+    fn day_fraction_passed() {
+        let mut events = scheduler(Trigger::Day(DUSK), (0.4, 0.0));
+        assert_eq!(events.due(&clock(0.44, 1.0)), vec![]);
+        assert_eq!(events.due(&clock(0.46, 2.0)), vec![EventAction::LampsOn]);
+        assert_eq!(events.due(&clock(0.6, 3.0)), vec![]);
+    }
+
+    #[test]
+    fn day_fraction_across_the_wrap() {
+        // Night wrapping back to day passes fractions late in the night and early in the day
+        let mut late = scheduler(Trigger::Day(0.99), (0.97, 0.0));
+        assert_eq!(late.due(&clock(0.02, 1.0)), vec![EventAction::LampsOn]);
+        let mut early = scheduler(Trigger::Day(0.01), (0.97, 0.0));
+        assert_eq!(early.due(&clock(0.02, 1.0)), vec![EventAction::LampsOn]);
+        let mut earlier = scheduler(Trigger::Day(DAWN), (0.97, 0.0));
+        assert_eq!(earlier.due(&clock(0.02, 1.0)), vec![]);
+    }
+
+    #[test]
+    fn every_interval() {
+        let mut events = scheduler(Trigger::Every(10.0), (0.0, 5.0));
+        assert_eq!(events.due(&clock(0.0, 9.0)), vec![]);
+        assert_eq!(events.due(&clock(0.0, 10.0)), vec![EventAction::LampsOn]);
+        assert_eq!(events.due(&clock(0.0, 19.5)), vec![]);
+        // A long step past several intervals fires once
+        assert_eq!(events.due(&clock(0.0, 45.0)), vec![EventAction::LampsOn]);
+    }
+
+    #[test]
+    fn at_fires_once() {
+        let mut events = scheduler(Trigger::At(3.0), (0.0, 1.0));
+        assert_eq!(events.due(&clock(0.0, 3.0)), vec![EventAction::LampsOn]);
+        assert_eq!(events.due(&clock(0.0, 8.0)), vec![]);
+    }
+
+    #[test]
+    fn due_in_the_order_added() {
+        let mut events = EventScheduler::new();
+        events.add(Trigger::Every(1.0), EventAction::RainStart);
+        events.add(Trigger::At(10.0), EventAction::Clouds(0.5));
+        events.add(Trigger::Every(2.0), EventAction::Petals(Vec3::new(0.0, 2.5, -1.0)));
+        assert!(events.due(&clock(0.0, 9.5)).is_empty());
+        assert_eq!(
+            events.due(&clock(0.0, 10.0)),
+            vec![EventAction::RainStart, EventAction::Clouds(0.5), EventAction::Petals(Vec3::new(0.0, 2.5, -1.0))]
+        );
+    }
+
+    #[test]
+    fn petals_need_a_position() {
+        assert!(EventAction::parse("petals", None, None).is_err());
+        let position = Vec3::new(1.0, 2.0, 3.0);
+        assert_eq!(EventAction::parse("petals", None, Some(position)), Ok(EventAction::Petals(position)));
+    }
+}
@@ -0,0 +1,924 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use toml_edit::{Decor, DocumentMut, Item};
+
+use crate::backdrop::{Backdrop, BackdropMapping};
+use crate::color::Color;
+use crate::cube::Cube;
+use crate::decorations::Decorator;
+use crate::environment::Environment;
+use crate::light::{LANTERN_LUMENS, SpotLight, lumens};
+use crate::lighting::LightingPreset;
+use crate::material::Material;
+use crate::material_palette::{MaterialId, MaterialPalette};
+use crate::palette::Palette;
+use crate::point_light::{Falloff, PointLight};
+use crate::scene::Scene;
+use crate::scene_builder::Prefab;
+use crate::scene_events::{EventAction, Trigger};
+use crate::scene_migration;
+use crate::sign::{Facing, Sign};
+use crate::skybox::Skybox;
+use crate::streaming::ChunkStreamer;
+use crate::texture::{Texture, TextureAnimation, TextureFilter};
+use crate::texture_cache;
+use crate::texture_atlas::TextureAtlas;
+use crate::utils::{Degrees, Real, Vec3};
+use crate::viewpoint::Viewpoint;
+use crate::weather::Weather;
+
+/// Built-in debug material every block can use without a [materials] entry (Material::uv_grid)
+pub const UV_GRID_MATERIAL: &str = "uv_grid";
+const LIGHT_TABLES: [&str; 2] = ["point_lights", "spot_lights"]; // The tables save_lights rewrites
+
+/// Built-in block materials, also usable without a [materials] entry (a scene's own entry of the
+/// same name wins)
+pub fn builtin_material(name: &str) -> Option<Material> {
+    match name {
+        UV_GRID_MATERIAL => Some(Material::uv_grid()),
+        "ice" => Some(Material::ice()),
+        "snow" => Some(Material::snow()),
+        "sand" => Some(Material::sand()),
+        "gravel" => Some(Material::gravel()),
+        _ => None,
+    }
+}
+
+/// Id of a built-in block material in a scene's palette, added the first time it's asked for
+/// (block editing places these; a scene's own material of the same name isn't used)
+pub fn builtin_material_id(materials: &mut MaterialPalette, name: &str) -> Option<MaterialId> {
+    materials.try_intern(&format!("builtin {}", name), || builtin_material(name).ok_or(())).ok()
+}
+
+/// Scene description loaded from a TOML file (see assets/scenes/diorama.toml)
+#[derive(Deserialize, Default)]
+pub struct SceneFile {
+    #[serde(default)]
+    pub atlases: HashMap<String, AtlasDef>,
+    #[serde(default)]
+    pub materials: HashMap<String, MaterialDef>,
+    #[serde(default)]
+    pub prefabs: Vec<PrefabDef>,
+    #[serde(default)]
+    pub fills: Vec<FillDef>,
+    #[serde(default)]
+    pub blocks: Vec<BlockDef>,
+    #[serde(default)]
+    pub point_lights: Vec<PointLightDef>,
+    #[serde(default)]
+    pub spot_lights: Vec<SpotLightDef>,
+    #[serde(default)]
+    pub signs: Vec<SignDef>,
+    #[serde(default)]
+    pub cameras: Vec<CameraDef>,
+    #[serde(default)]
+    pub events: Vec<EventDef>,
+    pub skybox: Option<SkyboxDef>,
+    pub backdrop: Option<BackdropDef>,
+    pub environment: Option<EnvironmentDef>,
+    pub streaming: Option<StreamingDef>,
+    pub decorations: Option<DecorationsDef>,
+    pub palette: Option<String>,  // Palette file whose names materials can use as colors, also at the top
+    pub lighting: Option<String>, // Lighting preset name, set at the top of the file before any [tables]
+    pub clouds: Option<f32>,      // Sky cloud coverage, 0 (clear) to 1 (overcast), also at the top
+    pub rain: Option<bool>,       // Start raining, with the ground already soaked (puddles), also at the top
+    pub critters: Option<bool>,   // false keeps the butterflies and fireflies away, also at the top
+}
+
+#[derive(Deserialize, PartialEq)]
+pub struct MaterialDef {
+    pub albedo: ColorDef,
+    pub texture: Option<String>,
+    pub texture_frames: Option<Vec<String>>, // One file per animation frame, used instead of texture
+    pub animation: Option<TextureAnimation>, // { frames = N, frame_time = seconds } for strip textures
+    pub atlas: Option<String>,   // Name of an [atlases] entry, used instead of texture
+    pub tile: Option<[usize; 2]>, // [column, row] in that atlas
+    #[serde(default)]
+    pub filter: TextureFilter, // "nearest" (default) or "bilinear"
+    #[serde(default)]
+    pub reflectivity: f32,
+    #[serde(default)]
+    pub specular: f32,
+    #[serde(default = "default_shininess")]
+    pub shininess: f32,
+    pub emissive: Option<ColorDef>,
+    #[serde(default)]
+    pub transparency: f32,
+    #[serde(default = "default_refractive_index")]
+    pub refractive_index: f32,
+    #[serde(default)]
+    pub alpha_cutoff: f32, // Texels with alpha below this are holes (0 = off)
+    pub normal_map: Option<String>, // Tangent-space normal map (green = up) for surface relief
+    #[serde(default = "default_normal_strength")]
+    pub normal_strength: f32,
+    pub absorption: Option<ColorDef>, // Tint picked up inside transparent volumes (Beer-Lambert)
+    #[serde(default = "default_absorption_density")]
+    pub absorption_density: f32,      // How fast that tint builds up, per block travelled
+    #[serde(default)]
+    pub frost: f32,      // Refraction roughness (ice ~0.15): blurs what's seen through the material
+    #[serde(default)]
+    pub subsurface: f32, // 0-1, light wrapping past the terminator (snow ~0.4)
+    #[serde(default)]
+    pub sparkle: f32,    // Sun glints from tiny crystals (snow 1.0)
+    #[serde(default)]
+    pub puddles: f32,    // 0-1, rain pools on the top face while the scene is wet (stone paths 1.0)
+    #[serde(default)]
+    pub glint: f32,      // Metal flakes glinting in the material's color under sun and lamps (gold 1.0)
+    #[serde(default)]
+    pub waves: f32, // Animated waves on the surface (water 1.0), so reflections ripple
+    #[serde(default)]
+    pub foam: f32, // Shoreline foam on water (0-1), where the top face meets solid blocks
+    #[serde(default)]
+    pub gravity: bool, // Falls when the block under it is removed in the app (sand, gravel)
+    #[serde(default)]
+    pub shadow_catcher: f32, // > 0: invisible ground for compositing, only shadows (this dark) and reflections show
+}
+
+/// Material color: [r, g, b] or the name of a palette color ("grass_green", see palette.rs)
+#[derive(Deserialize, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum ColorDef {
+    Rgb([f32; 3]),
+    Named(String),
+}
+
+impl ColorDef {
+    fn resolve(&self, palette: &Palette) -> Result<Color, String> {
+        match self {
+            ColorDef::Rgb(c) => Ok(color(*c)),
+            ColorDef::Named(name) => palette.color(name),
+        }
+    }
+}
+
+// Written the way it appears in the file, for scene diffs
+impl std::fmt::Debug for ColorDef {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ColorDef::Rgb(c) => write!(f, "{:?}", c),
+            ColorDef::Named(name) => write!(f, "{:?}", name),
+        }
+    }
+}
+
+/// Texture sheet split into a grid of equally sized tiles
+#[derive(Deserialize, PartialEq)]
+pub struct AtlasDef {
+    pub texture: String,
+    pub columns: usize,
+    pub rows: usize,
+}
+
+/// Built-in structure placed by name ("cherry_tree", "pond", "house", "axolotl", "cherry_tree_diorama")
+#[derive(Deserialize, PartialEq)]
+pub struct PrefabDef {
+    pub kind: String,
+    #[serde(default)]
+    pub position: [f32; 3],
+}
+
+/// Box of unit cubes from min to max (inclusive, 1 unit steps)
+#[derive(Deserialize)]
+pub struct FillDef {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+    pub material: String,
+    pub top: Option<String>,
+    pub bottom: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct BlockDef {
+    pub position: [f32; 3],
+    #[serde(default = "default_size")]
+    pub size: f32,
+    pub material: String,
+    pub top: Option<String>,
+    pub bottom: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
+pub struct PointLightDef {
+    pub position: [f32; 3],
+    pub color: [f32; 3],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intensity: Option<f32>, // Display units, or
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lumens: Option<f32>,    // luminous flux (light.rs has the scale); a lantern's 250 lm when neither is set
+    pub radius: f32,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub source_radius: f32, // Size of the glowing sphere, for soft shadows (0 = a point)
+    #[serde(default, skip_serializing_if = "is_smooth")]
+    pub falloff: Falloff, // "smooth" (default), "inverse_square", "linear" or { curve = [1.0, ..., 0.0] }
+}
+
+/// Cone-shaped light: full brightness within `inner_angle` of `direction`, fading out by `outer_angle` (degrees)
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
+pub struct SpotLightDef {
+    pub position: [f32; 3],
+    pub direction: [f32; 3],
+    pub color: [f32; 3],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intensity: Option<f32>, // Same as for point lights
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lumens: Option<f32>,
+    pub inner_angle: f32,
+    pub outer_angle: f32,
+    #[serde(default = "default_spot_radius")]
+    pub radius: f32,
+}
+
+impl PointLightDef {
+    pub fn to_light(&self) -> Result<PointLight, String> {
+        if self.falloff == Falloff::Curve(Vec::new()) {
+            return Err("Point light falloff curve needs at least one value".to_string());
+        }
+        Ok(PointLight::new(vec3(self.position), color(self.color), lamp_intensity(self.intensity, self.lumens)?, self.radius as Real)
+            .with_source_radius(self.source_radius as Real)
+            .with_falloff(self.falloff.clone()))
+    }
+}
+
+impl SpotLightDef {
+    pub fn to_light(&self) -> Result<SpotLight, String> {
+        if self.outer_angle <= 0.0 || self.outer_angle >= 90.0 {
+            return Err(format!("Spot light outer_angle must be between 0 and 90 degrees, got {}", self.outer_angle));
+        }
+        Ok(SpotLight::new(
+            vec3(self.position),
+            vec3(self.direction),
+            color(self.color),
+            lamp_intensity(self.intensity, self.lumens)?,
+            self.inner_angle,
+            self.outer_angle,
+        )
+        .with_radius(self.radius as Real))
+    }
+}
+
+/// Image behind the scene instead of the skybox: mapping = "screen" (pinned behind the view)
+/// or "plane" (hung in the world at `center`, facing along `normal`, `size` units wide/high)
+#[derive(Deserialize, PartialEq)]
+pub struct BackdropDef {
+    pub image: String,
+    #[serde(default = "default_backdrop_mapping")]
+    pub mapping: String,
+    #[serde(default)]
+    pub center: [f32; 3],
+    #[serde(default = "default_backdrop_normal")]
+    pub normal: [f32; 3],
+    #[serde(default = "default_backdrop_size")]
+    pub size: [f32; 2],
+}
+
+/// Cubemap sky pack: six images in +X, -X, +Y, -Y, +Z, -Z order (right, left, top, bottom, front, back)
+#[derive(Deserialize)]
+pub struct SkyboxDef {
+    pub faces: [String; 6],
+    pub night_faces: Option<[String; 6]>, // The day faces stay up at night when left out
+}
+
+impl SkyboxDef {
+    fn to_skybox(&self) -> Result<Skybox, String> {
+        let skybox = Skybox::from_cubemap(self.faces.each_ref().map(String::as_str))?;
+        match self.night_faces {
+            Some(ref night) => skybox.with_night_cubemap(night.each_ref().map(String::as_str)),
+            None => Ok(skybox),
+        }
+    }
+}
+
+/// Equirectangular HDR image (.hdr, .exr) for the sky and, with `lighting`, the ambient light
+#[derive(Deserialize)]
+pub struct EnvironmentDef {
+    pub image: String,
+    #[serde(default = "default_intensity")]
+    pub intensity: f32,
+    #[serde(default)]
+    pub rotation: f32, // Degrees around the vertical axis
+    #[serde(default = "default_true")]
+    pub lighting: bool,
+}
+
+/// Sign or banner with text on its front (sign.rs); also added from the command line with `sign`
+#[derive(Deserialize, Serialize, PartialEq)]
+pub struct SignDef {
+    pub text: String, // "\n" starts a new line
+    pub position: [f32; 3], // Top-left block, as seen from the front
+    #[serde(default = "default_facing")]
+    pub facing: String, // "north", "south" (default), "east" or "west"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ink: Option<ColorDef>,        // Letter color (default dark gray)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<ColorDef>, // Color behind the letters (default off-white)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub material: Option<String>,     // Board on the back and edges (default planks)
+}
+
+/// Flowers, bushes and a fenced garden scattered around the scene's structures (decorations.rs)
+#[derive(Deserialize)]
+pub struct DecorationsDef {
+    #[serde(default)]
+    pub seed: u32,
+    #[serde(default = "default_density")]
+    pub density: f32, // 0 (bare) to 1 (every spot in a flower patch)
+    #[serde(default = "default_decoration_radius")]
+    pub radius: i32, // Blocks around the structures that get decorated
+    #[serde(default = "default_true")]
+    pub garden: bool,
+}
+
+/// Endless procedural hills loaded around the camera (streaming.rs)
+#[derive(Deserialize)]
+pub struct StreamingDef {
+    #[serde(default)]
+    pub seed: u32,
+    #[serde(default = "default_render_distance")]
+    pub render_distance: i32, // Chunks (16 blocks) loaded in every direction around the camera
+}
+
+impl SignDef {
+    fn to_sign(&self, palette: &Palette) -> Result<Sign, String> {
+        if self.text.trim().is_empty() {
+            return Err("Sign text must not be empty".to_string());
+        }
+        let mut sign = Sign::new(&self.text, vec3(self.position), Facing::parse(&self.facing)?);
+        if let Some(ref ink) = self.ink {
+            sign.ink = ink.resolve(palette)?;
+        }
+        if let Some(ref background) = self.background {
+            sign.background = background.resolve(palette)?;
+        }
+        Ok(sign)
+    }
+}
+
+impl BackdropDef {
+    fn to_backdrop(&self) -> Result<Backdrop, String> {
+        let mapping = match self.mapping.as_str() {
+            "screen" => BackdropMapping::Screen,
+            "plane" => {
+                let normal = vec3(self.normal);
+                if normal.length() == 0.0 || self.size[0] <= 0.0 || self.size[1] <= 0.0 {
+                    return Err("Backdrop plane needs a non-zero normal and size".to_string());
+                }
+                BackdropMapping::Plane {
+                    center: vec3(self.center),
+                    normal: normal.normalize(),
+                    width: self.size[0],
+                    height: self.size[1],
+                }
+            }
+            other => return Err(format!("Unknown backdrop mapping '{}' (use \"screen\" or \"plane\")", other)),
+        };
+        Ok(Backdrop::new(texture_cache::load(&self.image), mapping))
+    }
+}
+
+/// A single block from a fill or [[blocks]] entry, with its material names
+pub struct PlacedBlock<'a> {
+    pub position: Vec3,
+    pub size: Real,
+    pub material: &'a str,
+    pub top: Option<&'a str>,
+    pub bottom: Option<&'a str>,
+}
+
+/// Named viewpoint, selectable with C in the app or --view / --views on the command line
+#[derive(Deserialize, PartialEq)]
+pub struct CameraDef {
+    pub name: String,
+    pub position: [f32; 3],
+    pub target: [f32; 3],
+    #[serde(default = "default_fov")]
+    pub fov: f32,
+    #[serde(default)]
+    pub roll: f32, // Dutch angle in degrees, positive = clockwise
+    #[serde(default)]
+    pub aperture: f32, // Depth of field: lens radius in blocks (0 = everything sharp)
+    pub focus_distance: Option<f32>, // Distance that stays sharp, the target's by default
+}
+
+/// Something that happens on the scene clock: one of `when`, `day`, `every` or `at`, and an action
+#[derive(Deserialize, PartialEq)]
+pub struct EventDef {
+    pub when: Option<String>, // "dusk" or "dawn"
+    pub day: Option<f32>,     // Each time the clock passes this day fraction
+    pub every: Option<f32>,   // Every so many seconds of scene time
+    pub at: Option<f32>,      // Once, this many seconds into the scene
+    pub action: String,       // "lamps_on", "lamps_off", "rain", "dry", "clouds" or "petals"
+    pub value: Option<f32>,   // The cloud coverage for "clouds"
+    pub position: Option<[f32; 3]>, // Where "petals" bursts from
+}
+
+impl EventDef {
+    pub fn to_event(&self) -> Result<(Trigger, EventAction), String> {
+        let trigger = match (&self.when, self.day, self.every, self.at) {
+            (Some(name), None, None, None) => Trigger::parse(name)?,
+            (None, Some(day), None, None) if (0.0..1.0).contains(&day) => Trigger::Day(day),
+            (None, Some(day), None, None) => return Err(format!("Event day must be from 0 up to 1, got {}", day)),
+            (None, None, Some(every), None) if every > 0.0 => Trigger::Every(every),
+            (None, None, Some(every), None) => return Err(format!("Event interval must be positive, got {}", every)),
+            (None, None, None, Some(at)) => Trigger::At(at),
+            _ => return Err(format!("Event '{}' needs exactly one of when, day, every or at", self.action)),
+        };
+        Ok((trigger, EventAction::parse(&self.action, self.value, self.position.map(vec3))?))
+    }
+}
+
+fn is_zero(value: &f32) -> bool { *value == 0.0 }
+fn is_smooth(falloff: &Falloff) -> bool { *falloff == Falloff::Smooth }
+
+// A point or spot light's `intensity` or `lumens`, whichever is set
+fn lamp_intensity(intensity: Option<f32>, flux: Option<f32>) -> Result<f32, String> {
+    match (intensity, flux) {
+        (Some(_), Some(_)) => Err("Lights take either intensity or lumens, not both".to_string()),
+        (Some(intensity), None) => Ok(intensity),
+        (None, Some(flux)) => Ok(lumens(flux)),
+        (None, None) => Ok(lumens(LANTERN_LUMENS)),
+    }
+}
+
+fn default_fov() -> f32 { 70.0 }
+fn default_shininess() -> f32 { 32.0 }
+fn default_refractive_index() -> f32 { 1.0 }
+fn default_normal_strength() -> f32 { 1.0 }
+fn default_absorption_density() -> f32 { 1.0 }
+fn default_size() -> f32 { 1.0 }
+fn default_spot_radius() -> f32 { 16.0 }
+fn default_backdrop_mapping() -> String { "screen".to_string() }
+fn default_backdrop_normal() -> [f32; 3] { [0.0, 0.0, 1.0] }
+fn default_backdrop_size() -> [f32; 2] { [32.0, 18.0] }
+fn default_intensity() -> f32 { 1.0 }
+fn default_true() -> bool { true }
+fn default_render_distance() -> i32 { 4 }
+fn default_facing() -> String { "south".to_string() }
+fn default_density() -> f32 { 0.5 }
+fn default_decoration_radius() -> i32 { 6 }
+
+fn vec3(v: [f32; 3]) -> Vec3 {
+    Vec3::new(v[0] as Real, v[1] as Real, v[2] as Real)
+}
+
+fn color(c: [f32; 3]) -> Color {
+    Color::new(c[0], c[1], c[2])
+}
+
+impl MaterialDef {
+    fn to_material(&self, atlases: &HashMap<&str, TextureAtlas>, palette: &Palette) -> Result<Material, String> {
+        let mut material = Material::new(self.albedo.resolve(palette)?)
+            .with_reflectivity(self.reflectivity)
+            .with_specular(self.specular, self.shininess)
+            .with_transparency(self.transparency, self.refractive_index)
+            .with_alpha_cutoff(self.alpha_cutoff);
+
+        if let Some(ref path) = self.texture {
+            material = material.with_texture(texture_cache::load(path));
+        }
+        if let Some(ref paths) = self.texture_frames {
+            let frames: Vec<_> = paths.iter().map(|path| texture_cache::load(path)).collect();
+            material = material.with_texture(Texture::stack_frames(&frames)?);
+        }
+        if let Some(ref name) = self.atlas {
+            let atlas = atlases
+                .get(name.as_str())
+                .ok_or_else(|| format!("Unknown atlas '{}'", name))?;
+            let [column, row] = self.tile.unwrap_or([0, 0]);
+            material = material.with_atlas_tile(atlas, column, row)?;
+        }
+        material = material.with_filter(self.filter);
+        match (self.animation, &self.texture_frames) {
+            (Some(animation), _) => material = material.with_animation(animation),
+            (None, Some(paths)) => material = material.with_animation(TextureAnimation::new(paths.len(), 0.1)),
+            (None, None) => {}
+        }
+        if let Some(ref emissive) = self.emissive {
+            material = material.with_emissive(emissive.resolve(palette)?);
+        }
+        if let Some(ref path) = self.normal_map {
+            material = material.with_normal_map(texture_cache::load_normal_map(path), self.normal_strength);
+        }
+        if let Some(ref absorption) = self.absorption {
+            material = material.with_absorption(absorption.resolve(palette)?, self.absorption_density);
+        }
+        if self.frost > 0.0 {
+            material = material.with_frost(self.frost);
+        }
+        if self.subsurface > 0.0 {
+            material = material.with_subsurface(self.subsurface);
+        }
+        if self.sparkle > 0.0 {
+            material = material.with_sparkle(self.sparkle);
+        }
+        if self.glint > 0.0 {
+            material = material.with_glint(self.glint);
+        }
+        if self.puddles > 0.0 {
+            material = material.with_puddles(self.puddles);
+        }
+        if self.waves > 0.0 {
+            material = material.with_waves(self.waves);
+        }
+        if self.foam > 0.0 {
+            material = material.with_foam(self.foam);
+        }
+        if self.gravity {
+            material = material.with_gravity();
+        }
+        if self.shadow_catcher > 0.0 {
+            material = material.with_shadow_catcher(self.shadow_catcher);
+        }
+        Ok(material)
+    }
+}
+
+impl SceneFile {
+    /// Parse a scene, migrating files written for older format versions (with warnings)
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut table: toml::Table = toml::from_str(source).map_err(|e| e.to_string())?;
+        for warning in scene_migration::migrate(&mut table)? {
+            eprintln!("Warning: {}", warning);
+        }
+        table.try_into().map_err(|e: toml::de::Error| e.to_string())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let source = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read scene file '{}': {}", path.display(), e))?;
+        Self::parse(&source).map_err(|e| format!("Failed to parse scene file '{}': {}", path.display(), e))
+    }
+
+    /// Every block placed by the fills and single blocks (prefabs not included), in file order
+    pub fn placed_blocks(&self) -> Vec<PlacedBlock<'_>> {
+        let mut blocks = Vec::new();
+
+        for fill in &self.fills {
+            let steps = |axis: usize| ((fill.max[axis] - fill.min[axis]).round() as i32).max(0);
+            for ix in 0..=steps(0) {
+                for iy in 0..=steps(1) {
+                    for iz in 0..=steps(2) {
+                        blocks.push(PlacedBlock {
+                            position: vec3([
+                                fill.min[0] + ix as f32,
+                                fill.min[1] + iy as f32,
+                                fill.min[2] + iz as f32,
+                            ]),
+                            size: 1.0,
+                            material: &fill.material,
+                            top: fill.top.as_deref(),
+                            bottom: fill.bottom.as_deref(),
+                        });
+                    }
+                }
+            }
+        }
+
+        for block in &self.blocks {
+            blocks.push(PlacedBlock {
+                position: vec3(block.position),
+                size: block.size as Real,
+                material: &block.material,
+                top: block.top.as_deref(),
+                bottom: block.bottom.as_deref(),
+            });
+        }
+
+        blocks
+    }
+
+    /// Build a Scene from this description
+    pub fn build(&self) -> Result<Scene, String> {
+        let mut scene = Scene::new();
+
+        // Each atlas sheet is loaded once and shared by all of its materials
+        let atlases: HashMap<&str, TextureAtlas> = self
+            .atlases
+            .iter()
+            .map(|(name, def)| (name.as_str(), TextureAtlas::load(&def.texture, def.columns, def.rows)))
+            .collect();
+
+        let palette = match self.palette {
+            Some(ref path) => Palette::load(path)?,
+            None => Palette::new(),
+        };
+
+        // Each material (and its texture) is loaded once and goes into the scene's palette the
+        // first time a block uses it
+        let materials: HashMap<&str, Material> = self
+            .materials
+            .iter()
+            .map(|(name, def)| {
+                def.to_material(&atlases, &palette)
+                    .map(|material| (name.as_str(), material))
+                    .map_err(|e| format!("Material '{}': {}", name, e))
+            })
+            .collect::<Result<_, _>>()?;
+
+        let lookup = |name: &str| -> Result<Material, String> {
+            materials
+                .get(name)
+                .cloned()
+                .or_else(|| builtin_material(name))
+                .ok_or_else(|| format!("Unknown material '{}'", name))
+        };
+
+        let intern = |scene_materials: &mut MaterialPalette, name: &str| scene_materials.try_intern(name, || lookup(name));
+
+        let make_cube = |scene_materials: &mut MaterialPalette, position: Vec3, size: Real, material: &str, top: Option<&str>, bottom: Option<&str>| -> Result<Cube, String> {
+            let sides = intern(scene_materials, material)?;
+            if top.is_none() && bottom.is_none() {
+                return Ok(Cube::new(position, size, sides));
+            }
+            let top = match top { Some(name) => intern(scene_materials, name)?, None => sides };
+            let bottom = match bottom { Some(name) => intern(scene_materials, name)?, None => sides };
+            Ok(Cube::new_multi_texture(position, size, top, sides, bottom))
+        };
+
+        for prefab in &self.prefabs {
+            Prefab::parse(&prefab.kind, vec3(prefab.position))?.build(&mut scene);
+        }
+
+        for block in self.placed_blocks() {
+            scene.grid.add(make_cube(&mut scene.materials, block.position, block.size, block.material, block.top, block.bottom)?);
+        }
+
+        for sign in &self.signs {
+            let board = match sign.material {
+                Some(ref name) => intern(&mut scene.materials, name)?,
+                None => scene.materials.intern("sign board", Sign::default_board),
+            };
+            scene.grid.extend(sign.to_sign(&palette)?.cubes(board, &mut scene.materials));
+        }
+
+        if let Some(ref decorations) = self.decorations {
+            if !(0.0..=1.0).contains(&decorations.density) {
+                return Err(format!("Decoration density must be between 0 and 1, got {}", decorations.density));
+            }
+            if decorations.radius < 1 {
+                return Err(format!("Decoration radius must be at least 1 block, got {}", decorations.radius));
+            }
+            let decorator = Decorator::new(decorations.seed)
+                .with_density(decorations.density)
+                .with_radius(decorations.radius)
+                .with_garden(decorations.garden);
+            decorator.decorate(&mut scene);
+        }
+
+        // Lights from the file go last, after the prefabs' lamps (the light editor relies on it)
+        for light in &self.point_lights {
+            scene.point_lights.push(light.to_light()?);
+        }
+        for light in &self.spot_lights {
+            scene.spot_lights.push(light.to_light()?);
+        }
+
+        for camera in &self.cameras {
+            if scene.viewpoints.iter().any(|v| v.name == camera.name) {
+                return Err(format!("Duplicate camera name '{}'", camera.name));
+            }
+            scene.viewpoints.push(Viewpoint {
+                name: camera.name.clone(),
+                position: vec3(camera.position),
+                target: vec3(camera.target),
+                fov: Degrees(camera.fov),
+                roll: Degrees(camera.roll),
+                aperture: camera.aperture,
+                focus_distance: camera.focus_distance.map(|d| d as Real),
+            });
+        }
+
+        for event in &self.events {
+            let (trigger, action) = event.to_event()?;
+            scene.events.add(trigger, action);
+        }
+
+        if let Some(ref skybox) = self.skybox {
+            scene.skybox = skybox.to_skybox()?;
+        }
+        if let Some(ref backdrop) = self.backdrop {
+            scene.backdrop = Some(backdrop.to_backdrop()?);
+        }
+        if let Some(ref environment) = self.environment {
+            if environment.intensity < 0.0 {
+                return Err(format!("Environment intensity must not be negative, got {}", environment.intensity));
+            }
+            let rotation = Degrees(environment.rotation);
+            scene.skybox.environment = Some(Environment::load(&environment.image, environment.intensity, rotation, environment.lighting)?);
+        }
+        if let Some(ref name) = self.lighting {
+            scene.set_lighting(Some(LightingPreset::parse(name)?));
+        }
+        if let Some(coverage) = self.clouds {
+            if !(0.0..=1.0).contains(&coverage) {
+                return Err(format!("Cloud coverage must be between 0 and 1, got {}", coverage));
+            }
+            scene.skybox.cloud_coverage = coverage;
+        }
+        if self.rain == Some(true) {
+            scene.weather = Weather::rain();
+        }
+        if let Some(critters) = self.critters {
+            scene.critters = critters;
+        }
+        if let Some(ref streaming) = self.streaming {
+            if streaming.render_distance < 1 {
+                return Err(format!("Render distance must be at least 1 chunk, got {}", streaming.render_distance));
+            }
+            scene.streaming = Some(ChunkStreamer::new(streaming.seed, streaming.render_distance));
+            scene.stream_around(Vec3::zero()); // Start with the hills around the origin, the app follows the camera
+        }
+
+        scene.update_grid();
+        scene.update_particles();
+
+        // A diorama built far from (0,0,0) is rendered relative to its center
+        if scene.center_origin().is_some() {
+            let origin = scene.origin;
+            println!("Scene is far from the origin, rendering relative to ({}, {}, {})", origin.x, origin.y, origin.z);
+        }
+
+        println!(
+            "Built scene: {} cubes ({} buried, not traced), {} meshes, {} point lights, {} spot lights, {} cameras",
+            scene.grid.cube_count(),
+            scene.grid.buried_count(),
+            scene.meshes.len(),
+            scene.point_lights.len(),
+            scene.spot_lights.len(),
+            scene.viewpoints.len()
+        );
+
+        Ok(scene)
+    }
+}
+
+/// Write new [[point_lights]] and [[spot_lights]] tables into a scene file. Everything else stays
+/// as it was, comments included; the lights go where the first old light table was, or at the end.
+pub fn save_lights(path: &Path, point_lights: &[PointLightDef], spot_lights: &[SpotLightDef]) -> Result<(), String> {
+    #[derive(Serialize)]
+    struct Lights<'a> {
+        #[serde(skip_serializing_if = "<[_]>::is_empty")]
+        point_lights: &'a [PointLightDef],
+        #[serde(skip_serializing_if = "<[_]>::is_empty")]
+        spot_lights: &'a [SpotLightDef],
+    }
+    let mut value = toml::Value::try_from(Lights { point_lights, spot_lights })
+        .map_err(|e| format!("Failed to write lights: {}", e))?;
+    tidy_floats(&mut value);
+    let tables = toml::to_string(&value).map_err(|e| format!("Failed to write lights: {}", e))?;
+    let source = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read scene file '{}': {}", path.display(), e))?;
+    fs::write(path, replace_light_tables(&source, &tables)?)
+        .map_err(|e| format!("Failed to save scene file '{}': {}", path.display(), e))
+}
+
+/// Add a [[signs]] table at the end of a scene file, leaving the rest of it as it was
+pub fn append_sign(path: &Path, sign: &SignDef) -> Result<(), String> {
+    #[derive(Serialize)]
+    struct Signs<'a> {
+        signs: [&'a SignDef; 1],
+    }
+    let mut value = toml::Value::try_from(Signs { signs: [sign] }).map_err(|e| format!("Failed to write sign: {}", e))?;
+    tidy_floats(&mut value);
+    let table = toml::to_string(&value).map_err(|e| format!("Failed to write sign: {}", e))?;
+    let source = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read scene file '{}': {}", path.display(), e))?;
+    let mut text = source.trim_end().to_string();
+    if !text.is_empty() {
+        text.push_str("\n\n");
+    }
+    text.push_str(&table);
+    fs::write(path, text).map_err(|e| format!("Failed to save scene file '{}': {}", path.display(), e))
+}
+
+// toml writes floats as f64, so 0.8f32 would come out as 0.800000011920929: round them back
+// to the shortest text that reads as the same f32
+fn tidy_floats(value: &mut toml::Value) {
+    match value {
+        toml::Value::Float(float) => *float = (*float as f32).to_string().parse().unwrap_or(*float),
+        toml::Value::Array(array) => array.iter_mut().for_each(tidy_floats),
+        toml::Value::Table(table) => table.iter_mut().for_each(|(_, value)| tidy_floats(value)),
+        _ => {}
+    }
+}
+
+// Put the light tables of `tables` (a document of nothing else) in place of the scene file's own,
+// arrays of tables and inline arrays alike. The new ones go where the first old table was, with
+// the comment above it, or at the end; everything else is written back as it was read.
+fn replace_light_tables(source: &str, tables: &str) -> Result<String, String> {
+    let mut document: DocumentMut = source.parse().map_err(|e| format!("Failed to read scene file: {}", e))?;
+    let mut lights: DocumentMut = tables.parse().map_err(|e| format!("Failed to write lights: {}", e))?;
+
+    // Position and leading comments of the first old light table
+    let mut first: Option<(usize, Decor)> = None;
+    for key in LIGHT_TABLES {
+        let Some(Item::ArrayOfTables(old)) = document.remove(key) else {
+            continue;
+        };
+        for table in old.iter() {
+            if let Some(position) = table.position()
+                && first.as_ref().is_none_or(|(first, _)| position < *first)
+            {
+                first = Some((position, table.decor().clone()));
+            }
+        }
+    }
+
+    // Without old tables the new ones go after the rest of the file, the comments at its end included
+    let (position, decor) = first.unwrap_or_else(|| {
+        let trailing = document.trailing().as_str().unwrap_or_default().to_string();
+        document.set_trailing("");
+        let gap = if source.trim().is_empty() { "" } else { "\n" };
+        (usize::MAX, Decor::new(format!("{}{}", trailing, gap), ""))
+    });
+    let mut decor = Some(decor);
+    for key in LIGHT_TABLES {
+        let Some(Item::ArrayOfTables(mut new)) = lights.remove(key) else {
+            continue;
+        };
+        for table in new.iter_mut() {
+            table.set_position(position);
+            if let Some(decor) = decor.take() {
+                *table.decor_mut() = decor;
+            }
+        }
+        document.insert(key, Item::ArrayOfTables(new));
+    }
+    Ok(document.to_string())
+}
+
+/// Polls a scene file's modification time so edits can be picked up while running
+pub struct SceneWatcher {
+    pub path: PathBuf,
+    last_modified: Option<SystemTime>,
+    poll_timer: f32,
+}
+
+impl SceneWatcher {
+    const POLL_INTERVAL: f32 = 0.5; // Seconds between mtime checks
+
+    pub fn new(path: PathBuf) -> Self {
+        let last_modified = Self::modified_time(&path);
+        Self {
+            path,
+            last_modified,
+            poll_timer: 0.0,
+        }
+    }
+
+    fn modified_time(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+
+    /// Returns true when the file changed on disk since the last check
+    pub fn poll(&mut self, delta_time: f32) -> bool {
+        self.poll_timer += delta_time;
+        if self.poll_timer < Self::POLL_INTERVAL {
+            return false;
+        }
+        self.poll_timer = 0.0;
+
+        let modified = Self::modified_time(&self.path);
+        if modified.is_some() && modified != self.last_modified {
+            self.last_modified = modified;
+            return true;
+        }
+        false
+    }
+
+    /// Load and build the watched scene file
+    pub fn load(&self) -> Result<Scene, String> {
+        SceneFile::load(&self.path)?.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIGHTS: &str = "[[point_lights]]\nposition = [1, 2, 3]\n";
+
+    #[test]
+    fn the_lights_replace_the_old_tables_in_place() {
+        let source = "name = \"lit\"\n\n# Lamps\n[[point_lights]]\nposition = [0, 0, 0]\n\n[[spot_lights]]\nposition = [5, 5, 5]\n\n# Ground\n[[cubes]]\nmaterial = \"stone\"\n";
+        let text = replace_light_tables(source, LIGHTS).unwrap();
+        assert_eq!(text, "name = \"lit\"\n\n# Lamps\n[[point_lights]]\nposition = [1, 2, 3]\n\n# Ground\n[[cubes]]\nmaterial = \"stone\"\n");
+    }
+
+    #[test]
+    fn inline_light_arrays_are_replaced_too() {
+        let source = "point_lights = [{ position = [0, 0, 0] }, { position = [4, 0, 0] }]\nspot_lights = []\n\n[[cubes]]\nmaterial = \"stone\"\n";
+        let text = replace_light_tables(source, LIGHTS).unwrap();
+        assert_eq!(text, "\n[[cubes]]\nmaterial = \"stone\"\n\n[[point_lights]]\nposition = [1, 2, 3]\n");
+    }
+
+    #[test]
+    fn lights_go_at_the_end_of_a_file_without_any() {
+        let source = "[[cubes]]\nmaterial = \"stone\"\n# The end\n";
+        let text = replace_light_tables(source, LIGHTS).unwrap();
+        assert_eq!(text, "[[cubes]]\nmaterial = \"stone\"\n# The end\n\n[[point_lights]]\nposition = [1, 2, 3]\n");
+    }
+}
@@ -0,0 +1,135 @@
+use toml::Table;
+
+/// Scene format version written by this build. Bump it together with a new MIGRATIONS entry
+/// whenever a change would make older files parse differently.
+pub const CURRENT_VERSION: u32 = 2;
+
+// Files without a `version` key predate versioning
+const UNVERSIONED: u32 = 1;
+
+// MIGRATIONS[i] upgrades a version i + 1 table to version i + 2 and returns notes for the user
+type Migration = fn(&mut Table) -> Vec<String>;
+const MIGRATIONS: &[Migration] = &[
+    migrate_1_to_2,
+];
+
+/// Bring a raw scene table up to CURRENT_VERSION in place.
+/// Returns warnings describing what was changed, or an error for files newer than this build.
+pub fn migrate(table: &mut Table) -> Result<Vec<String>, String> {
+    let version = match table.get("version") {
+        None => UNVERSIONED,
+        Some(value) => value
+            .as_integer()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|&v| v >= 1)
+            .ok_or_else(|| format!("Invalid scene version {}", value))?,
+    };
+
+    if version > CURRENT_VERSION {
+        return Err(format!(
+            "Scene file is version {}, but this build only reads up to version {}",
+            version, CURRENT_VERSION
+        ));
+    }
+
+    let mut warnings = Vec::new();
+    if version < CURRENT_VERSION {
+        warnings.push(format!(
+            "Scene file is version {} (current is {}), migrating; add `version = {}` after updating it",
+            version, CURRENT_VERSION, CURRENT_VERSION
+        ));
+    }
+    for step in &MIGRATIONS[(version - 1) as usize..] {
+        warnings.extend(step(table));
+    }
+    table.insert("version".to_string(), toml::Value::Integer(CURRENT_VERSION as i64));
+    Ok(warnings)
+}
+
+// Version 2 places every prefab at its `position`. Version 1 only moved cherry trees, and only
+// along x and z, so the rest of a version 1 position is dropped to keep the prefabs where they
+// were. (Texture filters and atlases came in version 2 too, but both are optional.)
+fn migrate_1_to_2(table: &mut Table) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let Some(prefabs) = table.get_mut("prefabs").and_then(|prefabs| prefabs.as_array_mut()) else {
+        return warnings;
+    };
+    for prefab in prefabs.iter_mut().filter_map(|prefab| prefab.as_table_mut()) {
+        let kind = prefab.get("kind").and_then(|kind| kind.as_str()).unwrap_or_default().to_string();
+        let Some(position) = prefab.get_mut("position").and_then(|position| position.as_array_mut()) else {
+            continue;
+        };
+        let nonzero = |value: &toml::Value| value.as_float().or(value.as_integer().map(|v| v as f64)).is_some_and(|v| v != 0.0);
+        if kind == "cherry_tree" {
+            if position.get(1).is_some_and(nonzero) {
+                position[1] = toml::Value::Float(0.0);
+                warnings.push("A cherry_tree prefab's height was ignored before version 2, set it to 0".to_string());
+            }
+        } else if position.iter().any(nonzero) {
+            prefab.remove("position");
+            warnings.push(format!("The {} prefab's position was ignored before version 2, removed it", kind));
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_1_prefabs_stay_where_they_were() {
+        let mut table: Table = toml::from_str(
+            r#"
+            [[prefabs]]
+            kind = "cherry_tree"
+            position = [3.0, 2.0, -1.0]
+
+            [[prefabs]]
+            kind = "house"
+            position = [4, 0, 4]
+
+            [[prefabs]]
+            kind = "pond"
+            "#,
+        )
+        .unwrap();
+        let warnings = migrate(&mut table).unwrap();
+        assert_eq!(warnings.len(), 3); // The version note and one per moved prefab
+
+        let expected: Table = toml::from_str(
+            r#"
+            version = 2
+
+            [[prefabs]]
+            kind = "cherry_tree"
+            position = [3.0, 0.0, -1.0]
+
+            [[prefabs]]
+            kind = "house"
+
+            [[prefabs]]
+            kind = "pond"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(table, expected);
+    }
+
+    #[test]
+    fn current_files_are_left_alone() {
+        let source = r#"
+            version = 2
+
+            [[prefabs]]
+            kind = "house"
+            position = [4.0, 0.0, 4.0]
+            "#;
+        let mut table: Table = toml::from_str(source).unwrap();
+        assert!(migrate(&mut table).unwrap().is_empty());
+        assert_eq!(table, toml::from_str::<Table>(source).unwrap());
+
+        let mut newer: Table = toml::from_str("version = 3").unwrap();
+        assert!(migrate(&mut newer).is_err());
+    }
+}
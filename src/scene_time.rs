@@ -0,0 +1,51 @@
+/// Simulation clock shared by every animated system (sky, sun, materials, animations).
+/// Advanced in fixed ticks so animation speed doesn't depend on the render frame rate.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SceneTime {
+    pub elapsed: f32,      // Simulated seconds since the scene started
+    pub day_fraction: f32, // 0.0 = full day ... 1.0 = full night (wraps back to day)
+    pub tick: u64,         // Number of fixed ticks simulated so far
+    pub day_speed: f32,    // Day fraction advanced per simulated second
+    pub days: u32,         // Day/night cycles completed (day_fraction wrapping back to day), for moon phases
+    accumulator: f32,      // Real time not yet consumed by a tick
+}
+
+impl SceneTime {
+    pub const TICKS_PER_SECOND: u32 = 20; // Same rate as Minecraft game ticks
+    pub const TICK_DURATION: f32 = 1.0 / Self::TICKS_PER_SECOND as f32;
+    pub const MOON_PHASES: u32 = 8; // Like Minecraft: full moon on the first night, one phase per day
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Consume real frame time and run as many fixed ticks as it covers.
+    /// Returns the number of ticks that were simulated.
+    pub fn advance(&mut self, delta_time: f32) -> u32 {
+        self.accumulator += delta_time;
+
+        let mut ticks = 0;
+        while self.accumulator >= Self::TICK_DURATION {
+            self.accumulator -= Self::TICK_DURATION;
+            self.step();
+            ticks += 1;
+        }
+        ticks
+    }
+
+    /// Run one fixed tick
+    pub fn step(&mut self) {
+        self.tick += 1;
+        self.elapsed += Self::TICK_DURATION;
+        let day_fraction = self.day_fraction + self.day_speed * Self::TICK_DURATION;
+        if day_fraction >= 1.0 {
+            self.days += 1;
+        }
+        self.day_fraction = day_fraction.rem_euclid(1.0);
+    }
+
+    /// Where the moon is in its cycle (0 = full, 0.5 = new), one of MOON_PHASES steps per day
+    pub fn moon_phase(&self) -> f32 {
+        (self.days % Self::MOON_PHASES) as f32 / Self::MOON_PHASES as f32
+    }
+}
@@ -0,0 +1,65 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const SCREENSHOT_DIR: &str = "screenshots";
+
+/// Write an RGBA frame buffer to a PNG file
+pub fn save_png(buffer: &[raylib::prelude::Color], width: i32, height: i32, path: &Path) -> Result<(), String> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory '{}': {}", parent.display(), e))?;
+    }
+
+    let mut pixels = Vec::with_capacity(buffer.len() * 4);
+    for color in buffer {
+        pixels.extend_from_slice(&[color.r, color.g, color.b, color.a]);
+    }
+
+    image::save_buffer(path, &pixels, width as u32, height as u32, image::ColorType::Rgba8)
+        .map_err(|e| format!("Failed to save '{}': {}", path.display(), e))
+}
+
+/// Save the frame into screenshots/ with a timestamped name, returning the file path
+pub fn save_screenshot(buffer: &[raylib::prelude::Color], width: i32, height: i32) -> Result<PathBuf, String> {
+    let path = Path::new(SCREENSHOT_DIR).join(format!("screenshot_{}.png", timestamp()));
+    save_png(buffer, width, height, &path)?;
+    Ok(path)
+}
+
+/// Current UTC time as YYYY-MM-DD_HH-MM-SS (sortable and safe for file names)
+pub fn timestamp() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+
+    let days = secs.div_euclid(86_400);
+    let seconds_of_day = secs.rem_euclid(86_400);
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{:04}-{:02}-{:02}_{:02}-{:02}-{:02}",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        (seconds_of_day % 3600) / 60,
+        seconds_of_day % 60
+    )
+}
+
+// Days since 1970-01-01 to (year, month, day) in the proleptic Gregorian calendar
+// (Howard Hinnant's civil_from_days algorithm)
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
@@ -0,0 +1,73 @@
+use crate::cube::Cube;
+use crate::noise::{fbm, smoothstep};
+use crate::scene::Scene;
+use crate::utils::{Real, Vec3, narrow};
+use crate::voxel_grid::VoxelGrid;
+
+const FOAM_WIDTH: f32 = 0.35; // Farthest the foam reaches out from the shore, in blocks
+const FOAM_SCALE: f32 = 5.0; // Noise cells per block
+const FOAM_DRIFT: [f32; 2] = [0.05, 0.03]; // Blocks per second the foam pattern slides along (x, z)
+const FOAM_OCTAVES: u32 = 3;
+
+// Neighbours on the same level that can hold the shore: the 4 sides, then the 4 corners
+const NEIGHBOURS: [(i32, i32); 8] = [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+/// How much foam covers a point on the top face of a water block (0-1). The voxel grid says which
+/// of the block's neighbours are solid; the foam is thickest against them and breaks up into noise
+/// toward open water, so a pond's rim gets a ragged white band that slowly drifts. Only full-size
+/// blocks count as shore (lily pads and other detail cubes don't).
+pub fn foam(scene: &Scene, position: Vec3, normal: Vec3, time: f32) -> f32 {
+    if normal.y < 0.5 {
+        return 0.0; // Only the surface, the sides of a raised water block stay clear
+    }
+    let Some(water) = block_at(scene, position - normal * 0.25) else {
+        return 0.0;
+    };
+    // Offset within the block, -0.5 to 0.5 on each axis
+    let (dx, dz) = (narrow(position.x - water.position.x), narrow(position.z - water.position.z));
+
+    let mut distance = f32::MAX;
+    for (ox, oz) in NEIGHBOURS {
+        let neighbour = water.position + Vec3::new(ox as Real, 0.0, oz as Real);
+        if !solid(scene, neighbour) {
+            continue;
+        }
+        // Gap to the neighbour's near edge (sides) or corner (diagonals)
+        let gap_x = if ox == 0 { 0.0 } else { (0.5 - dx * ox as f32).max(0.0) };
+        let gap_z = if oz == 0 { 0.0 } else { (0.5 - dz * oz as f32).max(0.0) };
+        distance = distance.min((gap_x * gap_x + gap_z * gap_z).sqrt());
+    }
+    if distance >= FOAM_WIDTH {
+        return 0.0;
+    }
+
+    let x = narrow(position.x) * FOAM_SCALE + FOAM_DRIFT[0] * time;
+    let z = narrow(position.z) * FOAM_SCALE + FOAM_DRIFT[1] * time;
+    let noise = fbm(x, z, FOAM_OCTAVES);
+    // The band reaches farther where the noise is high, and is patchy inside
+    let reach = FOAM_WIDTH * (0.35 + 0.65 * noise);
+    let band = 1.0 - smoothstep(reach * 0.4, reach, distance);
+    band * smoothstep(0.25, 0.6, noise + 0.3 * (1.0 - distance / FOAM_WIDTH))
+}
+
+// An opaque full block at this center, the kind a shore is made of
+fn solid(scene: &Scene, center: Vec3) -> bool {
+    scene
+        .grid
+        .get(VoxelGrid::cell_of(center))
+        .is_some_and(|cube| scene.materials[cube.material].transparency <= 0.0)
+}
+
+// Unit block containing a point. Block centers sit on whole or half coordinates and are indexed by
+// their rounded center, so the point's cell can be one off on each axis: the two cells below and
+// above it cover both cases.
+fn block_at(scene: &Scene, point: Vec3) -> Option<&Cube> {
+    let (x, y, z) = (point.x.floor() as i32, point.y.floor() as i32, point.z.floor() as i32);
+    (0..8)
+        .filter_map(|corner| scene.grid.get((x + (corner & 1), y + (corner >> 1 & 1), z + (corner >> 2))))
+        .find(|cube| {
+            let half = cube.size / 2.0;
+            let offset = point - cube.position;
+            offset.x.abs() <= half && offset.y.abs() <= half && offset.z.abs() <= half
+        })
+}
@@ -0,0 +1,276 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::color::Color;
+use crate::cube::Cube;
+use crate::material::Material;
+use crate::material_palette::{MaterialId, MaterialPalette};
+use crate::palette;
+use crate::scene_file::{self, SignDef};
+use crate::texture::Texture;
+use crate::texture_atlas::UvRect;
+use crate::texture_cache;
+use crate::utils::{Real, Vec3};
+
+const BLOCK_TEXELS: usize = 32; // Texels per block side: about five letters across, three lines down
+const GLYPH_WIDTH: usize = 5;
+const GLYPH_HEIGHT: usize = 7;
+const ADVANCE: usize = GLYPH_WIDTH + 1; // One texel between letters
+const LINE_HEIGHT: usize = GLYPH_HEIGHT + 3;
+const MARGIN: usize = 3; // Empty texels around the text, so it doesn't touch the block's edge
+
+// 5x7 pixel font, one row per entry (top first), high bit on the left. Lowercase letters use the
+// capitals; characters without a glyph are drawn as '?'.
+const FONT: &[(char, [u8; GLYPH_HEIGHT])] = &[
+    ('A', [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('B', [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110]),
+    ('C', [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110]),
+    ('D', [0b11110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11110]),
+    ('E', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111]),
+    ('F', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('G', [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111]),
+    ('H', [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('I', [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('J', [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100]),
+    ('K', [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001]),
+    ('L', [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111]),
+    ('M', [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001]),
+    ('N', [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001]),
+    ('O', [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('P', [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('Q', [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101]),
+    ('R', [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001]),
+    ('S', [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110]),
+    ('T', [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100]),
+    ('U', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('V', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100]),
+    ('W', [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010]),
+    ('X', [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001]),
+    ('Y', [0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100, 0b00100]),
+    ('Z', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111]),
+    ('0', [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110]),
+    ('1', [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('2', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111]),
+    ('3', [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110]),
+    ('4', [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010]),
+    ('5', [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110]),
+    ('6', [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110]),
+    ('7', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000]),
+    ('8', [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110]),
+    ('9', [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100]),
+    (' ', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000]),
+    ('.', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100]),
+    (',', [0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000]),
+    (':', [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000]),
+    ('!', [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100]),
+    ('?', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100]),
+    ('\'', [0b00100, 0b00100, 0b01000, 0b00000, 0b00000, 0b00000, 0b00000]),
+    ('"', [0b01010, 0b01010, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000]),
+    ('-', [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000]),
+    ('+', [0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000]),
+    ('=', [0b00000, 0b00000, 0b11111, 0b00000, 0b11111, 0b00000, 0b00000]),
+    ('/', [0b00001, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b10000]),
+    ('(', [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010]),
+    (')', [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000]),
+    ('&', [0b01100, 0b10010, 0b10100, 0b01000, 0b10101, 0b10010, 0b01101]),
+    ('#', [0b01010, 0b01010, 0b11111, 0b01010, 0b11111, 0b01010, 0b01010]),
+    ('@', [0b01110, 0b10001, 0b10111, 0b10101, 0b10111, 0b10000, 0b01110]),
+];
+
+fn glyph(c: char) -> &'static [u8; GLYPH_HEIGHT] {
+    let c = c.to_ascii_uppercase();
+    FONT.iter()
+        .find(|(glyph, _)| *glyph == c)
+        .or_else(|| FONT.iter().find(|(glyph, _)| *glyph == '?'))
+        .map(|(_, rows)| rows)
+        .expect("the font has a '?' glyph")
+}
+
+/// Which way a sign's text faces (the outward normal of its lettered side)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Facing {
+    North, // -z
+    South, // +z
+    East,  // +x
+    West,  // -x
+}
+
+impl Facing {
+    /// "north", "south", "east" or "west"
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "north" => Ok(Self::North),
+            "south" => Ok(Self::South),
+            "east" => Ok(Self::East),
+            "west" => Ok(Self::West),
+            _ => Err(format!("Unknown sign facing '{}', expected north, south, east or west", name)),
+        }
+    }
+
+    pub fn normal(self) -> Vec3 {
+        match self {
+            Self::North => Vec3::new(0.0, 0.0, -1.0),
+            Self::South => Vec3::new(0.0, 0.0, 1.0),
+            Self::East => Vec3::new(1.0, 0.0, 0.0),
+            Self::West => Vec3::new(-1.0, 0.0, 0.0),
+        }
+    }
+
+    /// The reader's right, standing in front of the sign: the way the text runs
+    pub fn right(self) -> Vec3 {
+        match self {
+            Self::North => Vec3::new(-1.0, 0.0, 0.0),
+            Self::South => Vec3::new(1.0, 0.0, 0.0),
+            Self::East => Vec3::new(0.0, 0.0, -1.0),
+            Self::West => Vec3::new(0.0, 0.0, 1.0),
+        }
+    }
+
+    // Whether the face's u runs against the reading direction (see Cube::get_uv), so the text has
+    // to be drawn mirrored to read right on it
+    fn mirrored(self) -> bool {
+        matches!(self, Self::North | Self::East)
+    }
+}
+
+/// A sign or banner: a wall of blocks whose front shows text, rasterized at runtime with a small
+/// bitmap font into one texture and split across the blocks' faces. `position` is the top-left
+/// block as seen by the reader; the sign grows to the reader's right and downward as far as the
+/// text needs (a new line for every '\n'). The other faces are the board material.
+#[derive(Clone)]
+pub struct Sign {
+    pub text: String,
+    pub position: Vec3,
+    pub facing: Facing,
+    pub ink: Color,
+    pub background: Color,
+}
+
+impl Sign {
+    pub fn new(text: &str, position: Vec3, facing: Facing) -> Self {
+        Self {
+            text: text.to_string(),
+            position,
+            facing,
+            ink: palette::SIGN_INK,
+            background: palette::BANNER_WHITE,
+        }
+    }
+
+    pub fn with_colors(mut self, ink: Color, background: Color) -> Self {
+        self.ink = ink;
+        self.background = background;
+        self
+    }
+
+    /// Width and height in blocks
+    pub fn size(&self) -> (usize, usize) {
+        let lines: Vec<&str> = self.text.lines().collect();
+        let longest = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0).max(1);
+        let width = longest * ADVANCE - 1 + 2 * MARGIN;
+        let height = lines.len().max(1) * LINE_HEIGHT - (LINE_HEIGHT - GLYPH_HEIGHT) + 2 * MARGIN;
+        (width.div_ceil(BLOCK_TEXELS), height.div_ceil(BLOCK_TEXELS))
+    }
+
+    /// The text on the background, centered on the whole sign face (mirrored for the facings
+    /// whose u runs right to left)
+    pub fn texture(&self) -> Texture {
+        let (columns, rows) = self.size();
+        let (width, height) = (columns * BLOCK_TEXELS, rows * BLOCK_TEXELS);
+        let mut texture = Texture::new(width, height);
+        texture.data.fill(self.background);
+
+        let lines: Vec<&str> = self.text.lines().collect();
+        let block_height = (lines.len() * LINE_HEIGHT).saturating_sub(LINE_HEIGHT - GLYPH_HEIGHT);
+        let top = (height - block_height) / 2;
+        for (line_index, line) in lines.iter().enumerate() {
+            let line_width = (line.chars().count() * ADVANCE).saturating_sub(1);
+            let left = (width - line_width) / 2;
+            for (char_index, c) in line.chars().enumerate() {
+                for (row, bits) in glyph(c).iter().enumerate() {
+                    for column in (0..GLYPH_WIDTH).filter(|column| bits & (0b10000 >> column) != 0) {
+                        let x = left + char_index * ADVANCE + column;
+                        let x = if self.facing.mirrored() { width - 1 - x } else { x };
+                        let y = top + line_index * LINE_HEIGHT + row;
+                        texture.data[y * width + x] = self.ink;
+                    }
+                }
+            }
+        }
+        texture
+    }
+
+    /// The sign's blocks: `board` on every face but the front, which shows its part of the text
+    /// (one material per block, added to `materials`)
+    pub fn cubes(&self, board: MaterialId, materials: &mut MaterialPalette) -> Vec<Cube> {
+        let (columns, rows) = self.size();
+        let texture = Arc::new(self.texture());
+        let (right, down) = (self.facing.right(), Vec3::new(0.0, -1.0, 0.0));
+        let mut cubes = Vec::with_capacity(columns * rows);
+        for row in 0..rows {
+            for column in 0..columns {
+                let tile = if self.facing.mirrored() { columns - 1 - column } else { column };
+                let mut face = Material::new(Color::white()).with_texture(Arc::clone(&texture));
+                face.uv_rect = UvRect {
+                    u: tile as f32 / columns as f32,
+                    v: row as f32 / rows as f32,
+                    width: 1.0 / columns as f32,
+                    height: 1.0 / rows as f32,
+                };
+                let position = self.position + right * column as Real + down * row as Real;
+                cubes.push(Cube::new(position, 1.0, board).with_face(self.facing.normal(), materials.add(face)));
+            }
+        }
+        cubes
+    }
+
+    /// Planks for the back and edges of a sign
+    pub fn default_board() -> Material {
+        Material::new(palette::NEUTRAL_GRAY)
+            .with_texture(texture_cache::load("assets/textures/wood.png"))
+    }
+}
+
+/// `sign <x> <y> <z> <facing> <text> [--scene path]`: add a sign to a scene file from the
+/// command line (a running app picks it up with its hot reload). `\n` in the text starts a new line.
+pub fn run(args: &[String], default_scene: &str) -> i32 {
+    let usage = || {
+        eprintln!("Usage: sign <x> <y> <z> <north|south|east|west> \"<text>\" [--scene path]");
+        2
+    };
+    let [x, y, z, facing, text, ..] = args else {
+        return usage();
+    };
+    let (Ok(x), Ok(y), Ok(z)) = (x.parse::<f32>(), y.parse::<f32>(), z.parse::<f32>()) else {
+        return usage();
+    };
+    if let Err(e) = Facing::parse(facing) {
+        eprintln!("{}", e);
+        return 2;
+    }
+    let path = match args.iter().position(|arg| arg == "--scene") {
+        Some(i) => match args.get(i + 1) {
+            Some(path) => path.as_str(),
+            None => return usage(),
+        },
+        None => default_scene,
+    };
+    let sign = SignDef {
+        text: text.replace("\\n", "\n"),
+        position: [x, y, z],
+        facing: facing.clone(),
+        ink: None,
+        background: None,
+        material: None,
+    };
+    match scene_file::append_sign(Path::new(path), &sign) {
+        Ok(()) => {
+            println!("Added a sign at ({}, {}, {}) to {}", x, y, z, path);
+            0
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
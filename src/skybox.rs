@@ -1,23 +1,58 @@
+use std::path::Path;
+use std::sync::Arc;
+
 use crate::color::Color;
+use crate::environment::Environment;
+use crate::noise::{fbm, smoothstep};
 use crate::ray::Ray;
+use crate::scene_time::SceneTime;
 use crate::texture::Texture;
+use crate::texture_cache;
+use crate::utils::{Degrees, TAU, Vec3, narrow};
+
+pub const CLOUD_COVERAGE: f32 = 0.45; // Default share of the sky under clouds
+const CLOUD_SCALE: f32 = 3.0; // Noise cells per unit of the cloud layer, which floats 1 unit above the eye
+const CLOUD_OCTAVES: u32 = 5;
+const WIND: [f32; 2] = [0.015, 0.006]; // Layer units per second the clouds drift (x, z)
+const MOON_RADIUS: Degrees = Degrees(8.0); // Half the width of the moon's square in the sky
+const MOON_GLOW: Degrees = Degrees(12.0);
+const EARTHSHINE: f32 = 0.04; // How much of the moon's dark side still shows
+const HORIZON_BAND: f32 = 0.35; // Height (direction.y) up to which the time-of-day horizon tint reaches
+
+// Time of day: (day_fraction, horizon color, sun color) stops, blended linearly in between. Blue
+// sky and a white sun through the day, orange then pink as the sun goes down toward 0.5, dark at
+// night, and back through pink and orange at sunrise just before the cycle wraps to day.
+const DAY_GRADIENT: [(f32, Color, Color); 8] = [
+    (0.0, Color::new(0.55, 0.72, 0.95), Color::new(1.0, 0.95, 0.9)),
+    (0.3, Color::new(0.55, 0.72, 0.95), Color::new(1.0, 0.95, 0.9)),
+    (0.42, Color::new(1.0, 0.5, 0.2), Color::new(1.0, 0.65, 0.35)),
+    (0.5, Color::new(0.85, 0.4, 0.55), Color::new(1.0, 0.5, 0.45)),
+    (0.6, Color::new(0.03, 0.04, 0.1), Color::new(0.6, 0.65, 0.9)),
+    (0.9, Color::new(0.03, 0.04, 0.1), Color::new(0.6, 0.65, 0.9)),
+    (0.96, Color::new(0.85, 0.4, 0.55), Color::new(1.0, 0.5, 0.45)),
+    (1.0, Color::new(1.0, 0.5, 0.2), Color::new(1.0, 0.65, 0.35)),
+];
 
 pub struct Skybox {
     // Cubemap textures - Day (6 faces)
-    pub right_day: Texture,   // +X
-    pub left_day: Texture,    // -X
-    pub top_day: Texture,     // +Y
-    pub bottom_day: Texture,  // -Y
-    pub front_day: Texture,   // +Z
-    pub back_day: Texture,    // -Z
+    pub right_day: Arc<Texture>,   // +X
+    pub left_day: Arc<Texture>,    // -X
+    pub top_day: Arc<Texture>,     // +Y
+    pub bottom_day: Arc<Texture>,  // -Y
+    pub front_day: Arc<Texture>,   // +Z
+    pub back_day: Arc<Texture>,    // -Z
     
     // Cubemap textures - Night (6 faces)
-    pub right_night: Texture,
-    pub left_night: Texture,
-    pub top_night: Texture,
-    pub bottom_night: Texture,
-    pub front_night: Texture,
-    pub back_night: Texture,
+    pub right_night: Arc<Texture>,
+    pub left_night: Arc<Texture>,
+    pub top_night: Arc<Texture>,
+    pub bottom_night: Arc<Texture>,
+    pub front_night: Arc<Texture>,
+    pub back_night: Arc<Texture>,
+
+    pub moon: Arc<Texture>, // Moon face, drawn square; transparent texels are outside the disk
+    pub cloud_coverage: f32, // Share of the sky covered by the cloud layer (0 = clear, 1 = overcast)
+    pub environment: Option<Environment>, // HDR image shown instead of everything above (scene file [environment])
 }
 
 impl Skybox {
@@ -25,27 +60,74 @@ impl Skybox {
         // Load the cubemap face textures from assets/skybox/
         Self {
             // Day textures
-            right_day: Texture::load("assets/skybox/side.jpeg"),
-            left_day: Texture::load("assets/skybox/side.jpeg"),
-            top_day: Texture::load("assets/skybox/top.jpeg"),
-            bottom_day: Texture::load("assets/skybox/bottom.jpg"),
-            front_day: Texture::load("assets/skybox/side.jpeg"),
-            back_day: Texture::load("assets/skybox/side.jpeg"),
+            right_day: texture_cache::load("assets/skybox/side.jpeg"),
+            left_day: texture_cache::load("assets/skybox/side.jpeg"),
+            top_day: texture_cache::load("assets/skybox/top.jpeg"),
+            bottom_day: texture_cache::load("assets/skybox/bottom.jpg"),
+            front_day: texture_cache::load("assets/skybox/side.jpeg"),
+            back_day: texture_cache::load("assets/skybox/side.jpeg"),
             
             // Night textures (create these files or reuse day textures as fallback)
-            right_night: Texture::load("assets/skybox/side_night.jpeg"),
-            left_night: Texture::load("assets/skybox/side_night.jpeg"),
-            top_night: Texture::load("assets/skybox/top_night.jpeg"),
-            bottom_night: Texture::load("assets/skybox/bottom_night.jpg"),
-            front_night: Texture::load("assets/skybox/side_night.jpeg"),
-            back_night: Texture::load("assets/skybox/side_night.jpeg"),
+            right_night: texture_cache::load("assets/skybox/side_night.jpeg"),
+            left_night: texture_cache::load("assets/skybox/side_night.jpeg"),
+            top_night: texture_cache::load("assets/skybox/top_night.jpeg"),
+            bottom_night: texture_cache::load("assets/skybox/bottom_night.jpg"),
+            front_night: texture_cache::load("assets/skybox/side_night.jpeg"),
+            back_night: texture_cache::load("assets/skybox/side_night.jpeg"),
+
+            moon: texture_cache::load("assets/skybox/moon.png"),
+            cloud_coverage: CLOUD_COVERAGE,
+            environment: None,
         }
     }
 
+    /// Sky from a six-image cubemap pack instead of the built-in faces, in the usual +X, -X, +Y,
+    /// -Y, +Z, -Z order (right, left, top, bottom, front, back; "px nx py ny pz nz" in most
+    /// packs), each face laid out the OpenGL way (looking at it from inside the cube, up is +Y
+    /// on the side faces and -Z/+Z at the top of the top/bottom ones). The faces stand in for both
+    /// day and night; with_night_cubemap gives the night its own set.
+    pub fn from_cubemap(paths: [&str; 6]) -> Result<Self, String> {
+        let [right, left, top, bottom, front, back] = load_faces(paths)?;
+        Ok(Self {
+            right_night: right.clone(),
+            left_night: left.clone(),
+            top_night: top.clone(),
+            bottom_night: bottom.clone(),
+            front_night: front.clone(),
+            back_night: back.clone(),
+            right_day: right,
+            left_day: left,
+            top_day: top,
+            bottom_day: bottom,
+            front_day: front,
+            back_day: back,
+            moon: texture_cache::load("assets/skybox/moon.png"),
+            cloud_coverage: CLOUD_COVERAGE,
+            environment: None,
+        })
+    }
+
+    /// Night faces for a cubemap sky, same order as from_cubemap
+    pub fn with_night_cubemap(mut self, paths: [&str; 6]) -> Result<Self, String> {
+        let [right, left, top, bottom, front, back] = load_faces(paths)?;
+        self.right_night = right;
+        self.left_night = left;
+        self.top_night = top;
+        self.bottom_night = bottom;
+        self.front_night = front;
+        self.back_night = back;
+        Ok(self)
+    }
+
     /// Sample the skybox cubemap based on ray direction
     /// This uses the standard cubemap sampling algorithm
-    pub fn sample(&self, ray: &Ray, day_time: f32, sun_dir: crate::utils::Vec3, _sun_color: Color, _sun_intensity: f32) -> Color {
+    pub fn sample(&self, ray: &Ray, time: &SceneTime, sun_dir: Vec3, moon_dir: Vec3, sun_color: Color, sun_intensity: f32) -> Color {
+        let day_time = time.day_fraction;
         let direction = ray.direction.normalize();
+        // An environment image brings its own sky, sun and clouds
+        if let Some(environment) = &self.environment {
+            return environment.sample(direction);
+        }
         
         // Determine which cube face to sample based on the largest component
         let abs_x = direction.x.abs();
@@ -94,6 +176,7 @@ impl Skybox {
         };
         
         // Sample both day and night textures
+        let (u, v) = (narrow(u), narrow(v));
         let day_color = texture_day.sample(u, v);
         let night_color = texture_night.sample(u, v);
         
@@ -102,62 +185,148 @@ impl Skybox {
         // day_time = 1.0 -> full night
         let mut base_color = day_color * (1.0 - day_time) + night_color * day_time;
 
-        // --- Draw VISIBLE SUN and MOON in the skybox ---
         let sun_dir = sun_dir.normalize();
-        let cos_angle_to_sun = direction.dot(&sun_dir).max(-1.0).min(1.0);
+        let cos_angle_to_sun = narrow(direction.dot(&sun_dir)).max(-1.0).min(1.0);
+
+        // Horizon tinted by the time of day, strongest on the sun's side of the sky
+        let horizon = 1.0 - smoothstep(0.0, HORIZON_BAND, narrow(direction.y).abs());
+        if horizon > 0.0 {
+            let weight = horizon * (0.35 + 0.3 * (cos_angle_to_sun + 1.0));
+            base_color = base_color * (1.0 - weight) + horizon_color(day_time) * weight;
+        }
+
+        // --- Draw VISIBLE SUN and MOON in the skybox ---
         
-        // Moon is opposite to the sun
-        let moon_dir = -sun_dir;
-        let cos_angle_to_moon = direction.dot(&moon_dir).max(-1.0).min(1.0);
+        // Moon across the sky from the sun, where the moonlight comes from (Scene::moon)
+        let moon_dir = moon_dir.normalize();
 
         // SUN - Very large and bright during daytime (when day_time is LOW/near 0)
-        let sun_radius_cos = (15.0f32.to_radians()).cos(); // Large 15-degree sun
-        let sun_glow_cos = (30.0f32.to_radians()).cos();   // 30-degree glow
+        let sun_radius_cos = narrow(Degrees(15.0).radians().cos()); // Large 15-degree sun
+        let sun_glow_cos = narrow(Degrees(30.0).radians().cos());   // 30-degree glow
         
         // Only show sun during day (day_time < 0.5)
         if day_time < 0.5 {
             // Core sun disk
             if cos_angle_to_sun >= sun_radius_cos {
                 let t = (cos_angle_to_sun - sun_radius_cos) / (1.0 - sun_radius_cos);
-                // Very bright yellow-white sun
+                // Very bright, in the sun's own color (white by day, orange toward sunset)
                 let brightness = t.powf(0.3) * (1.0 - day_time * 2.0); // Fade as evening approaches
-                let sun_disk = Color::new(1.0, 1.0, 0.95) * (5.0 * brightness);
+                let sun_disk = sun_color * (5.0 * brightness);
                 base_color = base_color + sun_disk;
             }
             // Sun glow/corona
             else if cos_angle_to_sun >= sun_glow_cos {
                 let t = (cos_angle_to_sun - sun_glow_cos) / (sun_radius_cos - sun_glow_cos);
                 let brightness = t.powf(1.5) * (1.0 - day_time * 2.0);
-                let sun_glow = Color::new(1.0, 0.9, 0.7) * (2.0 * brightness);
+                let sun_glow = sun_color * Color::new(1.0, 0.9, 0.75) * (2.0 * brightness);
                 base_color = base_color + sun_glow;
             }
         }
         
-        // MOON - Visible during night (when day_time is HIGH/near 1)
-        let moon_radius_cos = (8.0f32.to_radians()).cos(); // Smaller moon
-        let moon_glow_cos = (12.0f32.to_radians()).cos();
-        
-        // Only show moon during night (day_time > 0.5)
+        // MOON - Visible during night (when day_time is HIGH/near 1), in its phase for the day
         if day_time > 0.5 {
-            // Core moon disk - much dimmer now
-            if cos_angle_to_moon >= moon_radius_cos {
-                let t = (cos_angle_to_moon - moon_radius_cos) / (1.0 - moon_radius_cos);
-                // Soft white moon with reduced brightness
-                let brightness = t.powf(0.5) * (day_time - 0.5) * 2.0;
-                let moon_disk = Color::new(0.9, 0.9, 1.0) * (1.0 * brightness); // Reduced from 3.0 to 1.0
-                base_color = base_color + moon_disk;
-            }
-            // Moon glow - barely visible
-            else if cos_angle_to_moon >= moon_glow_cos {
-                let t = (cos_angle_to_moon - moon_glow_cos) / (moon_radius_cos - moon_glow_cos);
-                let brightness = t.powf(2.0) * (day_time - 0.5) * 2.0;
-                let moon_glow = Color::new(0.7, 0.7, 0.9) * (0.3 * brightness); // Reduced from 1.0 to 0.3
-                base_color = base_color + moon_glow;
-            }
+            base_color = base_color + self.moon_color(direction, moon_dir, time.moon_phase()) * ((day_time - 0.5) * 2.0);
+        }
+
+        // Clouds go over the sun and moon, thinning their disks where they pass
+        let cloud = self.cloud_density(direction, time.elapsed);
+        if cloud > 0.0 {
+            let color = Self::cloud_color(cloud, day_time, cos_angle_to_sun, sun_color, sun_intensity);
+            base_color = base_color * (1.0 - cloud) + color * cloud;
+        }
+
+        base_color // Unclamped, the sun disk is several times brighter than white (see tonemap.rs)
+    }
+
+    // The moon texture on a square facing the viewer, shaded as a ball lit from the side the phase
+    // puts the sun on (0 = full, 0.5 = new), with a faint halo that thins out toward new moon
+    fn moon_color(&self, direction: Vec3, moon_dir: Vec3, phase: f32) -> Color {
+        let cos_angle = narrow(direction.dot(&moon_dir));
+        let glow_cos = narrow(MOON_GLOW.radians().cos());
+        if cos_angle < glow_cos {
+            return Color::black();
+        }
+        let angle = phase * narrow(TAU);
+        let lit_share = 0.5 + 0.5 * angle.cos();
+        let t = (cos_angle - glow_cos) / (1.0 - glow_cos);
+        let glow = Color::new(0.7, 0.7, 0.9) * (0.3 * t.powf(2.0) * lit_share);
+
+        // Where the ray crosses the square, -1 to 1 from its center along each side
+        let helper = if moon_dir.y.abs() < 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+        let right = helper.cross(&moon_dir).normalize();
+        let up = moon_dir.cross(&right);
+        let half_width = MOON_RADIUS.radians().tan() * direction.dot(&moon_dir);
+        let x = narrow(direction.dot(&right) / half_width);
+        let y = narrow(direction.dot(&up) / half_width);
+        if x.abs() > 1.0 || y.abs() > 1.0 {
+            return glow;
+        }
+        let (u, v) = ((x + 1.0) * 0.5, (1.0 - y) * 0.5);
+        if self.moon.alpha_at(u, v) < 0.5 {
+            return glow;
         }
 
-        base_color.clamp()
+        // The ball's surface behind this point, and how much sunlight it gets
+        let z = (1.0 - x * x - y * y).max(0.0).sqrt();
+        let lit = smoothstep(-0.08, 0.08, x * angle.sin() + z * angle.cos());
+        self.moon.sample(u, v) * (lit + EARTHSHINE * (1.0 - lit))
+    }
+
+    // How much cloud covers this direction (0-1): 2D FBM noise on a flat layer overhead, seen
+    // through the sky dome, drifting with the wind
+    fn cloud_density(&self, direction: Vec3, elapsed: f32) -> f32 {
+        let up = narrow(direction.y);
+        if self.cloud_coverage <= 0.0 || up <= 0.0 {
+            return 0.0;
+        }
+        let x = narrow(direction.x) / up * CLOUD_SCALE + WIND[0] * elapsed;
+        let z = narrow(direction.z) / up * CLOUD_SCALE + WIND[1] * elapsed;
+
+        // More coverage lowers the cut-off, so more of the noise counts as cloud
+        let cut = 1.0 - self.cloud_coverage.min(1.0);
+        let cloud = smoothstep(cut - 0.15, cut + 0.2, fbm(x, z, CLOUD_OCTAVES));
+        // Toward the horizon the layer's features squash into noise, so the clouds fade out
+        cloud * smoothstep(0.03, 0.3, up)
+    }
+
+    // Cloud color through the day: white in daylight, dark blue-gray at night, and thick parts
+    // shaded underneath. Clouds near the sun catch its light at their edges (silver lining).
+    fn cloud_color(density: f32, day_time: f32, cos_angle_to_sun: f32, sun_color: Color, sun_intensity: f32) -> Color {
+        let day = Color::new(0.95, 0.95, 0.97) * (0.6 + 0.4 * sun_intensity.min(1.0));
+        let night = Color::new(0.08, 0.09, 0.13);
+        let lit = day * (1.0 - day_time) + night * day_time;
+        let shade = 1.0 - 0.35 * density;
+        let lining = cos_angle_to_sun.max(0.0).powi(16) * (1.0 - density) * (1.0 - day_time);
+        lit * shade + sun_color * (1.5 * lining)
+    }
+}
+
+/// Color of the sky at the horizon at this time of day (DAY_GRADIENT)
+pub fn horizon_color(day_fraction: f32) -> Color {
+    day_gradient(day_fraction, |(_, horizon, _)| horizon)
+}
+
+/// Color of the day/night cycle's sun at this time of day: white at noon, orange and pink near
+/// sunset and sunrise (DAY_GRADIENT)
+pub fn sun_color(day_fraction: f32) -> Color {
+    day_gradient(day_fraction, |(_, _, sun)| sun)
+}
+
+fn day_gradient(day_fraction: f32, pick: impl Fn((f32, Color, Color)) -> Color) -> Color {
+    let t = day_fraction.clamp(0.0, 1.0);
+    let next = DAY_GRADIENT.iter().position(|stop| stop.0 >= t).unwrap_or(DAY_GRADIENT.len() - 1).max(1);
+    let (from, to) = (DAY_GRADIENT[next - 1], DAY_GRADIENT[next]);
+    let blend = ((t - from.0) / (to.0 - from.0)).clamp(0.0, 1.0);
+    pick(from) * (1.0 - blend) + pick(to) * blend
+}
+
+// Cubemap faces through the texture cache. A missing file would quietly turn into the fallback
+// texture there, so it's an error here instead.
+fn load_faces(paths: [&str; 6]) -> Result<[Arc<Texture>; 6], String> {
+    if let Some(missing) = paths.iter().find(|path| !Path::new(path).is_file()) {
+        return Err(format!("Skybox face '{}' not found", missing));
     }
+    Ok(paths.map(texture_cache::load))
 }
 
 impl Default for Skybox {
@@ -0,0 +1,199 @@
+use std::collections::HashSet;
+
+use crate::chunk::CHUNK_SIZE;
+use crate::cube::Cube;
+use crate::material::Material;
+use crate::material_palette::{MaterialId, MaterialPalette};
+use crate::noise::fbm;
+use crate::palette;
+use crate::texture_cache;
+use crate::utils::{Real, Vec3};
+use crate::voxel_grid::VoxelGrid;
+
+const NOISE_SCALE: f32 = 0.04; // Noise cells per block: hills a couple of dozen blocks across
+const HILL_HEIGHT: f32 = 14.0; // Blocks between the deepest valley and the highest hilltop
+const LOWEST: i32 = -5;        // Ground level of the deepest valleys (the top of a flat ground is 0)
+const SEA_LEVEL: i32 = 0;      // Valleys below this fill with water up to it
+const SNOW_LINE: i32 = 7;      // Columns this high are bare stone
+
+/// Endless procedural hills loaded around the camera a chunk column (16x16 blocks, all heights)
+/// at a time. Columns within `render_distance` chunks of the camera are generated and the rest
+/// dropped, so the world goes on as far as the camera travels while only a fixed number of
+/// blocks is ever traced. Generation depends only on the seed and the block position, so a
+/// column dropped and loaded again comes back the same.
+#[derive(Clone)]
+pub struct ChunkStreamer {
+    pub seed: u32,
+    pub render_distance: i32, // Chunk columns loaded in every direction around the camera's
+    loaded: HashSet<(i32, i32)>,
+    materials: Option<Materials>, // Added to the scene's palette on the first update
+}
+
+// The hills' materials as ids into the scene's palette
+#[derive(Clone, Copy)]
+struct Materials {
+    grass: MaterialId,
+    grass_side: MaterialId,
+    dirt: MaterialId,
+    stone: MaterialId,
+    water: MaterialId,
+}
+
+impl Materials {
+    fn new(scene_materials: &mut MaterialPalette) -> Self {
+        Self {
+            grass: scene_materials.add(Material::new(palette::GRASS_GREEN).with_texture(texture_cache::load("assets/textures/grass.jpg"))),
+            grass_side: scene_materials.add(Material::new(palette::GRASS_SIDE_GREEN).with_texture(texture_cache::load("assets/textures/grass_side.jpg"))),
+            dirt: scene_materials.add(Material::new(palette::DIRT_BROWN).with_texture(texture_cache::load("assets/textures/dirt.jpg"))),
+            stone: scene_materials.add(Material::new(palette::STONE_GRAY).with_texture(texture_cache::load("assets/textures/stone.jpg"))),
+            water: scene_materials.add(
+                Material::new(palette::WATER_BLUE)
+                    .with_transparency(0.85, 1.33)
+                    .with_absorption(palette::WATER_DEPTH_TINT, 0.8)
+                    .with_reflectivity(0.3)
+                    .with_specular(0.8, 64.0)
+                    .with_waves(1.0),
+            ),
+        }
+    }
+}
+
+impl ChunkStreamer {
+    pub fn new(seed: u32, render_distance: i32) -> Self {
+        Self {
+            seed,
+            render_distance: render_distance.max(1),
+            loaded: HashSet::new(),
+            materials: None,
+        }
+    }
+
+    /// Number of chunk columns currently loaded
+    pub fn loaded_chunks(&self) -> usize {
+        self.loaded.len()
+    }
+
+    // Ground level of the (x, z) block column in world coordinates: its top face is at this height
+    fn height(&self, x: i32, z: i32) -> i32 {
+        let offset = (self.seed % 10_000) as f32 * 7.31; // Each seed samples its own stretch of noise
+        let noise = fbm(x as f32 * NOISE_SCALE + offset, z as f32 * NOISE_SCALE - offset, 4);
+        LOWEST + (noise * HILL_HEIGHT).round() as i32
+    }
+
+    /// Load the chunk columns around `center` (world coordinates) into `grid` (materials from
+    /// `scene_materials`), in render space (shifted by -origin), and take out the ones now out of
+    /// range. Columns that stay in range are left as they are, edits included. Returns true when
+    /// anything changed, and the caller updates the grid.
+    pub fn update(&mut self, grid: &mut VoxelGrid, scene_materials: &mut MaterialPalette, center: Vec3, origin: Vec3) -> bool {
+        let home = (
+            (center.x.round() as i32).div_euclid(CHUNK_SIZE),
+            (center.z.round() as i32).div_euclid(CHUNK_SIZE),
+        );
+        let reach = self.render_distance;
+        let wanted: HashSet<(i32, i32)> = (-reach..=reach)
+            .flat_map(|dz| (-reach..=reach).map(move |dx| (home.0 + dx, home.1 + dz)))
+            .filter(|&(x, z)| (x - home.0).pow(2) + (z - home.1).pow(2) <= reach * reach) // A round patch, not a square
+            .collect();
+        if wanted == self.loaded {
+            return false;
+        }
+
+        let shift = origin_chunks(origin);
+        for &(x, z) in self.loaded.difference(&wanted) {
+            grid.remove_streamed((x - shift.0, z - shift.1));
+        }
+        let materials = *self.materials.get_or_insert_with(|| Materials::new(scene_materials));
+        let mut columns: Vec<&(i32, i32)> = wanted.difference(&self.loaded).collect();
+        columns.sort(); // Same cube order for the same columns
+        for &column in columns {
+            self.generate(column, origin, materials, grid);
+        }
+        self.loaded = wanted;
+        true
+    }
+
+    // Blocks of one chunk column: each block column's top block (grass, or stone up high), dirt
+    // down to its lowest neighbour so cliff sides are filled, and water over the low ground. Cells
+    // the scene's own blocks (or edits) take are skipped.
+    fn generate(&self, (chunk_x, chunk_z): (i32, i32), origin: Vec3, materials: Materials, grid: &mut VoxelGrid) {
+        for z in chunk_z * CHUNK_SIZE..(chunk_z + 1) * CHUNK_SIZE {
+            for x in chunk_x * CHUNK_SIZE..(chunk_x + 1) * CHUNK_SIZE {
+                let top = self.height(x, z);
+                let lowest_neighbour = [(x - 1, z), (x + 1, z), (x, z - 1), (x, z + 1)]
+                    .into_iter()
+                    .map(|(x, z)| self.height(x, z))
+                    .min()
+                    .unwrap_or(top);
+                // Block whose top face is at height y
+                let at = |y: i32| Vec3::new(x as Real, y as Real - 0.5, z as Real) - origin;
+
+                let surface = if top >= SNOW_LINE {
+                    Cube::new(at(top), 1.0, materials.stone)
+                } else {
+                    Cube::new_multi_texture(at(top), 1.0, materials.grass, materials.grass_side, materials.dirt)
+                };
+                grid.add_streamed(surface);
+                for y in lowest_neighbour.min(top - 1)..top {
+                    let material = if top - y > 3 { materials.stone } else { materials.dirt };
+                    grid.add_streamed(Cube::new(at(y), 1.0, material));
+                }
+                for y in top + 1..=SEA_LEVEL {
+                    grid.add_streamed(Cube::new(at(y), 1.0, materials.water));
+                }
+            }
+        }
+    }
+}
+
+// The floating origin in chunks (it moves a whole chunk at a time): a world chunk column is this
+// far from the render space one
+fn origin_chunks(origin: Vec3) -> (i32, i32) {
+    let chunks = |v: Real| (v / CHUNK_SIZE as Real).round() as i32;
+    (chunks(origin.x), chunks(origin.z))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    // The world cell of the top block of the (x, z) block column (centered half a block under its top face)
+    fn top_cell(streamer: &ChunkStreamer, x: i32, z: i32) -> (i32, i32, i32) {
+        (x, streamer.height(x, z), z)
+    }
+
+    #[test]
+    fn columns_still_in_range_keep_their_edits() {
+        let mut materials = MaterialPalette::new();
+        let mut grid = VoxelGrid::new();
+        let mut streamer = ChunkStreamer::new(3, 2);
+        streamer.update(&mut grid, &mut materials, Vec3::zero(), Vec3::zero());
+        let cell = top_cell(&streamer, 3, 3);
+        assert!(grid.remove(cell).is_some());
+
+        // One chunk over: the (0, 0) column stays loaded, so the block doesn't come back
+        assert!(streamer.update(&mut grid, &mut materials, Vec3::new(16.0, 0.0, 0.0), Vec3::zero()));
+        assert!(grid.get(cell).is_none());
+
+        // Far enough for it to be dropped and loaded again, which brings it back
+        streamer.update(&mut grid, &mut materials, Vec3::new(160.0, 0.0, 0.0), Vec3::zero());
+        assert!(grid.get(cell).is_none());
+        streamer.update(&mut grid, &mut materials, Vec3::zero(), Vec3::zero());
+        assert!(grid.get(cell).is_some());
+    }
+
+    #[test]
+    fn the_scene_blocks_keep_their_cells() {
+        let mut materials = MaterialPalette::new();
+        let red = materials.add(Material::new(Color::new(1.0, 0.0, 0.0)));
+        let mut streamer = ChunkStreamer::new(3, 1);
+        let cell = top_cell(&streamer, 5, 5);
+        let mut grid = VoxelGrid::new();
+        grid.add(Cube::new(Vec3::new(cell.0 as Real, cell.1 as Real, cell.2 as Real), 1.0, red));
+
+        for center in [Vec3::zero(), Vec3::new(160.0, 0.0, 0.0), Vec3::zero()] {
+            streamer.update(&mut grid, &mut materials, center, Vec3::zero());
+            assert_eq!(grid.get(cell).map(|cube| cube.material), Some(red));
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::scene::Scene;
+use crate::utils::{Real, Vec3};
+
+const HISTORY_WEIGHT: f32 = 0.8; // Share of the reprojected last frame kept in each new one
+const DEPTH_TOLERANCE: Real = 0.05; // Depth difference (as a share of the distance) that counts as a different surface
+
+/// Temporal reprojection antialiasing for the app while the camera moves: the last shown frame is
+/// moved to where each pixel's surface is now (using its depth and the camera change) and blended
+/// with the new frame, so edges and the blocks of low-res quality levels smooth out over a few
+/// frames. History is only used where it saw the same surface and is clamped to the colors around
+/// the pixel now, so uncovered areas and changing light don't smear.
+pub struct TemporalFilter {
+    history: Vec<Color>, // Last shown frame (display resolution)
+    width: i32,
+    height: i32,
+    depth: Vec<Real>,    // Camera ray hit distance per scaled pixel of that frame (infinite = sky)
+    scaled_width: i32,
+    scaled_height: i32,
+    camera: Option<Camera>, // Pose it was traced from (None = no usable history)
+    origin: Vec3,           // Scene origin then, in case the floating origin moved since
+}
+
+impl TemporalFilter {
+    pub fn new() -> Self {
+        Self {
+            history: Vec::new(),
+            width: 0,
+            height: 0,
+            depth: Vec::new(),
+            scaled_width: 0,
+            scaled_height: 0,
+            camera: None,
+            origin: Vec3::zero(),
+        }
+    }
+
+    /// Forget the last frame (scene rebuilt, camera cut)
+    pub fn reset(&mut self) {
+        self.camera = None;
+    }
+
+    /// Blend a freshly traced frame with the reprojected last one and remember the result.
+    /// `depth` is the frame's G-buffer depth (see render_scene_with_colors). With `blend` off the
+    /// frame is only remembered (still views are averaged by the Accumulator).
+    #[allow(clippy::too_many_arguments)]
+    pub fn resolve(
+        &mut self,
+        buffer: &mut [raylib::prelude::Color],
+        scene: &Scene,
+        camera: &Camera,
+        depth: &[Real],
+        render_scale: i32,
+        width: i32,
+        height: i32,
+        blend: bool,
+    ) {
+        let scale = render_scale.max(1);
+        let (scaled_width, scaled_height) = (width / scale, height / scale);
+        if scaled_width <= 0 || scaled_height <= 0 || depth.len() != (scaled_width * scaled_height) as usize {
+            return;
+        }
+
+        // One traced sample per scaled pixel, the rest of the block is a copy of it
+        let current: Vec<Color> = (0..scaled_height)
+            .flat_map(|sy| (0..scaled_width).map(move |sx| (sx, sy)))
+            .map(|(sx, sy)| {
+                let pixel = buffer[(sy * scale * width + sx * scale) as usize];
+                Color::from_u8(pixel.r, pixel.g, pixel.b)
+            })
+            .collect();
+        let previous = self.camera.filter(|_| blend && (self.width, self.height) == (width, height));
+        let shift = scene.origin - self.origin;
+
+        let mut resolved = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let sx = (x / scale).min(scaled_width - 1);
+                let sy = (y / scale).min(scaled_height - 1);
+                // Smooth upscale: interpolate between the scaled samples instead of repeating them
+                let fx = (x as f32 + 0.5) / scale as f32 - 0.5;
+                let fy = (y as f32 + 0.5) / scale as f32 - 0.5;
+                let color = bilinear(&current, scaled_width, scaled_height, fx, fy);
+                let history = previous.and_then(|previous| {
+                    self.reproject(&previous, camera, x, y, width, height, depth[(sy * scaled_width + sx) as usize], shift)
+                });
+                resolved.push(match history {
+                    Some(history) => {
+                        let (low, high) = neighbourhood(&current, scaled_width, scaled_height, sx, sy);
+                        color * (1.0 - HISTORY_WEIGHT) + clamp_between(history, low, high) * HISTORY_WEIGHT
+                    }
+                    None => color,
+                });
+            }
+        }
+
+        for (pixel, color) in buffer.iter_mut().zip(&resolved) {
+            *pixel = color.to_raylib();
+        }
+        self.history = resolved;
+        (self.width, self.height) = (width, height);
+        self.depth = depth.to_vec();
+        (self.scaled_width, self.scaled_height) = (scaled_width, scaled_height);
+        self.camera = Some(*camera);
+        self.origin = scene.origin;
+    }
+
+    // Color the last frame showed at the surface pixel (x, y) sees now, None where it showed
+    // something else (off screen, behind the camera, or hidden behind another surface)
+    #[allow(clippy::too_many_arguments)]
+    fn reproject(&self, previous: &Camera, camera: &Camera, x: i32, y: i32, width: i32, height: i32, distance: Real, shift: Vec3) -> Option<Color> {
+        let ray = camera.get_pinhole_ray((x as f32 + 0.5) / width as f32, (y as f32 + 0.5) / height as f32);
+        // The sky is infinitely far away, so only the camera's turn moves it
+        let sky = !distance.is_finite();
+        let point = if sky { previous.position + ray.direction } else { ray.origin + ray.direction * distance + shift };
+        let view = previous.world_to_view(point);
+        if view.z <= 0.0 {
+            return None;
+        }
+        let (u, v) = previous.view_to_screen(view);
+        if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+            return None;
+        }
+
+        let psx = ((u * self.scaled_width as f32) as i32).min(self.scaled_width - 1);
+        let psy = ((v * self.scaled_height as f32) as i32).min(self.scaled_height - 1);
+        let seen = self.depth[(psy * self.scaled_width + psx) as usize];
+        let visible = if sky {
+            !seen.is_finite()
+        } else {
+            ((point - previous.position).length() - seen).abs() <= DEPTH_TOLERANCE * seen.max(1.0)
+        };
+        visible.then(|| bilinear(&self.history, self.width, self.height, u * self.width as f32 - 0.5, v * self.height as f32 - 0.5))
+    }
+}
+
+impl Default for TemporalFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Image sampled between pixel centers (pixel (0, 0)'s center is at 0.0, 0.0), edges clamped
+fn bilinear(image: &[Color], width: i32, height: i32, x: f32, y: f32) -> Color {
+    let x = x.clamp(0.0, (width - 1) as f32);
+    let y = y.clamp(0.0, (height - 1) as f32);
+    let (x0, y0) = (x.floor() as i32, y.floor() as i32);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (tx, ty) = (x - x0 as f32, y - y0 as f32);
+    let at = |px: i32, py: i32| image[(py * width + px) as usize];
+    let top = at(x0, y0) * (1.0 - tx) + at(x1, y0) * tx;
+    let bottom = at(x0, y1) * (1.0 - tx) + at(x1, y1) * tx;
+    top * (1.0 - ty) + bottom * ty
+}
+
+// Darkest and brightest channels among a scaled pixel and its 8 neighbours
+fn neighbourhood(image: &[Color], width: i32, height: i32, sx: i32, sy: i32) -> (Color, Color) {
+    let mut low = Color::new(f32::MAX, f32::MAX, f32::MAX);
+    let mut high = Color::new(f32::MIN, f32::MIN, f32::MIN);
+    for ny in (sy - 1).max(0)..=(sy + 1).min(height - 1) {
+        for nx in (sx - 1).max(0)..=(sx + 1).min(width - 1) {
+            let c = image[(ny * width + nx) as usize];
+            low = Color::new(low.r.min(c.r), low.g.min(c.g), low.b.min(c.b));
+            high = Color::new(high.r.max(c.r), high.g.max(c.g), high.b.max(c.b));
+        }
+    }
+    (low, high)
+}
+
+fn clamp_between(color: Color, low: Color, high: Color) -> Color {
+    Color::new(color.r.clamp(low.r, high.r), color.g.clamp(low.g, high.g), color.b.clamp(low.b, high.b))
+}
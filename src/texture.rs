@@ -1,12 +1,49 @@
 use crate::color::Color;
+use crate::procedural_texture;
+use crate::texture_atlas::UvRect;
 use crate::utils::clamp;
 use image::GenericImageView;
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// How texels are looked up between texel centers
+#[derive(Clone, Copy, Debug, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextureFilter {
+    #[default]
+    Nearest,  // Blocky Minecraft look, shimmers when minified
+    Bilinear, // Blend of the four nearest texels
+}
+
+/// Frames stacked top to bottom in one texture (a Minecraft-style strip), played in a loop
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub struct TextureAnimation {
+    pub frames: usize,
+    #[serde(default = "default_frame_time")]
+    pub frame_time: f32, // Seconds each frame is shown
+}
+
+fn default_frame_time() -> f32 { 0.1 } // Two game ticks, like Minecraft's water
+
+impl TextureAnimation {
+    pub fn new(frames: usize, frame_time: f32) -> Self {
+        Self { frames, frame_time }
+    }
+
+    /// Frame shown at `time` seconds on the scene clock
+    pub fn frame_at(&self, time: f32) -> usize {
+        (time / self.frame_time.max(0.001)) as usize % self.frames.max(1)
+    }
+}
 
 #[derive(Clone)]
 pub struct Texture {
     pub width: usize,
     pub height: usize,
     pub data: Vec<Color>,
+    pub filter: TextureFilter,
+    pub alpha: Vec<f32>, // Per-texel opacity, empty for fully opaque images
+    mips: Vec<Texture>, // Successively halved copies (level 1, 2, ...) for distant lookups
 }
 
 impl Texture {
@@ -15,6 +52,9 @@ impl Texture {
             width,
             height,
             data: vec![Color::white(); width * height],
+            filter: TextureFilter::Nearest,
+            alpha: Vec::new(),
+            mips: Vec::new(),
         }
     }
 
@@ -23,6 +63,9 @@ impl Texture {
             width: 1,
             height: 1,
             data: vec![color],
+            filter: TextureFilter::Nearest,
+            alpha: Vec::new(),
+            mips: Vec::new(),
         }
     }
 
@@ -53,6 +96,9 @@ impl Texture {
             width,
             height,
             data,
+            filter: TextureFilter::Nearest,
+            alpha: Vec::new(),
+            mips: Vec::new(),
         }
     }
 
@@ -97,10 +143,54 @@ impl Texture {
             width,
             height,
             data,
+            filter: TextureFilter::Nearest,
+            alpha: Vec::new(),
+            mips: Vec::new(),
         }
     }
 
+    /// Load an image file (or a fallback) with its mip chain. Images store sRGB-encoded colors,
+    /// the renderer mixes light linearly, so the texels are decoded first (alpha stays as it is).
     pub fn load(path: &str) -> Self {
+        let mut texture = Self::load_base(path);
+        for texel in &mut texture.data {
+            *texel = texel.to_linear();
+        }
+        texture.with_mips()
+    }
+
+    /// Load an image that holds data rather than colors (normal maps), without sRGB decoding
+    pub fn load_data(path: &str) -> Self {
+        Self::load_base(path).with_mips()
+    }
+
+    /// Load a high dynamic range image (.hdr, .exr) keeping its float values, which are linear
+    /// already and may go far past white. 8-bit files work too and are decoded from sRGB.
+    /// Sampled bilinearly; no fallback, a missing environment is an error.
+    pub fn load_hdr(path: &str) -> Result<Self, String> {
+        let image = image::open(path).map_err(|e| format!("Failed to load environment '{}': {}", path, e))?;
+        let linear = matches!(image.color(), image::ColorType::Rgb32F | image::ColorType::Rgba32F);
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        let data = image
+            .into_rgb32f()
+            .pixels()
+            .map(|pixel| {
+                let color = Color::new(pixel[0], pixel[1], pixel[2]);
+                if linear { color } else { color.to_linear() }
+            })
+            .collect();
+        println!("Loaded environment: {} ({}x{})", path, width, height);
+        Ok(Self {
+            width,
+            height,
+            data,
+            filter: TextureFilter::Bilinear,
+            alpha: Vec::new(),
+            mips: Vec::new(),
+        })
+    }
+
+    fn load_base(path: &str) -> Self {
         // Try to load the image file
         match image::open(path) {
             Ok(img) => {
@@ -108,20 +198,25 @@ impl Texture {
                 let width = width as usize;
                 let height = height as usize;
                 let mut data = Vec::with_capacity(width * height);
+                let mut alpha = Vec::new();
 
-                // Convert image to RGB8 format
-                let img_rgb = img.to_rgb8();
+                // Convert image to RGBA8 format (alpha is kept only when the file has a channel for it)
+                let has_alpha = img.color().has_alpha();
+                let img_rgba = img.to_rgba8();
 
                 // Load pixel data
                 for y in 0..height {
                     for x in 0..width {
-                        let pixel = img_rgb.get_pixel(x as u32, y as u32);
+                        let pixel = img_rgba.get_pixel(x as u32, y as u32);
                         let color = Color::new(
                             pixel[0] as f32 / 255.0,
                             pixel[1] as f32 / 255.0,
                             pixel[2] as f32 / 255.0,
                         );
                         data.push(color);
+                        if has_alpha {
+                            alpha.push(pixel[3] as f32 / 255.0);
+                        }
                     }
                 }
 
@@ -131,10 +226,19 @@ impl Texture {
                     width,
                     height,
                     data,
+                    filter: TextureFilter::Nearest,
+                    alpha,
+                    mips: Vec::new(),
                 }
             }
             Err(e) => {
                 eprintln!("Failed to load texture '{}': {}", path, e);
+
+                // Known block textures get a procedural look-alike so the demo works without assets
+                if let Some(texture) = procedural_texture::fallback_for(path) {
+                    eprintln!("Using procedural fallback texture");
+                    return texture;
+                }
                 eprintln!("Using fallback checkerboard pattern");
 
                 // Fallback: Create a checkerboard pattern
@@ -158,12 +262,160 @@ impl Texture {
                     width,
                     height,
                     data,
+                    filter: TextureFilter::Nearest,
+                    alpha: Vec::new(),
+                    mips: Vec::new(),
                 }
             }
         }
     }
 
+    /// Stack equally sized frame images into one vertical strip for TextureAnimation
+    pub fn stack_frames(frames: &[Arc<Texture>]) -> Result<Self, String> {
+        let first = frames.first().ok_or("No animation frames given")?;
+        if frames.iter().any(|f| f.width != first.width || f.height != first.height) {
+            return Err(format!("Animation frames must all be {}x{}", first.width, first.height));
+        }
+        let keep_alpha = frames.iter().any(|f| !f.alpha.is_empty());
+        let mut strip = Texture::new(first.width, first.height * frames.len());
+        strip.data = frames.iter().flat_map(|f| f.data.iter().copied()).collect();
+        if keep_alpha {
+            strip.alpha = frames
+                .iter()
+                .flat_map(|f| if f.alpha.is_empty() { vec![1.0; f.data.len()] } else { f.alpha.clone() })
+                .collect();
+        }
+        Ok(strip.with_mips())
+    }
+
+    /// Mean color of all texels (used for minimap/preview colors)
+    pub fn average_color(&self) -> Color {
+        let sum = self.data.iter().fold(Color::black(), |acc, &c| acc + c);
+        sum * (1.0 / self.data.len().max(1) as f32)
+    }
+
+    /// Mean color of the texels inside a uv rect (one atlas tile)
+    pub fn average_color_in(&self, rect: &UvRect) -> Color {
+        let x0 = (rect.u * self.width as f32) as usize;
+        let y0 = (rect.v * self.height as f32) as usize;
+        let x1 = (((rect.u + rect.width) * self.width as f32) as usize).clamp(x0 + 1, self.width);
+        let y1 = (((rect.v + rect.height) * self.height as f32) as usize).clamp(y0 + 1, self.height);
+
+        let mut sum = Color::black();
+        for y in y0..y1 {
+            for x in x0..x1 {
+                sum = sum + self.texel(x, y);
+            }
+        }
+        sum * (1.0 / ((x1 - x0) * (y1 - y0)).max(1) as f32)
+    }
+
+    /// Build the mip chain by averaging 2x2 texel blocks down to 1x1
+    pub fn with_mips(mut self) -> Self {
+        let mut mips: Vec<Texture> = Vec::new();
+        loop {
+            let source = mips.last().unwrap_or(&self);
+            if source.width <= 1 && source.height <= 1 {
+                break;
+            }
+            let next = source.downsample();
+            mips.push(next);
+        }
+        self.mips = mips;
+        self
+    }
+
+    fn downsample(&self) -> Texture {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let mut data = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let sum = self.texel(x * 2, y * 2)
+                    + self.texel(x * 2 + 1, y * 2)
+                    + self.texel(x * 2, y * 2 + 1)
+                    + self.texel(x * 2 + 1, y * 2 + 1);
+                data.push(sum * 0.25);
+            }
+        }
+        Texture {
+            width,
+            height,
+            data,
+            filter: self.filter,
+            alpha: Vec::new(), // Cutout tests always use the full-resolution alpha
+            mips: Vec::new(),
+        }
+    }
+
+    /// Sample at full resolution
     pub fn sample(&self, u: f32, v: f32) -> Color {
+        self.sample_with(self.filter, u, v)
+    }
+
+    /// Sample with a lookup footprint in uv units (how much of the texture one pixel covers).
+    /// Larger footprints read from smaller mips; bilinear textures blend two levels (trilinear).
+    pub fn sample_lod(&self, u: f32, v: f32, footprint: f32) -> Color {
+        let texels = footprint * self.width.max(self.height) as f32;
+        self.sample_texels(u, v, texels, self.mips.len() as f32)
+    }
+
+    /// Sample with a footprint already measured in texels, never going past max_lod
+    /// (atlas tiles and animation frames stop before their texels mix with neighbors)
+    pub fn sample_texels(&self, u: f32, v: f32, texels: f32, max_lod: f32) -> Color {
+        if self.mips.is_empty() || texels <= 1.0 || max_lod <= 0.0 {
+            return self.sample(u, v);
+        }
+
+        let lod = texels.log2().min(max_lod).min(self.mips.len() as f32);
+        let level = |i: usize| if i == 0 { self } else { &self.mips[i - 1] };
+        match self.filter {
+            TextureFilter::Nearest => level(lod.round() as usize).sample_with(self.filter, u, v),
+            TextureFilter::Bilinear => {
+                let lower = lod.floor() as usize;
+                let upper = (lower + 1).min(self.mips.len());
+                let blend = lod - lower as f32;
+                level(lower).sample_with(self.filter, u, v) * (1.0 - blend)
+                    + level(upper).sample_with(self.filter, u, v) * blend
+            }
+        }
+    }
+
+    /// Opacity of the texel at (u, v), nearest lookup so cutout edges stay crisp (1.0 without alpha)
+    pub fn alpha_at(&self, u: f32, v: f32) -> f32 {
+        if self.alpha.is_empty() {
+            return 1.0;
+        }
+        let x = ((clamp(u, 0.0, 1.0) * self.width as f32) as usize).min(self.width - 1);
+        let y = ((clamp(v, 0.0, 1.0) * self.height as f32) as usize).min(self.height - 1);
+        self.alpha[y * self.width + x]
+    }
+
+    fn sample_with(&self, filter: TextureFilter, u: f32, v: f32) -> Color {
+        match filter {
+            TextureFilter::Nearest => self.sample_nearest(u, v),
+            TextureFilter::Bilinear => self.sample_bilinear(u, v),
+        }
+    }
+
+    fn texel(&self, x: usize, y: usize) -> Color {
+        self.data[y.min(self.height - 1) * self.width + x.min(self.width - 1)]
+    }
+
+    fn sample_bilinear(&self, u: f32, v: f32) -> Color {
+        // Texel centers sit at (i + 0.5) / size, so shift by half a texel before splitting
+        let x = (clamp(u, 0.0, 1.0) * self.width as f32 - 0.5).max(0.0);
+        let y = (clamp(v, 0.0, 1.0) * self.height as f32 - 0.5).max(0.0);
+
+        let (x0, y0) = (x as usize, y as usize);
+        let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+
+        let top = self.texel(x0, y0) * (1.0 - fx) + self.texel(x0 + 1, y0) * fx;
+        let bottom = self.texel(x0, y0 + 1) * (1.0 - fx) + self.texel(x0 + 1, y0 + 1) * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+
+    fn sample_nearest(&self, u: f32, v: f32) -> Color {
         let u = clamp(u, 0.0, 1.0);
         let v = clamp(v, 0.0, 1.0);
 
@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use crate::texture::Texture;
+use crate::texture_cache;
+
+/// Sub-rectangle of a texture in uv units (the whole texture is 0,0 1x1)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UvRect {
+    pub u: f32,
+    pub v: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl UvRect {
+    pub const FULL: UvRect = UvRect { u: 0.0, v: 0.0, width: 1.0, height: 1.0 };
+
+    pub fn is_full(&self) -> bool {
+        *self == Self::FULL
+    }
+
+    /// Map a face uv (0..1) into this rect, kept half a texel inside so lookups
+    /// never bleed into the neighboring tile
+    pub fn map(&self, u: f32, v: f32, texture: &Texture) -> (f32, f32) {
+        let half_u = 0.5 / texture.width as f32;
+        let half_v = 0.5 / texture.height as f32;
+        let u = (self.u + u * self.width).clamp(self.u + half_u, self.u + self.width - half_u);
+        let v = (self.v + v * self.height).clamp(self.v + half_v, self.v + self.height - half_v);
+        (u, v)
+    }
+}
+
+/// One big sheet of equally sized block tiles (like Minecraft's terrain.png).
+/// Materials made from it share the same texture allocation and only differ in their uv rect.
+#[derive(Clone)]
+pub struct TextureAtlas {
+    pub texture: Arc<Texture>,
+    pub columns: usize,
+    pub rows: usize,
+}
+
+impl TextureAtlas {
+    pub fn new(texture: Arc<Texture>, columns: usize, rows: usize) -> Self {
+        Self {
+            texture,
+            columns: columns.max(1),
+            rows: rows.max(1),
+        }
+    }
+
+    /// Load a sheet split into columns x rows tiles (falls back like Texture::load when missing)
+    pub fn load(path: &str, columns: usize, rows: usize) -> Self {
+        let atlas = Self::new(texture_cache::load(path), columns, rows);
+        println!(
+            "Texture atlas: {} tiles of {}x{}",
+            atlas.columns * atlas.rows,
+            atlas.texture.width / atlas.columns,
+            atlas.texture.height / atlas.rows
+        );
+        atlas
+    }
+
+    /// uv rect of the tile at (column, row), counted from the top-left corner
+    pub fn tile(&self, column: usize, row: usize) -> Result<UvRect, String> {
+        if column >= self.columns || row >= self.rows {
+            return Err(format!(
+                "Atlas tile [{}, {}] is outside the {}x{} grid",
+                column, row, self.columns, self.rows
+            ));
+        }
+        Ok(UvRect {
+            u: column as f32 / self.columns as f32,
+            v: row as f32 / self.rows as f32,
+            width: 1.0 / self.columns as f32,
+            height: 1.0 / self.rows as f32,
+        })
+    }
+}
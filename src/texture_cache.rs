@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+use crate::texture::Texture;
+
+// Loaded textures by path and kind (color or data), with the file's modification time when it was read
+struct Entry {
+    modified: Option<SystemTime>,
+    texture: Arc<Texture>,
+}
+
+fn cache() -> &'static Mutex<HashMap<(String, bool), Entry>> {
+    static CACHE: OnceLock<Mutex<HashMap<(String, bool), Entry>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn modified_time(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Texture::load shared by path: every caller gets the same pixel data.
+/// A file edited on disk since it was cached is read again (so scene hot-reload picks it up).
+pub fn load(path: &str) -> Arc<Texture> {
+    load_cached(path, false)
+}
+
+/// Same for normal maps (Texture::load_data, texels kept as stored)
+pub fn load_normal_map(path: &str) -> Arc<Texture> {
+    load_cached(path, true)
+}
+
+fn load_cached(path: &str, data: bool) -> Arc<Texture> {
+    let key = (path.to_string(), data);
+    let modified = modified_time(path);
+    let mut cache = cache().lock().unwrap_or_else(|e| e.into_inner());
+
+    if let Some(entry) = cache.get(&key)
+        && entry.modified == modified
+    {
+        return Arc::clone(&entry.texture);
+    }
+
+    let texture = Arc::new(if data { Texture::load_data(path) } else { Texture::load(path) });
+    cache.insert(
+        key,
+        Entry {
+            modified,
+            texture: Arc::clone(&texture),
+        },
+    );
+    texture
+}
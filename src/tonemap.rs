@@ -0,0 +1,45 @@
+use serde::Deserialize;
+
+use crate::color::Color;
+
+/// Names accepted by `tone_map = "..."` in the [display] config section and `--tone-map`
+pub const TONE_MAP_NAMES: [&str; 3] = ["clamp", "reinhard", "aces"];
+
+// Extended Reinhard: channels this bright come out as pure white
+const REINHARD_WHITE: f32 = 4.0;
+
+/// How the traced colors (linear, unclamped: the sun disk and lamps go well past 1.0) are
+/// squeezed into the 0-1 range of the screen
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToneMap {
+    Clamp,    // Cut off at 1.0: the old look, bright areas clip to flat white
+    Reinhard, // x (1 + x / white^2) / (1 + x): soft, keeps colors, a bit darker overall
+    #[default]
+    Aces,     // Filmic curve (Narkowicz's fit of ACES): more contrast, highlights roll off to white
+}
+
+impl ToneMap {
+    pub fn by_name(name: &str) -> Result<Self, String> {
+        match name {
+            "clamp" => Ok(ToneMap::Clamp),
+            "reinhard" => Ok(ToneMap::Reinhard),
+            "aces" => Ok(ToneMap::Aces),
+            other => Err(format!("Unknown tone map '{}', expected one of: {}", other, TONE_MAP_NAMES.join(", "))),
+        }
+    }
+
+    /// Display color (0-1) for a linear color, after scaling it by `exposure`
+    pub fn apply(&self, color: Color, exposure: f32) -> Color {
+        let color = color * exposure.max(0.0);
+        let curve = |x: f32| {
+            let x = x.max(0.0);
+            match self {
+                ToneMap::Clamp => x,
+                ToneMap::Reinhard => x * (1.0 + x / (REINHARD_WHITE * REINHARD_WHITE)) / (1.0 + x),
+                ToneMap::Aces => (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14),
+            }
+        };
+        Color::new(curve(color.r), curve(color.g), curve(color.b)).clamp()
+    }
+}
@@ -0,0 +1,195 @@
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+
+use crate::batch;
+use crate::camera::Camera;
+use crate::export::{arg_value, parse_size, path_tracing_samples};
+use crate::lighting::LightingPreset;
+use crate::progress;
+use crate::renderer::{self, RenderSettings};
+use crate::scene::Scene;
+use crate::utils::{Degrees, Radians, Real, TAU, Vec3};
+
+const ELEVATION: Degrees = Degrees(25.0); // Above the horizon
+const FOV: Degrees = Degrees(50.0);
+
+/// `--turntable N`: orbit the camera once around the scene over N frames and write a looping
+/// animated GIF (or APNG when the output ends in .png) for quick previews
+pub struct TurntableOptions {
+    pub frames: u32,
+    pub output: PathBuf,
+    pub width: i32,
+    pub height: i32,
+    pub fps: u32,
+    pub lighting: Option<LightingPreset>,
+    pub settings: RenderSettings,
+}
+
+impl TurntableOptions {
+    /// Parse `--turntable N [--out FILE.gif|FILE.png] [--size WxH] [--quality low|medium|high|ultra] [--fps N]
+    /// [--lighting PRESET] [--path-tracing SAMPLES] [--denoise]` from the command line
+    pub fn from_args(args: &[String]) -> Option<Result<Self, String>> {
+        let frames = arg_value(args, "--turntable")?;
+        Some(Self::parse(args, frames))
+    }
+
+    fn parse(args: &[String], frames: &str) -> Result<Self, String> {
+        let frames: u32 = frames
+            .parse()
+            .ok()
+            .filter(|frames| *frames > 0)
+            .ok_or_else(|| format!("Invalid turntable frame count '{}'", frames))?;
+
+        let output = PathBuf::from(arg_value(args, "--out").unwrap_or("turntable.gif"));
+        let (width, height) = match arg_value(args, "--size") {
+            Some(size) => parse_size(size)?,
+            None => (480, 360),
+        };
+        let fps: u32 = match arg_value(args, "--fps") {
+            Some(fps) => fps.parse().ok().filter(|fps| *fps > 0).ok_or_else(|| format!("Invalid fps '{}'", fps))?,
+            None => 20,
+        };
+        let quality = arg_value(args, "--quality").unwrap_or("medium");
+        let lighting = match arg_value(args, "--lighting") {
+            Some(name) => Some(LightingPreset::parse(name)?),
+            None => None,
+        };
+        let path_samples = path_tracing_samples(args)?;
+
+        Ok(Self {
+            frames,
+            output,
+            width,
+            height,
+            fps,
+            lighting,
+            settings: RenderSettings {
+                render_scale: batch::render_scale(quality)?,
+                use_threading: true,
+                frame_budget: 0.0,
+                ray_budget: 0,
+                shadow_samples: batch::shadow_samples(quality),
+                ao_samples: batch::ao_samples(quality),
+                aa_samples: batch::aa_samples(quality),
+                path_tracing: path_samples > 0,
+                path_samples,
+                denoise: args.iter().any(|a| a == "--denoise"),
+                ..RenderSettings::default()
+            },
+        })
+    }
+}
+
+/// Camera on the orbit at `t` (0..1 = one full turn), framing the whole bounding box
+fn orbit_camera(min: Vec3, max: Vec3, t: f32, aspect: f32) -> Camera {
+    let center = (min + max) * 0.5;
+    // Distance that fits the bounding sphere into the narrower field of view
+    let radius = (max - min).length() * 0.5;
+    let half_fov = (FOV * 0.5).radians();
+    let half_fov = if aspect < 1.0 { Radians::atan(half_fov.tan() * aspect as Real) } else { half_fov };
+    let distance = radius / half_fov.sin() * 0.9; // The box corners are never all in view, so a little closer
+
+    let angle = t as Real * TAU;
+    let elevation = ELEVATION.radians();
+    let offset = Vec3::new(
+        angle.cos() * elevation.cos(),
+        elevation.sin(),
+        angle.sin() * elevation.cos(),
+    );
+    Camera::new(center + offset * distance, center, FOV, aspect)
+}
+
+pub fn render_turntable(scene: &mut Scene, options: &TurntableOptions) -> Result<(), String> {
+    progress::install_interrupt_handler();
+    let (min, max) = scene.bounds().ok_or("The scene is empty, nothing to orbit")?;
+    if let Some(ref lighting) = options.lighting {
+        scene.set_lighting(Some(lighting.clone()));
+    }
+
+    println!(
+        "Turntable: {} frames ({}x{}, {} fps) to {}",
+        options.frames,
+        options.width,
+        options.height,
+        options.fps,
+        options.output.display()
+    );
+
+    let start = Instant::now();
+    let aspect = options.width as f32 / options.height as f32;
+    let mut buffer = vec![raylib::prelude::Color::BLACK; (options.width * options.height) as usize];
+    let mut frames = Vec::with_capacity(options.frames as usize);
+    for frame in 0..options.frames {
+        // The last frame stops one step short of the first, so the loop has no repeated frame
+        let camera = orbit_camera(min, max, frame as f32 / options.frames as f32, aspect);
+        let label = format!("frame {}/{}", frame + 1, options.frames);
+        buffer.fill(raylib::prelude::Color::BLACK);
+        let finished = renderer::render_scene_with_progress(
+            scene,
+            &camera,
+            &mut buffer,
+            options.width,
+            options.height,
+            &options.settings,
+            &label,
+            None,
+        );
+        if !finished {
+            return Err(format!("Interrupted after {} of {} frames, nothing written", frame, options.frames));
+        }
+        let pixels = buffer.iter().flat_map(|c| [c.r, c.g, c.b, 255]).collect();
+        frames.push(pixels);
+    }
+
+    if let Some(dir) = options.output.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create directory '{}': {}", dir.display(), e))?;
+    }
+    let is_png = options.output.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("png"));
+    if is_png {
+        write_apng(&options.output, &frames, options)?;
+    } else {
+        write_gif(&options.output, frames, options)?;
+    }
+    println!("Turntable finished in {:.1}s: {}", start.elapsed().as_secs_f32(), options.output.display());
+    Ok(())
+}
+
+fn create(path: &Path) -> Result<BufWriter<File>, String> {
+    File::create(path)
+        .map(BufWriter::new)
+        .map_err(|e| format!("Failed to create '{}': {}", path.display(), e))
+}
+
+// GIF: 256-color palette per frame, quantized at a speed that stays quick on large frames
+fn write_gif(path: &Path, frames: Vec<Vec<u8>>, options: &TurntableOptions) -> Result<(), String> {
+    let error = |e: image::ImageError| format!("Failed to write '{}': {}", path.display(), e);
+    let mut encoder = GifEncoder::new_with_speed(create(path)?, 10);
+    encoder.set_repeat(Repeat::Infinite).map_err(error)?;
+    let delay = Delay::from_numer_denom_ms(1000, options.fps);
+    for pixels in frames {
+        let image = RgbaImage::from_raw(options.width as u32, options.height as u32, pixels)
+            .ok_or("Frame buffer has the wrong size")?;
+        encoder.encode_frame(Frame::from_parts(image, 0, 0, delay)).map_err(error)?;
+    }
+    Ok(())
+}
+
+// APNG: full color, no palette banding
+fn write_apng(path: &Path, frames: &[Vec<u8>], options: &TurntableOptions) -> Result<(), String> {
+    let error = |e: png::EncodingError| format!("Failed to write '{}': {}", path.display(), e);
+    let mut encoder = png::Encoder::new(create(path)?, options.width as u32, options.height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_animated(frames.len() as u32, 0).map_err(error)?; // 0 plays = loop forever
+    encoder.set_frame_delay(1, options.fps.min(u16::MAX as u32) as u16).map_err(error)?;
+    let mut writer = encoder.write_header().map_err(error)?;
+    for pixels in frames {
+        writer.write_image_data(pixels).map_err(error)?;
+    }
+    writer.finish().map_err(error)
+}
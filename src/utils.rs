@@ -1,18 +1,39 @@
+use std::cell::Cell;
 use std::ops::{Add, Sub, Mul, Div, Neg};
+use std::sync::atomic::{AtomicU32, Ordering};
 
-#[derive(Debug, Clone, Copy)]
+use serde::{Deserialize, Serialize};
+
+/// Scalar of all geometry (positions, directions, hit distances). f32 by default; the `f64`
+/// feature switches to double precision for worlds far from the origin, at some speed cost.
+/// Colors and texture coordinates stay f32 either way.
+#[cfg(not(feature = "f64"))]
+pub type Real = f32;
+#[cfg(feature = "f64")]
+pub type Real = f64;
+
+pub const PI: Real = std::f64::consts::PI as Real;
+pub const TAU: Real = std::f64::consts::TAU as Real;
+
+/// Geometry value into the f32 world of colors, uvs and intensities (a no-op in the default build)
+#[allow(clippy::unnecessary_cast)]
+pub fn narrow(x: Real) -> f32 {
+    x as f32
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Vec3 {
-    pub x: f32,
-    pub y: f32,
-    pub z: f32,
+    pub x: Real,
+    pub y: Real,
+    pub z: Real,
 }
 
 impl Vec3 {
-    pub fn new(x: f32, y: f32, z: f32) -> Self { Self { x, y, z } }
+    pub fn new(x: Real, y: Real, z: Real) -> Self { Self { x, y, z } }
     pub fn zero() -> Self { Self::new(0.0, 0.0, 0.0) }
     pub fn one() -> Self { Self::new(1.0, 1.0, 1.0) }
 
-    pub fn dot(&self, other: &Vec3) -> f32 { self.x * other.x + self.y * other.y + self.z * other.z }
+    pub fn dot(&self, other: &Vec3) -> Real { self.x * other.x + self.y * other.y + self.z * other.z }
     pub fn cross(&self, other: &Vec3) -> Vec3 {
         Vec3::new(
             self.y * other.z - self.z * other.y,
@@ -21,7 +42,7 @@ impl Vec3 {
         )
     }
 
-    pub fn length(&self) -> f32 { (self.x * self.x + self.y * self.y + self.z * self.z).sqrt() }
+    pub fn length(&self) -> Real { (self.x * self.x + self.y * self.y + self.z * self.z).sqrt() }
 
     pub fn normalize(&self) -> Vec3 {
         let len = self.length();
@@ -32,7 +53,7 @@ impl Vec3 {
         *self - *normal * 2.0 * self.dot(normal)
     }
 
-    pub fn refract(&self, normal: &Vec3, eta: f32) -> Option<Vec3> {
+    pub fn refract(&self, normal: &Vec3, eta: Real) -> Option<Vec3> {
         let cos_i = -self.dot(normal).max(-1.0).min(1.0);
         let sin_t2 = eta * eta * (1.0 - cos_i * cos_i);
         if sin_t2 > 1.0 { None } else {
@@ -44,11 +65,110 @@ impl Vec3 {
 
 impl Add for Vec3 { type Output = Vec3; fn add(self, o: Vec3) -> Vec3 { Vec3::new(self.x + o.x, self.y + o.y, self.z + o.z) } }
 impl Sub for Vec3 { type Output = Vec3; fn sub(self, o: Vec3) -> Vec3 { Vec3::new(self.x - o.x, self.y - o.y, self.z - o.z) } }
-impl Mul<f32> for Vec3 { type Output = Vec3; fn mul(self, s: f32) -> Vec3 { Vec3::new(self.x * s, self.y * s, self.z * s) } }
-impl Div<f32> for Vec3 { type Output = Vec3; fn div(self, s: f32) -> Vec3 { Vec3::new(self.x / s, self.y / s, self.z / s) } }
+impl Mul<Real> for Vec3 { type Output = Vec3; fn mul(self, s: Real) -> Vec3 { Vec3::new(self.x * s, self.y * s, self.z * s) } }
+impl Div<Real> for Vec3 { type Output = Vec3; fn div(self, s: Real) -> Vec3 { Vec3::new(self.x / s, self.y / s, self.z / s) } }
 impl Neg for Vec3 { type Output = Vec3; fn neg(self) -> Vec3 { Vec3::new(-self.x, -self.y, -self.z) } }
 
+/// Angle in degrees, the unit angles are written and shown in (FOVs, sun position, disk sizes).
+/// Trig only takes `Radians`, so get there with `.radians()` and a degree value can't slip into
+/// `sin`/`tan` by mistake.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default, Serialize, Deserialize)]
+pub struct Degrees(pub f32);
+
+/// Angle in radians, for the math
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Radians(pub Real);
+
+impl Degrees {
+    pub fn radians(self) -> Radians { Radians((self.0 as Real).to_radians()) }
+    pub fn clamp(self, min: Degrees, max: Degrees) -> Degrees { Degrees(self.0.clamp(min.0, max.0)) }
+    pub fn max(self, other: Degrees) -> Degrees { Degrees(self.0.max(other.0)) }
+    /// The same direction as an angle in [0, 360)
+    pub fn wrapped(self) -> Degrees { Degrees(self.0.rem_euclid(360.0)) }
+    /// Raw bits, for hashing
+    pub fn to_bits(self) -> u32 { self.0.to_bits() }
+}
+
+impl Radians {
+    pub fn degrees(self) -> Degrees { Degrees(narrow(self.0.to_degrees())) }
+    pub fn sin(self) -> Real { self.0.sin() }
+    pub fn cos(self) -> Real { self.0.cos() }
+    pub fn tan(self) -> Real { self.0.tan() }
+    pub fn atan(tangent: Real) -> Radians { Radians(tangent.atan()) }
+    pub fn clamp(self, min: Radians, max: Radians) -> Radians { Radians(self.0.clamp(min.0, max.0)) }
+}
+
+impl From<Degrees> for Radians { fn from(d: Degrees) -> Radians { d.radians() } }
+impl From<Radians> for Degrees { fn from(r: Radians) -> Degrees { r.degrees() } }
+impl Add for Degrees { type Output = Degrees; fn add(self, o: Degrees) -> Degrees { Degrees(self.0 + o.0) } }
+impl Sub for Degrees { type Output = Degrees; fn sub(self, o: Degrees) -> Degrees { Degrees(self.0 - o.0) } }
+impl Mul<f32> for Degrees { type Output = Degrees; fn mul(self, s: f32) -> Degrees { Degrees(self.0 * s) } }
+impl Neg for Degrees { type Output = Degrees; fn neg(self) -> Degrees { Degrees(-self.0) } }
+impl Add for Radians { type Output = Radians; fn add(self, o: Radians) -> Radians { Radians(self.0 + o.0) } }
+impl Neg for Radians { type Output = Radians; fn neg(self) -> Radians { Radians(-self.0) } }
+
 pub fn lerp(a: f32, b: f32, t: f32) -> f32 { a + (b - a) * t }
 pub fn clamp(value: f32, min: f32, max: f32) -> f32 {
     if value < min { min } else if value > max { max } else { value }
 }
+
+// Per-thread xorshift state, seeded differently for every render thread
+static NEXT_SEED: AtomicU32 = AtomicU32::new(0x9E37_79B9);
+
+thread_local! {
+    static RNG_STATE: Cell<u32> = Cell::new(NEXT_SEED.fetch_add(0x6C8E_9CF5, Ordering::Relaxed) | 1);
+}
+
+/// Fast pseudo-random number in [0, 1) for stochastic sampling (not cryptographic)
+pub fn random_f32() -> f32 {
+    RNG_STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        state.set(x);
+        (x >> 8) as f32 / (1u32 << 24) as f32
+    })
+}
+
+// Deterministic numbers in [0, 1) for tests, so a failing case comes back the same
+#[cfg(test)]
+pub(crate) fn numbers(mut seed: u32) -> impl FnMut() -> Real {
+    move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        (seed % 10_000) as Real / 10_000.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn degrees_and_radians_convert() {
+        assert!((Degrees(180.0).radians().0 - PI).abs() < 1e-6);
+        assert!((Degrees(-90.0).radians().0 + PI / 2.0).abs() < 1e-6);
+        assert!((Radians(PI / 4.0).degrees().0 - 45.0).abs() < 1e-4);
+        assert!((Degrees(30.0).radians().sin() - 0.5).abs() < 1e-6);
+        assert_eq!(Radians::from(Degrees(0.0)), Radians(0.0));
+    }
+
+    #[test]
+    fn angles_survive_the_round_trip() {
+        for degrees in [-720.0, -180.0, -89.5, 0.0, 0.25, 45.0, 70.0, 359.9, 1080.0] {
+            let back = Degrees(degrees).radians().degrees();
+            assert!((back.0 - degrees).abs() <= 1e-4 * degrees.abs().max(1.0), "{} came back as {:?}", degrees, back);
+            assert_eq!(Degrees::from(Radians::from(Degrees(degrees))), back);
+        }
+    }
+
+    #[test]
+    fn wrapped_angles_stay_in_one_turn() {
+        assert_eq!(Degrees(-90.0).wrapped(), Degrees(270.0));
+        assert_eq!(Degrees(720.0).wrapped(), Degrees(0.0));
+        assert_eq!(Degrees(45.0).wrapped(), Degrees(45.0));
+        assert_eq!(Radians(1.0).clamp(-Radians(0.5), Radians(0.5)), Radians(0.5));
+    }
+}
@@ -0,0 +1,99 @@
+use crate::camera::Camera;
+use crate::cube::Cube;
+use crate::material::Material;
+use crate::scene::Scene;
+use crate::utils::{Real, Vec3};
+
+// What was swapped: blocks by position (the cube list shifts when blocks are edited), meshes by index
+#[derive(Clone, Copy, PartialEq)]
+enum Target {
+    Block(Vec3),
+    Mesh(usize),
+}
+
+// The block as it was (for its face materials) or the mesh's material, put back when the grid comes off
+enum Original {
+    Block(Cube),
+    Mesh(Material),
+}
+
+/// In-app UV check: the block or mesh under the crosshair trades its materials for the numbered
+/// UV grid (Material::uv_grid) on every face, and gets them back on a second press. The swap only
+/// lives in the running scene; reloading the scene file clears it.
+pub struct UvDebug {
+    swapped: Vec<(Target, Original)>,
+}
+
+impl UvDebug {
+    pub fn new() -> Self {
+        Self { swapped: Vec::new() }
+    }
+
+    /// Put the grid on whatever is under the crosshair, or take it off again. Returns whether the
+    /// grid is now on it, None when nothing is targeted.
+    pub fn toggle(&mut self, scene: &mut Scene, camera: &Camera) -> Option<bool> {
+        let target = Self::target(scene, camera)?;
+        if let Some(index) = self.swapped.iter().position(|(swapped, _)| *swapped == target) {
+            let (_, original) = self.swapped.remove(index);
+            Self::restore(scene, target, original);
+            scene.update_grid();
+            return Some(false);
+        }
+
+        let original = match target {
+            Target::Block(position) => {
+                let grid = scene.materials.intern("uv grid", Material::uv_grid);
+                let cube = scene.grid.find_mut(position)?;
+                let original = *cube;
+                cube.material = grid;
+                cube.top_material = None;
+                cube.side_material = None;
+                cube.bottom_material = None;
+                cube.face_material = None;
+                Original::Block(original)
+            }
+            Target::Mesh(index) => Original::Mesh(std::mem::replace(&mut scene.meshes[index].material, Material::uv_grid())),
+        };
+        self.swapped.push((target, original));
+        scene.update_grid(); // A glowing block loses its light while it wears the grid
+        Some(true)
+    }
+
+    /// Forget the swaps (the scene they were made in was replaced)
+    pub fn clear(&mut self) {
+        self.swapped.clear();
+    }
+
+    // Nearest block or mesh along the center ray, the same one the light editor aims at
+    fn target(scene: &Scene, camera: &Camera) -> Option<Target> {
+        let ray = camera.get_pinhole_ray(0.5, 0.5);
+        let blocks = scene.grid.cubes().filter_map(|cube| Some((cube.intersect(&ray, &scene.materials)?.t, Target::Block(cube.position))));
+        let meshes = scene.meshes.iter().enumerate().filter_map(|(i, mesh)| Some((mesh.intersect(&ray)?.t, Target::Mesh(i))));
+        blocks
+            .chain(meshes)
+            .min_by(|a: &(Real, Target), b| a.0.total_cmp(&b.0))
+            .map(|(_, target)| target)
+    }
+
+    fn restore(scene: &mut Scene, target: Target, original: Original) {
+        match (target, original) {
+            (Target::Block(position), Original::Block(original)) => {
+                if let Some(cube) = scene.grid.find_mut(position) {
+                    *cube = original;
+                }
+            }
+            (Target::Mesh(index), Original::Mesh(material)) => {
+                if let Some(mesh) = scene.meshes.get_mut(index) {
+                    mesh.material = material;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Default for UvDebug {
+    fn default() -> Self {
+        Self::new()
+    }
+}
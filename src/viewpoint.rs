@@ -0,0 +1,31 @@
+use crate::camera::Camera;
+use crate::utils::{Degrees, Real, Vec3};
+
+/// Named camera placement from the scene file ([[cameras]]), e.g. a hero shot or a top-down view
+#[derive(Clone, Debug)]
+pub struct Viewpoint {
+    pub name: String,
+    pub position: Vec3,
+    pub target: Vec3,
+    pub fov: Degrees,
+    pub roll: Degrees,                // Dutch angle; keeps the camera's horizon unlocked
+    pub aperture: f32,                // Depth of field lens radius (0 = everything sharp)
+    pub focus_distance: Option<Real>, // Defaults to the distance to the target
+}
+
+impl Viewpoint {
+    pub fn apply(&self, camera: &mut Camera) {
+        camera.set_pose(self.position, self.target, self.fov);
+        camera.roll = self.roll;
+        camera.horizon_lock = self.roll == Degrees(0.0);
+        camera.aperture = self.aperture;
+        camera.focus_distance = self.focus_distance.unwrap_or_else(|| (self.target - self.position).length());
+    }
+
+    pub fn find<'a>(viewpoints: &'a [Viewpoint], name: &str) -> Result<&'a Viewpoint, String> {
+        viewpoints.iter().find(|v| v.name == name).ok_or_else(|| {
+            let names: Vec<&str> = viewpoints.iter().map(|v| v.name.as_str()).collect();
+            format!("Unknown camera '{}' (scene cameras: {})", name, if names.is_empty() { "none".to_string() } else { names.join(", ") })
+        })
+    }
+}
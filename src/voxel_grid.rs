@@ -0,0 +1,568 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::box_batch::{RayLanes, RayPacket};
+use crate::chunk::{CHUNK_SIZE, Chunk, ChunkCoord, chunk_of, packet_slab_entry, slab_entry};
+use crate::cube::Cube;
+use crate::intersection::Intersection;
+use crate::material_palette::MaterialPalette;
+use crate::ray::Ray;
+use crate::utils::{Real, Vec3};
+
+/// Integer block coordinate of a unit cell
+pub type Cell = (i32, i32, i32);
+
+const NEIGHBOURS: [Cell; 6] = [(1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1)];
+
+/// The scene's static cubes, stored in 16x16x16 chunks keyed by chunk coordinate (chunk.rs).
+/// Each chunk owns the cubes centered in it and has a dense array of its unit cells; only
+/// full-size (1.0) cubes go in a cell, small detail cubes (eyes, gills, lily pads) are only kept in
+/// their chunk's list. Blocks buried on all six sides by opaque blocks (the dirt under the ground,
+/// the inside of thick walls) keep their cell but aren't traced: every ray reaches one of their
+/// neighbours first.
+///
+/// Adding or removing cubes only marks their chunks (and the neighbouring ones, whose border
+/// blocks may be buried or uncovered) as edited; `update` then redoes the traced lists of just
+/// those chunks and the bounding volume hierarchy over the chunks that rays walk, nearest first.
+#[derive(Clone, Default)]
+pub struct VoxelGrid {
+    chunks: HashMap<ChunkCoord, Chunk>,
+    edited: HashSet<ChunkCoord>, // Chunks whose traced lists are out of date until the next update
+    hierarchy: Vec<Node>, // Over the chunks with something to trace, root first
+    generation: u64, // Counts the edits, so caches of the cubes can tell they changed
+    min_y: i32,
+    max_y: i32,
+}
+
+// A node of the chunk hierarchy, the box around everything traced below it
+#[derive(Clone)]
+struct Node {
+    min: Vec3,
+    max: Vec3,
+    content: Content,
+}
+
+#[derive(Clone, Copy)]
+enum Content {
+    Chunk(ChunkCoord),
+    // The children split the chunks along `axis`: the lower ones right after this node, the
+    // upper ones at `upper`
+    Split { axis: usize, upper: usize },
+}
+
+// Nodes still to visit; the hierarchy is split at the median, so it's never deeper than this
+struct NodeStack {
+    nodes: [usize; 64],
+    len: usize,
+}
+
+impl NodeStack {
+    fn new(hierarchy: &[Node]) -> Self {
+        Self { nodes: [0; 64], len: usize::from(!hierarchy.is_empty()) }
+    }
+
+    fn push(&mut self, node: usize) {
+        self.nodes[self.len] = node;
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<usize> {
+        self.len = self.len.checked_sub(1)?;
+        Some(self.nodes[self.len])
+    }
+}
+
+impl VoxelGrid {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cell of a block centered at `position` (centers sit on integer or half-integer heights).
+    /// Halves round up on both sides of zero, so cells follow each other without a gap or a
+    /// shared cell at 0.
+    pub fn cell_of(position: Vec3) -> Cell {
+        let cell = |v: Real| (v + 0.5).floor() as i32;
+        (cell(position.x), cell(position.y), cell(position.z))
+    }
+
+    fn cube_cell(cube: &Cube) -> Option<Cell> {
+        if (cube.size - 1.0).abs() < 0.01 {
+            Some(Self::cell_of(cube.position))
+        } else {
+            None
+        }
+    }
+
+    /// Add a cube. A unit block whose cell is already taken is left out: the first block put in a
+    /// cell stays (a prefab's wall where a fill runs under it). Returns whether it went in.
+    pub fn add(&mut self, cube: Cube) -> bool {
+        self.add_cube(cube, false)
+    }
+
+    /// Add a block of the streamed world (ChunkStreamer), which the scene's own blocks and edits
+    /// keep out of their cells the same way
+    pub fn add_streamed(&mut self, cube: Cube) -> bool {
+        self.add_cube(cube, true)
+    }
+
+    fn add_cube(&mut self, cube: Cube, streamed: bool) -> bool {
+        let cell = Self::cell_of(cube.position);
+        let coord = chunk_of(cell);
+        let added = self.chunks.entry(coord).or_insert_with(|| Chunk::new(coord)).add(cube, Self::cube_cell(&cube), streamed);
+        if added {
+            self.touch(cell);
+        }
+        added
+    }
+
+    /// Take the unit block out of a cell
+    pub fn remove(&mut self, cell: Cell) -> Option<Cube> {
+        let cube = self.chunks.get_mut(&chunk_of(cell))?.remove(cell)?;
+        self.touch(cell);
+        Some(cube)
+    }
+
+    /// Take the streamed blocks out of a column of chunks (chunk x and z), leaving the scene's own
+    pub fn remove_streamed(&mut self, (x, z): (i32, i32)) {
+        let column: Vec<ChunkCoord> = self.chunks.keys().copied().filter(|coord| (coord.0, coord.2) == (x, z)).collect();
+        for coord in column {
+            if self.chunks.get_mut(&coord).is_some_and(|chunk| chunk.remove_streamed() > 0) {
+                self.touch_chunk(coord);
+            }
+        }
+    }
+
+    // A cell changed: its chunk needs tracing again, and so do the chunks across any face it
+    // shares with one, whose blocks against it may have been buried or uncovered
+    fn touch(&mut self, (x, y, z): Cell) {
+        self.generation += 1;
+        self.edited.insert(chunk_of((x, y, z)));
+        for (dx, dy, dz) in NEIGHBOURS {
+            self.edited.insert(chunk_of((x + dx, y + dy, z + dz)));
+        }
+    }
+
+    fn touch_chunk(&mut self, (x, y, z): ChunkCoord) {
+        self.generation += 1;
+        self.edited.insert((x, y, z));
+        for (dx, dy, dz) in NEIGHBOURS {
+            self.edited.insert((x + dx, y + dy, z + dz));
+        }
+    }
+
+    /// Unit block in a cell
+    pub fn get(&self, cell: Cell) -> Option<&Cube> {
+        self.chunks.get(&chunk_of(cell))?.get(cell)
+    }
+
+    /// Unit block in a cell, to change in place (counts as an edit of the cell)
+    pub fn get_mut(&mut self, cell: Cell) -> Option<&mut Cube> {
+        self.chunks.get(&chunk_of(cell))?.get(cell)?;
+        self.touch(cell);
+        self.chunks.get_mut(&chunk_of(cell))?.get_mut(cell)
+    }
+
+    /// The cube centered exactly at `position`, unit block or detail cube, to change in place
+    pub fn find_mut(&mut self, position: Vec3) -> Option<&mut Cube> {
+        let cell = Self::cell_of(position);
+        self.chunks.get(&chunk_of(cell))?.find(position)?;
+        self.touch(cell);
+        self.chunks.get_mut(&chunk_of(cell))?.find_mut(position)
+    }
+
+    /// Every cube, chunk by chunk
+    pub fn cubes(&self) -> impl Iterator<Item = &Cube> {
+        self.chunks.values().flat_map(Chunk::cubes)
+    }
+
+    /// Move every cube by `shift`, a whole number of chunks along each axis (the floating origin's
+    /// steps)
+    pub fn translate(&mut self, shift: Vec3) {
+        let chunks = |v: Real| (v / CHUNK_SIZE as Real).round() as i32;
+        let by = (chunks(shift.x), chunks(shift.y), chunks(shift.z));
+        self.chunks = self
+            .chunks
+            .drain()
+            .map(|(_, mut chunk)| {
+                chunk.translate(by);
+                (chunk.coord(), chunk)
+            })
+            .collect();
+        self.edited = self.chunks.keys().copied().collect();
+        self.generation += 1;
+    }
+
+    /// Whether cubes changed since the last update (the traced lists and hierarchy miss them)
+    pub fn is_stale(&self) -> bool {
+        !self.edited.is_empty()
+    }
+
+    /// Number of edits so far (add, remove, get_mut, ...), for telling whether the cubes changed
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Trace again the chunks edited since the last update, with the opacity of `materials` (the
+    /// palette the cubes' material ids refer to), and rebuild the hierarchy over the chunks
+    pub fn update(&mut self, materials: &MaterialPalette) {
+        if self.edited.is_empty() {
+            return;
+        }
+        for coord in std::mem::take(&mut self.edited) {
+            let Some(chunk) = self.chunks.get(&coord) else { continue };
+            if chunk.is_empty() {
+                self.chunks.remove(&coord);
+                continue;
+            }
+            let buried: Vec<bool> = chunk.cubes().iter().map(|cube| Self::cube_cell(cube).is_some() && self.is_buried(materials, cube)).collect();
+            self.chunks.get_mut(&coord).unwrap().trace(&buried);
+        }
+
+        let heights = self.chunks.values().filter_map(Chunk::height_range);
+        (self.min_y, self.max_y) = heights.reduce(|(low, high), (y0, y1)| (low.min(y0), high.max(y1))).unwrap_or((0, 0));
+
+        let mut leaves: Vec<(ChunkCoord, Vec3, Vec3)> = self
+            .chunks
+            .values()
+            .filter(|chunk| chunk.traced_count() > 0)
+            .map(|chunk| (chunk.coord(), chunk.bounds().0, chunk.bounds().1))
+            .collect();
+        self.hierarchy.clear();
+        if !leaves.is_empty() {
+            Self::build_hierarchy(&mut self.hierarchy, &mut leaves);
+        }
+    }
+
+    // Every face of the unit block is against an opaque block lined up with it. Neighbours are
+    // looked up by where they'd be centered rather than by cell: a block in the next cell over can
+    // be half a block off and not cover the face.
+    fn is_buried(&self, materials: &MaterialPalette, block: &Cube) -> bool {
+        NEIGHBOURS.iter().all(|&(dx, dy, dz)| {
+            let position = block.position + Vec3::new(dx as Real, dy as Real, dz as Real);
+            self.get(Self::cell_of(position)).is_some_and(|cube| cube.position == position && cube.is_opaque(materials))
+        })
+    }
+
+    // Nodes for `leaves` (chunk and traced box) and their parents, split in half by box center
+    // along the axis they're most spread out on
+    fn build_hierarchy(nodes: &mut Vec<Node>, leaves: &mut [(ChunkCoord, Vec3, Vec3)]) {
+        let (min, max) = leaves.iter().fold((leaves[0].1, leaves[0].2), |(min, max), &(_, low, high)| {
+            (
+                Vec3::new(min.x.min(low.x), min.y.min(low.y), min.z.min(low.z)),
+                Vec3::new(max.x.max(high.x), max.y.max(high.y), max.z.max(high.z)),
+            )
+        });
+        if let [(coord, _, _)] = leaves {
+            nodes.push(Node { min, max, content: Content::Chunk(*coord) });
+            return;
+        }
+        let extent = max - min;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z { 0 } else if extent.y >= extent.z { 1 } else { 2 };
+        let middle = leaves.len() / 2;
+        leaves.select_nth_unstable_by(middle, |a, b| component(a.1 + a.2, axis).total_cmp(&component(b.1 + b.2, axis)));
+
+        let node = nodes.len();
+        nodes.push(Node { min, max, content: Content::Split { axis, upper: 0 } });
+        let (lower, upper) = leaves.split_at_mut(middle);
+        Self::build_hierarchy(nodes, lower);
+        nodes[node].content = Content::Split { axis, upper: nodes.len() };
+        Self::build_hierarchy(nodes, upper);
+    }
+
+    /// Highest occupied cell in the (x, z) column, as (y, block)
+    pub fn top_block(&self, x: i32, z: i32) -> Option<(i32, &Cube)> {
+        (self.min_y..=self.max_y)
+            .rev()
+            .find_map(|y| self.get((x, y, z)).map(|cube| (y, cube)))
+    }
+
+    /// Lowest and highest occupied cell, as of the last update
+    pub fn height_range(&self) -> (i32, i32) {
+        (self.min_y, self.max_y)
+    }
+
+    /// Number of cubes, detail cubes and buried blocks included
+    pub fn cube_count(&self) -> usize {
+        self.chunks.values().map(|chunk| chunk.cubes().len()).sum()
+    }
+
+    /// Number of buried blocks rays skip, as of the last update
+    pub fn buried_count(&self) -> usize {
+        self.chunks.values().map(|chunk| chunk.cubes().len() - chunk.traced_count()).sum()
+    }
+
+    pub fn chunks(&self) -> impl Iterator<Item = &Chunk> {
+        self.chunks.values()
+    }
+
+    /// Closest hit nearer than `max_t`, with the palette the cubes' material ids refer to (the
+    /// grid must be up to date, see update). The hierarchy is walked nearer half first, skipping
+    /// boxes the ray misses or only enters past the closest hit so far; inside a chunk the cubes'
+    /// boxes are slab tested a batch at a time and only the ones the ray can hit get the full cube
+    /// test.
+    pub fn intersect(&self, materials: &MaterialPalette, ray: &Ray, max_t: Real) -> Option<Intersection> {
+        let lanes = RayLanes::new(ray);
+        let mut closest: Option<Intersection> = None;
+        let mut closest_t = max_t;
+        let mut stack = NodeStack::new(&self.hierarchy);
+        while let Some(index) = stack.pop() {
+            let node = &self.hierarchy[index];
+            if slab_entry(ray, node.min, node.max, closest_t).is_none() {
+                continue;
+            }
+            match node.content {
+                Content::Chunk(coord) => {
+                    for (batch, cubes) in self.chunks[&coord].batches() {
+                        for lane in batch.hits(&lanes, closest_t) {
+                            if let Some(intersection) = cubes[lane].intersect(ray, materials)
+                                && intersection.t < closest_t
+                            {
+                                closest_t = intersection.t;
+                                closest = Some(intersection);
+                            }
+                        }
+                    }
+                }
+                Content::Split { axis, upper } => push_children(&mut stack, index, axis, upper, ray.direction),
+            }
+        }
+        closest
+    }
+
+    /// Closest hits for a packet of coherent rays (neighbouring camera rays), each the one
+    /// intersect finds for that ray. The hierarchy and the batches of boxes are culled for the
+    /// whole packet at once (RayPacket), and only what the packet may hit is tested ray by ray.
+    /// Divergent rays, which RayPacket can't bound, are traced one by one.
+    pub fn intersect_packet<const N: usize>(&self, materials: &MaterialPalette, rays: &[Ray; N], max_t: Real) -> [Option<Intersection>; N] {
+        let Some(packet) = RayPacket::new(rays) else {
+            return rays.each_ref().map(|ray| self.intersect(materials, ray, max_t));
+        };
+        let lanes = rays.each_ref().map(RayLanes::new);
+        let mut closest: [Option<Intersection>; N] = std::array::from_fn(|_| None);
+        let mut closest_t = [max_t; N];
+        let mut stack = NodeStack::new(&self.hierarchy);
+        while let Some(index) = stack.pop() {
+            let node = &self.hierarchy[index];
+            let farthest = closest_t.iter().copied().fold(Real::NEG_INFINITY, Real::max);
+            let Some(entry) = packet_slab_entry(&packet, node.min, node.max, farthest) else {
+                continue;
+            };
+            match node.content {
+                Content::Chunk(coord) => {
+                    for (batch, cubes) in self.chunks[&coord].batches() {
+                        if !batch.packet_hits(&packet, farthest) {
+                            continue;
+                        }
+                        for (i, ray) in rays.iter().enumerate() {
+                            if entry > closest_t[i] {
+                                continue;
+                            }
+                            for lane in batch.hits(&lanes[i], closest_t[i]) {
+                                if let Some(intersection) = cubes[lane].intersect(ray, materials)
+                                    && intersection.t < closest_t[i]
+                                {
+                                    closest_t[i] = intersection.t;
+                                    closest[i] = Some(intersection);
+                                }
+                            }
+                        }
+                    }
+                }
+                // The packet's rays all go the same way along each axis, so the first's direction orders the children for all of them
+                Content::Split { axis, upper } => push_children(&mut stack, index, axis, upper, rays[0].direction),
+            }
+        }
+        closest
+    }
+
+    /// Cells occupied in only one of the two grids (blocks added or removed between them)
+    pub fn changed_cells(&self, other: &VoxelGrid) -> Vec<Cell> {
+        let added = other.occupied().filter(|&cell| self.get(cell).is_none());
+        let removed = self.occupied().filter(|&cell| other.get(cell).is_none());
+        added.chain(removed).collect()
+    }
+
+    fn occupied(&self) -> impl Iterator<Item = Cell> + '_ {
+        self.chunks.values().flat_map(|chunk| chunk.blocks().map(|(cell, _)| cell))
+    }
+}
+
+impl Extend<Cube> for VoxelGrid {
+    fn extend<T: IntoIterator<Item = Cube>>(&mut self, cubes: T) {
+        for cube in cubes {
+            self.add(cube);
+        }
+    }
+}
+
+// Queue a split node's children so the one on the side the ray comes from is visited first
+fn push_children(stack: &mut NodeStack, node: usize, axis: usize, upper: usize, direction: Vec3) {
+    let lower = node + 1;
+    if component(direction, axis) >= 0.0 {
+        stack.push(upper);
+        stack.push(lower);
+    } else {
+        stack.push(lower);
+        stack.push(upper);
+    }
+}
+
+fn component(v: Vec3, axis: usize) -> Real {
+    match axis {
+        0 => v.x,
+        1 => v.y,
+        _ => v.z,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::material::Material;
+    use crate::material_palette::MaterialId;
+    use crate::utils::numbers;
+
+    fn stone() -> (MaterialPalette, MaterialId) {
+        let mut materials = MaterialPalette::new();
+        let id = materials.add(Material::new(Color::new(0.5, 0.5, 0.5)));
+        (materials, id)
+    }
+
+    fn brute_force(grid: &VoxelGrid, materials: &MaterialPalette, ray: &Ray) -> Option<Real> {
+        grid.cubes().filter_map(|cube| cube.intersect(ray, materials)).map(|hit| hit.t).reduce(Real::min)
+    }
+
+    // Blocks and detail cubes scattered over several chunks on both sides of 0, under y = 10
+    fn scattered(materials: &MaterialPalette, stone: MaterialId) -> VoxelGrid {
+        let mut next = numbers(7);
+        let mut grid = VoxelGrid::new();
+        for _ in 0..400 {
+            let cell = Vec3::new((next() * 60.0 - 30.0).round(), (next() * 10.0).round(), (next() * 60.0 - 30.0).round());
+            let size = if next() < 0.8 { 1.0 } else { 0.2 };
+            grid.add(Cube::new(cell, size, stone));
+        }
+        grid.update(materials);
+        grid
+    }
+
+    // Rays from above the blocks, every tenth running along x or z
+    fn rays(count: usize) -> Vec<Ray> {
+        let mut next = numbers(11);
+        (0..count)
+            .map(|i| {
+                let origin = Vec3::new(next() * 80.0 - 40.0, 12.0 + next() * 8.0, next() * 80.0 - 40.0);
+                let mut direction = Vec3::new(next() - 0.5, -next(), next() - 0.5);
+                if i % 10 == 0 {
+                    direction = Vec3::new(next() - 0.5, 0.0, 0.0);
+                } else if i % 10 == 5 {
+                    direction.z = 0.0;
+                }
+                Ray::new(origin, direction.normalize())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn hierarchy_finds_the_closest_hit() {
+        let (materials, stone) = stone();
+        let grid = scattered(&materials, stone);
+        for ray in rays(2000) {
+            let hit = grid.intersect(&materials, &ray, Real::INFINITY).map(|hit| hit.t);
+            assert_eq!(hit, brute_force(&grid, &materials, &ray), "ray from {:?} along {:?}", ray.origin, ray.direction);
+        }
+    }
+
+    #[test]
+    fn edits_are_traced_after_update() {
+        let (materials, stone) = stone();
+        let mut grid = scattered(&materials, stone);
+        let taken: Vec<Cell> = grid.occupied().take(100).collect();
+        for &cell in &taken {
+            assert!(grid.remove(cell).is_some());
+        }
+        grid.add(Cube::new(Vec3::new(40.0, 3.0, 40.0), 1.0, stone));
+        assert!(grid.is_stale());
+        grid.update(&materials);
+        assert!(!grid.is_stale());
+        assert!(taken.iter().all(|&cell| grid.get(cell).is_none()));
+        for ray in rays(1000) {
+            let hit = grid.intersect(&materials, &ray, Real::INFINITY).map(|hit| hit.t);
+            assert_eq!(hit, brute_force(&grid, &materials, &ray));
+        }
+    }
+
+    #[test]
+    fn the_first_block_in_a_cell_stays() {
+        let (mut materials, stone) = stone();
+        let glass = materials.add(Material::new(Color::new(0.9, 0.9, 0.9)));
+        let mut grid = VoxelGrid::new();
+        assert!(grid.add(Cube::new(Vec3::new(1.0, 2.0, 3.0), 1.0, stone)));
+        assert!(!grid.add(Cube::new(Vec3::new(1.0, 2.0, 3.0), 1.0, glass)));
+        assert_eq!(grid.cube_count(), 1);
+        assert_eq!(grid.remove((1, 2, 3)).map(|cube| cube.material), Some(stone));
+        assert_eq!(grid.cube_count(), 0);
+    }
+
+    #[test]
+    fn finding_nothing_is_no_edit() {
+        let (materials, stone) = stone();
+        let mut grid = VoxelGrid::new();
+        grid.add(Cube::new(Vec3::new(0.0, 0.0, 0.0), 1.0, stone));
+        grid.update(&materials);
+        let generation = grid.generation();
+        assert!(grid.find_mut(Vec3::new(0.25, 0.0, 0.0)).is_none());
+        assert_eq!(grid.generation(), generation);
+        assert!(!grid.is_stale());
+        assert!(grid.find_mut(Vec3::new(0.0, 0.0, 0.0)).is_some());
+        assert!(grid.generation() > generation);
+    }
+
+    #[test]
+    fn streamed_blocks_leave_the_scene_blocks_alone() {
+        let (materials, stone) = stone();
+        let mut grid = VoxelGrid::new();
+        grid.add(Cube::new(Vec3::new(0.0, 0.0, 0.0), 1.0, stone));
+        assert!(!grid.add_streamed(Cube::new(Vec3::new(0.0, 0.0, 0.0), 1.0, stone)));
+        assert!(grid.add_streamed(Cube::new(Vec3::new(1.0, 0.0, 0.0), 1.0, stone)));
+        assert!(grid.add_streamed(Cube::new(Vec3::new(17.0, 0.0, 0.0), 1.0, stone)));
+        grid.update(&materials);
+
+        grid.remove_streamed((0, 0));
+        grid.update(&materials);
+        assert!(grid.get((0, 0, 0)).is_some());
+        assert!(grid.get((1, 0, 0)).is_none());
+        assert!(grid.get((17, 0, 0)).is_some()); // Another column
+    }
+
+    #[test]
+    fn buried_blocks_are_not_traced() {
+        let (materials, stone) = stone();
+        let mut grid = VoxelGrid::new();
+        for (x, y, z) in (0..27).map(|i| (i % 3, i / 3 % 3, i / 9)) {
+            grid.add(Cube::new(Vec3::new(x as Real, y as Real, z as Real), 1.0, stone));
+        }
+        grid.update(&materials);
+        assert_eq!(grid.buried_count(), 1);
+        grid.remove((1, 2, 1));
+        grid.update(&materials);
+        assert_eq!(grid.buried_count(), 0);
+    }
+
+    #[test]
+    fn neighbours_bury_only_when_lined_up() {
+        let (materials, stone) = stone();
+        let mut grid = VoxelGrid::new();
+        // Three layers centered on half heights, across y = 0
+        for (x, y, z) in (0..27).map(|i| (i % 3, i / 3 % 3, i / 9)) {
+            grid.add(Cube::new(Vec3::new(x as Real, y as Real - 1.5, z as Real), 1.0, stone));
+        }
+        grid.update(&materials);
+        assert_eq!(grid.buried_count(), 1);
+
+        // The block over the middle one raised half a block (same cell): it leaves a gap at its sides
+        assert!(grid.remove((1, 1, 1)).is_some());
+        assert!(grid.add(Cube::new(Vec3::new(1.0, 1.0, 1.0), 1.0, stone)));
+        grid.update(&materials);
+        assert_eq!(grid.buried_count(), 0);
+    }
+}
@@ -0,0 +1,100 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Instant;
+
+use crate::renderer::RenderSettings;
+
+/// Per-frame time/ray budget shared by the render threads. Once a frame goes over it
+/// (e.g. someone builds a box of mirrors), the tiles still left are traced roughly so the
+/// app keeps responding instead of appearing hung.
+pub struct Watchdog {
+    start: Instant,
+    time_budget: f32, // Seconds, 0 = unlimited
+    ray_budget: u64,  // 0 = unlimited
+    rays: AtomicU64,
+    rough_tiles: AtomicUsize,
+}
+
+impl Watchdog {
+    pub fn new(settings: &RenderSettings) -> Self {
+        Self {
+            start: Instant::now(),
+            time_budget: settings.frame_budget,
+            ray_budget: settings.ray_budget,
+            rays: AtomicU64::new(0),
+            rough_tiles: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn add_rays(&self, rays: u64) {
+        self.rays.fetch_add(rays, Ordering::Relaxed);
+    }
+
+    /// Checked before each tile: true once the frame has used up its time or rays
+    pub fn exceeded(&self) -> bool {
+        (self.time_budget > 0.0 && self.start.elapsed().as_secs_f32() > self.time_budget)
+            || (self.ray_budget > 0 && self.rays.load(Ordering::Relaxed) > self.ray_budget)
+    }
+
+    pub fn rough_tile(&self) {
+        self.rough_tiles.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self, tiles: usize, pixels: usize) -> FrameStats {
+        FrameStats {
+            tiles,
+            rough_tiles: self.rough_tiles.load(Ordering::Relaxed),
+            rays: self.rays.load(Ordering::Relaxed),
+            pixels,
+            seconds: self.start.elapsed().as_secs_f32(),
+        }
+    }
+}
+
+/// What a frame cost, returned by render_scene
+#[derive(Clone, Copy, Default)]
+pub struct FrameStats {
+    pub tiles: usize,
+    pub rough_tiles: usize, // Finished at reduced quality after the budget ran out
+    pub rays: u64,
+    pub pixels: usize, // Traced (scaled) pixels
+    pub seconds: f32,
+}
+
+impl FrameStats {
+    pub fn degraded(&self) -> bool {
+        self.rough_tiles > 0
+    }
+
+    pub fn rays_per_pixel(&self) -> f32 {
+        self.rays as f32 / self.pixels.max(1) as f32
+    }
+
+    /// One line for the console: what went over and which settings would bring it back in budget
+    pub fn report(&self, settings: &RenderSettings) -> String {
+        let mut advice = Vec::new();
+        if settings.render_scale < 4 {
+            advice.push(format!("lower the quality (render scale is {}x)", settings.render_scale));
+        }
+        if settings.motion_blur_samples > 1 {
+            advice.push(format!("turn off motion blur ({} samples per pixel)", settings.motion_blur_samples));
+        }
+        if !settings.use_threading {
+            advice.push("turn threading back on".to_string());
+        }
+        // A plain diffuse hit costs about 2 rays (camera + shadow); facing mirrors and glass multiply that
+        if self.rays_per_pixel() > 8.0 {
+            advice.push(format!(
+                "move away from facing mirrors/glass ({:.0} rays per pixel)",
+                self.rays_per_pixel()
+            ));
+        }
+        format!(
+            "Frame over budget ({:.2}s, {:.1}M rays): {}/{} tiles drawn at reduced quality. Try to {}",
+            self.seconds,
+            self.rays as f32 / 1_000_000.0,
+            self.rough_tiles,
+            self.tiles,
+            if advice.is_empty() { "simplify the scene".to_string() } else { advice.join(", ") }
+        )
+    }
+}
@@ -0,0 +1,46 @@
+use crate::noise::fbm;
+use crate::utils::{Real, TAU, Vec3, narrow};
+
+// Long swells: (direction x, direction z, wavelength in blocks, speed in blocks per second, height).
+// A few directions that don't line up, so the pattern never reads as stripes.
+const SWELLS: [(f32, f32, f32, f32, f32); 3] = [
+    (1.0, 0.3, 2.3, 0.5, 0.025),
+    (-0.4, 1.0, 1.5, 0.35, 0.018),
+    (0.7, -0.7, 0.9, 0.6, 0.01),
+];
+const RIPPLE_SCALE: f32 = 4.0; // Noise cells per block for the small ripples on top
+const RIPPLE_DRIFT: [f32; 2] = [0.12, -0.08]; // Blocks per second the ripples slide along (x, z)
+const RIPPLE_HEIGHT: f32 = 0.02; // Height of the small ripples, in blocks
+const RIPPLE_STEP: f32 = 0.05; // Blocks between the samples that measure the ripple slope
+
+/// Shading normal of a water surface with animated waves: a few sine swells plus drifting noise
+/// ripples, summed into a height field over x/z whose slope tilts the normal (by `strength`, 1 =
+/// a gently rippled pond). Only flat faces get waves, seen from above or from under the water;
+/// the sides of a water block stay as they are.
+pub fn normal(position: Vec3, normal: Vec3, time: f32, strength: f32) -> Vec3 {
+    if normal.y.abs() < 0.5 {
+        return normal;
+    }
+    let (x, z) = (narrow(position.x), narrow(position.z));
+
+    // Slope of the height field along x and z
+    let (mut slope_x, mut slope_z) = (0.0, 0.0);
+    for (dx, dz, wavelength, speed, height) in SWELLS {
+        let length = (dx * dx + dz * dz).sqrt();
+        let (dx, dz) = (dx / length, dz / length);
+        let k = narrow(TAU) / wavelength;
+        let slope = height * k * (k * (dx * x + dz * z - speed * time)).cos();
+        slope_x += slope * dx;
+        slope_z += slope * dz;
+    }
+    let ripple = |x: f32, z: f32| {
+        RIPPLE_HEIGHT * fbm((x + RIPPLE_DRIFT[0] * time) * RIPPLE_SCALE, (z + RIPPLE_DRIFT[1] * time) * RIPPLE_SCALE, 2)
+    };
+    let here = ripple(x, z);
+    slope_x += (ripple(x + RIPPLE_STEP, z) - here) / RIPPLE_STEP;
+    slope_z += (ripple(x, z + RIPPLE_STEP) - here) / RIPPLE_STEP;
+
+    // The surface normal of height h(x, z) is (-dh/dx, 1, -dh/dz); from below it's flipped
+    let tilt = Vec3::new(-slope_x as Real, 0.0, -slope_z as Real) * (strength as Real * normal.y.signum());
+    (normal + tilt).normalize()
+}
@@ -0,0 +1,61 @@
+use crate::noise::{fbm, smoothstep};
+use crate::utils::{Vec3, narrow};
+
+const WETTING_SECONDS: f32 = 40.0; // Rain time for dry ground to get fully wet
+const DRYING_SECONDS: f32 = 90.0;  // Dry time for fully wet ground to dry off again
+const PUDDLE_SCALE: f32 = 0.6;     // Noise cells per block: puddles one to two blocks across
+const PUDDLE_COVERAGE: f32 = 0.55; // Share of the surface under water once it's fully wet
+const PUDDLE_EDGE: f32 = 0.05;     // Noise range over which a puddle's rim fades in
+const PUDDLE_REFLECTIVITY: f32 = 0.4; // Head-on mirror share of standing water, rising at grazing angles
+const WET_DARKENING: f32 = 0.2;    // Wet surfaces darken a little all over...
+const PUDDLE_DARKENING: f32 = 0.35; // ...and more where the water stands
+
+/// Rain, and how wet it has left the ground (0 = dry, 1 = soaked). Wetness builds up while it
+/// rains and dries off after it stops, on the scene clock, so puddles spread out and shrink
+/// back instead of popping in and out.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Weather {
+    pub raining: bool,
+    pub wetness: f32,
+}
+
+impl Weather {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Already raining with the ground soaked, for scenes that start out wet (scene file `rain`)
+    pub fn rain() -> Self {
+        Self { raining: true, wetness: 1.0 }
+    }
+
+    /// Advance by simulated seconds
+    pub fn step(&mut self, seconds: f32) {
+        self.wetness = if self.raining {
+            (self.wetness + seconds / WETTING_SECONDS).min(1.0)
+        } else {
+            (self.wetness - seconds / DRYING_SECONDS).max(0.0)
+        };
+    }
+}
+
+/// How much standing water covers this point of an upward-facing surface (0-1). Puddles fill the
+/// low spots of a noise field over the ground first and spread as the wetness rises.
+pub fn puddle(position: Vec3, wetness: f32) -> f32 {
+    if wetness <= 0.0 {
+        return 0.0;
+    }
+    let depth = fbm(narrow(position.x) * PUDDLE_SCALE, narrow(position.z) * PUDDLE_SCALE, 3);
+    let cut = 1.0 - wetness.min(1.0) * PUDDLE_COVERAGE;
+    smoothstep(cut, cut + PUDDLE_EDGE, depth)
+}
+
+/// Factor a wet surface's color is scaled by
+pub fn darkening(wetness: f32, puddle: f32) -> f32 {
+    1.0 - WET_DARKENING * wetness.min(1.0) - PUDDLE_DARKENING * puddle
+}
+
+/// Share of light standing water reflects at a view angle (cos_theta = view·normal), Schlick-style
+pub fn puddle_reflectance(cos_theta: f32, puddle: f32) -> f32 {
+    puddle * (PUDDLE_REFLECTIVITY + (1.0 - PUDDLE_REFLECTIVITY) * (1.0 - cos_theta).powi(5))
+}
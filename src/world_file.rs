@@ -0,0 +1,333 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use crate::cube::Cube;
+use crate::scene::Scene;
+use crate::scene_file::builtin_material_id;
+use crate::utils::{Real, Vec3};
+use crate::voxel_grid::{Cell, VoxelGrid};
+
+const MAGIC: &[u8; 7] = b"MCWORLD";
+const VERSION: u8 = 1;
+const TAG_MOVED: u8 = 0;
+const TAG_PLACED: u8 = 1;
+const MAX_RUN: u32 = 1 << 16; // Cells in one run of removed cells; longer rows are split
+const RUN_SIZE: usize = 16;   // Bytes of a run: start cell and length
+const ADDED_SIZE: usize = 15; // Fewest bytes of an added block: cell, tag and an empty name
+
+/// Where an edited block came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockSource {
+    Moved(Cell),    // The scene file's block from this world cell (it fell, or landed back)
+    Placed(String), // A new block of a built-in material (scene_file::builtin_material)
+}
+
+/// The changes block editing made to a scene, in world cells (render cells plus the floating
+/// origin), so they still line up after the scene is recentered or reloaded: the scene file's
+/// blocks taken out of their cells, and the blocks now in cells the file doesn't put them in.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorldEdits {
+    pub scene: String, // Scene file the edits were made to
+    pub removed: BTreeSet<Cell>,
+    pub added: BTreeMap<Cell, BlockSource>,
+}
+
+impl WorldEdits {
+    pub fn new(scene: &str) -> Self {
+        Self { scene: scene.to_string(), ..Self::default() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty()
+    }
+
+    /// Number of cells the edits touch
+    pub fn len(&self) -> usize {
+        let added: BTreeSet<Cell> = self.added.keys().copied().collect();
+        self.removed.union(&added).count()
+    }
+
+    /// Block taken out of a world cell: if it was added by an edit that edit is undone, otherwise
+    /// the file's block is gone. Returns where the block came from.
+    pub fn take(&mut self, cell: Cell) -> BlockSource {
+        self.added.remove(&cell).unwrap_or_else(|| {
+            self.removed.insert(cell);
+            BlockSource::Moved(cell)
+        })
+    }
+
+    /// Block put into an empty world cell
+    pub fn put(&mut self, cell: Cell, source: BlockSource) {
+        self.added.insert(cell, source);
+    }
+
+    /// Make the edits in a scene freshly loaded from the scene file (same file, possibly changed
+    /// since). Edits that no longer fit, a moved block whose cell is now empty or an added one
+    /// whose cell is now taken, are skipped; returns how many were.
+    pub fn apply(&self, scene: &mut Scene) -> Result<usize, String> {
+        let origin = VoxelGrid::cell_of(scene.origin);
+        // Cells read from disk can be anywhere in i32, so the shift into render space is checked
+        let local = |cell: Cell| match (cell.0.checked_sub(origin.0), cell.1.checked_sub(origin.1), cell.2.checked_sub(origin.2)) {
+            (Some(x), Some(y), Some(z)) => Ok((x, y, z)),
+            _ => Err(format!("Cell ({}, {}, {}) in world file is out of range", cell.0, cell.1, cell.2)),
+        };
+        let mut skipped = 0;
+
+        // Every added block is copied out before any cell is cleared
+        let mut blocks = Vec::new();
+        for (&cell, source) in &self.added {
+            let (x, y, z) = local(cell)?;
+            let position = Vec3::new(x as Real, y as Real, z as Real);
+            match source {
+                BlockSource::Moved(from) => match scene.grid.get(local(*from)?) {
+                    Some(cube) => {
+                        let mut cube = *cube;
+                        cube.position = position;
+                        blocks.push(cube);
+                    }
+                    None => skipped += 1,
+                },
+                BlockSource::Placed(name) => {
+                    let material = builtin_material_id(&mut scene.materials, name)
+                        .ok_or_else(|| format!("Unknown block material '{}' in world file", name))?;
+                    blocks.push(Cube::new(position, 1.0, material));
+                }
+            }
+        }
+
+        let removed = self.removed.iter().map(|&cell| local(cell)).collect::<Result<Vec<_>, _>>()?;
+        for cell in removed {
+            scene.grid.remove(cell);
+        }
+        for cube in blocks {
+            if !scene.grid.add(cube) {
+                skipped += 1;
+            }
+        }
+        scene.update_grid();
+        Ok(skipped)
+    }
+
+    /// Write the edits as a small binary file: a header naming the scene file, the removed cells
+    /// as runs along x, then the added blocks with their sources
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        write_string(&mut out, &self.scene)?;
+
+        // Removed cells grouped into runs: digging out a floor or a trench takes a few entries
+        let mut cells: Vec<Cell> = self.removed.iter().copied().collect();
+        cells.sort_unstable_by_key(|&(x, y, z)| (y, z, x));
+        let mut runs: Vec<(Cell, u32)> = Vec::new();
+        for cell in cells {
+            match runs.last_mut() {
+                Some((start, length))
+                    if (start.1, start.2) == (cell.1, cell.2) && *length < MAX_RUN && start.0.checked_add(*length as i32) == Some(cell.0) =>
+                {
+                    *length += 1
+                }
+                _ => runs.push((cell, 1)),
+            }
+        }
+        write_u32(&mut out, runs.len() as u32);
+        for (start, length) in runs {
+            write_cell(&mut out, start);
+            write_u32(&mut out, length);
+        }
+
+        write_u32(&mut out, self.added.len() as u32);
+        for (&cell, source) in &self.added {
+            write_cell(&mut out, cell);
+            match source {
+                BlockSource::Moved(from) => {
+                    out.push(TAG_MOVED);
+                    write_cell(&mut out, *from);
+                }
+                BlockSource::Placed(name) => {
+                    out.push(TAG_PLACED);
+                    write_string(&mut out, name)?;
+                }
+            }
+        }
+
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create '{}': {}", parent.display(), e))?;
+        }
+        std::fs::write(path, out).map_err(|e| format!("Failed to write world file '{}': {}", path.display(), e))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let data = std::fs::read(path).map_err(|e| format!("Failed to read world file '{}': {}", path.display(), e))?;
+        Self::parse(&data).map_err(|e| format!("World file '{}': {}", path.display(), e))
+    }
+
+    fn parse(data: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader { data, at: 0 };
+        if reader.bytes(MAGIC.len())? != MAGIC {
+            return Err("not a world file".to_string());
+        }
+        let version = reader.u8()?;
+        if version != VERSION {
+            return Err(format!("unsupported version {} (expected {})", version, VERSION));
+        }
+        let mut edits = Self::new(&reader.string()?);
+
+        for _ in 0..reader.count(RUN_SIZE)? {
+            let start = reader.cell()?;
+            let length = reader.u32()?;
+            if length == 0 || length > MAX_RUN || start.0.checked_add(length as i32 - 1).is_none() {
+                return Err(format!("bad run of {} removed cells", length));
+            }
+            for step in 0..length as i32 {
+                edits.removed.insert((start.0 + step, start.1, start.2));
+            }
+        }
+        for _ in 0..reader.count(ADDED_SIZE)? {
+            let cell = reader.cell()?;
+            let source = match reader.u8()? {
+                TAG_MOVED => BlockSource::Moved(reader.cell()?),
+                TAG_PLACED => BlockSource::Placed(reader.string()?),
+                tag => return Err(format!("unknown block source {}", tag)),
+            };
+            edits.added.insert(cell, source);
+        }
+        if reader.at != data.len() {
+            return Err("unexpected data after the last block".to_string());
+        }
+        Ok(edits)
+    }
+}
+
+fn write_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_cell(out: &mut Vec<u8>, cell: Cell) {
+    for value in [cell.0, cell.1, cell.2] {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+fn write_string(out: &mut Vec<u8>, text: &str) -> Result<(), String> {
+    let length = u16::try_from(text.len()).map_err(|_| format!("Name too long for a world file: '{}'", text))?;
+    out.extend_from_slice(&length.to_le_bytes());
+    out.extend_from_slice(text.as_bytes());
+    Ok(())
+}
+
+// Little-endian fields read front to back, failing on a truncated file
+struct Reader<'a> {
+    data: &'a [u8],
+    at: usize,
+}
+
+impl Reader<'_> {
+    fn bytes(&mut self, count: usize) -> Result<&[u8], String> {
+        let bytes = self.data.get(self.at..self.at + count).ok_or("file ends early")?;
+        self.at += count;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        Ok(i32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    // Number of entries of at least `entry_size` bytes each, checked against what's left of the
+    // file so a corrupt count fails here rather than after a long loop
+    fn count(&mut self, entry_size: usize) -> Result<usize, String> {
+        let count = self.u32()? as usize;
+        if count > (self.data.len() - self.at) / entry_size {
+            return Err("file ends early".to_string());
+        }
+        Ok(count)
+    }
+
+    fn cell(&mut self) -> Result<Cell, String> {
+        Ok((self.i32()?, self.i32()?, self.i32()?))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let length = u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()) as usize;
+        String::from_utf8(self.bytes(length)?.to_vec()).map_err(|_| "name is not UTF-8".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edits() -> WorldEdits {
+        let mut edits = WorldEdits::new("assets/scenes/diorama.toml");
+        for x in -3..5 {
+            edits.removed.insert((x, -2, 7)); // One run across 0
+        }
+        edits.removed.insert((10, 0, 0));
+        edits.removed.insert((i32::MAX, 4, 4));
+        edits.added.insert((1, 2, 3), BlockSource::Moved((1, 5, 3)));
+        edits.added.insert((-8, 0, 9), BlockSource::Placed("sand".to_string()));
+        edits
+    }
+
+    #[test]
+    fn saved_edits_load_back() {
+        let path = std::env::temp_dir().join(format!("world_file_test_{}.mcw", std::process::id()));
+        edits().save(&path).unwrap();
+        let loaded = WorldEdits::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, Ok(edits()));
+    }
+
+    #[test]
+    fn long_rows_are_split_into_runs() {
+        let mut edits = WorldEdits::new("scene.toml");
+        edits.removed.extend((0..MAX_RUN as i32 + 10).map(|x| (x, 0, 0)));
+        let path = std::env::temp_dir().join(format!("world_file_rows_{}.mcw", std::process::id()));
+        edits.save(&path).unwrap();
+        let loaded = WorldEdits::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, Ok(edits));
+    }
+
+    #[test]
+    fn corrupt_files_are_rejected() {
+        let path = std::env::temp_dir().join(format!("world_file_corrupt_{}.mcw", std::process::id()));
+        edits().save(&path).unwrap();
+        let data = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let runs_at = MAGIC.len() + 1 + 2 + "assets/scenes/diorama.toml".len();
+
+        for end in 0..data.len() {
+            assert!(WorldEdits::parse(&data[..end]).is_err(), "{} bytes", end);
+        }
+        let mut huge_count = data.clone();
+        huge_count[runs_at..runs_at + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(WorldEdits::parse(&huge_count).is_err());
+        for length in [0, MAX_RUN + 1, u32::MAX] {
+            let mut bad_run = data.clone();
+            bad_run[runs_at + 16..runs_at + 20].copy_from_slice(&length.to_le_bytes());
+            assert!(WorldEdits::parse(&bad_run).is_err(), "run of {}", length);
+        }
+        // A run going past i32::MAX
+        let mut overflow = data.clone();
+        overflow[runs_at + 4..runs_at + 8].copy_from_slice(&i32::MAX.to_le_bytes());
+        assert!(WorldEdits::parse(&overflow).is_err());
+    }
+
+    #[test]
+    fn cells_out_of_range_of_the_origin_are_an_error() {
+        let mut scene = Scene::new();
+        scene.origin = Vec3::new(-32.0, 0.0, 0.0);
+        let mut edits = WorldEdits::new("scene.toml");
+        edits.removed.insert((i32::MAX, 0, 0));
+        assert!(edits.apply(&mut scene).is_err());
+    }
+}